@@ -0,0 +1,433 @@
+#!/usr/bin/env python3
+"""Makes the test fixtures that are rewrites of demo.blend.
+
+Some are in a layout Blender 3.1 can't save, like a newer or 32-bit Blender's,
+the others just edit a few objects. Run this from the assets directory after
+demo.blend changes:
+
+    python3 make_fixtures.py
+"""
+
+import re
+import struct
+
+# Blender's struct index for blocks of raw data, like int and pointer arrays
+RAW_DATA = 0
+
+
+class Blend:
+    """The blocks of an uncompressed .blend file and the SDNA describing them"""
+
+    def __init__(self, path):
+        data = open(path, "rb").read()
+        assert data[:7] == b"BLENDER", "not an uncompressed .blend file"
+        self.pointer_size = 8 if data[7:8] == b"-" else 4
+        assert data[8:9] == b"v", "big-endian files aren't supported"
+        self.version = data[9:12]
+        self.blocks = []
+        offset = 12
+        while offset < len(data):
+            block, offset = self._read_block(data, offset)
+            self.blocks.append(block)
+            if block["code"] == b"ENDB":
+                break
+        self._read_sdna(self.block(b"DNA1")["body"])
+
+    def _read_block(self, data, offset):
+        ps = self.pointer_size
+        code = data[offset : offset + 4]
+        (size,) = struct.unpack("<i", data[offset + 4 : offset + 8])
+        old = int.from_bytes(data[offset + 8 : offset + 8 + ps], "little")
+        sdna, count = struct.unpack("<ii", data[offset + 8 + ps : offset + 16 + ps])
+        start = offset + 16 + ps
+        block = {
+            "code": code,
+            "old": old,
+            "sdna": sdna,
+            "count": count,
+            "body": bytearray(data[start : start + size]),
+        }
+        return block, start + size
+
+    def _read_sdna(self, dna):
+        self._offset = 8
+
+        def read_int():
+            (value,) = struct.unpack("<i", dna[self._offset : self._offset + 4])
+            self._offset += 4
+            return value
+
+        def read_strings():
+            strings = []
+            for _ in range(read_int()):
+                end = dna.index(b"\0", self._offset)
+                strings.append(dna[self._offset : end].decode())
+                self._offset = end + 1
+            # Every section is 4-byte aligned and starts with its 4-byte tag
+            self._offset = ((self._offset + 3) & ~3) + 4
+            return strings
+
+        self.names = read_strings()
+        self.types = read_strings()
+        count = len(self.types)
+        self.type_lengths = list(
+            struct.unpack("<%dh" % count, dna[self._offset : self._offset + 2 * count])
+        )
+        self._offset = ((self._offset + 2 * count + 3) & ~3) + 4
+        self.structs = []
+        for _ in range(read_int()):
+            struct_type, field_count = struct.unpack("<hh", dna[self._offset : self._offset + 4])
+            self._offset += 4
+            fields = []
+            for _ in range(field_count):
+                fields.append(list(struct.unpack("<hh", dna[self._offset : self._offset + 4])))
+                self._offset += 4
+            self.structs.append([struct_type, fields])
+
+    def write(self, path):
+        self.block(b"DNA1")["body"] = self._write_sdna()
+        ps = self.pointer_size
+        out = bytearray(b"BLENDER" + (b"-" if ps == 8 else b"_") + b"v" + self.version)
+        for block in self.blocks:
+            out += block["code"]
+            out += struct.pack("<i", len(block["body"]))
+            out += block["old"].to_bytes(ps, "little")
+            out += struct.pack("<ii", block["sdna"], block["count"])
+            out += block["body"]
+        open(path, "wb").write(out)
+
+    def _write_sdna(self):
+        def pad(out):
+            out += b"\0" * (-len(out) % 4)
+
+        out = bytearray(b"SDNA")
+        for tag, strings in ((b"NAME", self.names), (b"TYPE", self.types)):
+            out += tag + struct.pack("<i", len(strings))
+            for string in strings:
+                out += string.encode() + b"\0"
+            pad(out)
+        out += b"TLEN" + struct.pack("<%dh" % len(self.type_lengths), *self.type_lengths)
+        pad(out)
+        out += b"STRC" + struct.pack("<i", len(self.structs))
+        for struct_type, fields in self.structs:
+            out += struct.pack("<hh", struct_type, len(fields))
+            for field in fields:
+                out += struct.pack("<hh", *field)
+        return out
+
+    def block(self, code):
+        return next(block for block in self.blocks if block["code"] == code)
+
+    def code(self, block):
+        return block["code"].rstrip(b"\0").decode()
+
+    def struct_index(self, name):
+        return next(
+            index
+            for index, (struct_type, _) in enumerate(self.structs)
+            if self.types[struct_type] == name
+        )
+
+    def is_pointer(self, name):
+        return name.startswith("*") or name.startswith("(*")
+
+    def field_size(self, field_type, name):
+        size = self.pointer_size if self.is_pointer(name) else self.type_lengths[field_type]
+        for dimension in re.findall(r"\[(\d+)\]", name):
+            size *= int(dimension)
+        return size
+
+    def fields(self, struct_index):
+        """(bare name, offset, type, full name) of each field of a struct"""
+        offset = 0
+        for field_type, name in self.structs[struct_index][1]:
+            name = self.names[name]
+            bare = re.sub(r"\[.*", "", name).lstrip("*").strip("()")
+            yield bare, offset, field_type, name
+            offset += self.field_size(field_type, name)
+
+    def field(self, struct_index, bare):
+        return next(field for field in self.fields(struct_index) if field[0] == bare)
+
+    def read_pointer(self, body, offset):
+        return int.from_bytes(body[offset : offset + self.pointer_size], "little")
+
+    def write_pointer(self, body, offset, address):
+        body[offset : offset + self.pointer_size] = address.to_bytes(self.pointer_size, "little")
+
+    def by_address(self, address):
+        # ENDB's address is 0 too
+        if address == 0:
+            return None
+        return next((block for block in self.blocks if block["old"] == address), None)
+
+    def by_name(self, name):
+        return next(
+            block
+            for block in self.blocks
+            if block["code"][:2] == name[:2].encode() and self.id_name(block) == name
+        )
+
+    def set_field(self, block, struct_name, field, fmt, value):
+        _, offset, _, _ = self.field(self.struct_index(struct_name), field)
+        struct.pack_into("<" + fmt, block["body"], offset, value)
+
+    def rename(self, block, name):
+        _, offset, _, _ = self.field(self.struct_index("ID"), "name")
+        block["body"][offset : offset + 66] = name.encode().ljust(66, b"\0")
+
+    def id_name(self, block):
+        _, offset, _, _ = self.field(self.struct_index("ID"), "name")
+        name = block["body"][offset : offset + 66]
+        return name.split(b"\0")[0].decode()
+
+    def unused_address(self):
+        return max(block["old"] for block in self.blocks) + 0x100
+
+    def add_raw_block(self, body, before):
+        """Adds a raw data block in front of `before`, the way Blender writes arrays"""
+        block = {
+            "code": b"DATA",
+            "old": self.unused_address(),
+            "sdna": RAW_DATA,
+            "count": 1,
+            "body": bytearray(body),
+        }
+        self.blocks.insert(self.blocks.index(before) + 1, block)
+        return block["old"]
+
+
+def blender_3_6_faces(blend):
+    """Replaces each mesh's `MPoly` array with the face offsets Blender 3.6 writes instead"""
+    mesh = blend.struct_index("Mesh")
+    poly = blend.struct_index("MPoly")
+    blend.names.append("*poly_offset_indices")
+    blend.structs[mesh][1].append([blend.types.index("int"), len(blend.names) - 1])
+    blend.type_lengths[blend.structs[mesh][0]] += blend.pointer_size
+
+    _, mpoly, _, _ = blend.field(mesh, "mpoly")
+    _, loopstart, _, _ = blend.field(poly, "loopstart")
+    _, totloop, _, _ = blend.field(poly, "totloop")
+    poly_size = blend.type_lengths[blend.structs[poly][0]]
+    for block in list(blend.blocks):
+        if block["sdna"] != mesh or blend.code(block) != "ME":
+            continue
+        block["body"] += bytes(blend.pointer_size)
+        faces = blend.by_address(blend.read_pointer(block["body"], mpoly))
+        if faces is None:
+            continue
+        offsets = []
+        for index in range(faces["count"]):
+            face = faces["body"][index * poly_size : (index + 1) * poly_size]
+            (start,) = struct.unpack("<i", face[loopstart : loopstart + 4])
+            (corners,) = struct.unpack("<i", face[totloop : totloop + 4])
+            offsets.append(start)
+        offsets.append(start + corners)
+        address = blend.add_raw_block(struct.pack("<%di" % len(offsets), *offsets), block)
+        blend.write_pointer(block["body"], len(block["body"]) - blend.pointer_size, address)
+        blend.write_pointer(block["body"], mpoly, 0)
+        blend.blocks.remove(faces)
+    blend.version = b"306"
+
+
+def empty_and_two_slots(blend):
+    """Turns the Cylinder into an empty, and gives SuzanneNoMat's mesh a Red and a Blue slot
+    with its faces split between them"""
+    ps = blend.pointer_size
+    cylinder = blend.by_name("OBCylinder")
+    blend.rename(cylinder, "OBEmpty")
+    blend.set_field(cylinder, "Object", "type", "h", 0)
+    blend.set_field(cylinder, "Object", "empty_drawtype", "b", 2)
+    blend.set_field(cylinder, "Object", "empty_drawsize", "f", 1.0)
+    for field in ("data", "mat", "matbits"):
+        _, offset, _, _ = blend.field(blend.struct_index("Object"), field)
+        blend.write_pointer(cylinder["body"], offset, 0)
+    blend.set_field(cylinder, "Object", "totcol", "i", 0)
+    blend.set_field(cylinder, "Object", "actcol", "i", 0)
+
+    # Slots linked to the mesh like the rest of the file's, the object's own are empty
+    suzanne = blend.by_name("OBSuzanneNoMat")
+    blend.rename(suzanne, "OBSuzanneTwoMats")
+    mesh_field = blend.field(blend.struct_index("Object"), "data")[1]
+    mesh = blend.by_address(blend.read_pointer(suzanne["body"], mesh_field))
+    object_slots = blend.add_raw_block(bytes(2 * ps), suzanne)
+    matbits = blend.add_raw_block(bytes(4), suzanne)
+    for field, address in (("mat", object_slots), ("matbits", matbits)):
+        _, offset, _, _ = blend.field(blend.struct_index("Object"), field)
+        blend.write_pointer(suzanne["body"], offset, address)
+    blend.set_field(suzanne, "Object", "totcol", "i", 2)
+    blend.set_field(suzanne, "Object", "actcol", "i", 1)
+
+    materials = b"".join(
+        blend.by_name(name)["old"].to_bytes(ps, "little") for name in ("MARed", "MABlue")
+    )
+    mesh_slots = blend.add_raw_block(materials, mesh)
+    _, offset, _, _ = blend.field(blend.struct_index("Mesh"), "mat")
+    blend.write_pointer(mesh["body"], offset, mesh_slots)
+    blend.set_field(mesh, "Mesh", "totcol", "h", 2)
+
+    # The second half of the faces use the second slot
+    poly = blend.struct_index("MPoly")
+    poly_size = blend.type_lengths[blend.structs[poly][0]]
+    _, mat_nr, _, _ = blend.field(poly, "mat_nr")
+    _, mpoly, _, _ = blend.field(blend.struct_index("Mesh"), "mpoly")
+    faces = blend.by_address(blend.read_pointer(mesh["body"], mpoly))
+    for index in range(faces["count"] // 2, faces["count"]):
+        struct.pack_into("<h", faces["body"], index * poly_size + mat_nr, 1)
+
+
+class Layout:
+    """Where each struct's pointers sit with 8-byte pointers and with 4-byte ones.
+    Blender pads its structs by hand so both are laid out without any gaps."""
+
+    def __init__(self, blend):
+        self.blend = blend
+        self.struct_of_type = {
+            struct_type: index for index, (struct_type, _) in enumerate(blend.structs)
+        }
+        self._ops = {}
+
+    def ops(self, struct_index):
+        """("copy", offset 64, offset 32, size) and ("pointer", offset 64, offset 32, is array)
+        for each piece of a struct, with the fields of nested structs flattened in"""
+        if struct_index in self._ops:
+            return self._ops[struct_index]
+        blend = self.blend
+        ops = []
+        offset64 = offset32 = 0
+        for field_type, name in blend.structs[struct_index][1]:
+            name = blend.names[name]
+            count = 1
+            for dimension in re.findall(r"\[(\d+)\]", name):
+                count *= int(dimension)
+            if blend.is_pointer(name):
+                for _ in range(count):
+                    ops.append(("pointer", offset64, offset32, name.startswith("**")))
+                    offset64 += 8
+                    offset32 += 4
+            elif field_type in self.struct_of_type:
+                nested = self.struct_of_type[field_type]
+                size64, size32 = self.sizes(nested)
+                for _ in range(count):
+                    for kind, at64, at32, extra in self.ops(nested):
+                        ops.append((kind, offset64 + at64, offset32 + at32, extra))
+                    offset64 += size64
+                    offset32 += size32
+            else:
+                size = blend.type_lengths[field_type] * count
+                ops.append(("copy", offset64, offset32, size))
+                offset64 += size
+                offset32 += size
+        assert offset64 == blend.type_lengths[blend.structs[struct_index][0]]
+        self._ops[struct_index] = ops
+        self._ops[(struct_index, "sizes")] = (offset64, offset32)
+        return ops
+
+    def sizes(self, struct_index):
+        self.ops(struct_index)
+        return self._ops[(struct_index, "sizes")]
+
+
+def pointers_32_bit(blend, ids):
+    """Rewrites the file the way a 32-bit Blender would have saved it, keeping only the
+    datablocks with an ID code in `ids` and the data they lead to. Pointers to anything
+    else are cleared."""
+    assert blend.pointer_size == 8
+    layout = Layout(blend)
+    by_address = {block["old"]: block for block in blend.blocks if block["old"]}
+
+    def pointers(block):
+        """(offset, is array) of each pointer in a block of structs"""
+        size64, _ = layout.sizes(block["sdna"])
+        for element in range(block["count"]):
+            for kind, at64, _, extra in layout.ops(block["sdna"]):
+                if kind == "pointer":
+                    yield element * size64 + at64, extra
+
+    # The pointer arrays are raw data, the fields pointing at them say what they hold
+    pointer_arrays = set()
+    kept = set()
+    queue = [
+        block
+        for block in blend.blocks
+        if blend.code(block) in ids or block["code"] in (b"DNA1", b"ENDB")
+    ]
+    while queue:
+        block = queue.pop()
+        if id(block) in kept:
+            continue
+        kept.add(id(block))
+        if block["sdna"] == RAW_DATA:
+            if block["old"] not in pointer_arrays:
+                continue
+            targets = [(offset, False) for offset in range(0, len(block["body"]), 8)]
+        else:
+            targets = list(pointers(block))
+        for offset, is_array in targets:
+            target = by_address.get(blend.read_pointer(block["body"], offset))
+            if target is None:
+                continue
+            # Other kinds of datablocks stay out, along with everything only they use
+            if target["code"][2:4] == b"\0\0" and blend.code(target) not in ids:
+                continue
+            if is_array and target["old"] not in pointer_arrays:
+                pointer_arrays.add(target["old"])
+                kept.discard(id(target))
+            if id(target) not in kept:
+                queue.append(target)
+
+    addresses = {}
+    for block in blend.blocks:
+        if id(block) in kept and block["old"]:
+            addresses[block["old"]] = 0x1000 + 0x10 * len(addresses)
+
+    def convert_pointer(body, at64, out, at32):
+        address = addresses.get(int.from_bytes(body[at64 : at64 + 8], "little"), 0)
+        out[at32 : at32 + 4] = address.to_bytes(4, "little")
+
+    blocks = []
+    for block in blend.blocks:
+        if id(block) not in kept:
+            continue
+        body = block["body"]
+        if block["sdna"] == RAW_DATA and block["old"] in pointer_arrays:
+            out = bytearray(len(body) // 2)
+            for index in range(len(body) // 8):
+                convert_pointer(body, index * 8, out, index * 4)
+        elif block["sdna"] == RAW_DATA or block["code"] == b"DNA1":
+            out = bytearray(body)
+        else:
+            size64, size32 = layout.sizes(block["sdna"])
+            out = bytearray(size32 * block["count"])
+            for element in range(block["count"]):
+                for kind, at64, at32, extra in layout.ops(block["sdna"]):
+                    at64 += element * size64
+                    at32 += element * size32
+                    if kind == "pointer":
+                        convert_pointer(body, at64, out, at32)
+                    else:
+                        out[at32 : at32 + extra] = body[at64 : at64 + extra]
+        blocks.append(dict(block, old=addresses.get(block["old"], 0), body=out))
+
+    for index, (struct_type, _) in enumerate(blend.structs):
+        blend.type_lengths[struct_type] = layout.sizes(index)[1]
+    blend.blocks = blocks
+    blend.pointer_size = 4
+
+
+def main():
+    blend = Blend("demo.blend")
+    blender_3_6_faces(blend)
+    blend.write("demo-3.6-faces.blend")
+
+    blend = Blend("demo.blend")
+    empty_and_two_slots(blend)
+    blend.write("demo-empty-and-slots.blend")
+
+    blend = Blend("demo.blend")
+    pointers_32_bit(blend, {"OB", "ME", "MA", "SC", "GR", "CA", "LA", "WO", "IM"})
+    blend.write("demo-32-bit.blend")
+
+
+if __name__ == "__main__":
+    main()
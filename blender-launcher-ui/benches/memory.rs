@@ -0,0 +1,136 @@
+//! How much memory opening a large file takes when it's memory-mapped, the way the launcher
+//! opens files, compared with reading it into a buffer first.
+//!
+//! Don't expect the map to win. The blend crate copies all of its input into a buffer of its
+//! own, so the map only trades our buffer for mapped pages, which count as resident too once
+//! they've been read.
+//!
+//! `cargo bench --bench memory`
+//!
+//! The large file is demo.blend with a block of raw data added, written to the temp
+//! directory. The peak resident set size only ever grows, so each way of opening the file is
+//! measured in a fresh process, this benchmark running itself with `--measure <way> <file>`.
+
+use std::{fs, path::Path, process::Command};
+
+use blender_launcher_ui::standalone::{open_blend, peak_memory};
+
+/// Raw data added to demo.blend, so the file dwarfs everything else the process holds
+const PADDING: usize = 256 * 1024 * 1024;
+/// A `DATA` block's header with 64-bit pointers: code, size, address, SDNA index and count
+const BLOCK_HEADER: usize = 24;
+const WAYS: [&str; 2] = ["mapped", "buffered"];
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--measure") {
+        let (way, path) = (&args[index + 1], Path::new(&args[index + 2]));
+        measure(way, path);
+        return;
+    }
+
+    let path = std::env::temp_dir().join(format!(
+        "blender-launcher-{}-large.blend",
+        std::process::id()
+    ));
+    write_large_file(&path);
+    println!(
+        "{}: {:.1} MiB",
+        path.display(),
+        mebibytes(fs::metadata(&path).unwrap().len())
+    );
+    let mut peaks = Vec::new();
+    for way in WAYS {
+        let output = Command::new(std::env::current_exe().unwrap())
+            .arg("--measure")
+            .arg(way)
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "measuring {} failed", way);
+        let report = String::from_utf8_lossy(&output.stdout);
+        print!("{}", report);
+        peaks.push(
+            report
+                .lines()
+                .last()
+                .and_then(|line| line.parse::<u64>().ok()),
+        );
+    }
+    let _ = fs::remove_file(&path);
+
+    match peaks.as_slice() {
+        [Some(mapped), Some(buffered)] => println!(
+            "peak with the map minus peak with a buffer: {:+.1} MiB",
+            mebibytes(*mapped) - mebibytes(*buffered)
+        ),
+        _ => println!("peak memory isn't available on this platform"),
+    }
+}
+
+/// Opens the file one way and prints the resident memory before, after, and at its peak.
+/// The last line is the peak in bytes, for the parent process to compare.
+fn measure(way: &str, path: &Path) {
+    let before = resident_memory();
+    let blend = match way {
+        "mapped" => open_blend(path).unwrap(),
+        _ => blend::Blend::new(&fs::read(path).unwrap()[..]).unwrap(),
+    };
+    let after = resident_memory();
+    let peak = peak_memory();
+    println!(
+        "{:>8}: before {}, after {}, peak {}",
+        way,
+        describe(before),
+        describe(after),
+        describe(peak)
+    );
+    drop(blend);
+    println!("{}", peak.map_or_else(String::new, |peak| peak.to_string()));
+}
+
+/// demo.blend with a raw data block in front of its closing `ENDB` block
+fn write_large_file(path: &Path) {
+    let demo = fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/demo.blend")).unwrap();
+    let (blocks, end) = demo.split_at(demo.len() - BLOCK_HEADER);
+    assert!(end.starts_with(b"ENDB"));
+
+    let mut large = Vec::with_capacity(demo.len() + BLOCK_HEADER + PADDING);
+    large.extend_from_slice(blocks);
+    large.extend_from_slice(b"DATA");
+    large.extend_from_slice(&(PADDING as i32).to_le_bytes());
+    // An address no other block has
+    large.extend_from_slice(&u64::MAX.to_le_bytes());
+    large.extend_from_slice(&0i32.to_le_bytes());
+    large.extend_from_slice(&1i32.to_le_bytes());
+    large.resize(large.len() + PADDING, 0xab);
+    large.extend_from_slice(end);
+    fs::write(path, large).unwrap();
+}
+
+/// What's resident right now, which unlike the peak can go down again
+#[cfg(target_os = "linux")]
+fn resident_memory() -> Option<u64> {
+    // `size resident shared ...`, in pages
+    let statm = fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    // SAFETY: `sysconf` only reads a configuration value
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    Some(pages * u64::try_from(page_size).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_memory() -> Option<u64> {
+    None
+}
+
+fn mebibytes(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+fn describe(bytes: Option<u64>) -> String {
+    bytes.map_or_else(
+        || "?".to_string(),
+        |bytes| format!("{:.1} MiB", mebibytes(bytes)),
+    )
+}
@@ -0,0 +1,51 @@
+//! How long the bundled fixtures take to parse, and their meshes to build.
+//!
+//! `cargo bench --bench parse`
+
+use blender_launcher_ui::standalone::{load_object_mesh, parse_blend_file};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// The fixtures in `assets`, each with a different layout to read
+const FIXTURES: [&str; 4] = [
+    "demo.blend",
+    "demo-3.6-faces.blend",
+    "demo-32-bit.blend",
+    "test-monkey.blend",
+];
+
+/// (fixture, object) of the meshes to build, small to large and in both face layouts
+const MESHES: [(&str, &str); 4] = [
+    ("demo.blend", "Cube"),
+    ("demo.blend", "Cylinder"),
+    ("demo.blend", "Suzanne"),
+    ("demo-3.6-faces.blend", "Suzanne"),
+];
+
+fn fixture(name: &str) -> String {
+    format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for name in FIXTURES {
+        let path = fixture(name);
+        group.bench_function(name, |b| {
+            b.iter(|| parse_blend_file(black_box(&path)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn build_mesh(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_mesh");
+    for (name, object) in MESHES {
+        let path = fixture(name);
+        group.bench_function(format!("{}/{}", name, object), |b| {
+            b.iter(|| load_object_mesh(black_box(&path), black_box(object)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, build_mesh);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+//! Embeds what the About dialog reports: the commit the launcher was built from and the
+//! versions of the crates that matter most for bug reports, as locked in `Cargo.lock`.
+
+use std::{env, fs, path::PathBuf, process::Command};
+
+/// Crates whose locked version is embedded, as `BUILD_<NAME>_VERSION`
+const REPORTED_CRATES: [&str; 4] = ["bevy", "bevy_egui", "egui", "blend"];
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short=10", "HEAD"])
+        .current_dir(&manifest_dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=BUILD_GIT_HASH={git_hash}");
+    // Paths that don't exist (e.g. building from a source tarball) are re-checked every build
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+
+    // The workspace's lock file wins, it's the one cargo uses when building from the root
+    let lock = ["../Cargo.lock", "Cargo.lock"]
+        .iter()
+        .map(|path| manifest_dir.join(path))
+        .find(|path| path.is_file());
+    let contents = lock
+        .as_ref()
+        .and_then(|lock| fs::read_to_string(lock).ok())
+        .unwrap_or_default();
+    if let Some(lock) = &lock {
+        println!("cargo:rerun-if-changed={}", lock.display());
+    }
+    for name in REPORTED_CRATES {
+        let version = locked_version(&contents, name).unwrap_or("unknown");
+        println!(
+            "cargo:rustc-env=BUILD_{}_VERSION={version}",
+            name.to_uppercase()
+        );
+    }
+}
+
+/// The version of the first `[[package]]` with this name. Packages are sorted by name and
+/// version, so a crate that's in the graph twice reports its oldest copy.
+fn locked_version<'a>(lock: &'a str, name: &str) -> Option<&'a str> {
+    let mut lines = lock.lines();
+    let package = format!("name = \"{name}\"");
+    lines.find(|line| *line == package)?;
+    lines
+        .next()?
+        .strip_prefix("version = \"")?
+        .strip_suffix('"')
+}
@@ -0,0 +1,127 @@
+//! The launcher inside another app, which keeps its own camera and only takes the file list
+//! and the inspector. Turn the camera with the arrow keys, and watch the log for what the
+//! launcher reports.
+//!
+//! `cargo run --example embed -- path/to/file.blend`
+
+use bevy::prelude::*;
+use blender_launcher_ui::{
+    overlay_camera, AppState, BlenderLauncherPlugin, FileAdded, FileRemoved, LauncherPanels,
+    MetadataLoaded, ObjectDespawned, ObjectSpawned, SelectionChanged,
+};
+
+/// Radians per second the arrow keys turn the camera
+const TURN_SPEED: f32 = 1.5;
+
+#[derive(Component)]
+struct HostCamera;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(BlenderLauncherPlugin {
+            panels: LauncherPanels {
+                top_bar: false,
+                status_bar: false,
+                ..Default::default()
+            },
+            own_camera: false,
+        })
+        .add_startup_system(setup)
+        .add_system(turn_camera)
+        .add_system(print_launcher_events)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut app_state: ResMut<AppState>) {
+    commands
+        .spawn((
+            HostCamera,
+            Camera3dBundle {
+                transform: Transform::from_xyz(0.0, 3.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
+                ..Default::default()
+            },
+        ))
+        // So the launcher's origin markers and gizmo show in this camera too
+        .with_children(|camera| {
+            camera.spawn(overlay_camera());
+        });
+
+    // Parsed in the background, `MetadataLoaded` says when each is done
+    for path in std::env::args().skip(1) {
+        app_state.add_file(path);
+    }
+}
+
+/// Where a host app would take over, e.g. importing the spawned asset into its project
+fn print_launcher_events(
+    app_state: Res<AppState>,
+    mut added: EventReader<FileAdded>,
+    mut removed: EventReader<FileRemoved>,
+    mut loaded: EventReader<MetadataLoaded>,
+    mut spawned: EventReader<ObjectSpawned>,
+    mut despawned: EventReader<ObjectDespawned>,
+    mut selections: EventReader<SelectionChanged>,
+) {
+    for FileAdded { file, path } in added.iter() {
+        info!(?file, path = path.as_str(), "file added");
+    }
+    for FileRemoved { file, path } in removed.iter() {
+        info!(?file, path = path.as_str(), "file removed");
+    }
+    for MetadataLoaded(file) in loaded.iter() {
+        let objects = app_state.file(*file).map_or(0, |file| file.objects().len());
+        info!(?file, objects, "metadata loaded");
+    }
+    for ObjectSpawned {
+        file,
+        object,
+        entity,
+    } in spawned.iter()
+    {
+        info!(?file, object = object.as_str(), ?entity, "object spawned");
+    }
+    for ObjectDespawned {
+        file,
+        object,
+        entity,
+    } in despawned.iter()
+    {
+        info!(?file, object = object.as_str(), ?entity, "object despawned");
+    }
+    for selection in selections.iter() {
+        info!(file = ?selection.file, object = ?selection.object, "selection changed");
+    }
+}
+
+/// Orbits the host camera around the origin
+fn turn_camera(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    mut cameras: Query<&mut Transform, With<HostCamera>>,
+) {
+    let mut turn = Vec2::ZERO;
+    if keys.pressed(KeyCode::Left) {
+        turn.x -= 1.0;
+    }
+    if keys.pressed(KeyCode::Right) {
+        turn.x += 1.0;
+    }
+    if keys.pressed(KeyCode::Up) {
+        turn.y -= 1.0;
+    }
+    if keys.pressed(KeyCode::Down) {
+        turn.y += 1.0;
+    }
+    if turn == Vec2::ZERO {
+        return;
+    }
+    let turn = turn * TURN_SPEED * time.delta_seconds();
+    for mut transform in cameras.iter_mut() {
+        let pitch_axis = transform.right();
+        transform.rotate_around(
+            Vec3::ZERO,
+            Quat::from_rotation_y(turn.x) * Quat::from_axis_angle(pitch_axis, turn.y),
+        );
+    }
+}
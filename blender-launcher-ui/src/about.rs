@@ -0,0 +1,146 @@
+use bevy::{prelude::*, render::renderer::RenderAdapterInfo};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    commands::{Action, ActionEvent},
+    i18n::t,
+    toasts::{Severity, Toasts},
+};
+
+/// Embedded by the build script
+const GIT_HASH: &str = env!("BUILD_GIT_HASH");
+const DEPENDENCIES: [(&str, &str); 4] = [
+    ("bevy", env!("BUILD_BEVY_VERSION")),
+    ("bevy_egui", env!("BUILD_BEVY_EGUI_VERSION")),
+    ("egui", env!("BUILD_EGUI_VERSION")),
+    ("blend", env!("BUILD_BLEND_VERSION")),
+];
+
+/// Whether Help ▸ About is open
+#[derive(Default, Resource)]
+struct AboutDialog {
+    open: bool,
+}
+
+pub struct AboutPlugin;
+
+impl Plugin for AboutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AboutDialog>()
+            .add_system(show_about.run_if(on_event::<ActionEvent>()))
+            .add_system(
+                about_ui
+                    .after(show_about)
+                    .run_if(|about: Res<AboutDialog>| about.open),
+            );
+    }
+}
+
+fn show_about(mut actions: EventReader<ActionEvent>, mut about: ResMut<AboutDialog>) {
+    if actions
+        .iter()
+        .any(|ActionEvent(action)| *action == Action::ShowAbout)
+    {
+        about.open = true;
+    }
+}
+
+/// Everything a bug report needs, one `name: value` line each
+fn diagnostics(adapter: Option<&RenderAdapterInfo>) -> Vec<(&'static str, String)> {
+    let mut lines = vec![
+        ("blender-launcher", env!("CARGO_PKG_VERSION").to_string()),
+        ("commit", GIT_HASH.to_string()),
+    ];
+    lines.extend(
+        DEPENDENCIES
+            .iter()
+            .map(|(name, version)| (*name, version.to_string())),
+    );
+    let gpu = adapter.map_or_else(
+        || "unknown".to_string(),
+        |adapter| {
+            format!(
+                "{} ({:?}, {:?}, {} {})",
+                adapter.name,
+                adapter.backend,
+                adapter.device_type,
+                adapter.driver,
+                adapter.driver_info
+            )
+        },
+    );
+    lines.push(("gpu", gpu));
+    lines.push((
+        "os",
+        format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+    ));
+    lines
+}
+
+/// A modal with the versions, and a button that copies them as a block to paste into an issue
+fn about_ui(
+    mut contexts: EguiContexts,
+    mut about: ResMut<AboutDialog>,
+    mut toasts: ResMut<Toasts>,
+    adapter: Option<Res<RenderAdapterInfo>>,
+) {
+    let ctx = contexts.ctx_mut();
+    let lines = diagnostics(adapter.as_deref());
+    let mut close = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+
+    // Clicks outside the modal close it, like the Escape key
+    let screen_rect = ctx.screen_rect();
+    egui::Area::new("about_backdrop")
+        .order(egui::Order::Middle)
+        .fixed_pos(screen_rect.min)
+        .show(ctx, |ui| {
+            close |= ui
+                .allocate_response(screen_rect.size(), egui::Sense::click())
+                .clicked();
+            ui.painter()
+                .rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(96));
+        });
+
+    egui::Area::new("about_modal")
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            egui::Frame::window(ui.style()).show(ui, |ui| {
+                ui.heading(t!("app.name"));
+                ui.add_space(4.0);
+                egui::Grid::new("about_versions")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (name, value) in lines.iter() {
+                            ui.label(*name);
+                            ui.monospace(value);
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(t!("about.copy"))
+                        .on_hover_text(t!("about.copy.hint"))
+                        .clicked()
+                    {
+                        let block = lines
+                            .iter()
+                            .map(|(name, value)| format!("{name}: {value}"))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        // Fenced so it stays one block when pasted into a Markdown issue
+                        let block = format!("```\n{block}\n```");
+                        ui.output_mut(|output| output.copied_text = block);
+                        toasts.push(Severity::Success, t!("about.copied"));
+                    }
+                    close |= ui.button(t!("about.close")).clicked();
+                });
+            });
+        });
+
+    if close {
+        about.open = false;
+    }
+}
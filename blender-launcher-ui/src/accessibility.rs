@@ -0,0 +1,142 @@
+use std::{
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
+
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::t;
+
+/// Whether the UI animates: egui's own transitions and the toast fades
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Motion {
+    Full,
+    /// Snaps to the end of every animation instead
+    Reduced,
+    /// Follows the OS preference where we can detect it, otherwise full
+    #[default]
+    System,
+}
+
+impl Motion {
+    pub const ALL: [Motion; 3] = [Motion::Full, Motion::Reduced, Motion::System];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Motion::Full => t!("motion.full"),
+            Motion::Reduced => t!("motion.reduced"),
+            Motion::System => t!("motion.system"),
+        }
+    }
+
+    pub fn is_reduced(&self) -> bool {
+        match self {
+            Motion::Full => false,
+            Motion::Reduced => true,
+            Motion::System => system_prefers_reduced_motion(),
+        }
+    }
+}
+
+/// Whether the OS asks apps to cut down on animation, `false` where we can't tell.
+/// Read once, since on most platforms it means running a command.
+pub fn system_prefers_reduced_motion() -> bool {
+    static REDUCED: OnceLock<bool> = OnceLock::new();
+    *REDUCED.get_or_init(|| detect_reduced_motion().unwrap_or(false))
+}
+
+/// "Reduce motion" in the accessibility settings
+#[cfg(target_os = "macos")]
+fn detect_reduced_motion() -> Option<bool> {
+    let output = Command::new("defaults")
+        .args(["read", "com.apple.universalaccess", "reduceMotion"])
+        .output()
+        .ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).trim() == "1")
+}
+
+/// "Animation effects" turned off, which Windows keeps in the registry
+#[cfg(target_os = "windows")]
+fn detect_reduced_motion() -> Option<bool> {
+    let output = Command::new("reg")
+        .args([
+            "query",
+            r"HKCU\Control Panel\Desktop\WindowMetrics",
+            "/v",
+            "MinAnimate",
+        ])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(stdout.split_whitespace().last()? == "0")
+}
+
+/// GNOME's animation switch, which most other desktops also set
+#[cfg(all(unix, not(target_os = "macos")))]
+fn detect_reduced_motion() -> Option<bool> {
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "enable-animations"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim() == "false")
+}
+
+#[cfg(not(any(unix, windows)))]
+fn detect_reduced_motion() -> Option<bool> {
+    None
+}
+
+// Read by every status indicator, so global like the language in `i18n`
+static COLOR_BLIND_PALETTE: AtomicBool = AtomicBool::new(false);
+
+/// Swaps the red and green of status indicators for orange and blue
+pub fn set_color_blind_palette(enabled: bool) {
+    COLOR_BLIND_PALETTE.store(enabled, Ordering::Relaxed);
+}
+
+/// What a status indicator reports. Each has its own shape as well as its own color,
+/// so telling them apart never comes down to color alone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl Status {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Status::Ok => "✔",
+            Status::Warning => "⚠",
+            Status::Error => "✖",
+        }
+    }
+
+    pub fn color(&self) -> egui::Color32 {
+        let color_blind = COLOR_BLIND_PALETTE.load(Ordering::Relaxed);
+        match (self, color_blind) {
+            (Status::Ok, false) => egui::Color32::LIGHT_GREEN,
+            (Status::Error, false) => egui::Color32::LIGHT_RED,
+            // Okabe-Ito sky blue and vermillion, which stay apart under every common deficiency
+            (Status::Ok, true) => egui::Color32::from_rgb(86, 180, 233),
+            (Status::Error, true) => egui::Color32::from_rgb(230, 120, 30),
+            (Status::Warning, _) => egui::Color32::YELLOW,
+        }
+    }
+}
+
+/// The status's shape followed by `text`, both in the status's color
+pub fn status_label(
+    ui: &mut egui::Ui,
+    status: Status,
+    text: impl std::fmt::Display,
+) -> egui::Response {
+    ui.colored_label(status.color(), format!("{} {}", status.icon(), text))
+}
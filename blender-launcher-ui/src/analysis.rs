@@ -0,0 +1,346 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    asset::HandleId,
+    prelude::*,
+    render::mesh::{Indices, VertexAttributeValues},
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use futures_lite::future;
+
+use crate::{decimate::FullMesh, AppState, BlenderPreviewObject};
+
+/// Vertices closer than this (but not bit-identical) count as duplicates
+const DUPLICATE_EPSILON: f32 = 1e-4;
+/// Triangles with less area than this count as degenerate
+const ZERO_AREA_EPSILON: f32 = 1e-10;
+
+/// Geometry health results for a single mesh asset
+#[derive(Clone, Debug, Default)]
+pub struct MeshAnalysis {
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+    /// From the object's [`crate::metadata::MeshStats`], the runtime mesh only has the
+    /// vertices its faces use
+    pub loose_vertices: usize,
+    pub non_manifold_edges: usize,
+    pub zero_area_triangles: usize,
+    pub duplicate_vertices: usize,
+    /// Center of the bounding box, relative to the object's origin
+    pub bounds_center: Vec3,
+    pub bounds_half_size: Vec3,
+}
+
+impl MeshAnalysis {
+    /// Whether the origin sits outside the geometry's bounding box
+    pub fn origin_outside_bounds(&self) -> bool {
+        self.bounds_center.abs().cmpgt(self.bounds_half_size).any()
+    }
+
+    /// A mesh "passes" when none of the checks found anything
+    pub fn passed(&self) -> bool {
+        self.loose_vertices == 0
+            && self.non_manifold_edges == 0
+            && self.zero_area_triangles == 0
+            && self.duplicate_vertices == 0
+    }
+}
+
+/// Analysis results (and in-flight analysis tasks) keyed by mesh handle,
+/// so re-selecting a mesh doesn't recompute anything
+#[derive(Default, Resource)]
+pub struct MeshAnalysisCache {
+    results: HashMap<HandleId, MeshAnalysis>,
+    pending: HashMap<HandleId, Task<MeshAnalysis>>,
+}
+
+impl MeshAnalysisCache {
+    pub fn get(&self, handle: &Handle<Mesh>) -> Option<&MeshAnalysis> {
+        self.results.get(&handle.id())
+    }
+
+    pub fn is_pending(&self, handle: &Handle<Mesh>) -> bool {
+        self.pending.contains_key(&handle.id())
+    }
+
+    /// Forgets a mesh's analysis so it runs again, e.g. after the mesh was reloaded
+    pub fn invalidate(&mut self, handle: &Handle<Mesh>) {
+        self.results.remove(&handle.id());
+        self.pending.remove(&handle.id());
+    }
+}
+
+pub struct MeshAnalysisPlugin;
+
+impl Plugin for MeshAnalysisPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeshAnalysisCache>()
+            .add_system(queue_mesh_analysis)
+            .add_system(poll_mesh_analysis);
+    }
+}
+
+/// Kicks off a background analysis for every previewed mesh that finished loading
+fn queue_mesh_analysis(
+    mut cache: ResMut<MeshAnalysisCache>,
+    meshes: Res<Assets<Mesh>>,
+    app_state: Res<AppState>,
+    preview_meshes: Query<(&Handle<Mesh>, Option<&FullMesh>, &BlenderPreviewObject)>,
+) {
+    let thread_pool = AsyncComputeTaskPool::get();
+
+    for (handle, full_mesh, preview) in preview_meshes.iter() {
+        // Decimated previews are checked on their original mesh
+        let handle = full_mesh.map_or(handle, |full_mesh| &full_mesh.handle);
+        let id = handle.id();
+        if cache.results.contains_key(&id) || cache.pending.contains_key(&id) {
+            continue;
+        }
+
+        // The mesh might still be loading, we'll try again next frame
+        let Some(mesh) = meshes.get(handle) else {
+            continue;
+        };
+
+        // We copy the data out so the task doesn't need access to the asset storage
+        let positions = mesh_positions(mesh);
+        let indices = mesh_indices(mesh, positions.len());
+
+        let loose_vertices = app_state
+            .file(preview.file)
+            .and_then(|file| file.meshes.get(preview.mesh))
+            .and_then(|object| object.stats.as_ref())
+            .map_or(0, |stats| stats.loose_vertices);
+
+        let task = thread_pool.spawn(async move {
+            MeshAnalysis {
+                loose_vertices,
+                ..analyze_mesh(&positions, &indices)
+            }
+        });
+        cache.pending.insert(id, task);
+    }
+}
+
+/// Moves finished analysis tasks into the results cache
+fn poll_mesh_analysis(mut cache: ResMut<MeshAnalysisCache>) {
+    let mut finished = Vec::new();
+
+    for (id, task) in cache.pending.iter_mut() {
+        if let Some(analysis) = future::block_on(future::poll_once(task)) {
+            finished.push((*id, analysis));
+        }
+    }
+
+    for (id, analysis) in finished {
+        cache.pending.remove(&id);
+        cache.results.insert(id, analysis);
+    }
+}
+
+pub fn mesh_positions(mesh: &Mesh) -> Vec<[f32; 3]> {
+    match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(positions)) => positions.clone(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn mesh_indices(mesh: &Mesh, vertex_count: usize) -> Vec<u32> {
+    match mesh.indices() {
+        Some(Indices::U32(indices)) => indices.clone(),
+        Some(Indices::U16(indices)) => indices.iter().map(|index| *index as u32).collect(),
+        // Non-indexed meshes are just a flat list of triangles
+        None => (0..vertex_count as u32).collect(),
+    }
+}
+
+/// Runs the geometry checks over a triangle list, all but the loose vertices.
+///
+/// Runtime meshes split vertices along UV seams and hard edges, so we first weld
+/// bit-identical positions back together before looking at the topology.
+/// Duplicate vertices are then the welded vertices that are *almost* on top of each other.
+pub fn analyze_mesh(positions: &[[f32; 3]], indices: &[u32]) -> MeshAnalysis {
+    // Weld identical positions so split vertices share one topological vertex
+    let mut welded_ids: HashMap<[u32; 3], u32> = HashMap::new();
+    let mut welded_positions: Vec<Vec3> = Vec::new();
+    let remap: Vec<u32> = positions
+        .iter()
+        .map(|position| {
+            let key = position.map(f32::to_bits);
+            *welded_ids.entry(key).or_insert_with(|| {
+                welded_positions.push(Vec3::from_array(*position));
+                welded_positions.len() as u32 - 1
+            })
+        })
+        .collect();
+
+    let (min, max) = welded_positions.iter().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), position| (min.min(*position), max.max(*position)),
+    );
+    let (bounds_center, bounds_half_size) = if welded_positions.is_empty() {
+        (Vec3::ZERO, Vec3::ZERO)
+    } else {
+        ((min + max) * 0.5, (max - min) * 0.5)
+    };
+    let mut analysis = MeshAnalysis {
+        vertex_count: welded_positions.len(),
+        triangle_count: indices.len() / 3,
+        bounds_center,
+        bounds_half_size,
+        ..Default::default()
+    };
+
+    let mut edge_faces: HashMap<(u32, u32), u32> = HashMap::new();
+
+    for triangle in indices.chunks_exact(3) {
+        let Some(corners) = triangle
+            .iter()
+            .map(|index| remap.get(*index as usize).copied())
+            .collect::<Option<Vec<u32>>>()
+        else {
+            // Out of range indices are skipped rather than crashing the analysis
+            continue;
+        };
+
+        let [a, b, c] = [0, 1, 2].map(|i| welded_positions[corners[i] as usize]);
+        if (b - a).cross(c - a).length_squared() * 0.25 <= ZERO_AREA_EPSILON {
+            analysis.zero_area_triangles += 1;
+        }
+
+        for (start, end) in [(0, 1), (1, 2), (2, 0)] {
+            let edge = (
+                corners[start].min(corners[end]),
+                corners[start].max(corners[end]),
+            );
+            *edge_faces.entry(edge).or_insert(0) += 1;
+        }
+    }
+
+    analysis.non_manifold_edges = edge_faces.values().filter(|faces| **faces > 2).count();
+    analysis.duplicate_vertices = count_duplicate_vertices(&welded_positions);
+
+    analysis
+}
+
+/// Counts vertices that have another vertex within `DUPLICATE_EPSILON`.
+/// We bucket vertices into a grid of epsilon-sized cells and only compare neighbouring cells.
+fn count_duplicate_vertices(positions: &[Vec3]) -> usize {
+    let cell = |position: Vec3| (position / DUPLICATE_EPSILON).floor().as_ivec3();
+
+    let mut grid: HashMap<IVec3, Vec<usize>> = HashMap::new();
+    for (index, position) in positions.iter().enumerate() {
+        grid.entry(cell(*position)).or_default().push(index);
+    }
+
+    let mut duplicates = HashSet::new();
+    for (index, position) in positions.iter().enumerate() {
+        let center = cell(*position);
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    let Some(neighbours) = grid.get(&(center + IVec3::new(x, y, z))) else {
+                        continue;
+                    };
+                    for other in neighbours {
+                        if *other != index
+                            && position.distance(positions[*other]) <= DUPLICATE_EPSILON
+                        {
+                            duplicates.insert(index);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    duplicates.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit square made of two triangles, split along its diagonal like a UV seam would
+    fn square() -> (Vec<[f32; 3]>, Vec<u32>) {
+        let positions = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            // The diagonal's ends again, for the second triangle
+            [0.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+        ];
+        (positions, vec![0, 1, 2, 4, 5, 3])
+    }
+
+    #[test]
+    fn clean_meshes_pass() {
+        let (positions, indices) = square();
+        let analysis = analyze_mesh(&positions, &indices);
+        assert_eq!((analysis.vertex_count, analysis.triangle_count), (4, 2));
+        assert_eq!(analysis.bounds_center, Vec3::new(0.5, 0.5, 0.0));
+        assert!(!analysis.origin_outside_bounds());
+        assert!(analysis.passed(), "{:?}", analysis);
+    }
+
+    #[test]
+    fn edges_shared_by_more_than_two_faces_are_non_manifold() {
+        let (mut positions, mut indices) = square();
+        // A fin standing up from the diagonal
+        positions.push([0.5, 0.5, 1.0]);
+        indices.extend([0, 2, 6]);
+        let analysis = analyze_mesh(&positions, &indices);
+        assert_eq!(analysis.non_manifold_edges, 1);
+        assert!(!analysis.passed());
+    }
+
+    #[test]
+    fn triangles_without_area_are_degenerate() {
+        let (mut positions, mut indices) = square();
+        // On the bottom edge, so it's a line
+        positions.push([0.5, 0.0, 0.0]);
+        indices.extend([0, 6, 1]);
+        let analysis = analyze_mesh(&positions, &indices);
+        assert_eq!(analysis.zero_area_triangles, 1);
+        assert_eq!(analysis.non_manifold_edges, 0);
+        assert!(!analysis.passed());
+    }
+
+    #[test]
+    fn nearly_identical_vertices_are_duplicates() {
+        let (mut positions, mut indices) = square();
+        positions.push([1.0, 0.0, DUPLICATE_EPSILON * 0.5]);
+        indices.extend([1, 6, 2]);
+        let analysis = analyze_mesh(&positions, &indices);
+        // Each is the other's duplicate
+        assert_eq!(analysis.duplicate_vertices, 2);
+        // Bit-identical ones are welded instead
+        let (positions, indices) = square();
+        assert_eq!(analyze_mesh(&positions, &indices).duplicate_vertices, 0);
+    }
+
+    #[test]
+    fn loose_vertices_come_from_the_file() {
+        // A vertex no triangle uses isn't loose on its own, the file's edges decide that
+        let (mut positions, indices) = square();
+        positions.push([5.0, 5.0, 5.0]);
+        let analysis = analyze_mesh(&positions, &indices);
+        assert_eq!(analysis.loose_vertices, 0);
+        let analysis = MeshAnalysis {
+            loose_vertices: 1,
+            ..analysis
+        };
+        assert!(!analysis.passed());
+    }
+
+    #[test]
+    fn out_of_range_indices_are_skipped() {
+        let (positions, mut indices) = square();
+        indices.extend([0, 1, 99]);
+        let analysis = analyze_mesh(&positions, &indices);
+        assert_eq!(analysis.triangle_count, 3);
+        assert!(analysis.passed());
+    }
+}
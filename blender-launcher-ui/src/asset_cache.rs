@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use bevy::{asset::HandleId, asset::LoadState, prelude::*};
+
+use crate::{
+    asset_paths::mesh_asset_path,
+    mesh_builder::MeshLoader,
+    shading::{lit_material, set_lit_material, OriginalMaterial},
+    AppState, BlendObject, BlenderPreviewObject, File,
+};
+
+/// Blender's default material display color, for materials we have no color for
+const DEFAULT_DISPLAY_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 1.0];
+
+/// Strong handles to every mesh and material we've loaded from a blend file,
+/// keyed by (file path, datablock name).
+/// Holding on to them keeps the assets loaded, so respawning is instant.
+#[derive(Default, Resource)]
+pub struct AssetCache {
+    meshes: HashMap<(String, String), Handle<Mesh>>,
+    /// Meshes built from parsed data rather than loaded, e.g. curve outlines.
+    /// They're kept when the loader changes.
+    generated: HashMap<(String, String), Handle<Mesh>>,
+    /// Which loader new meshes come from, following the settings
+    loader: MeshLoader,
+    /// Meshes handed out for the built-in loader that it hasn't started reading yet
+    pending_builds: Vec<(HandleId, String, String)>,
+    materials: HashMap<(String, String), Handle<StandardMaterial>>,
+    /// Materials made from display colors, keyed by the color's bits
+    colors: HashMap<[u32; 3], Handle<StandardMaterial>>,
+    /// Materials from a file that haven't finished loading, to fall back if they fail
+    loading: HashMap<HandleId, (String, String)>,
+}
+
+impl AssetCache {
+    /// Returns the cached handle for an object's mesh, loading it on first use.
+    /// With the built-in loader the handle is empty until the mesh has been read,
+    /// which happens in the background like an asset load.
+    pub fn mesh(
+        &mut self,
+        asset_server: &AssetServer,
+        path: &str,
+        object: &BlendObject,
+    ) -> Handle<Mesh> {
+        self.mesh_named(asset_server, path, &object.name, object.mesh_datablock())
+    }
+
+    /// `mesh` for an object known by name. The built-in loader finds the mesh through the
+    /// object, bevy_blender by the name of the mesh datablock.
+    pub fn mesh_named(
+        &mut self,
+        asset_server: &AssetServer,
+        path: &str,
+        name: &str,
+        datablock: &str,
+    ) -> Handle<Mesh> {
+        let key = (path.to_string(), name.to_string());
+        if let Some(handle) = self.generated.get(&key) {
+            return handle.clone();
+        }
+        if let Some(handle) = self.meshes.get(&key) {
+            return handle.clone();
+        }
+        let handle = match self.loader {
+            MeshLoader::BevyBlender => asset_server.load(mesh_asset_path(path, datablock)),
+            MeshLoader::BuiltIn => {
+                let id = HandleId::random::<Mesh>();
+                self.pending_builds.push((id, key.0.clone(), key.1.clone()));
+                asset_server.get_handle(id)
+            }
+        };
+        self.meshes.insert(key, handle.clone());
+        handle
+    }
+
+    pub fn has_mesh(&self, path: &str, name: &str) -> bool {
+        self.generated
+            .contains_key(&(path.to_string(), name.to_string()))
+    }
+
+    /// Caches a mesh we built ourselves, so spawns use it instead of loading one
+    pub fn insert_mesh(&mut self, path: &str, name: &str, handle: Handle<Mesh>) {
+        self.generated
+            .insert((path.to_string(), name.to_string()), handle);
+    }
+
+    pub fn loader(&self) -> MeshLoader {
+        self.loader
+    }
+
+    /// Switches the loader. Cached meshes are dropped, so the next spawn of each object goes
+    /// through the new loader, while the previews already spawned keep theirs.
+    pub fn set_loader(&mut self, loader: MeshLoader) {
+        self.loader = loader;
+        self.meshes.clear();
+        self.pending_builds.clear();
+    }
+
+    pub fn has_pending_builds(&self) -> bool {
+        !self.pending_builds.is_empty()
+    }
+
+    pub fn take_pending_builds(&mut self) -> Vec<(HandleId, String, String)> {
+        std::mem::take(&mut self.pending_builds)
+    }
+
+    /// Returns the cached handle for a material, loading it on first use
+    pub fn material(
+        &mut self,
+        asset_server: &AssetServer,
+        path: &str,
+        name: &str,
+    ) -> Handle<StandardMaterial> {
+        let key = (path.to_string(), name.to_string());
+        if let Some(handle) = self.materials.get(&key) {
+            return handle.clone();
+        }
+        let handle: Handle<StandardMaterial> = asset_server.load(format!("{}#MA{}", path, name));
+        self.loading.insert(handle.id(), key.clone());
+        self.materials.insert(key, handle.clone());
+        handle
+    }
+
+    /// A plain opaque material in a viewport display color, shared by everything with that color
+    pub fn color_material(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+        [r, g, b, _]: [f32; 4],
+    ) -> Handle<StandardMaterial> {
+        self.colors
+            .entry([r, g, b].map(f32::to_bits))
+            .or_insert_with(|| materials.add(StandardMaterial::from(Color::rgb_linear(r, g, b))))
+            .clone()
+    }
+
+    /// What an object previews with: the material in its first slot, or its
+    /// viewport display color when none of its slots hold a material from the file
+    pub fn object_material(
+        &mut self,
+        asset_server: &AssetServer,
+        materials: &mut Assets<StandardMaterial>,
+        file: &File,
+        object: &BlendObject,
+    ) -> Handle<StandardMaterial> {
+        match object
+            .materials
+            .iter()
+            .find(|material| file.materials.contains(*material))
+        {
+            Some(material) => self.material(asset_server, &file.path, material),
+            None => self.color_material(materials, object.color),
+        }
+    }
+
+    pub fn is_loading_materials(&self) -> bool {
+        !self.loading.is_empty()
+    }
+
+    /// Drops every handle loaded from `path`, e.g. when the file is removed from the list
+    pub fn invalidate_file(&mut self, path: &str) {
+        self.meshes.retain(|(file, _), _| file != path);
+        self.generated.retain(|(file, _), _| file != path);
+        self.pending_builds.retain(|(_, file, _)| file != path);
+        self.materials.retain(|(file, _), _| file != path);
+        self.loading.retain(|_, (file, _)| file != path);
+    }
+}
+
+/// Swaps materials the blend loader couldn't read for the material's viewport display color,
+/// so the previews using them stay distinguishable
+pub fn fall_back_to_display_colors(
+    asset_server: Res<AssetServer>,
+    app_state: Res<AppState>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut previews: Query<
+        (&mut Handle<StandardMaterial>, Option<&mut OriginalMaterial>),
+        With<BlenderPreviewObject>,
+    >,
+) {
+    let mut failed = Vec::new();
+    asset_cache
+        .loading
+        .retain(|handle, key| match asset_server.get_load_state(*handle) {
+            LoadState::Loaded => false,
+            LoadState::Failed => {
+                failed.push((*handle, key.clone()));
+                false
+            }
+            _ => true,
+        });
+
+    for (handle, (path, name)) in failed {
+        warn!(
+            path,
+            material = name,
+            "couldn't load material, using its display color"
+        );
+        let color = app_state
+            .files
+            .iter()
+            .find(|file| file.path == path)
+            .and_then(|file| file.material_colors.get(&name))
+            .copied()
+            .unwrap_or(DEFAULT_DISPLAY_COLOR);
+        let fallback = asset_cache.color_material(&mut materials, color);
+        asset_cache.materials.insert((path, name), fallback.clone());
+        for (mut material, mut original) in previews.iter_mut() {
+            if lit_material(&material, original.as_deref()).id() == handle {
+                set_lit_material(&mut material, original.as_deref_mut(), fallback.clone());
+            }
+        }
+    }
+}
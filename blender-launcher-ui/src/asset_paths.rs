@@ -0,0 +1,365 @@
+use std::{collections::HashSet, path::Path};
+
+use bevy::{
+    asset::LoadState,
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+};
+use bevy_egui::egui;
+use futures_lite::future;
+
+use crate::{
+    accessibility::{status_label, Status},
+    i18n::t,
+    metadata::{id_name, open_blend, ObjectKind, OB_MESH},
+    AppState, BlendObject, File, FileId,
+};
+
+/// Frames a loaded file gets to hand over a labeled mesh before we count it as missing
+const LABEL_GRACE_FRAMES: u32 = 10;
+
+/// The path bevy_blender loads an object's mesh from, what a game would pass to `load`
+pub fn mesh_asset_path(path: &str, name: &str) -> String {
+    format!("{}#ME{}", path, name)
+}
+
+/// A file's path the way a game loads it: relative to `root`, with forward slashes like
+/// Bevy's asset paths. `None` when there's a root the file isn't under.
+/// Without a root it's the path as is.
+pub fn game_path(path: &str, root: Option<&Path>) -> Option<String> {
+    let Some(root) = root else {
+        return Some(path.to_string());
+    };
+    let relative = Path::new(path).strip_prefix(root).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// The `blender_mesh!` call that loads an object's mesh, for pasting into a game
+fn mesh_snippet(path: &str, name: &str) -> String {
+    format!("blender_mesh!({:?}, {:?})", path, name)
+}
+
+/// What validating an object's asset path found
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssetCheck {
+    Checking,
+    Passed,
+    /// There's no mesh datablock by the name we load. `mesh` is what the object's mesh is
+    /// called instead, which usually means it was renamed since the file was parsed.
+    NotFound {
+        mesh: Option<String>,
+    },
+    /// The object isn't a mesh, so there's no mesh to load by its name
+    WrongType(ObjectKind),
+    /// The file has the mesh, but bevy_blender couldn't load it
+    LoadFailed,
+}
+
+impl AssetCheck {
+    pub fn failed(&self) -> bool {
+        !matches!(self, AssetCheck::Checking | AssetCheck::Passed)
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            AssetCheck::Checking => t!("asset_path.checking").to_string(),
+            AssetCheck::Passed => t!("asset_path.passed").to_string(),
+            AssetCheck::NotFound { mesh: Some(mesh) } => {
+                t!("asset_path.renamed", mesh = mesh)
+            }
+            AssetCheck::NotFound { mesh: None } => t!("asset_path.not_found").to_string(),
+            AssetCheck::WrongType(kind) => t!("asset_path.wrong_type", kind = kind.label()),
+            AssetCheck::LoadFailed => t!("asset_path.load_failed").to_string(),
+        }
+    }
+}
+
+/// Checks the asset paths of some of a file's objects
+pub struct ValidateAssetPaths {
+    pub file: FileId,
+    pub objects: Vec<String>,
+}
+
+/// What the file says about each object, before trying the actual load.
+/// `None` comes with the mesh datablock to load.
+type FileChecks = Vec<(String, String, Option<AssetCheck>)>;
+
+#[derive(Default, Resource)]
+struct PendingValidations {
+    /// Files being read to see which names have a mesh
+    reads: Vec<(FileId, Task<FileChecks>)>,
+    /// Objects whose mesh the file has, being loaded through bevy_blender,
+    /// with how many frames the file has been loaded for
+    loads: Vec<(FileId, String, Handle<Mesh>, u32)>,
+}
+
+pub struct AssetPathsPlugin;
+
+impl Plugin for AssetPathsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ValidateAssetPaths>()
+            .init_resource::<PendingValidations>()
+            .add_system(start_validations.run_if(on_event::<ValidateAssetPaths>()))
+            .add_system(
+                finish_validations.run_if(|pending: Res<PendingValidations>| {
+                    !pending.reads.is_empty() || !pending.loads.is_empty()
+                }),
+            );
+    }
+}
+
+fn start_validations(
+    mut events: EventReader<ValidateAssetPaths>,
+    mut app_state: ResMut<AppState>,
+    mut pending: ResMut<PendingValidations>,
+) {
+    let thread_pool = IoTaskPool::get();
+    for event in events.iter() {
+        let Some(file) = app_state.file_mut(event.file) else {
+            continue;
+        };
+        let mut objects = Vec::new();
+        for name in event.objects.iter() {
+            file.asset_checks.insert(name.clone(), AssetCheck::Checking);
+            let datablock = file
+                .meshes
+                .iter()
+                .find(|object| object.name == *name)
+                .map_or(name.as_str(), BlendObject::mesh_datablock);
+            objects.push((name.clone(), datablock.to_string()));
+        }
+        let path = file.path.clone();
+        let task = thread_pool.spawn(async move { check_file(&path, objects) });
+        pending.reads.push((event.file, task));
+    }
+}
+
+/// Looks each (object, mesh datablock) up the way bevy_blender would, by the datablock's name.
+/// `None` means the mesh is there and only the load is left to try.
+fn check_file(path: &str, objects: Vec<(String, String)>) -> FileChecks {
+    let blend = match open_blend(Path::new(path)) {
+        Ok(blend) => blend,
+        Err(error) => {
+            warn!(path, %error, "couldn't read file to validate asset paths");
+            return objects
+                .into_iter()
+                .map(|(name, datablock)| (name, datablock, Some(AssetCheck::LoadFailed)))
+                .collect();
+        }
+    };
+    let meshes: HashSet<String> = blend
+        .instances_with_code(*b"ME")
+        .map(|mesh| id_name(&mesh))
+        .collect();
+
+    objects
+        .into_iter()
+        .map(|(name, datablock)| {
+            let obj = blend
+                .instances_with_code(*b"OB")
+                .find(|obj| id_name(obj) == name);
+            let check = match obj {
+                Some(obj) if obj.get_i16("type") != OB_MESH => Some(AssetCheck::WrongType(
+                    ObjectKind::from_type(obj.get_i16("type")),
+                )),
+                _ if meshes.contains(&datablock) => None,
+                Some(obj) => Some(AssetCheck::NotFound {
+                    mesh: obj.is_valid("data").then(|| id_name(&obj.get("data"))),
+                }),
+                None => Some(AssetCheck::NotFound { mesh: None }),
+            };
+            (name, datablock, check)
+        })
+        .collect()
+}
+
+fn finish_validations(
+    asset_server: Res<AssetServer>,
+    meshes: Res<Assets<Mesh>>,
+    mut app_state: ResMut<AppState>,
+    mut pending: ResMut<PendingValidations>,
+) {
+    let mut read = Vec::new();
+    pending.reads.retain_mut(
+        |(file, task)| match future::block_on(future::poll_once(task)) {
+            Some(checks) => {
+                read.push((*file, checks));
+                false
+            }
+            None => true,
+        },
+    );
+    for (file_id, checks) in read {
+        let Some(file) = app_state.file_mut(file_id) else {
+            continue;
+        };
+        for (name, datablock, check) in checks {
+            match check {
+                Some(check) => {
+                    file.asset_checks.insert(name, check);
+                }
+                None => {
+                    let handle = asset_server.load(mesh_asset_path(&file.path, &datablock));
+                    pending.loads.push((file_id, name, handle, 0));
+                }
+            }
+        }
+    }
+
+    let mut loaded = Vec::new();
+    pending
+        .loads
+        .retain_mut(|(file, name, handle, loaded_frames)| {
+            // The mesh showing up is what counts as a pass, a file can load fine
+            // without ever producing the label we asked for
+            let check = if meshes.contains(&*handle) {
+                AssetCheck::Passed
+            } else {
+                match asset_server.get_load_state(&*handle) {
+                    LoadState::Failed => AssetCheck::LoadFailed,
+                    LoadState::Loaded if *loaded_frames >= LABEL_GRACE_FRAMES => {
+                        AssetCheck::NotFound { mesh: None }
+                    }
+                    LoadState::Loaded => {
+                        *loaded_frames += 1;
+                        return true;
+                    }
+                    _ => return true,
+                }
+            };
+            loaded.push((*file, name.clone(), check));
+            false
+        });
+    for (file_id, name, check) in loaded {
+        if let Some(file) = app_state.file_mut(file_id) {
+            file.asset_checks.insert(name, check);
+        }
+    }
+}
+
+fn check_label(ui: &mut egui::Ui, check: &AssetCheck) {
+    match check {
+        AssetCheck::Checking => ui.weak(check.describe()),
+        AssetCheck::Passed => status_label(ui, Status::Ok, check.describe()),
+        _ => status_label(ui, Status::Warning, check.describe()),
+    };
+}
+
+/// The asset path of the inspected object, relative to the asset root when there is one,
+/// with copy and validate buttons. Validating always goes through the absolute path.
+pub fn asset_path_ui(
+    ui: &mut egui::Ui,
+    file: &File,
+    object: &BlendObject,
+    asset_root: Option<&Path>,
+    events: &mut EventWriter<ValidateAssetPaths>,
+) {
+    let relative = game_path(&file.path, asset_root);
+    let path = relative.as_deref().unwrap_or(&file.path);
+    let asset_path = mesh_asset_path(path, object.mesh_datablock());
+    ui.horizontal_wrapped(|ui| {
+        ui.monospace(asset_path.as_str());
+        if ui
+            .small_button("📋")
+            .on_hover_text(t!("asset_path.copy"))
+            .clicked()
+        {
+            ui.output_mut(|output| output.copied_text = asset_path.clone());
+        }
+        if ui
+            .small_button("{ }")
+            .on_hover_text(t!("asset_path.copy_snippet"))
+            .clicked()
+        {
+            ui.output_mut(|output| {
+                output.copied_text = mesh_snippet(path, object.mesh_datablock())
+            });
+        }
+        if ui.small_button(t!("asset_path.validate")).clicked() {
+            events.send(ValidateAssetPaths {
+                file: file.id,
+                objects: vec![object.name.clone()],
+            });
+        }
+    });
+    if relative.is_none() {
+        status_label(ui, Status::Warning, t!("asset_path.outside_root"));
+    }
+    if let Some(check) = file.asset_checks.get(&object.name) {
+        check_label(ui, check);
+    }
+}
+
+/// Validates every mesh object in the file, then lists the ones that failed
+pub fn asset_path_report_ui(
+    ui: &mut egui::Ui,
+    file: &File,
+    asset_root: Option<&Path>,
+    events: &mut EventWriter<ValidateAssetPaths>,
+) {
+    let objects: Vec<&BlendObject> = file
+        .meshes
+        .iter()
+        .filter(|object| object.kind == ObjectKind::Mesh)
+        .collect();
+    if objects.is_empty() {
+        return;
+    }
+    if ui.button(t!("asset_path.validate_all")).clicked() {
+        events.send(ValidateAssetPaths {
+            file: file.id,
+            objects: objects.iter().map(|object| object.name.clone()).collect(),
+        });
+    }
+
+    let checks: Vec<(&BlendObject, &AssetCheck)> = objects
+        .iter()
+        .filter_map(|object| Some((*object, file.asset_checks.get(&object.name)?)))
+        .collect();
+    if checks.is_empty() {
+        return;
+    }
+    let checking = checks
+        .iter()
+        .filter(|(_, check)| **check == AssetCheck::Checking)
+        .count();
+    let failed: Vec<&(&BlendObject, &AssetCheck)> =
+        checks.iter().filter(|(_, check)| check.failed()).collect();
+    let passed = checks.len() - checking - failed.len();
+
+    ui.label(t!(
+        "asset_path.report",
+        passed = passed,
+        failed = failed.len()
+    ));
+    if checking > 0 {
+        ui.weak(t!("asset_path.report_checking", count = checking));
+    }
+    if failed.is_empty() {
+        return;
+    }
+    for (object, check) in failed.iter() {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(object.name.as_str());
+            check_label(ui, check);
+        });
+    }
+    if ui.button(t!("asset_path.copy_report")).clicked() {
+        let path = game_path(&file.path, asset_root).unwrap_or_else(|| file.path.clone());
+        let report = failed
+            .iter()
+            .map(|(object, check)| {
+                let asset_path = mesh_asset_path(&path, object.mesh_datablock());
+                format!("{}\t{}", asset_path, check.describe())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        ui.output_mut(|output| output.copied_text = report);
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use std::{path::PathBuf, process::Command};
+
+use bevy::prelude::*;
+
+use crate::{
+    commands::{Action, ActionEvent},
+    i18n::t,
+    toasts::{Severity, Toasts},
+};
+
+use platform::{register, unregister};
+
+pub struct FileAssociationPlugin;
+
+impl Plugin for FileAssociationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(handle_association_actions.run_if(on_event::<ActionEvent>()));
+    }
+}
+
+fn handle_association_actions(
+    mut action_events: EventReader<ActionEvent>,
+    mut toasts: ResMut<Toasts>,
+) {
+    for ActionEvent(action) in action_events.iter() {
+        let result = match action {
+            Action::RegisterFileHandler => register().map(|_| t!("association.registered")),
+            Action::UnregisterFileHandler => unregister().map(|_| t!("association.unregistered")),
+            _ => continue,
+        };
+
+        match result {
+            Ok(message) => toasts.push(Severity::Info, message),
+            Err(error) => {
+                warn!(%error, "couldn't change the file association");
+                toasts.push(Severity::Error, error);
+            }
+        }
+    }
+}
+
+/// The executable a file manager should run to open a file with us
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn current_exe() -> Result<PathBuf, String> {
+    std::env::current_exe().map_err(|error| t!("association.no_executable", error = error))
+}
+
+/// Runs a command, turning a non-zero exit into its stderr
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn run(command: &mut Command) -> Result<(), String> {
+    let output = command.output().map_err(|error| {
+        t!(
+            "association.run_failed",
+            program = command.get_program().to_string_lossy(),
+            error = error
+        )
+    })?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::process::Command;
+
+    use super::{current_exe, run};
+    use crate::i18n::t;
+
+    /// Our ProgID, listed as an "Open with" option for `.blend`
+    const PROG_ID: &str = "BlenderLauncher.blend";
+
+    /// Adds a per-user ProgID under `HKCU\Software\Classes` and lists it for `.blend`.
+    /// Nothing needs admin rights, so this doesn't replace Blender as the default for everyone.
+    pub fn register() -> Result<(), String> {
+        let exe = current_exe()?;
+        let classes = r"HKCU\Software\Classes";
+        let command_key = format!(r"{}\{}\shell\open\command", classes, PROG_ID);
+        let command = format!("\"{}\" \"%1\"", exe.display());
+
+        let permission_error = |error: String| t!("association.registry_failed", error = error);
+        run(Command::new("reg").args(["add", &command_key, "/ve", "/d", &command, "/f"]))
+            .map_err(permission_error)?;
+        run(Command::new("reg").args([
+            "add",
+            &format!(r"{}\{}", classes, PROG_ID),
+            "/ve",
+            "/d",
+            "Blender file (Blender Launcher)",
+            "/f",
+        ]))
+        .map_err(permission_error)?;
+        run(Command::new("reg").args([
+            "add",
+            &format!(r"{}\.blend\OpenWithProgids", classes),
+            "/v",
+            PROG_ID,
+            "/t",
+            "REG_NONE",
+            "/f",
+        ]))
+        .map_err(permission_error)
+    }
+
+    pub fn unregister() -> Result<(), String> {
+        let classes = r"HKCU\Software\Classes";
+        let permission_error = |error: String| t!("association.registry_failed", error = error);
+        run(Command::new("reg").args([
+            "delete",
+            &format!(r"{}\.blend\OpenWithProgids", classes),
+            "/v",
+            PROG_ID,
+            "/f",
+        ]))
+        .map_err(permission_error)?;
+        run(Command::new("reg").args(["delete", &format!(r"{}\{}", classes, PROG_ID), "/f"]))
+            .map_err(permission_error)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::{fs, path::PathBuf, process::Command};
+
+    use super::{current_exe, run};
+    use crate::i18n::t;
+
+    /// Name of the desktop entry we install
+    const DESKTOP_FILE: &str = "blender-launcher.desktop";
+    const BLEND_MIME_TYPE: &str = "application/x-blender";
+
+    /// Installs a desktop entry for the `.blend` MIME type in the user's applications folder
+    /// and makes it the default handler
+    pub fn register() -> Result<(), String> {
+        let exe = current_exe()?;
+        let path = desktop_file_path()?;
+        let entry = format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Blender Launcher\n\
+             Exec=\"{}\" %f\n\
+             MimeType={};\n\
+             Terminal=false\n\
+             Categories=Graphics;3DGraphics;\n",
+            exe.display(),
+            BLEND_MIME_TYPE
+        );
+
+        let write_error = |error: std::io::Error| {
+            t!(
+                "association.write_failed",
+                path = path.display(),
+                error = error
+            )
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(write_error)?;
+        }
+        fs::write(&path, entry).map_err(write_error)?;
+
+        run(Command::new("xdg-mime").args(["default", DESKTOP_FILE, BLEND_MIME_TYPE]))?;
+        // Only refreshes the menu cache, file managers pick up the entry without it
+        let _ = run(Command::new("update-desktop-database").arg(path.parent().unwrap_or(&path)));
+        Ok(())
+    }
+
+    pub fn unregister() -> Result<(), String> {
+        let path = desktop_file_path()?;
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(t!(
+                "association.remove_failed",
+                path = path.display(),
+                error = error
+            )),
+        }
+    }
+
+    fn desktop_file_path() -> Result<PathBuf, String> {
+        dirs::data_dir()
+            .map(|dir| dir.join("applications").join(DESKTOP_FILE))
+            .ok_or_else(|| t!("association.no_applications_folder").to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    use crate::i18n::t;
+
+    /// Launch Services associations can't be set from outside an app bundle
+    pub fn register() -> Result<(), String> {
+        Err(t!("association.manual_register").to_string())
+    }
+
+    pub fn unregister() -> Result<(), String> {
+        Err(t!("association.manual_unregister").to_string())
+    }
+}
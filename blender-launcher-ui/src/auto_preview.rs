@@ -0,0 +1,131 @@
+use std::cmp::Reverse;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    file_status::FileOp, i18n::t, metadata::ObjectKind, properties::PropertyValue,
+    safe_mode::SafeMode, settings::Settings, AppState, File, FileId, SpawnEvent,
+};
+
+/// Custom property marking the object to preview in [`AutoPreview::Flagged`] mode,
+/// e.g. `launcher_preview = 1`
+pub const PREVIEW_PROPERTY: &str = "launcher_preview";
+
+/// Which object to spawn on its own once a selected file's metadata is in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutoPreview {
+    #[default]
+    Off,
+    FirstMesh,
+    /// The one with the most triangles, the first of them on a tie
+    LargestMesh,
+    /// The first with a truthy [`PREVIEW_PROPERTY`]. Files without one aren't previewed.
+    Flagged,
+}
+
+impl AutoPreview {
+    pub const ALL: [AutoPreview; 4] = [
+        AutoPreview::Off,
+        AutoPreview::FirstMesh,
+        AutoPreview::LargestMesh,
+        AutoPreview::Flagged,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AutoPreview::Off => t!("auto_preview.off"),
+            AutoPreview::FirstMesh => t!("auto_preview.first_mesh"),
+            AutoPreview::LargestMesh => t!("auto_preview.largest_mesh"),
+            AutoPreview::Flagged => t!("auto_preview.flagged"),
+        }
+    }
+}
+
+/// The object `mode` picks from a parsed file, by index
+pub fn preview_object(file: &File, mode: AutoPreview) -> Option<usize> {
+    let mut meshes = file
+        .meshes
+        .iter()
+        .enumerate()
+        .filter(|(_, object)| object.kind == ObjectKind::Mesh)
+        .filter_map(|(index, object)| Some((index, object, object.stats.as_ref()?)));
+    match mode {
+        AutoPreview::Off => None,
+        AutoPreview::FirstMesh => meshes.next().map(|(index, _, _)| index),
+        AutoPreview::LargestMesh => meshes
+            .max_by_key(|(index, _, stats)| (stats.triangles, Reverse(*index)))
+            .map(|(index, _, _)| index),
+        AutoPreview::Flagged => meshes
+            .find(|(_, object, _)| {
+                object
+                    .properties
+                    .iter()
+                    .any(|property| property.name == PREVIEW_PROPERTY && truthy(&property.value))
+            })
+            .map(|(index, _, _)| index),
+    }
+}
+
+/// The way Python would read the property, which is how they're usually set
+fn truthy(value: &PropertyValue) -> bool {
+    match value {
+        PropertyValue::Bool(value) => *value,
+        PropertyValue::Int(value) => *value != 0,
+        PropertyValue::Float(value) => *value != 0.0,
+        PropertyValue::String(value) => !value.is_empty(),
+        _ => false,
+    }
+}
+
+pub struct AutoPreviewPlugin;
+
+impl Plugin for AutoPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            auto_preview
+                .run_if(resource_changed::<AppState>())
+                .run_if(|safe_mode: Res<SafeMode>| !safe_mode.is_active()),
+        );
+    }
+}
+
+/// Waits for the selected file to finish parsing, then selects and spawns the object the
+/// setting picks. Only the latest selection waits, so clicking through files never spawns
+/// one that's already been clicked past, whichever order their parses finish in.
+fn auto_preview(
+    mut app_state: ResMut<AppState>,
+    settings: Res<Settings>,
+    mut spawns: EventWriter<SpawnEvent>,
+    mut seen_selection: Local<Option<FileId>>,
+    mut waiting: Local<Option<FileId>>,
+) {
+    let selected = app_state.selected_file;
+    if selected != *seen_selection {
+        *seen_selection = selected;
+        *waiting = selected.filter(|_| settings.auto_preview != AutoPreview::Off);
+    }
+    let Some(file_id) = *waiting else {
+        return;
+    };
+    let Some(file) = app_state.file(file_id) else {
+        *waiting = None;
+        return;
+    };
+    // A failed parse keeps waiting, a retry that works still gets its preview
+    if !file.parsed || file.status.blocked(FileOp::Spawn).is_some() {
+        return;
+    }
+    *waiting = None;
+
+    // Picking an object by hand while it parsed wins
+    if matches!(app_state.selected_object, Some((object_file, _)) if object_file == file_id) {
+        return;
+    }
+    let Some(index) = preview_object(file, settings.auto_preview) else {
+        return;
+    };
+    debug!(object = %file.meshes[index].name, "auto-previewing");
+    app_state.select_object(file_id, index);
+    spawns.send(SpawnEvent(file_id, index));
+}
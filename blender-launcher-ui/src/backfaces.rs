@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use bevy::{pbr::NotShadowCaster, prelude::*, render::render_resource::Face};
+
+use crate::{settings::Settings, BlenderPreviewObject};
+
+/// A red copy of a preview's mesh that only draws its back faces
+#[derive(Component)]
+struct BackfaceHighlight;
+
+#[derive(Resource)]
+struct BackfaceHighlightMaterial(Handle<StandardMaterial>);
+
+pub struct BackfacePlugin;
+
+impl Plugin for BackfacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_backface_material)
+            .add_system(apply_two_sided)
+            .add_system(
+                update_backface_highlights.run_if(|settings: Res<Settings>| {
+                    settings.highlight_backfaces || settings.is_changed()
+                }),
+            );
+    }
+}
+
+fn setup_backface_material(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(BackfaceHighlightMaterial(materials.add(StandardMaterial {
+        base_color: Color::RED,
+        unlit: true,
+        // Culling the front leaves exactly the faces pointing away from the camera
+        cull_mode: Some(Face::Front),
+        // Wins against the preview itself when it's drawn two-sided
+        depth_bias: 10.0,
+        ..Default::default()
+    })));
+}
+
+/// Keeps the culling of every preview material in line with the "Two-sided" setting.
+/// Materials are checked every frame since spawned previews get theirs once loaded.
+fn apply_two_sided(
+    settings: Res<Settings>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    previews: Query<&Handle<StandardMaterial>, With<BlenderPreviewObject>>,
+) {
+    let cull_mode = if settings.two_sided {
+        None
+    } else {
+        Some(Face::Back)
+    };
+
+    for handle in previews.iter() {
+        // Only borrow mutably when needed, that re-uploads the material
+        let needs_update = materials.get(handle).map_or(false, |material| {
+            material.cull_mode != cull_mode || material.double_sided != settings.two_sided
+        });
+        if !needs_update {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            material.cull_mode = cull_mode;
+            material.double_sided = settings.two_sided;
+        }
+    }
+}
+
+/// Adds or removes backface highlights to match the setting,
+/// and keeps their mesh the same as their preview's
+fn update_backface_highlights(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    material: Res<BackfaceHighlightMaterial>,
+    previews: Query<(Entity, &Handle<Mesh>), With<BlenderPreviewObject>>,
+    mut highlights: Query<(Entity, &Parent, &mut Handle<Mesh>), With<BackfaceHighlight>>,
+) {
+    if !settings.highlight_backfaces {
+        for (highlight, ..) in highlights.iter() {
+            commands.entity(highlight).despawn();
+        }
+        return;
+    }
+
+    let mut highlighted = HashMap::new();
+    for (highlight, parent, mesh) in highlights.iter_mut() {
+        highlighted.insert(parent.get(), (highlight, mesh));
+    }
+
+    for (preview, mesh) in previews.iter() {
+        match highlighted.get_mut(&preview) {
+            // The preview's mesh can be swapped, e.g. for a decimated copy
+            Some((_, highlight_mesh)) => {
+                if **highlight_mesh != *mesh {
+                    **highlight_mesh = mesh.clone();
+                }
+            }
+            None => {
+                let highlight = commands
+                    .spawn((
+                        BackfaceHighlight,
+                        NotShadowCaster,
+                        PbrBundle {
+                            mesh: mesh.clone(),
+                            material: material.0.clone(),
+                            ..Default::default()
+                        },
+                    ))
+                    .id();
+                commands.entity(preview).add_child(highlight);
+            }
+        }
+    }
+}
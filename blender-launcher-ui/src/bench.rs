@@ -0,0 +1,152 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::parse_queue::{parse_blend_file, BlockCounts};
+
+/// Parses run by `--bench` when `--runs` isn't given
+const DEFAULT_RUNS: usize = 10;
+
+/// `--bench <file.blend> [--runs <n>] [--json]`, which parses the file instead of starting the app
+pub struct BenchArgs {
+    path: PathBuf,
+    runs: usize,
+    json: bool,
+}
+
+/// What `--json` prints, one object on a single line
+#[derive(Serialize)]
+struct BenchReport {
+    file: String,
+    runs: usize,
+    min_ms: f64,
+    avg_ms: f64,
+    max_ms: f64,
+    blocks: BlockCounts,
+    /// Objects that couldn't be read and were left out
+    skipped_objects: usize,
+    /// The most memory the process had in use at once, where the OS tells us
+    peak_memory_bytes: Option<u64>,
+}
+
+/// Reads the benchmark flags, `None` without `--bench` so the app starts as usual
+pub fn cli_bench() -> Option<BenchArgs> {
+    let mut path = None;
+    let mut runs = DEFAULT_RUNS;
+    let mut json = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bench" => path = args.next().map(PathBuf::from),
+            "--runs" => match args.next().and_then(|runs| runs.parse().ok()) {
+                Some(count) => runs = count,
+                None => eprintln!("--runs expects a number, using {}", DEFAULT_RUNS),
+            },
+            "--json" => json = true,
+            _ => {}
+        }
+    }
+    Some(BenchArgs {
+        path: path?,
+        runs: runs.max(1),
+        json,
+    })
+}
+
+/// Parses the file `runs` times and prints how long that took. Returns the exit code.
+pub fn run_bench(args: BenchArgs) -> i32 {
+    let path = args.path.display().to_string();
+    let mut timings = Vec::with_capacity(args.runs);
+    let mut parsed = None;
+    for _ in 0..args.runs {
+        let started = Instant::now();
+        match parse_blend_file(&path) {
+            Ok(file) => parsed = Some(file),
+            Err(error) => {
+                eprintln!("Couldn't parse {}: {}", path, error);
+                return 1;
+            }
+        }
+        timings.push(started.elapsed());
+    }
+
+    let report = BenchReport {
+        file: path,
+        runs: args.runs,
+        min_ms: millis(timings.iter().min().copied().unwrap_or_default()),
+        avg_ms: millis(timings.iter().sum::<Duration>() / args.runs as u32),
+        max_ms: millis(timings.iter().max().copied().unwrap_or_default()),
+        blocks: parsed
+            .as_ref()
+            .map(|parsed| parsed.block_counts())
+            .unwrap_or_default(),
+        skipped_objects: parsed.map_or(0, |parsed| parsed.skipped_objects()),
+        peak_memory_bytes: peak_memory(),
+    };
+    if args.json {
+        match serde_json::to_string(&report) {
+            Ok(json) => println!("{}", json),
+            Err(error) => {
+                eprintln!("Couldn't write the report: {}", error);
+                return 1;
+            }
+        }
+    } else {
+        print_report(&report);
+    }
+    0
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn print_report(report: &BenchReport) {
+    let name = Path::new(&report.file).file_name().map_or_else(
+        || report.file.clone(),
+        |name| name.to_string_lossy().to_string(),
+    );
+    println!(
+        "{}: {} runs, min {:.2} ms, avg {:.2} ms, max {:.2} ms",
+        name, report.runs, report.min_ms, report.avg_ms, report.max_ms
+    );
+    let blocks = &report.blocks;
+    println!(
+        "{} objects, {} meshes, {} materials, {} images, {} scenes",
+        blocks.objects, blocks.meshes, blocks.materials, blocks.images, blocks.scenes
+    );
+    if report.skipped_objects > 0 {
+        println!("{} unreadable objects skipped", report.skipped_objects);
+    }
+    if let Some(bytes) = report.peak_memory_bytes {
+        println!("peak memory {:.1} MiB", bytes as f64 / (1024.0 * 1024.0));
+    }
+}
+
+/// The peak resident set size of this process
+#[cfg(unix)]
+pub fn peak_memory() -> Option<u64> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+    // SAFETY: `getrusage` only writes to the struct we hand it, and we only read it on success
+    let usage = unsafe {
+        if libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) != 0 {
+            return None;
+        }
+        usage.assume_init()
+    };
+    let max_rss = u64::try_from(usage.ru_maxrss).ok()?;
+    // macOS reports bytes, everything else kilobytes
+    Some(if cfg!(target_os = "macos") {
+        max_rss
+    } else {
+        max_rss * 1024
+    })
+}
+
+#[cfg(not(unix))]
+pub fn peak_memory() -> Option<u64> {
+    None
+}
@@ -0,0 +1,875 @@
+use std::{
+    collections::HashMap,
+    env, fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+};
+use bevy_egui::{egui, EguiContexts};
+use futures_lite::future;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    commands::{Action, ActionEvent},
+    i18n::t,
+    settings::{config_dir, Settings},
+    toasts::{Severity, Toasts},
+};
+
+/// Directory listing of every official release, one `BlenderX.Y/` folder per series
+const RELEASE_INDEX: &str = "https://download.blender.org/release/";
+/// The daily builds as JSON, one entry per file
+const DAILY_INDEX: &str = "https://builder.blender.org/download/daily/?format=json&v=1";
+/// How many of the newest release series we list
+const RELEASE_SERIES_COUNT: usize = 6;
+/// The oldest series that still ships the archive layout we know how to install
+const OLDEST_SERIES: BlenderVersion = BlenderVersion {
+    major: 2,
+    minor: 80,
+    patch: 0,
+};
+
+/// The end of this platform's release archive names, e.g. `blender-4.2.1-linux-x64.tar.xz`
+const RELEASE_SUFFIX: &str = if cfg!(target_os = "windows") {
+    "-windows-x64.zip"
+} else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+    "-macos-arm64.dmg"
+} else if cfg!(target_os = "macos") {
+    "-macos-x64.dmg"
+} else {
+    "-linux-x64.tar.xz"
+};
+/// (platform, file extension) of this platform's daily builds
+const DAILY_PLATFORM: (&str, &str) = if cfg!(target_os = "windows") {
+    ("windows", "zip")
+} else if cfg!(target_os = "macos") {
+    ("darwin", "dmg")
+} else {
+    ("linux", "xz")
+};
+const DAILY_ARCHITECTURES: &[&str] = if cfg!(target_arch = "aarch64") {
+    &["arm64"]
+} else {
+    &["x86_64", "amd64"]
+};
+/// Where the executable is inside an extracted build
+const BUILD_EXECUTABLE: &str = if cfg!(target_os = "windows") {
+    "blender.exe"
+} else if cfg!(target_os = "macos") {
+    "Blender.app/Contents/MacOS/Blender"
+} else {
+    "blender"
+};
+const PATH_EXECUTABLE: &str = if cfg!(target_os = "windows") {
+    "blender.exe"
+} else {
+    "blender"
+};
+
+/// A Blender version like 4.2.1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct BlenderVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl BlenderVersion {
+    /// Parses `4.2`, `4.2.1`, or the start of something like `4.2.1-linux-x64`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split('.').map(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse::<u32>().ok()
+        });
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = parts.next().flatten().unwrap_or(0);
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+}
+
+impl fmt::Display for BlenderVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A Blender we can launch
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlenderInstall {
+    /// `None` when the executable didn't tell us
+    pub version: Option<BlenderVersion>,
+    pub executable: PathBuf,
+    /// Downloaded by the build manager, rather than installed by the user
+    pub managed: bool,
+}
+
+impl BlenderInstall {
+    pub fn label(&self) -> String {
+        match self.version {
+            Some(version) => format!("Blender {}", version),
+            None => t!("builds.unknown_version").to_string(),
+        }
+    }
+}
+
+/// Every Blender found on this machine, newest first
+#[derive(Default, Resource)]
+pub struct BlenderInstalls {
+    installs: Vec<BlenderInstall>,
+    scan: Option<Task<Vec<BlenderInstall>>>,
+}
+
+impl BlenderInstalls {
+    pub fn iter(&self) -> impl Iterator<Item = &BlenderInstall> {
+        self.installs.iter()
+    }
+
+    pub fn newest(&self) -> Option<&BlenderInstall> {
+        self.installs.first()
+    }
+
+    /// The install picked in the builds window, or the newest one
+    pub fn preferred(&self, settings: &Settings) -> Option<&BlenderInstall> {
+        settings
+            .default_blender
+            .as_ref()
+            .and_then(|executable| {
+                self.installs
+                    .iter()
+                    .find(|install| install.executable == *executable)
+            })
+            .or_else(|| self.newest())
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.scan.is_some()
+    }
+
+    /// Looks for installs again in the background
+    pub fn rescan(&mut self) {
+        if self.scan.is_none() {
+            self.scan = Some(IoTaskPool::get().spawn(async { find_installs() }));
+        }
+    }
+
+    fn set(&mut self, mut installs: Vec<BlenderInstall>) {
+        // Newest first, installs without a version last
+        installs.sort_by(|a, b| b.version.cmp(&a.version));
+        self.installs = installs;
+    }
+}
+
+/// Which feed a build comes from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Channel {
+    Stable,
+    /// The risk level of the daily build, like `alpha` or `candidate`
+    Daily(String),
+}
+
+impl Channel {
+    fn label(&self) -> &str {
+        match self {
+            Channel::Stable => t!("builds.stable"),
+            Channel::Daily(risk) => risk,
+        }
+    }
+}
+
+/// A build on the download mirror for this platform
+#[derive(Clone, Debug)]
+pub struct AvailableBuild {
+    pub version: BlenderVersion,
+    pub channel: Channel,
+    pub url: String,
+    pub file_name: String,
+    /// The published `sha256` file listing this archive's checksum, if there is one
+    pub checksum_url: Option<String>,
+}
+
+impl AvailableBuild {
+    /// The archive name without its extension, also the name of the folder it's installed to
+    fn stem(&self) -> &str {
+        [".tar.xz", ".zip", ".dmg"]
+            .iter()
+            .find_map(|extension| self.file_name.strip_suffix(extension))
+            .unwrap_or(&self.file_name)
+    }
+}
+
+/// Byte counts shared with the download task, and a flag to stop it
+#[derive(Default)]
+struct DownloadProgress {
+    downloaded: AtomicU64,
+    total: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+struct Download {
+    progress: Arc<DownloadProgress>,
+    task: Task<Result<BlenderInstall, String>>,
+}
+
+/// The builds window: the mirror listing and any downloads in flight
+#[derive(Default, Resource)]
+pub struct BuildManager {
+    open: bool,
+    available: Vec<AvailableBuild>,
+    listing: Option<Task<Result<Vec<AvailableBuild>, String>>>,
+    /// Keyed by the archive's URL
+    downloads: HashMap<String, Download>,
+}
+
+impl BuildManager {
+    fn is_busy(&self) -> bool {
+        self.listing.is_some() || !self.downloads.is_empty()
+    }
+
+    fn refresh(&mut self, include_daily: bool) {
+        if self.listing.is_none() {
+            self.listing =
+                Some(IoTaskPool::get().spawn(async move { list_available_builds(include_daily) }));
+        }
+    }
+
+    fn download(&mut self, build: &AvailableBuild) {
+        if self.downloads.contains_key(&build.url) {
+            return;
+        }
+        let progress = Arc::new(DownloadProgress::default());
+        let task_progress = progress.clone();
+        let task_build = build.clone();
+        let task =
+            IoTaskPool::get().spawn(async move { install_build(&task_build, &task_progress) });
+        self.downloads
+            .insert(build.url.clone(), Download { progress, task });
+    }
+}
+
+pub struct BuildsPlugin;
+
+impl Plugin for BuildsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlenderInstalls>()
+            .init_resource::<BuildManager>()
+            .add_startup_system(|mut installs: ResMut<BlenderInstalls>| installs.rescan())
+            .add_system(toggle_builds_window.run_if(on_event::<ActionEvent>()))
+            .add_system(poll_build_tasks.run_if(
+                |installs: Res<BlenderInstalls>, manager: Res<BuildManager>| {
+                    installs.is_scanning() || manager.is_busy()
+                },
+            ))
+            .add_system(builds_ui.run_if(|manager: Res<BuildManager>| manager.open));
+    }
+}
+
+fn toggle_builds_window(
+    mut action_events: EventReader<ActionEvent>,
+    mut manager: ResMut<BuildManager>,
+    settings: Res<Settings>,
+) {
+    for ActionEvent(action) in action_events.iter() {
+        if *action == Action::ShowBuilds {
+            manager.open = !manager.open;
+            // The listing is fetched the first time the window opens, then on Refresh
+            if manager.open && manager.available.is_empty() {
+                manager.refresh(settings.include_daily_builds);
+            }
+        }
+    }
+}
+
+/// Applies finished scans, listings, and downloads
+fn poll_build_tasks(
+    mut installs: ResMut<BlenderInstalls>,
+    mut manager: ResMut<BuildManager>,
+    mut toasts: ResMut<Toasts>,
+) {
+    if let Some(scan) = installs.scan.as_mut() {
+        if let Some(found) = future::block_on(future::poll_once(scan)) {
+            installs.scan = None;
+            installs.set(found);
+        }
+    }
+
+    if let Some(listing) = manager.listing.as_mut() {
+        if let Some(result) = future::block_on(future::poll_once(listing)) {
+            manager.listing = None;
+            match result {
+                Ok(available) => manager.available = available,
+                Err(error) => {
+                    warn!(%error, "couldn't list Blender builds");
+                    toasts.push(Severity::Error, t!("builds.list_failed", error = error));
+                }
+            }
+        }
+    }
+
+    let mut finished = Vec::new();
+    for (url, download) in manager.downloads.iter_mut() {
+        if let Some(result) = future::block_on(future::poll_once(&mut download.task)) {
+            finished.push((url.clone(), result));
+        }
+    }
+    for (url, result) in finished {
+        manager.downloads.remove(&url);
+        match result {
+            Ok(install) => {
+                info!(executable = %install.executable.display(), "installed Blender build");
+                toasts.push(
+                    Severity::Success,
+                    t!("builds.installed", name = install.label()),
+                );
+                installs.rescan();
+            }
+            Err(error) => {
+                warn!(%url, %error, "couldn't install Blender build");
+                toasts.push(Severity::Error, t!("builds.install_failed", error = error));
+            }
+        }
+    }
+}
+
+fn builds_ui(
+    mut contexts: EguiContexts,
+    mut manager: ResMut<BuildManager>,
+    mut installs: ResMut<BlenderInstalls>,
+    mut settings: ResMut<Settings>,
+    mut toasts: ResMut<Toasts>,
+) {
+    let mut open = true;
+    // Copied out so the settings are only flagged as changed on an actual edit
+    let mut default_blender = settings.default_blender.clone();
+    let mut include_daily = settings.include_daily_builds;
+    let mut remove = None;
+    let mut start_download = None;
+    let mut cancel = None;
+    let mut refresh = false;
+    let builds_dir = builds_dir().unwrap_or_default();
+
+    egui::Window::new(t!("builds.heading"))
+        .id(egui::Id::new("blender_builds"))
+        .open(&mut open)
+        .default_width(420.0)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.strong(t!("builds.installed_heading"));
+                if installs.is_scanning() {
+                    ui.spinner();
+                } else if ui.small_button(t!("builds.rescan")).clicked() {
+                    installs.rescan();
+                }
+            });
+            if installs.iter().next().is_none() && !installs.is_scanning() {
+                ui.weak(t!("builds.none_installed"));
+            }
+            egui::Grid::new("installed_builds")
+                .striped(true)
+                .show(ui, |ui| {
+                    for install in installs.iter() {
+                        let is_default = default_blender.as_ref() == Some(&install.executable);
+                        if ui.radio(is_default, install.label()).clicked() {
+                            default_blender = Some(install.executable.clone());
+                        }
+                        ui.weak(install.executable.display().to_string());
+                        if install.managed && ui.small_button(t!("builds.remove")).clicked() {
+                            remove = Some(install.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.strong(t!("builds.available_heading"));
+                if manager.listing.is_some() {
+                    ui.spinner();
+                } else if ui.small_button(t!("builds.refresh")).clicked() {
+                    refresh = true;
+                }
+                refresh |= ui
+                    .checkbox(&mut include_daily, t!("builds.include_daily"))
+                    .changed();
+            });
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("available_builds")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for build in manager.available.iter() {
+                                ui.label(build.version.to_string());
+                                ui.weak(build.channel.label());
+
+                                let install_dir = builds_dir.join(build.stem());
+                                let installed = installs
+                                    .iter()
+                                    .any(|install| install.executable.starts_with(&install_dir));
+                                if let Some(download) = manager.downloads.get(&build.url) {
+                                    let downloaded =
+                                        download.progress.downloaded.load(Ordering::Relaxed);
+                                    let total = download.progress.total.load(Ordering::Relaxed);
+                                    let fraction = if total > 0 {
+                                        downloaded as f32 / total as f32
+                                    } else {
+                                        0.0
+                                    };
+                                    ui.add(
+                                        egui::ProgressBar::new(fraction)
+                                            .desired_width(120.0)
+                                            .show_percentage(),
+                                    );
+                                    if ui.small_button(t!("builds.cancel")).clicked() {
+                                        cancel = Some(build.url.clone());
+                                    }
+                                } else if installed {
+                                    ui.weak(t!("builds.installed_badge"));
+                                } else if ui.small_button(t!("builds.download")).clicked() {
+                                    start_download = Some(build.clone());
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+
+    if default_blender != settings.default_blender {
+        settings.default_blender = default_blender;
+    }
+    if include_daily != settings.include_daily_builds {
+        settings.include_daily_builds = include_daily;
+    }
+    if refresh {
+        manager.refresh(include_daily);
+    }
+    if let Some(build) = start_download {
+        manager.download(&build);
+    }
+    if let Some(url) = cancel {
+        // The task notices on its next chunk, the partial file is kept to resume from
+        if let Some(download) = manager.downloads.get(&url) {
+            download.progress.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+    if let Some(install) = remove {
+        match remove_build(&install) {
+            Ok(()) => installs.rescan(),
+            Err(error) => toasts.push(Severity::Error, t!("builds.remove_failed", error = error)),
+        }
+    }
+    if !open {
+        manager.open = false;
+    }
+}
+
+/// Where downloaded builds are extracted, one folder per build
+fn builds_dir() -> Result<PathBuf, String> {
+    config_dir()
+        .map(|dir| dir.join("builds"))
+        .ok_or_else(|| t!("builds.no_config_dir").to_string())
+}
+
+/// Managed builds, then Blender on the `PATH` and in the usual install locations
+fn find_installs() -> Vec<BlenderInstall> {
+    let mut installs = Vec::new();
+
+    if let Ok(entries) = builds_dir().and_then(|dir| fs::read_dir(dir).map_err(|e| e.to_string())) {
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            let executable = dir.join(BUILD_EXECUTABLE);
+            if !executable.is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            installs.push(BlenderInstall {
+                version: name
+                    .strip_prefix("blender-")
+                    .and_then(BlenderVersion::parse),
+                executable,
+                managed: true,
+            });
+        }
+    }
+
+    let mut candidates: Vec<PathBuf> = env::var_os("PATH")
+        .map(|path| {
+            env::split_paths(&path)
+                .map(|dir| dir.join(PATH_EXECUTABLE))
+                .collect()
+        })
+        .unwrap_or_default();
+    if cfg!(target_os = "windows") {
+        let foundation = Path::new(r"C:\Program Files\Blender Foundation");
+        if let Ok(entries) = fs::read_dir(foundation) {
+            candidates.extend(
+                entries
+                    .flatten()
+                    .map(|entry| entry.path().join("blender.exe")),
+            );
+        }
+    } else if cfg!(target_os = "macos") {
+        candidates.push(PathBuf::from(
+            "/Applications/Blender.app/Contents/MacOS/Blender",
+        ));
+    }
+
+    for candidate in candidates {
+        if !candidate.is_file() {
+            continue;
+        }
+        // The same install is often reachable through a symlink on the PATH
+        let executable = fs::canonicalize(&candidate).unwrap_or(candidate);
+        if installs
+            .iter()
+            .any(|install| install.executable == executable)
+        {
+            continue;
+        }
+        installs.push(BlenderInstall {
+            version: executable_version(&executable),
+            executable,
+            managed: false,
+        });
+    }
+
+    installs
+}
+
+/// Asks Blender for its version, the first line of `--version` is `Blender 4.2.1`
+fn executable_version(executable: &Path) -> Option<BlenderVersion> {
+    let output = Command::new(executable).arg("--version").output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first_line = stdout.lines().next()?;
+    BlenderVersion::parse(first_line.trim().strip_prefix("Blender ")?)
+}
+
+/// Every `href` in an HTML directory listing
+fn listing_links(html: &str) -> impl Iterator<Item = &str> {
+    html.split("href=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+}
+
+fn fetch_text(url: &str) -> Result<String, String> {
+    ureq::get(url)
+        .call()
+        .map_err(|error| error.to_string())?
+        .into_string()
+        .map_err(|error| error.to_string())
+}
+
+/// Stable releases for this platform from the newest few series, plus daily builds if asked
+fn list_available_builds(include_daily: bool) -> Result<Vec<AvailableBuild>, String> {
+    let index = fetch_text(RELEASE_INDEX)?;
+    let mut series: Vec<(BlenderVersion, String)> = listing_links(&index)
+        .filter_map(|link| {
+            let name = link.strip_prefix("Blender")?.strip_suffix('/')?;
+            let version = BlenderVersion::parse(name)?;
+            (version >= OLDEST_SERIES).then(|| (version, link.to_string()))
+        })
+        .collect();
+    series.sort_by(|a, b| b.0.cmp(&a.0));
+    series.dedup_by(|a, b| a.0 == b.0);
+
+    let mut builds = Vec::new();
+    for (_, link) in series.iter().take(RELEASE_SERIES_COUNT) {
+        let series_url = format!("{}{}", RELEASE_INDEX, link);
+        let listing = fetch_text(&series_url)?;
+        let links: Vec<&str> = listing_links(&listing).collect();
+        for file_name in links.iter() {
+            let Some(version_text) = file_name
+                .strip_prefix("blender-")
+                .and_then(|rest| rest.strip_suffix(RELEASE_SUFFIX))
+            else {
+                continue;
+            };
+            let Some(version) = BlenderVersion::parse(version_text) else {
+                continue;
+            };
+            // One checksum file per release covers every platform's archive
+            let checksum_name = format!("blender-{}.sha256", version_text);
+            builds.push(AvailableBuild {
+                version,
+                channel: Channel::Stable,
+                url: format!("{}{}", series_url, file_name),
+                file_name: file_name.to_string(),
+                checksum_url: links
+                    .contains(&checksum_name.as_str())
+                    .then(|| format!("{}{}", series_url, checksum_name)),
+            });
+        }
+    }
+
+    if include_daily {
+        builds.extend(list_daily_builds()?);
+    }
+    builds.sort_by(|a, b| b.version.cmp(&a.version));
+    Ok(builds)
+}
+
+/// The fields we use from the daily builds feed
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct DailyEntry {
+    url: String,
+    file_name: String,
+    file_extension: String,
+    version: String,
+    risk_id: String,
+    platform: String,
+    architecture: String,
+}
+
+fn list_daily_builds() -> Result<Vec<AvailableBuild>, String> {
+    let entries: Vec<DailyEntry> =
+        serde_json::from_str(&fetch_text(DAILY_INDEX)?).map_err(|error| error.to_string())?;
+    let (platform, extension) = DAILY_PLATFORM;
+
+    let builds = entries
+        .iter()
+        .filter(|entry| {
+            entry.platform == platform
+                && entry.file_extension == extension
+                && DAILY_ARCHITECTURES.contains(&entry.architecture.as_str())
+        })
+        .filter_map(|entry| {
+            // Checksums are listed as their own entries next to the archive
+            let checksum_name = format!("{}.sha256", entry.file_name);
+            Some(AvailableBuild {
+                version: BlenderVersion::parse(&entry.version)?,
+                channel: Channel::Daily(entry.risk_id.clone()),
+                url: entry.url.clone(),
+                file_name: entry.file_name.clone(),
+                checksum_url: entries
+                    .iter()
+                    .find(|other| other.file_name == checksum_name)
+                    .map(|other| other.url.clone()),
+            })
+        })
+        .collect();
+    Ok(builds)
+}
+
+/// Downloads, verifies, and extracts a build into the builds directory
+fn install_build(
+    build: &AvailableBuild,
+    progress: &DownloadProgress,
+) -> Result<BlenderInstall, String> {
+    let builds_dir = builds_dir()?;
+    let downloads_dir = builds_dir.join("downloads");
+    fs::create_dir_all(&downloads_dir).map_err(|error| error.to_string())?;
+    let archive = downloads_dir.join(format!("{}.part", build.file_name));
+
+    download_resumable(&build.url, &archive, progress)?;
+
+    // Checked before anything gets extracted, and a bad download isn't resumed from
+    let Some(checksum_url) = &build.checksum_url else {
+        return Err(t!("builds.no_checksum", file = build.file_name));
+    };
+    let expected = expected_checksum(&fetch_text(checksum_url)?, &build.file_name)
+        .ok_or_else(|| t!("builds.no_checksum", file = build.file_name))?;
+    let actual = sha256_file(&archive).map_err(|error| error.to_string())?;
+    if !actual.eq_ignore_ascii_case(&expected) {
+        let _ = fs::remove_file(&archive);
+        return Err(t!("builds.checksum_mismatch", file = build.file_name));
+    }
+
+    let install_dir = builds_dir.join(build.stem());
+    // Extract next to the final folder and move it into place, so a failed
+    // extraction never leaves a half-installed build that looks usable
+    let staging = builds_dir.join(format!(".{}.staging", build.stem()));
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging).map_err(|error| error.to_string())?;
+    let result = extract(&build.file_name, &archive, &staging)
+        .and_then(|()| move_build_into_place(&staging, &install_dir));
+    let _ = fs::remove_dir_all(&staging);
+    result?;
+    let _ = fs::remove_file(&archive);
+
+    Ok(BlenderInstall {
+        version: Some(build.version),
+        executable: install_dir.join(BUILD_EXECUTABLE),
+        managed: true,
+    })
+}
+
+/// Downloads `url` to `path`, picking up from where an earlier attempt left off
+fn download_resumable(url: &str, path: &Path, progress: &DownloadProgress) -> Result<(), String> {
+    let existing = fs::metadata(path).map_or(0, |metadata| metadata.len());
+    let request = ureq::get(url);
+    let response = if existing > 0 {
+        match request.set("Range", &format!("bytes={}-", existing)).call() {
+            // The partial file is already complete (or bigger than the archive), start over
+            Err(ureq::Error::Status(416, _)) => {
+                let _ = fs::remove_file(path);
+                ureq::get(url).call()
+            }
+            response => response,
+        }
+    } else {
+        request.call()
+    }
+    .map_err(|error| error.to_string())?;
+
+    // Servers that ignore the range send the whole file again
+    let resumed = response.status() == 206;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(path)
+        .map_err(|error| error.to_string())?;
+    let mut downloaded = if resumed { existing } else { 0 };
+    let remaining: u64 = response
+        .header("Content-Length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    progress
+        .total
+        .store(downloaded + remaining, Ordering::Relaxed);
+    progress.downloaded.store(downloaded, Ordering::Relaxed);
+
+    let mut reader = response.into_reader();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        if progress.cancelled.load(Ordering::Relaxed) {
+            return Err(t!("builds.cancelled").to_string());
+        }
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|error| error.to_string())?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])
+            .map_err(|error| error.to_string())?;
+        downloaded += read as u64;
+        progress.downloaded.store(downloaded, Ordering::Relaxed);
+    }
+    file.flush().map_err(|error| error.to_string())
+}
+
+/// Finds a file's hash in a `sha256sum` style listing
+fn expected_checksum(listing: &str, file_name: &str) -> Option<String> {
+    listing.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        let name = parts.next()?.trim_start_matches('*');
+        (name == file_name).then(|| hash.to_string())
+    })
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Unpacks the archive based on its original file name
+fn extract(file_name: &str, archive: &Path, destination: &Path) -> Result<(), String> {
+    if file_name.ends_with(".tar.xz") {
+        let file = File::open(archive).map_err(|error| error.to_string())?;
+        tar::Archive::new(xz2::read::XzDecoder::new(file))
+            .unpack(destination)
+            .map_err(|error| error.to_string())
+    } else if file_name.ends_with(".zip") {
+        let file = File::open(archive).map_err(|error| error.to_string())?;
+        zip::ZipArchive::new(file)
+            .and_then(|mut zip| zip.extract(destination))
+            .map_err(|error| error.to_string())
+    } else if file_name.ends_with(".dmg") {
+        extract_dmg(archive, destination)
+    } else {
+        Err(t!("builds.unknown_archive", file = file_name))
+    }
+}
+
+/// Mounts the disk image and copies the app bundle out of it
+fn extract_dmg(archive: &Path, destination: &Path) -> Result<(), String> {
+    let mount_point = destination.join("mount");
+    let run = |command: &mut Command| -> Result<(), String> {
+        let output = command.output().map_err(|error| error.to_string())?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    };
+
+    // The bundle goes in a folder of its own, like the other platforms' archives
+    let build_dir = destination.join("build");
+    fs::create_dir_all(&build_dir).map_err(|error| error.to_string())?;
+
+    run(Command::new("hdiutil")
+        .args(["attach", "-nobrowse", "-readonly", "-mountpoint"])
+        .arg(&mount_point)
+        .arg(archive))?;
+    let copied = run(Command::new("cp")
+        .arg("-R")
+        .arg(mount_point.join("Blender.app"))
+        .arg(&build_dir));
+    let _ = run(Command::new("hdiutil").arg("detach").arg(&mount_point));
+    let _ = fs::remove_dir(&mount_point);
+    copied
+}
+
+/// Archives wrap the build in a single top level folder, that folder becomes the install
+fn move_build_into_place(staging: &Path, install_dir: &Path) -> Result<(), String> {
+    let entries: Vec<PathBuf> = fs::read_dir(staging)
+        .map_err(|error| error.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    let root = match entries.as_slice() {
+        [single] if single.is_dir() => single.clone(),
+        _ => staging.to_path_buf(),
+    };
+    if !root.join(BUILD_EXECUTABLE).is_file() {
+        return Err(t!("builds.no_executable").to_string());
+    }
+
+    let _ = fs::remove_dir_all(install_dir);
+    fs::rename(&root, install_dir).map_err(|error| error.to_string())
+}
+
+/// Deletes a managed build's folder
+fn remove_build(install: &BlenderInstall) -> Result<(), String> {
+    let builds_dir = builds_dir()?;
+    // Never delete anything outside our own folder
+    let Some(install_dir) = install
+        .executable
+        .strip_prefix(&builds_dir)
+        .ok()
+        .and_then(|relative| relative.components().next())
+        .map(|folder| builds_dir.join(folder))
+    else {
+        return Err(t!("builds.not_managed").to_string());
+    };
+    fs::remove_dir_all(install_dir).map_err(|error| error.to_string())
+}
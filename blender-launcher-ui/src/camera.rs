@@ -0,0 +1,298 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    input::mouse::MouseMotion,
+    prelude::*,
+    render::{camera::Projection, primitives::Aabb},
+};
+use bevy_egui::EguiContexts;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    gizmo::TranslateGizmo, i18n::t, offscreen::OffscreenCamera, overlays::OverlayCamera,
+    settings::Settings, BlenderPreviewObject, OriginalCameraTransform, CAMERA_TARGET,
+};
+
+/// Auto clip never puts the near plane closer than this
+const MIN_AUTO_NEAR: f32 = 0.001;
+/// Radians of orbit per pixel of mouse movement
+const ORBIT_SPEED: f32 = 0.005;
+
+/// How dragging in the viewport rotates the camera around the target
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrbitMode {
+    /// Yaw around the world up axis and pitch up to just short of the poles,
+    /// so the camera never ends up upside-down
+    #[default]
+    Turntable,
+    /// Rotate freely around the camera's own axes, over the poles too
+    Trackball,
+}
+
+impl OrbitMode {
+    pub const ALL: [OrbitMode; 2] = [OrbitMode::Turntable, OrbitMode::Trackball];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OrbitMode::Turntable => t!("camera.turntable"),
+            OrbitMode::Trackball => t!("camera.trackball"),
+        }
+    }
+}
+
+/// Turns mouse drags into camera orbits around `CAMERA_TARGET`
+#[derive(Resource)]
+pub struct OrbitController {
+    pub mode: OrbitMode,
+    /// Turntable pitch is clamped to this, in radians either side of the horizon
+    pub max_pitch: f32,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self {
+            mode: OrbitMode::default(),
+            max_pitch: FRAC_PI_2 - 0.01,
+        }
+    }
+}
+
+impl OrbitController {
+    /// Where the camera ends up after dragging the mouse by `delta` pixels
+    pub fn orbit(&self, transform: &Transform, target: Vec3, delta: Vec2) -> Transform {
+        let offset = transform.translation - target;
+        let radius = offset.length();
+        if radius <= f32::EPSILON {
+            return *transform;
+        }
+
+        match self.mode {
+            OrbitMode::Turntable => {
+                let yaw = offset.x.atan2(offset.z) - delta.x * ORBIT_SPEED;
+                let pitch = ((offset.y / radius).clamp(-1.0, 1.0).asin() + delta.y * ORBIT_SPEED)
+                    .clamp(-self.max_pitch, self.max_pitch);
+                let direction = Vec3::new(
+                    pitch.cos() * yaw.sin(),
+                    pitch.sin(),
+                    pitch.cos() * yaw.cos(),
+                );
+                Transform {
+                    translation: target + direction * radius,
+                    scale: transform.scale,
+                    ..Default::default()
+                }
+                .looking_at(target, Vec3::Y)
+            }
+            OrbitMode::Trackball => {
+                let up = transform.rotation * Vec3::Y;
+                let right = transform.rotation * Vec3::X;
+                let rotation = Quat::from_axis_angle(up, -delta.x * ORBIT_SPEED)
+                    * Quat::from_axis_angle(right, -delta.y * ORBIT_SPEED);
+                Transform {
+                    translation: target + rotation * offset,
+                    rotation: (rotation * transform.rotation).normalize(),
+                    scale: transform.scale,
+                }
+            }
+        }
+    }
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OrbitController>()
+            .add_system(apply_projection.run_if(|settings: Res<Settings>| {
+                settings.camera.auto_clip || settings.is_changed()
+            }))
+            .add_system(sync_orbit_controller.run_if(resource_changed::<Settings>()))
+            .add_system(orbit_camera.run_if(on_event::<MouseMotion>()));
+    }
+}
+
+fn sync_orbit_controller(settings: Res<Settings>, mut controller: ResMut<OrbitController>) {
+    if controller.mode != settings.camera.orbit_mode {
+        controller.mode = settings.camera.orbit_mode;
+    }
+}
+
+/// Orbits the camera while the left mouse button is dragged over the viewport
+pub fn orbit_camera(
+    mut contexts: EguiContexts,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    controller: Res<OrbitController>,
+    gizmo: Res<TranslateGizmo>,
+    mut original_camera_transform: ResMut<OriginalCameraTransform>,
+    mut cameras: Query<
+        &mut Transform,
+        (
+            With<Camera3d>,
+            Without<OffscreenCamera>,
+            Without<OverlayCamera>,
+        ),
+    >,
+) {
+    let delta: Vec2 = mouse_motion.iter().map(|motion| motion.delta).sum();
+    // Drags that start on a panel belong to egui, and ones that start on a handle to the gizmo
+    if !mouse_buttons.pressed(MouseButton::Left)
+        || gizmo.is_dragging()
+        || contexts.ctx_mut().is_using_pointer()
+        || contexts.ctx_mut().is_pointer_over_area()
+        || delta == Vec2::ZERO
+    {
+        return;
+    }
+
+    let orbited = controller.orbit(&original_camera_transform, CAMERA_TARGET, delta);
+    **original_camera_transform = orbited;
+    // The translation follows from `update_camera_transform_system`, which offsets it for the panels
+    for mut camera_transform in cameras.iter_mut() {
+        camera_transform.rotation = orbited.rotation;
+    }
+}
+
+/// Copies the FOV and clipping planes from the settings onto the preview camera,
+/// or fits the clipping planes around the previews when auto clip is on
+pub fn apply_projection(
+    settings: Res<Settings>,
+    previews: Query<(&GlobalTransform, &Aabb), With<BlenderPreviewObject>>,
+    // The overlay pass follows along on its own
+    mut cameras: Query<
+        (&GlobalTransform, &mut Projection),
+        (
+            With<Camera3d>,
+            Without<OffscreenCamera>,
+            Without<OverlayCamera>,
+        ),
+    >,
+) {
+    for (camera_transform, mut projection) in cameras.iter_mut() {
+        let Projection::Perspective(perspective) = &*projection else {
+            continue;
+        };
+
+        let (near, far) = if settings.camera.auto_clip {
+            let bounds = previews.iter().map(|(transform, aabb)| {
+                let center = transform.transform_point(aabb.center.into());
+                let radius =
+                    (Vec3::from(aabb.half_extents) * transform.compute_transform().scale).length();
+                (center, radius)
+            });
+            auto_clip_planes(camera_transform.translation(), bounds)
+                .unwrap_or((settings.camera.near, settings.camera.far))
+        } else {
+            (settings.camera.near, settings.camera.far)
+        };
+        let fov = settings.camera.fov_degrees.to_radians();
+
+        // Only touch the projection when something changed, the camera offset reruns on it
+        if perspective.fov != fov || perspective.near != near || perspective.far != far {
+            if let Projection::Perspective(perspective) = &mut *projection {
+                perspective.fov = fov;
+                perspective.near = near;
+                perspective.far = far;
+            }
+        }
+    }
+}
+
+/// Near and far planes that just contain every bounding sphere, with some slack.
+/// `None` when there's nothing to fit.
+fn auto_clip_planes(camera: Vec3, bounds: impl Iterator<Item = (Vec3, f32)>) -> Option<(f32, f32)> {
+    let (closest, farthest) =
+        bounds.fold(None, |range: Option<(f32, f32)>, (center, radius)| {
+            let distance = camera.distance(center);
+            let (closest, farthest) = range.unwrap_or((f32::MAX, 0.0));
+            Some((
+                closest.min(distance - radius),
+                farthest.max(distance + radius),
+            ))
+        })?;
+
+    let far = farthest * 1.5;
+    // Inside the bounds there's nothing to fit the near plane to, so keep it a fraction of far
+    let near = if closest > 0.0 {
+        closest * 0.5
+    } else {
+        far * 0.0001
+    }
+    .max(MIN_AUTO_NEAR);
+    Some((near, far.max(near * 10.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start() -> Transform {
+        Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(CAMERA_TARGET, Vec3::Y)
+    }
+
+    /// Drags the mouse by `delta` every frame for `frames` frames
+    fn drag(controller: &OrbitController, delta: Vec2, frames: usize) -> Vec<Transform> {
+        let mut transform = start();
+        (0..frames)
+            .map(|_| {
+                transform = controller.orbit(&transform, CAMERA_TARGET, delta);
+                transform
+            })
+            .collect()
+    }
+
+    #[test]
+    fn turntable_never_flips_upside_down() {
+        let controller = OrbitController::default();
+
+        for delta in [
+            Vec2::new(0.0, 40.0),
+            Vec2::new(0.0, -40.0),
+            Vec2::new(25.0, 60.0),
+            Vec2::new(-70.0, -15.0),
+        ] {
+            for transform in drag(&controller, delta, 200) {
+                let up = transform.rotation * Vec3::Y;
+                assert!(
+                    up.y > 0.0,
+                    "inverted up {:?} after dragging {:?}",
+                    up,
+                    delta
+                );
+
+                let offset = transform.translation - CAMERA_TARGET;
+                let pitch = (offset.y / offset.length()).asin();
+                assert!(pitch.abs() <= controller.max_pitch + 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn orbiting_keeps_the_distance_to_the_target() {
+        let distance = start().translation.distance(CAMERA_TARGET);
+
+        for mode in OrbitMode::ALL {
+            let controller = OrbitController {
+                mode,
+                ..Default::default()
+            };
+            for transform in drag(&controller, Vec2::new(30.0, 20.0), 100) {
+                let orbited = transform.translation.distance(CAMERA_TARGET);
+                assert!((orbited - distance).abs() < 1e-3, "{:?}", mode);
+            }
+        }
+    }
+
+    #[test]
+    fn trackball_goes_over_the_poles() {
+        let controller = OrbitController {
+            mode: OrbitMode::Trackball,
+            ..Default::default()
+        };
+
+        let flipped = drag(&controller, Vec2::new(0.0, 40.0), 200)
+            .iter()
+            .any(|transform| (transform.rotation * Vec3::Y).y < 0.0);
+        assert!(flipped);
+    }
+}
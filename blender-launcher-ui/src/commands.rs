@@ -0,0 +1,588 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    accessibility::{status_label, Status},
+    confirm::Confirmation,
+    i18n::{self, t},
+};
+
+/// Everything the user can trigger from a button, a shortcut, or the command palette
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    OpenFiles,
+    ScanFolder,
+    ClearPreview,
+    Undo,
+    Redo,
+    ExportMetadata,
+    ToggleHelp,
+    CommandPalette,
+    DetachPreview,
+    RegisterFileHandler,
+    UnregisterFileHandler,
+    ShowBuilds,
+    OpenInBlender,
+    ShowRenderQueue,
+    ExportContactSheet,
+    ReloadFile,
+    RevealFile,
+    RemoveFile,
+    ExportLayout,
+    ImportLayout,
+    ToggleMeasure,
+    Quit,
+    ShowAbout,
+}
+
+/// Sent whenever an action should run, regardless of where it was triggered from
+pub struct ActionEvent(pub Action);
+
+/// A key plus the modifiers that need to be held with it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyChord {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    pub const fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    /// Builds a chord from a key and whatever modifiers are currently held
+    pub fn with_held_modifiers(key: KeyCode, keys: &Input<KeyCode>) -> Self {
+        Self {
+            key,
+            ctrl: keys.any_pressed([KeyCode::LControl, KeyCode::RControl]),
+            shift: keys.any_pressed([KeyCode::LShift, KeyCode::RShift]),
+            alt: keys.any_pressed([KeyCode::LAlt, KeyCode::RAlt]),
+        }
+    }
+
+    /// Was the chord pressed this frame? Modifiers have to match exactly
+    /// so `Ctrl+Z` doesn't also fire `Z`.
+    pub fn just_pressed(&self, keys: &Input<KeyCode>) -> bool {
+        let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+        let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+        let alt = keys.any_pressed([KeyCode::LAlt, KeyCode::RAlt]);
+
+        keys.just_pressed(self.key) && ctrl == self.ctrl && shift == self.shift && alt == self.alt
+    }
+
+    /// Human readable version of the chord, e.g. `Ctrl+P`
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        if self.ctrl {
+            label.push_str("Ctrl+");
+        }
+        if self.shift {
+            label.push_str("Shift+");
+        }
+        if self.alt {
+            label.push_str("Alt+");
+        }
+        label.push_str(&key_name(self.key));
+        label
+    }
+}
+
+fn is_modifier(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::LControl
+            | KeyCode::RControl
+            | KeyCode::LShift
+            | KeyCode::RShift
+            | KeyCode::LAlt
+            | KeyCode::RAlt
+            | KeyCode::LWin
+            | KeyCode::RWin
+    )
+}
+
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Slash => "/".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        key => format!("{:?}", key),
+    }
+}
+
+/// A registered command, the single place an action gets its name and shortcut
+pub struct Command {
+    pub action: Action,
+    /// Translation key of the name shown in menus and settings
+    name_key: &'static str,
+    pub chord: Option<KeyChord>,
+}
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        i18n::tr(self.name_key)
+    }
+}
+
+/// A saved binding for an action, `None` means the user unbound it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Binding {
+    pub action: Action,
+    pub chord: Option<KeyChord>,
+}
+
+/// All the commands in the app. New features register their action here
+/// so they show up in the help overlay and the command palette automatically.
+#[derive(Resource)]
+pub struct Keybindings {
+    pub commands: Vec<Command>,
+    /// The action waiting for the user to press a new chord
+    pub rebinding: Option<Action>,
+}
+
+impl Keybindings {
+    pub fn command(&self, action: Action) -> Option<&Command> {
+        self.commands
+            .iter()
+            .find(|command| command.action == action)
+    }
+
+    pub fn chord(&self, action: Action) -> Option<KeyChord> {
+        self.commands
+            .iter()
+            .find(|command| command.action == action)
+            .and_then(|command| command.chord)
+    }
+
+    pub fn set_chord(&mut self, action: Action, chord: Option<KeyChord>) {
+        if let Some(command) = self
+            .commands
+            .iter_mut()
+            .find(|command| command.action == action)
+        {
+            command.chord = chord;
+        }
+    }
+
+    /// Other commands bound to the same chord as `action`
+    pub fn conflicts(&self, action: Action) -> Vec<&Command> {
+        let Some(chord) = self.chord(action) else {
+            return Vec::new();
+        };
+
+        self.commands
+            .iter()
+            .filter(|command| command.action != action && command.chord == Some(chord))
+            .collect()
+    }
+
+    /// The bindings we persist in the settings file
+    pub fn bindings(&self) -> Vec<Binding> {
+        self.commands
+            .iter()
+            .map(|command| Binding {
+                action: command.action,
+                chord: command.chord,
+            })
+            .collect()
+    }
+
+    /// Applies saved bindings on top of the defaults, so commands added
+    /// since the settings were saved still get their default chord
+    pub fn from_bindings(bindings: &[Binding]) -> Self {
+        let mut keybindings = Self::default();
+        for binding in bindings {
+            keybindings.set_chord(binding.action, binding.chord);
+        }
+        keybindings
+    }
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            commands: vec![
+                Command {
+                    action: Action::OpenFiles,
+                    name_key: "command.open_files",
+                    chord: Some(KeyChord::new(KeyCode::O).ctrl()),
+                },
+                Command {
+                    action: Action::ScanFolder,
+                    name_key: "command.scan_folder",
+                    chord: Some(KeyChord::new(KeyCode::O).ctrl().shift()),
+                },
+                Command {
+                    action: Action::ClearPreview,
+                    name_key: "command.clear_preview",
+                    chord: Some(KeyChord::new(KeyCode::Delete)),
+                },
+                Command {
+                    action: Action::Undo,
+                    name_key: "command.undo",
+                    chord: Some(KeyChord::new(KeyCode::Z).ctrl()),
+                },
+                Command {
+                    action: Action::Redo,
+                    name_key: "command.redo",
+                    chord: Some(KeyChord::new(KeyCode::Z).ctrl().shift()),
+                },
+                Command {
+                    action: Action::ExportMetadata,
+                    name_key: "command.export_metadata",
+                    chord: Some(KeyChord::new(KeyCode::E).ctrl()),
+                },
+                Command {
+                    action: Action::ToggleHelp,
+                    name_key: "command.show_shortcuts",
+                    // `?` is Shift+/ on most layouts
+                    chord: Some(KeyChord::new(KeyCode::Slash).shift()),
+                },
+                Command {
+                    action: Action::CommandPalette,
+                    name_key: "command.palette",
+                    chord: Some(KeyChord::new(KeyCode::P).ctrl()),
+                },
+                Command {
+                    action: Action::DetachPreview,
+                    name_key: "command.detach_preview",
+                    chord: None,
+                },
+                Command {
+                    action: Action::RegisterFileHandler,
+                    name_key: "command.register_handler",
+                    chord: None,
+                },
+                Command {
+                    action: Action::UnregisterFileHandler,
+                    name_key: "command.unregister_handler",
+                    chord: None,
+                },
+                Command {
+                    action: Action::ShowBuilds,
+                    name_key: "command.builds",
+                    chord: None,
+                },
+                Command {
+                    action: Action::OpenInBlender,
+                    name_key: "command.open_in_blender",
+                    chord: None,
+                },
+                Command {
+                    action: Action::ShowRenderQueue,
+                    name_key: "command.render_queue",
+                    chord: None,
+                },
+                Command {
+                    action: Action::ExportContactSheet,
+                    name_key: "command.contact_sheet",
+                    chord: None,
+                },
+                Command {
+                    action: Action::ReloadFile,
+                    name_key: "command.reload_file",
+                    chord: Some(KeyChord::new(KeyCode::F5)),
+                },
+                Command {
+                    action: Action::RevealFile,
+                    name_key: "command.reveal_file",
+                    chord: None,
+                },
+                Command {
+                    action: Action::RemoveFile,
+                    name_key: "command.remove_file",
+                    chord: None,
+                },
+                Command {
+                    action: Action::ExportLayout,
+                    name_key: "command.export_layout",
+                    chord: None,
+                },
+                Command {
+                    action: Action::ImportLayout,
+                    name_key: "command.import_layout",
+                    chord: None,
+                },
+                Command {
+                    action: Action::ToggleMeasure,
+                    name_key: "command.measure",
+                    chord: Some(KeyChord::new(KeyCode::M)),
+                },
+                Command {
+                    action: Action::Quit,
+                    name_key: "command.quit",
+                    chord: Some(KeyChord::new(KeyCode::Q).ctrl()),
+                },
+                Command {
+                    action: Action::ShowAbout,
+                    name_key: "command.about",
+                    chord: None,
+                },
+            ],
+            rebinding: None,
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct CommandPaletteState {
+    pub help_open: bool,
+    pub palette_open: bool,
+    pub query: String,
+}
+
+pub struct CommandsPlugin;
+
+impl Plugin for CommandsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Keybindings>()
+            .init_resource::<CommandPaletteState>()
+            .add_event::<ActionEvent>()
+            .add_system(keyboard_shortcuts.before(capture_rebinding))
+            .add_system(capture_rebinding)
+            .add_system(toggle_overlays)
+            .add_system(command_palette_ui)
+            .add_system(help_overlay_ui);
+    }
+}
+
+/// Turns key presses into actions using the registered keybindings
+fn keyboard_shortcuts(
+    mut contexts: EguiContexts,
+    keys: Res<Input<KeyCode>>,
+    keybindings: Res<Keybindings>,
+    confirmation: Res<Confirmation>,
+    mut action_events: EventWriter<ActionEvent>,
+) {
+    // Don't steal keys while the user is typing in a text field, picking a new chord,
+    // or answering a confirmation
+    if contexts.ctx_mut().wants_keyboard_input()
+        || keybindings.rebinding.is_some()
+        || confirmation.is_open()
+    {
+        return;
+    }
+
+    for command in keybindings.commands.iter() {
+        if let Some(chord) = command.chord {
+            if chord.just_pressed(&keys) {
+                action_events.send(ActionEvent(command.action));
+            }
+        }
+    }
+}
+
+/// Waits for the next chord after the user clicked an action in the settings
+fn capture_rebinding(keys: Res<Input<KeyCode>>, mut keybindings: ResMut<Keybindings>) {
+    let Some(action) = keybindings.rebinding else {
+        return;
+    };
+
+    // Escape cancels, we don't allow binding Escape itself
+    if keys.just_pressed(KeyCode::Escape) {
+        keybindings.rebinding = None;
+        return;
+    }
+
+    // Modifiers on their own aren't a chord, wait for the actual key
+    let Some(key) = keys
+        .get_just_pressed()
+        .copied()
+        .find(|key| !is_modifier(*key))
+    else {
+        return;
+    };
+
+    let chord = KeyChord::with_held_modifiers(key, &keys);
+    keybindings.set_chord(action, Some(chord));
+    keybindings.rebinding = None;
+}
+
+/// Renders the rebinding controls for the settings page.
+/// Returns `true` when a binding changed so the settings get saved.
+pub fn keybindings_ui(ui: &mut egui::Ui, keybindings: &mut Keybindings) -> bool {
+    let mut changed = false;
+
+    egui::Grid::new("rebind_grid").striped(true).show(ui, |ui| {
+        for index in 0..keybindings.commands.len() {
+            let action = keybindings.commands[index].action;
+            ui.label(keybindings.commands[index].name());
+
+            let label = if keybindings.rebinding == Some(action) {
+                t!("shortcuts.press_a_key").to_string()
+            } else {
+                keybindings.commands[index]
+                    .chord
+                    .map_or(t!("shortcuts.unbound").to_string(), |chord| chord.label())
+            };
+            if ui.button(label).clicked() {
+                keybindings.rebinding = Some(action);
+                changed = true;
+            }
+
+            let conflicts = keybindings.conflicts(action);
+            if conflicts.is_empty() {
+                ui.label("");
+            } else {
+                let names: Vec<&str> = conflicts.iter().map(|command| command.name()).collect();
+                status_label(
+                    ui,
+                    Status::Warning,
+                    t!("shortcuts.conflicts", commands = names.join(", ")),
+                );
+            }
+            ui.end_row();
+        }
+    });
+
+    if ui.button(t!("shortcuts.reset")).clicked() {
+        *keybindings = Keybindings::default();
+        changed = true;
+    }
+
+    changed
+}
+
+fn toggle_overlays(
+    mut action_events: EventReader<ActionEvent>,
+    mut palette: ResMut<CommandPaletteState>,
+) {
+    for ActionEvent(action) in action_events.iter() {
+        match action {
+            Action::ToggleHelp => palette.help_open = !palette.help_open,
+            Action::CommandPalette => {
+                palette.palette_open = !palette.palette_open;
+                palette.query.clear();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Scores how well `query` matches `name` as a case insensitive subsequence.
+/// Lower is better, `None` means the query doesn't match at all.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+
+    for query_char in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let offset = name[position..]
+            .iter()
+            .position(|name_char| *name_char == query_char)?;
+        // Characters that are far apart make for a worse match
+        score += offset;
+        position += offset + 1;
+    }
+
+    Some(score)
+}
+
+fn command_palette_ui(
+    mut contexts: EguiContexts,
+    keybindings: Res<Keybindings>,
+    mut palette: ResMut<CommandPaletteState>,
+    mut action_events: EventWriter<ActionEvent>,
+) {
+    if !palette.palette_open {
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+    let mut selected_action = None;
+
+    egui::Window::new(t!("command.palette"))
+        .id(egui::Id::new("command_palette"))
+        .collapsible(false)
+        .resizable(false)
+        .title_bar(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .show(ctx, |ui| {
+            let response = ui.text_edit_singleline(&mut palette.query);
+            response.request_focus();
+
+            // Rank the commands by how well they match
+            let mut matches: Vec<(usize, &Command)> = keybindings
+                .commands
+                .iter()
+                .filter(|command| command.action != Action::CommandPalette)
+                .filter_map(|command| Some((fuzzy_score(&palette.query, command.name())?, command)))
+                .collect();
+            matches.sort_by_key(|(score, _)| *score);
+
+            for (_, command) in matches.iter() {
+                ui.horizontal(|ui| {
+                    if ui.button(command.name()).clicked() {
+                        selected_action = Some(command.action);
+                    }
+                    if let Some(chord) = command.chord {
+                        ui.weak(chord.label());
+                    }
+                });
+            }
+
+            // Enter runs the best match
+            if ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                selected_action = matches.first().map(|(_, command)| command.action);
+            }
+        });
+
+    let escape_pressed = ctx.input(|input| input.key_pressed(egui::Key::Escape));
+    if let Some(action) = selected_action {
+        action_events.send(ActionEvent(action));
+        palette.palette_open = false;
+    } else if escape_pressed {
+        palette.palette_open = false;
+    }
+}
+
+fn help_overlay_ui(
+    mut contexts: EguiContexts,
+    keybindings: Res<Keybindings>,
+    mut palette: ResMut<CommandPaletteState>,
+) {
+    if !palette.help_open {
+        return;
+    }
+
+    let mut open = true;
+    egui::Window::new(t!("shortcuts.heading"))
+        .id(egui::Id::new("keyboard_shortcuts"))
+        .open(&mut open)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("keybindings_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    for command in keybindings.commands.iter() {
+                        if let Some(chord) = command.chord {
+                            ui.label(command.name());
+                            ui.monospace(chord.label());
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+
+    if !open {
+        palette.help_open = false;
+    }
+}
@@ -0,0 +1,354 @@
+use bevy::{prelude::*, render::primitives::Aabb, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+
+use crate::{
+    asset_cache::AssetCache, commands::fuzzy_score, file_status::FileOp,
+    history::PreviewDescriptor, i18n::t, spawn_preview, viewport::viewport_to_egui, AppState,
+    BlenderPreviewObject, FileId, LauncherCamera,
+};
+
+/// Gap between the two compared objects, in world units
+const COMPARE_PADDING: f32 = 0.5;
+
+/// A (file, object) pair
+type ObjectRef = (FileId, usize);
+
+/// Replaces the preview with `left` and `right` side by side
+pub struct CompareEvent {
+    pub left: ObjectRef,
+    pub right: ObjectRef,
+}
+
+pub struct ClearComparison;
+
+/// Which side of the comparison an entity is on, 0 is left
+#[derive(Component)]
+struct CompareSide(usize);
+
+#[derive(Default, Resource)]
+pub struct CompareState {
+    /// The object we're picking something to compare against
+    picking_for: Option<ObjectRef>,
+    query: String,
+    pair: Option<[ObjectRef; 2]>,
+    /// Set between spawning and the entities showing up in queries
+    awaiting_spawn: bool,
+}
+
+impl CompareState {
+    pub fn open_picker(&mut self, object: ObjectRef) {
+        self.picking_for = Some(object);
+        self.query.clear();
+    }
+}
+
+pub struct ComparePlugin;
+
+impl Plugin for ComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CompareState>()
+            .add_event::<CompareEvent>()
+            .add_event::<ClearComparison>()
+            .add_system(spawn_comparison.run_if(on_event::<CompareEvent>()))
+            .add_system(clear_comparison.run_if(on_event::<ClearComparison>()))
+            .add_system(
+                layout_comparison.run_if(|compare: Res<CompareState>| compare.pair.is_some()),
+            )
+            .add_system(
+                comparison_labels_ui.run_if(|compare: Res<CompareState>| compare.pair.is_some()),
+            )
+            .add_system(
+                compare_picker_ui
+                    .run_if(|compare: Res<CompareState>| compare.picking_for.is_some()),
+            );
+    }
+}
+
+fn spawn_comparison(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut compare_events: EventReader<CompareEvent>,
+    mut compare: ResMut<CompareState>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    app_state: Res<AppState>,
+    blender_objects: Query<Entity, With<BlenderPreviewObject>>,
+) {
+    let Some(event) = compare_events.iter().last() else {
+        return;
+    };
+
+    let mut descriptors = Vec::new();
+    for (file_id, mesh_id) in [event.left, event.right] {
+        let Some(file) = app_state
+            .file(file_id)
+            .filter(|file| file.status.blocked(FileOp::Spawn).is_none())
+        else {
+            return;
+        };
+        let Some(mesh) = file.meshes.get(mesh_id) else {
+            return;
+        };
+        descriptors.push(PreviewDescriptor {
+            file: file_id,
+            mesh: mesh_id,
+            object: mesh.name.clone(),
+            datablock: mesh.mesh_datablock().to_string(),
+            mesh_handle: asset_cache.mesh(&asset_server, &file.path, mesh),
+            material_handle: asset_cache.object_material(&asset_server, &mut materials, file, mesh),
+            transform: Transform::default(),
+        });
+    }
+
+    for blender_entity in blender_objects.iter() {
+        commands.entity(blender_entity).despawn_recursive();
+    }
+
+    // The right side is moved over by `layout_comparison` once both meshes have bounds
+    for (side, descriptor) in descriptors.iter().enumerate() {
+        let entity = spawn_preview(&mut commands, descriptor);
+        commands.entity(entity).insert(CompareSide(side));
+    }
+
+    compare.pair = Some([event.left, event.right]);
+    compare.awaiting_spawn = true;
+}
+
+fn clear_comparison(
+    mut commands: Commands,
+    mut clear_events: EventReader<ClearComparison>,
+    mut compare: ResMut<CompareState>,
+    sides: Query<Entity, With<CompareSide>>,
+) {
+    clear_events.clear();
+
+    for entity in sides.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    compare.pair = None;
+}
+
+/// Puts the right object next to the left one's bounds,
+/// and forgets the comparison once something else replaced the preview
+fn layout_comparison(
+    mut compare: ResMut<CompareState>,
+    mut sides: Query<(
+        &CompareSide,
+        &mut Transform,
+        &mut BlenderPreviewObject,
+        Option<&Aabb>,
+    )>,
+) {
+    if sides.is_empty() {
+        if !compare.awaiting_spawn {
+            compare.pair = None;
+        }
+        return;
+    }
+    if compare.awaiting_spawn {
+        compare.awaiting_spawn = false;
+    }
+
+    let mut bounds = [None, None];
+    for (side, _, _, aabb) in sides.iter() {
+        if let Some(aabb) = aabb {
+            bounds[side.0.min(1)] = Some((aabb.center.x, aabb.half_extents.x));
+        }
+    }
+    let [Some((left_center, left_half_width)), Some((right_center, right_half_width))] = bounds
+    else {
+        return;
+    };
+
+    let offset_x =
+        left_center + left_half_width + COMPARE_PADDING + right_half_width - right_center;
+    for (side, mut transform, mut preview, _) in sides.iter_mut() {
+        if side.0 == 1 && preview.original_transform.translation.x != offset_x {
+            preview.original_transform.translation.x = offset_x;
+            transform.translation.x = offset_x;
+        }
+    }
+}
+
+/// Floats each object's name above it
+fn comparison_labels_ui(
+    mut contexts: EguiContexts,
+    app_state: Res<AppState>,
+    egui_settings: Res<EguiSettings>,
+    sides: Query<(
+        &CompareSide,
+        &BlenderPreviewObject,
+        &GlobalTransform,
+        Option<&Aabb>,
+    )>,
+    // The labels are drawn on the primary window, over its camera
+    cameras: Query<(&Camera, &GlobalTransform), With<LauncherCamera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let ui_scale = egui_settings.scale_factor as f32;
+    let ctx = contexts.ctx_mut();
+
+    for (side, preview, transform, aabb) in sides.iter() {
+        let top = aabb.map_or(Vec3::ZERO, |aabb| {
+            Vec3::from(aabb.center) + Vec3::Y * aabb.half_extents.y
+        });
+        let Some(viewport_position) =
+            camera.world_to_viewport(camera_transform, transform.transform_point(top))
+        else {
+            continue;
+        };
+        let Some(object) = app_state
+            .file(preview.file)
+            .and_then(|file| file.meshes.get(preview.mesh))
+        else {
+            continue;
+        };
+
+        let position = viewport_to_egui(viewport_position, window, ui_scale);
+        let label = format!("{} {}", side_name(side.0), object.name);
+        egui::Area::new(format!("compare_label_{}", side.0))
+            .fixed_pos(position)
+            .pivot(egui::Align2::CENTER_BOTTOM)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(label);
+                });
+            });
+    }
+}
+
+fn side_name(side: usize) -> &'static str {
+    if side == 0 {
+        "A"
+    } else {
+        "B"
+    }
+}
+
+/// A searchable list of every object in every file
+fn compare_picker_ui(
+    mut contexts: EguiContexts,
+    mut compare: ResMut<CompareState>,
+    app_state: Res<AppState>,
+    mut compare_events: EventWriter<CompareEvent>,
+) {
+    let Some(left) = compare.picking_for else {
+        return;
+    };
+
+    let mut open = true;
+    let mut picked = None;
+    egui::Window::new(t!("compare.picker"))
+        .id(egui::Id::new("compare_picker"))
+        .open(&mut open)
+        .collapsible(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.text_edit_singleline(&mut compare.query).request_focus();
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for file in app_state.files.iter() {
+                        for (mesh_id, object) in file.meshes.iter().enumerate() {
+                            if (file.id, mesh_id) == left || object.stats.is_none() {
+                                continue;
+                            }
+                            if fuzzy_score(&compare.query, &object.name).is_none() {
+                                continue;
+                            }
+                            let label = format!("{}  ({})", object.name, file.path);
+                            if ui.selectable_label(false, label).clicked() {
+                                picked = Some((file.id, mesh_id));
+                            }
+                        }
+                    }
+                });
+        });
+
+    if let Some(right) = picked {
+        compare_events.send(CompareEvent { left, right });
+    }
+    if picked.is_some() || !open {
+        compare.picking_for = None;
+    }
+}
+
+/// What the user asked for from the comparison section of the inspector
+pub enum ComparisonAction {
+    Swap,
+    Clear,
+}
+
+/// Both objects' stats in two columns, with swap and clear buttons
+pub fn comparison_ui(
+    ui: &mut egui::Ui,
+    compare: &CompareState,
+    app_state: &AppState,
+) -> Option<ComparisonAction> {
+    let [left, right] = compare.pair?;
+    let objects = [left, right].map(|(file_id, mesh_id)| {
+        app_state
+            .file(file_id)
+            .and_then(|file| file.meshes.get(mesh_id))
+    });
+
+    let mut action = None;
+    ui.heading(t!("compare.heading"));
+    egui::Grid::new("comparison_stats")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            for (side, object) in objects.iter().enumerate() {
+                let name = object.map_or("?", |object| object.name.as_str());
+                ui.strong(format!("{} {}", side_name(side), name));
+            }
+            ui.end_row();
+
+            type Stat = fn(&crate::metadata::MeshStats) -> usize;
+            let rows: [(&str, Stat); 5] = [
+                (t!("compare.vertices"), |stats| stats.vertices),
+                (t!("compare.edges"), |stats| stats.edges),
+                (t!("compare.faces"), |stats| stats.faces),
+                (t!("compare.triangles"), |stats| stats.triangles),
+                (t!("compare.ngons"), |stats| stats.ngons),
+            ];
+            for (label, stat) in rows {
+                ui.label(label);
+                for object in objects.iter() {
+                    match object.and_then(|object| object.stats.as_ref()) {
+                        Some(stats) => ui.label(stat(stats).to_string()),
+                        None => ui.weak("-"),
+                    };
+                }
+                ui.end_row();
+            }
+        });
+
+    ui.horizontal(|ui| {
+        if ui.button(t!("compare.swap")).clicked() {
+            action = Some(ComparisonAction::Swap);
+        }
+        if ui.button(t!("compare.clear")).clicked() {
+            action = Some(ComparisonAction::Clear);
+        }
+    });
+    action
+}
+
+impl CompareState {
+    /// The pair with its sides swapped, ready to respawn
+    pub fn swapped(&self) -> Option<CompareEvent> {
+        let [left, right] = self.pair?;
+        Some(CompareEvent {
+            left: right,
+            right: left,
+        })
+    }
+}
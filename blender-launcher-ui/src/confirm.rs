@@ -0,0 +1,190 @@
+use bevy::{app::AppExit, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::t, render_queue::RenderProcesses, settings::Settings, workspace::WorkspaceAction,
+    AppState, FileId, RemoveFile,
+};
+
+/// The kinds of destructive actions we ask about, used for "don't ask again"
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ConfirmKind {
+    RemoveFile,
+    ClearFiles,
+    DeleteWorkspace,
+    QuitWhileRendering,
+}
+
+impl ConfirmKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConfirmKind::RemoveFile => t!("confirm.kind.remove_file"),
+            ConfirmKind::ClearFiles => t!("confirm.kind.clear_files"),
+            ConfirmKind::DeleteWorkspace => t!("confirm.kind.delete_workspace"),
+            ConfirmKind::QuitWhileRendering => t!("confirm.kind.quit_while_rendering"),
+        }
+    }
+}
+
+/// An action that only runs after the user confirms it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DestructiveAction {
+    RemoveFile(FileId),
+    ClearFiles,
+    /// Deletes a named workspace along with its session file
+    DeleteWorkspace(String),
+    /// Closes the app while renders are running
+    Quit,
+}
+
+impl DestructiveAction {
+    fn kind(&self) -> ConfirmKind {
+        match self {
+            DestructiveAction::RemoveFile(_) => ConfirmKind::RemoveFile,
+            DestructiveAction::ClearFiles => ConfirmKind::ClearFiles,
+            DestructiveAction::DeleteWorkspace(_) => ConfirmKind::DeleteWorkspace,
+            DestructiveAction::Quit => ConfirmKind::QuitWhileRendering,
+        }
+    }
+
+    fn message(&self, app_state: &AppState, settings: &Settings, renders: usize) -> String {
+        match self {
+            DestructiveAction::RemoveFile(file_id) => match app_state.file(*file_id) {
+                Some(file) => t!("confirm.remove_file", path = file.path),
+                None => t!("confirm.remove_unknown_file").to_string(),
+            },
+            DestructiveAction::ClearFiles => {
+                t!("confirm.clear_files", count = app_state.files.len())
+            }
+            DestructiveAction::DeleteWorkspace(name) => {
+                t!("confirm.delete_workspace", name = name)
+            }
+            DestructiveAction::Quit if settings.kill_renders_on_exit => {
+                t!("confirm.quit_stops_renders", count = renders)
+            }
+            DestructiveAction::Quit => t!("confirm.quit_leaves_renders", count = renders),
+        }
+    }
+}
+
+/// The action waiting on the confirmation modal, if any
+#[derive(Default, Resource)]
+pub struct Confirmation {
+    pending: Option<DestructiveAction>,
+    dont_ask_again: bool,
+}
+
+impl Confirmation {
+    /// Asks the user to confirm `action`, replacing anything already pending
+    pub fn request(&mut self, action: DestructiveAction) {
+        self.pending = Some(action);
+        self.dont_ask_again = false;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+/// Whether closing the app has to be confirmed first, which it does while renders are running
+pub fn confirms_quit(settings: &Settings, processes: &RenderProcesses) -> bool {
+    processes.is_busy()
+        && !settings
+            .skip_confirmations
+            .contains(&ConfirmKind::QuitWhileRendering)
+}
+
+pub struct ConfirmPlugin;
+
+impl Plugin for ConfirmPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Confirmation>()
+            .add_system(confirmation_ui);
+    }
+}
+
+/// Shows the modal for the pending action and runs it once confirmed.
+/// Actions the user opted out of confirming run straight away.
+fn confirmation_ui(
+    mut contexts: EguiContexts,
+    mut confirmation: ResMut<Confirmation>,
+    mut settings: ResMut<Settings>,
+    app_state: Res<AppState>,
+    render_processes: Res<RenderProcesses>,
+    mut remove_file_events: EventWriter<RemoveFile>,
+    mut workspace_actions: EventWriter<WorkspaceAction>,
+    mut exits: EventWriter<AppExit>,
+) {
+    let Some(action) = confirmation.pending.clone() else {
+        return;
+    };
+
+    let confirmed = if settings.skip_confirmations.contains(&action.kind()) {
+        true
+    } else {
+        let ctx = contexts.ctx_mut();
+        let mut confirmed = ctx.input(|i| i.key_pressed(egui::Key::Enter));
+        let mut cancelled = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+
+        // An invisible layer over everything that swallows clicks and hovers,
+        // so the panels underneath can't be used while the modal is open.
+        // It's only UI, the viewport keeps rendering.
+        let screen_rect = ctx.screen_rect();
+        egui::Area::new("confirmation_backdrop")
+            .order(egui::Order::Middle)
+            .fixed_pos(screen_rect.min)
+            .show(ctx, |ui| {
+                ui.allocate_response(screen_rect.size(), egui::Sense::click_and_drag());
+                ui.painter()
+                    .rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(96));
+            });
+
+        let dont_ask_again = &mut confirmation.dont_ask_again;
+        // An area rather than a window so it's always drawn above the backdrop
+        egui::Area::new("confirmation_modal")
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Frame::window(ui.style()).show(ui, |ui| {
+                    ui.heading(t!("confirm.heading"));
+                    ui.label(action.message(
+                        &app_state,
+                        &settings,
+                        render_processes.running_count(),
+                    ));
+                    ui.checkbox(dont_ask_again, t!("confirm.dont_ask_again"));
+                    ui.horizontal(|ui| {
+                        confirmed |= ui.button(t!("confirm.ok")).clicked();
+                        cancelled |= ui.button(t!("confirm.cancel")).clicked();
+                    });
+                });
+            });
+
+        if cancelled {
+            confirmation.pending = None;
+            return;
+        }
+        confirmed
+    };
+
+    if !confirmed {
+        return;
+    }
+
+    if confirmation.dont_ask_again {
+        settings.skip_confirmations.insert(action.kind());
+    }
+    confirmation.pending = None;
+
+    match action {
+        DestructiveAction::RemoveFile(file_id) => remove_file_events.send(RemoveFile(file_id)),
+        DestructiveAction::ClearFiles => {
+            remove_file_events.send_batch(app_state.files.iter().map(|file| RemoveFile(file.id)))
+        }
+        DestructiveAction::DeleteWorkspace(name) => {
+            workspace_actions.send(WorkspaceAction::Delete(name))
+        }
+        DestructiveAction::Quit => exits.send(AppExit),
+    }
+}
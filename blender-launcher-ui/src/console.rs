@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// How many lines we keep before dropping the oldest ones
+const MAX_LINES: usize = 500;
+
+/// Messages shown in the log console in the bottom panel
+#[derive(Default, Resource)]
+pub struct LogConsole {
+    lines: Vec<String>,
+}
+
+impl LogConsole {
+    pub fn push(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+        if self.lines.len() > MAX_LINES {
+            let overflow = self.lines.len() - MAX_LINES;
+            self.lines.drain(..overflow);
+        }
+    }
+}
+
+/// Renders the console lines, newest at the bottom
+pub fn console_ui(ui: &mut egui::Ui, console: &LogConsole, monospace: bool) {
+    egui::ScrollArea::vertical()
+        .stick_to_bottom(true)
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for line in console.lines.iter() {
+                if monospace {
+                    ui.monospace(line.as_str());
+                } else {
+                    ui.label(line.as_str());
+                }
+            }
+        });
+}
@@ -0,0 +1,244 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use bevy_egui::egui::{
+    self,
+    epaint::text::{FontDefinitions, Fonts},
+};
+use futures_lite::future;
+use image::{
+    imageops::{self, FilterType},
+    Rgba, RgbaImage,
+};
+use rfd::FileDialog;
+
+use crate::{
+    commands::{Action, ActionEvent},
+    i18n::t,
+    settings::{ContactSheetLayout, Settings},
+    thumbnails::{cache_dir, thumbnail_objects, thumbnail_path, Subject},
+    toasts::{Severity, ToastAction, Toasts},
+    AppState,
+};
+
+/// Space under each cell for the object's name, in pixels
+const LABEL_HEIGHT: u32 = 20;
+const FONT_SIZE: f32 = 12.0;
+/// Space around each cell, in pixels
+const PADDING: u32 = 4;
+const BACKGROUND: Rgba<u8> = Rgba([32, 32, 36, 255]);
+/// Fills the cells of objects without a cached thumbnail
+const MISSING: Rgba<u8> = Rgba([48, 48, 54, 255]);
+const TEXT_COLOR: [u8; 3] = [230, 230, 230];
+
+/// Contact sheets being composited. Each finishes with the first page's path
+/// and the number of pages.
+#[derive(Default, Resource)]
+pub struct ContactSheetExports(Vec<Task<Result<(PathBuf, usize), String>>>);
+
+impl ContactSheetExports {
+    pub fn is_exporting(&self) -> bool {
+        !self.0.is_empty()
+    }
+}
+
+pub struct ContactSheetPlugin;
+
+impl Plugin for ContactSheetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContactSheetExports>()
+            .add_system(export_contact_sheet.run_if(on_event::<ActionEvent>()))
+            .add_system(
+                finish_contact_sheets
+                    .run_if(|exports: Res<ContactSheetExports>| exports.is_exporting()),
+            );
+    }
+}
+
+/// Asks where to save the selected file's contact sheet, and composites it in the background
+fn export_contact_sheet(
+    mut actions: EventReader<ActionEvent>,
+    app_state: Res<AppState>,
+    settings: Res<Settings>,
+    mut exports: ResMut<ContactSheetExports>,
+    mut toasts: ResMut<Toasts>,
+) {
+    if !actions
+        .iter()
+        .any(|ActionEvent(action)| *action == Action::ExportContactSheet)
+    {
+        return;
+    }
+    let Some(file) = app_state.selected_file() else {
+        return;
+    };
+    let names: Vec<String> = thumbnail_objects(file).map(str::to_string).collect();
+    if names.is_empty() {
+        toasts.push(Severity::Warning, t!("contact_sheet.no_objects"));
+        return;
+    }
+
+    let stem = Path::new(&file.path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "contact_sheet".to_string());
+    let Some(path) = FileDialog::new()
+        .add_filter("PNG", &["png"])
+        .set_file_name(&format!("{stem}.png"))
+        .save_file()
+    else {
+        return;
+    };
+
+    let source = file.path.clone();
+    let layout = settings.contact_sheet;
+    exports
+        .0
+        .push(AsyncComputeTaskPool::get().spawn(async move {
+            // Without a cache folder every cell is empty, the names are still worth having
+            let dir = cache_dir(&source);
+            write_contact_sheets(&names, dir.as_deref(), layout, &path)
+        }));
+}
+
+fn finish_contact_sheets(mut exports: ResMut<ContactSheetExports>, mut toasts: ResMut<Toasts>) {
+    exports
+        .0
+        .retain_mut(|task| match future::block_on(future::poll_once(task)) {
+            Some(Ok((path, pages))) => {
+                toasts.push_with_action(
+                    Severity::Success,
+                    t!("contact_sheet.done", pages = pages),
+                    ToastAction::RevealFile(path),
+                );
+                false
+            }
+            Some(Err(error)) => {
+                error!(%error, "failed to export contact sheet");
+                toasts.push(Severity::Error, t!("contact_sheet.failed", error = error));
+                false
+            }
+            None => true,
+        });
+}
+
+/// Writes as many pages as the layout needs for `names`, numbered when there's more
+/// than one
+fn write_contact_sheets(
+    names: &[String],
+    dir: Option<&Path>,
+    layout: ContactSheetLayout,
+    path: &Path,
+) -> Result<(PathBuf, usize), String> {
+    let fonts = Fonts::new(1.0, 2048, FontDefinitions::default());
+    let pages: Vec<&[String]> = names.chunks(layout.per_page()).collect();
+    let paths = page_paths(path, pages.len());
+    for (page, page_path) in pages.iter().zip(&paths) {
+        compose_page(page, dir, layout, &fonts)
+            .save(page_path)
+            .map_err(|error| error.to_string())?;
+    }
+    Ok((paths[0].clone(), pages.len()))
+}
+
+/// `sheet.png` for a single page, `sheet-01.png`, `sheet-02.png`, ... otherwise
+fn page_paths(path: &Path, pages: usize) -> Vec<PathBuf> {
+    if pages <= 1 {
+        return vec![path.to_path_buf()];
+    }
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let width = pages.to_string().len().max(2);
+    (1..=pages)
+        .map(|page| path.with_file_name(format!("{stem}-{page:0width$}.png")))
+        .collect()
+}
+
+fn compose_page(
+    names: &[String],
+    dir: Option<&Path>,
+    layout: ContactSheetLayout,
+    fonts: &Fonts,
+) -> RgbaImage {
+    let columns = layout.columns.clamp(1, names.len().max(1));
+    let rows = names.len().div_ceil(columns);
+    let cell = layout.cell_size;
+    let cell_width = cell + PADDING * 2;
+    let cell_height = cell + LABEL_HEIGHT + PADDING * 2;
+    let mut sheet = RgbaImage::from_pixel(
+        columns as u32 * cell_width,
+        rows as u32 * cell_height,
+        BACKGROUND,
+    );
+
+    for (index, name) in names.iter().enumerate() {
+        let x = (index % columns) as u32 * cell_width + PADDING;
+        let y = (index / columns) as u32 * cell_height + PADDING;
+        match dir.and_then(|dir| image::open(thumbnail_path(dir, Subject::Object, name)).ok()) {
+            Some(thumbnail) => {
+                let thumbnail = imageops::resize(&thumbnail, cell, cell, FilterType::Triangle);
+                imageops::overlay(&mut sheet, &thumbnail, x.into(), y.into());
+            }
+            None => {
+                let missing = RgbaImage::from_pixel(cell, cell, MISSING);
+                imageops::replace(&mut sheet, &missing, x.into(), y.into());
+            }
+        }
+        draw_label(&mut sheet, fonts, name, (x, y + cell), cell);
+    }
+    sheet
+}
+
+/// Draws a name centered in the label space at `(x, y)`, shortened to fit `width`
+fn draw_label(sheet: &mut RgbaImage, fonts: &Fonts, name: &str, (x, y): (u32, u32), width: u32) {
+    let layout = |text: String| {
+        fonts.layout_no_wrap(
+            text,
+            egui::FontId::proportional(FONT_SIZE),
+            egui::Color32::WHITE,
+        )
+    };
+    let mut galley = layout(name.to_string());
+    let mut chars: Vec<char> = name.chars().collect();
+    while galley.size().x > width as f32 && !chars.is_empty() {
+        chars.pop();
+        galley = layout(chars.iter().collect::<String>() + "…");
+    }
+
+    // Glyphs are rasterized into the atlas as they're laid out, so it's read after
+    let atlas = fonts.image();
+    let left = x as f32 + (width as f32 - galley.size().x).max(0.0) * 0.5;
+    let top = y as f32 + (LABEL_HEIGHT as f32 - galley.size().y).max(0.0) * 0.5;
+    for glyph in galley.rows.iter().flat_map(|row| row.glyphs.iter()) {
+        let uv = glyph.uv_rect;
+        if uv.is_nothing() {
+            continue;
+        }
+        let glyph_left = (left + glyph.pos.x + uv.offset.x).round() as i64;
+        let glyph_top = (top + glyph.pos.y + uv.offset.y).round() as i64;
+        for texel_y in uv.min[1]..uv.max[1] {
+            for texel_x in uv.min[0]..uv.max[0] {
+                let coverage = atlas.pixels[texel_y as usize * atlas.size[0] + texel_x as usize];
+                let pixel_x = glyph_left + i64::from(texel_x - uv.min[0]);
+                let pixel_y = glyph_top + i64::from(texel_y - uv.min[1]);
+                let (Ok(pixel_x), Ok(pixel_y)) = (u32::try_from(pixel_x), u32::try_from(pixel_y))
+                else {
+                    continue;
+                };
+                if pixel_x >= sheet.width() || pixel_y >= sheet.height() {
+                    continue;
+                }
+                let pixel = sheet.get_pixel_mut(pixel_x, pixel_y);
+                for (channel, text) in pixel.0.iter_mut().zip(TEXT_COLOR) {
+                    let (channel_value, text) = (f32::from(*channel), f32::from(text));
+                    *channel = (channel_value + (text - channel_value) * coverage).round() as u8;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use std::{
+    backtrace::Backtrace,
+    cell::Cell,
+    collections::VecDeque,
+    fs, io,
+    panic::{self, UnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Mutex, TryLockError},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{app::AppExit, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    i18n::t,
+    parse_queue::panic_message,
+    safe_mode::{restart_in_safe_mode, SafeMode},
+    settings::config_dir,
+    toasts::{Severity, Toasts},
+};
+
+/// How many log lines a report ends with
+const REPORT_LOG_LINES: usize = 200;
+/// Kept next to the reports, holding the path of the one the next launch should offer
+const PENDING_FILE: &str = "pending";
+
+/// The latest log lines of every level, oldest first
+static RECENT_LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+/// The session file's path and contents as last written, put back by the hook in case
+/// the panic cut a write short
+static LAST_SESSION: Mutex<Option<(PathBuf, String)>> = Mutex::new(None);
+
+thread_local! {
+    /// Set while a panic is expected and caught, like the blend crate's on damaged files
+    static CATCHING: Cell<bool> = Cell::new(false);
+}
+
+/// Where crash reports are written, e.g. `~/.config/blender-launcher/crashes` on Linux
+pub fn reports_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("crashes"))
+}
+
+pub fn remember_log_line(line: String) {
+    // A panic while the lock was held poisons it, the lines are still good
+    let mut log = RECENT_LOG
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if log.len() >= REPORT_LOG_LINES {
+        log.pop_front();
+    }
+    log.push_back(line);
+}
+
+pub fn remember_session(path: PathBuf, contents: String) {
+    *LAST_SESSION
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some((path, contents));
+}
+
+/// `catch_unwind` for panics that are handled, which don't get a crash report
+pub fn catch_expected_panic<R>(f: impl FnOnce() -> R + UnwindSafe) -> thread::Result<R> {
+    let was_catching = CATCHING.with(|catching| catching.replace(true));
+    let result = panic::catch_unwind(f);
+    CATCHING.with(|catching| catching.set(was_catching));
+    result
+}
+
+/// Writes a report into `dir` for every panic that isn't caught on purpose, and puts the
+/// session back on disk. Rust's own hook still runs afterwards, so the panic is printed and
+/// unwinds as before. Exiting normally never comes through here.
+pub fn install_panic_hook(dir: PathBuf) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !CATCHING.with(Cell::get) {
+            let message = panic_message(info.payload());
+            let location = info.location().map(ToString::to_string);
+            let session = try_read(&LAST_SESSION, Clone::clone).flatten();
+            report_panic(&dir, &message, location.as_deref(), session);
+        }
+        default_hook(info);
+    }));
+}
+
+/// What the hook does for a panic: writes the report and puts `session` (path, contents)
+/// back on disk. Logging could be what panicked, so this goes straight to stderr.
+fn report_panic(
+    dir: &Path,
+    message: &str,
+    location: Option<&str>,
+    session: Option<(PathBuf, String)>,
+) {
+    match write_report(dir, message, location) {
+        Ok(path) => eprintln!("Crash report written to {}", path.display()),
+        Err(error) => eprintln!("Couldn't write crash report: {}", error),
+    }
+    if let Some((path, contents)) = session {
+        if let Err(error) = fs::write(&path, contents) {
+            eprintln!("Couldn't save session after crash: {}", error);
+        }
+    }
+}
+
+/// Reads what's behind the lock without waiting for it. The panicking thread may be the
+/// one holding it, which would never let go.
+fn try_read<T, R>(mutex: &Mutex<T>, read: impl FnOnce(&T) -> R) -> Option<R> {
+    match mutex.try_lock() {
+        Ok(value) => Some(read(&value)),
+        Err(TryLockError::Poisoned(poisoned)) => Some(read(&poisoned.into_inner())),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+fn write_report(dir: &Path, message: &str, location: Option<&str>) -> io::Result<PathBuf> {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let log =
+        try_read(&RECENT_LOG, |log| log.iter().cloned().collect::<Vec<_>>()).unwrap_or_default();
+
+    let mut report = format!(
+        "Blender Launcher crash report\n\
+         version: {} ({})\n\
+         os: {} {}\n\
+         time: {} (seconds since 1970)\n\
+         thread: {}\n\
+         panic: {}\n\
+         at: {}\n\n\
+         backtrace:\n{}\n\
+         last {} log lines:\n",
+        env!("CARGO_PKG_VERSION"),
+        env!("BUILD_GIT_HASH"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        time,
+        thread::current().name().unwrap_or("unnamed"),
+        message,
+        location.unwrap_or("unknown"),
+        Backtrace::force_capture(),
+        log.len(),
+    );
+    for line in log {
+        report.push_str(&line);
+        report.push('\n');
+    }
+
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("crash-{}.txt", time));
+    fs::write(&path, report)?;
+    fs::write(dir.join(PENDING_FILE), path.display().to_string())?;
+    Ok(path)
+}
+
+/// The report the last run left behind when it crashed. Taken, so it's only offered once.
+fn take_pending_report(dir: &Path) -> Option<PathBuf> {
+    let marker = dir.join(PENDING_FILE);
+    let path = fs::read_to_string(&marker).ok()?;
+    let _ = fs::remove_file(&marker);
+    Some(PathBuf::from(path.trim())).filter(|path| path.is_file())
+}
+
+/// The report from a crash of the last run, until the dialog about it is dismissed
+#[derive(Default, Resource)]
+struct CrashRecovery {
+    report: Option<PathBuf>,
+}
+
+pub struct CrashPlugin;
+
+impl Plugin for CrashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrashRecovery>()
+            .add_startup_system(detect_crash)
+            .add_system(
+                crash_recovery_ui.run_if(|recovery: Res<CrashRecovery>| recovery.report.is_some()),
+            );
+    }
+}
+
+fn detect_crash(mut recovery: ResMut<CrashRecovery>) {
+    let Some(report) = reports_dir().and_then(|dir| take_pending_report(&dir)) else {
+        return;
+    };
+    warn!(report = %report.display(), "the last run crashed");
+    recovery.report = Some(report);
+}
+
+/// Tells the user the last run crashed and where the report is, to attach to a bug report
+fn crash_recovery_ui(
+    mut contexts: EguiContexts,
+    mut recovery: ResMut<CrashRecovery>,
+    mut toasts: ResMut<Toasts>,
+    safe_mode: Res<SafeMode>,
+    mut exits: EventWriter<AppExit>,
+) {
+    let Some(report) = recovery.report.clone() else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+    let mut dismissed = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+
+    let screen_rect = ctx.screen_rect();
+    egui::Area::new("crash_backdrop")
+        .order(egui::Order::Middle)
+        .fixed_pos(screen_rect.min)
+        .show(ctx, |ui| {
+            ui.allocate_response(screen_rect.size(), egui::Sense::click_and_drag());
+            ui.painter()
+                .rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(96));
+        });
+
+    egui::Area::new("crash_modal")
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            egui::Frame::window(ui.style()).show(ui, |ui| {
+                ui.set_max_width(420.0);
+                ui.heading(t!("crash.heading"));
+                ui.label(t!("crash.body"));
+                ui.monospace(report.display().to_string());
+                ui.horizontal(|ui| {
+                    if ui.button(t!("crash.open_location")).clicked() {
+                        if let Err(error) = opener::reveal(&report) {
+                            error!(%error, "couldn't reveal crash report");
+                            toasts.push(Severity::Error, t!("crash.reveal_failed", error = error));
+                        }
+                    }
+                    // Already there when the crash was from a safe mode run
+                    if !safe_mode.is_active()
+                        && ui
+                            .button(t!("crash.safe_mode"))
+                            .on_hover_text(t!("crash.safe_mode.hint"))
+                            .clicked()
+                    {
+                        match restart_in_safe_mode() {
+                            Ok(()) => exits.send(AppExit),
+                            Err(error) => {
+                                error!(%error, "couldn't restart in safe mode");
+                                toasts.push(
+                                    Severity::Error,
+                                    t!("crash.restart_failed", error = error),
+                                );
+                            }
+                        }
+                    }
+                    dismissed |= ui.button(t!("crash.dismiss")).clicked();
+                });
+            });
+        });
+
+    if dismissed {
+        recovery.report = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::schedule::ExecutorKind;
+
+    /// Held by every test that swaps out the process's panic hook
+    static PANIC_HOOK: Mutex<()> = Mutex::new(());
+
+    /// An empty directory of its own for each test, they run in parallel
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "blender-launcher-crash-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn reports_are_written_and_offered_once() {
+        let dir = test_dir("report");
+        remember_log_line("INFO test: before the panic".to_string());
+        let path = write_report(&dir, "controlled test panic", Some("src/crash.rs:1:1")).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("panic: controlled test panic"));
+        assert!(contents.contains("at: src/crash.rs:1:1"));
+        assert!(contents.contains(env!("CARGO_PKG_VERSION")));
+        assert!(contents.contains("INFO test: before the panic"));
+        assert_eq!(take_pending_report(&dir), Some(path));
+        assert!(take_pending_report(&dir).is_none());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reporting_a_panic_puts_the_session_back() {
+        let dir = test_dir("session");
+        let session_path = dir.join("session.json");
+        report_panic(
+            &dir,
+            "controlled test panic",
+            None,
+            Some((session_path.clone(), "{\"files\":[]}".to_string())),
+        );
+
+        assert_eq!(fs::read_to_string(&session_path).unwrap(), "{\"files\":[]}");
+        assert!(take_pending_report(&dir).is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn uncaught_panic_in_a_headless_app_is_reported() {
+        let _hook = PANIC_HOOK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let dir = test_dir("hook");
+        let previous_hook = panic::take_hook();
+        install_panic_hook(dir.clone());
+
+        // A headless app whose only system panics, run on this thread so the panic
+        // comes back out of `update`
+        let mut app = App::new();
+        app.edit_schedule(CoreSchedule::Main, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        })
+        .add_system(|| panic!("controlled test panic"));
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| app.update()));
+        let _ = panic::take_hook();
+        panic::set_hook(previous_hook);
+        assert!(result.is_err());
+
+        // Panics in other tests while the hook was in can add reports of their own, so
+        // this looks for ours rather than at the pending one
+        let reported = fs::read_dir(&dir).unwrap().any(|entry| {
+            fs::read_to_string(entry.unwrap().path())
+                .is_ok_and(|contents| contents.contains("panic: controlled test panic"))
+        });
+        assert!(reported);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn expected_panics_are_not_reported() {
+        let result = catch_expected_panic(|| panic!("damaged file"));
+        assert!(result.is_err());
+        assert!(!CATCHING.with(Cell::get));
+    }
+}
@@ -0,0 +1,323 @@
+use bevy::{prelude::*, render::mesh::PrimitiveTopology, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+use blend::Instance;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_cache::AssetCache, metadata::ObjectKind, viewport::viewport_to_egui, AppState,
+    BlenderPreviewObject, LauncherCamera,
+};
+
+/// Points evaluated per Bezier segment or NURBS span
+const CURVE_RESOLUTION: usize = 12;
+/// Longest spline list we follow, in case a broken file links it in a loop
+const MAX_SPLINES: usize = 4096;
+/// Blender's `Nurb::type` values
+const CU_POLY: i16 = 0;
+const CU_BEZIER: i16 = 1;
+const CU_NURBS: i16 = 4;
+/// Blender's `Nurb::flagu` bits
+const CU_NURB_CYCLIC: i16 = 1;
+const CU_NURB_ENDPOINT: i16 = 2;
+/// Half the size of the marker text objects get in the viewport
+const TEXT_MARKER_SIZE: f32 = 0.1;
+
+/// A stand-in for objects we can't load as meshes, on Blender's axes.
+/// Only an approximation: bevels, extrusions, and fonts aren't evaluated.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Outline {
+    /// One polyline per spline
+    Curves(Vec<Vec<[f32; 3]>>),
+    /// The body of a text object
+    Text(String),
+}
+
+/// Reads the outline of a curve or text object, `None` for everything else
+pub fn object_outline(obj: &Instance, kind: ObjectKind) -> Option<Outline> {
+    if !matches!(kind, ObjectKind::Curve | ObjectKind::Text) || !obj.is_valid("data") {
+        return None;
+    }
+    let curve = obj.get("data");
+
+    if kind == ObjectKind::Text {
+        // `str` holds `len` bytes of UTF-8
+        let len = curve.get_i32("len").max(0) as usize;
+        let mut bytes = if curve.is_valid("str") {
+            curve.get_u8_vec("str")
+        } else {
+            Vec::new()
+        };
+        bytes.truncate(len);
+        return Some(Outline::Text(String::from_utf8_lossy(&bytes).to_string()));
+    }
+
+    let splines = curve.get("nurb");
+    if !splines.is_valid("first") {
+        return Some(Outline::Curves(Vec::new()));
+    }
+    let mut polylines = Vec::new();
+    let mut spline = splines.get("first");
+    for _ in 0..MAX_SPLINES {
+        let polyline = evaluate_spline(&spline);
+        if polyline.len() > 1 {
+            polylines.push(polyline);
+        }
+        if !spline.is_valid("next") {
+            break;
+        }
+        spline = spline.get("next");
+    }
+    Some(Outline::Curves(polylines))
+}
+
+/// Samples one `Nurb` into points. Types we don't know come back empty rather than failing.
+fn evaluate_spline(spline: &Instance) -> Vec<[f32; 3]> {
+    let point_count = spline.get_i16("pntsu").max(0) as usize;
+    let cyclic = spline.get_i16("flagu") & CU_NURB_CYCLIC != 0;
+
+    match spline.get_i16("type") {
+        CU_BEZIER if spline.is_valid("bezt") => {
+            // Each `BezTriple::vec` is the left handle, the point, and the right handle
+            let triples: Vec<[[f32; 3]; 3]> = spline
+                .get_iter("bezt")
+                .take(point_count)
+                .filter_map(|triple| {
+                    let vec = triple.get_f32_vec("vec");
+                    (vec.len() >= 9).then(|| {
+                        [0, 1, 2].map(|handle| {
+                            [vec[handle * 3], vec[handle * 3 + 1], vec[handle * 3 + 2]]
+                        })
+                    })
+                })
+                .collect();
+            evaluate_bezier(&triples, cyclic)
+        }
+        kind @ (CU_POLY | CU_NURBS) if spline.is_valid("bp") => {
+            // `BPoint::vec` is a position and a weight
+            let points: Vec<[f32; 4]> = spline
+                .get_iter("bp")
+                .take(point_count)
+                .filter_map(|point| match point.get_f32_vec("vec").as_slice() {
+                    [x, y, z, w, ..] => Some([*x, *y, *z, *w]),
+                    _ => None,
+                })
+                .collect();
+            if kind == CU_POLY {
+                let mut polyline: Vec<[f32; 3]> =
+                    points.iter().map(|[x, y, z, _]| [*x, *y, *z]).collect();
+                if cyclic {
+                    if let Some(first) = polyline.first().copied() {
+                        polyline.push(first);
+                    }
+                }
+                polyline
+            } else {
+                let order = spline.get_i16("orderu").max(2) as usize;
+                let endpoint = spline.get_i16("flagu") & CU_NURB_ENDPOINT != 0;
+                evaluate_nurbs(&points, order, cyclic, endpoint)
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn evaluate_bezier(triples: &[[[f32; 3]; 3]], cyclic: bool) -> Vec<[f32; 3]> {
+    let segments = if cyclic {
+        triples.len()
+    } else {
+        triples.len().saturating_sub(1)
+    };
+    let mut points = Vec::new();
+    for segment in 0..segments {
+        let start = triples[segment];
+        let end = triples[(segment + 1) % triples.len()];
+        let controls = [start[1], start[2], end[0], end[1]].map(Vec3::from);
+        for step in 0..CURVE_RESOLUTION {
+            let t = step as f32 / CURVE_RESOLUTION as f32;
+            let u = 1.0 - t;
+            let point = controls[0] * u * u * u
+                + controls[1] * 3.0 * u * u * t
+                + controls[2] * 3.0 * u * t * t
+                + controls[3] * t * t * t;
+            points.push(point.to_array());
+        }
+    }
+    if let Some(last) = triples.get(if cyclic { 0 } else { segments }) {
+        points.push(last[1]);
+    }
+    points
+}
+
+/// Rational B-spline through de Boor's algorithm, with uniform knots
+/// (clamped at the ends with the endpoint flag, like Blender's "Endpoint" option)
+fn evaluate_nurbs(
+    points: &[[f32; 4]],
+    order: usize,
+    cyclic: bool,
+    endpoint: bool,
+) -> Vec<[f32; 3]> {
+    // Cyclic splines wrap by repeating their first points
+    let mut controls: Vec<Vec4> = points
+        .iter()
+        .map(|[x, y, z, w]| Vec4::new(x * w, y * w, z * w, *w))
+        .collect();
+    if cyclic && controls.len() >= order {
+        controls.extend_from_within(..order - 1);
+    }
+    let order = order.min(controls.len());
+    if order < 2 {
+        return Vec::new();
+    }
+    let degree = order - 1;
+    let count = controls.len();
+
+    let knots: Vec<f32> = (0..count + order)
+        .map(|index| {
+            if endpoint && !cyclic {
+                index.clamp(degree, count) as f32
+            } else {
+                index as f32
+            }
+        })
+        .collect();
+    let (start, end) = (knots[degree], knots[count]);
+
+    let samples = (count - degree) * CURVE_RESOLUTION;
+    (0..=samples)
+        .map(|sample| {
+            let t = start + (end - start) * sample as f32 / samples as f32;
+            // The span holding t, the last one for t == end
+            let span = (degree..count)
+                .find(|span| t < knots[span + 1])
+                .unwrap_or(count - 1);
+
+            let mut d: Vec<Vec4> = (0..=degree).map(|j| controls[j + span - degree]).collect();
+            for r in 1..=degree {
+                for j in (r..=degree).rev() {
+                    let i = j + span - degree;
+                    let denominator = knots[i + degree + 1 - r] - knots[i];
+                    let alpha = if denominator == 0.0 {
+                        0.0
+                    } else {
+                        (t - knots[i]) / denominator
+                    };
+                    d[j] = d[j - 1] * (1.0 - alpha) + d[j] * alpha;
+                }
+            }
+            let point = d[degree];
+            let weight = if point.w == 0.0 { 1.0 } else { point.w };
+            (point.truncate() / weight).to_array()
+        })
+        .collect()
+}
+
+pub struct CurvePreviewPlugin;
+
+impl Plugin for CurvePreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(build_outline_meshes.run_if(resource_changed::<AppState>()))
+            .add_system(text_labels_ui);
+    }
+}
+
+/// Blender is Z-up, Bevy is Y-up
+pub fn to_bevy([x, y, z]: [f32; 3]) -> [f32; 3] {
+    [x, z, -y]
+}
+
+/// A line list mesh, with a normal and UV per vertex so the regular materials can draw it
+pub fn line_mesh(segments: Vec<[f32; 3]>) -> Mesh {
+    let count = segments.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, segments);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; count]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; count]);
+    mesh
+}
+
+fn outline_mesh(outline: &Outline) -> Mesh {
+    match outline {
+        Outline::Curves(polylines) => line_mesh(
+            polylines
+                .iter()
+                .flat_map(|polyline| polyline.windows(2))
+                .flat_map(|segment| [to_bevy(segment[0]), to_bevy(segment[1])])
+                .collect(),
+        ),
+        // A small cross marks where the text is, its label is drawn by egui
+        Outline::Text(_) => {
+            let size = TEXT_MARKER_SIZE;
+            line_mesh(vec![
+                [-size, 0.0, 0.0],
+                [size, 0.0, 0.0],
+                [0.0, -size, 0.0],
+                [0.0, size, 0.0],
+                [0.0, 0.0, -size],
+                [0.0, 0.0, size],
+            ])
+        }
+    }
+}
+
+/// Puts generated meshes for curve and text objects in the asset cache,
+/// so every spawn path finds them instead of asking the blend loader for a mesh it can't load
+fn build_outline_meshes(
+    app_state: Res<AppState>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for file in app_state.files.iter() {
+        for object in file.meshes.iter() {
+            let Some(outline) = &object.outline else {
+                continue;
+            };
+            if !asset_cache.has_mesh(&file.path, &object.name) {
+                let handle = meshes.add(outline_mesh(outline));
+                asset_cache.insert_mesh(&file.path, &object.name, handle);
+            }
+        }
+    }
+}
+
+/// Floats the body of each previewed text object at its location
+fn text_labels_ui(
+    mut contexts: EguiContexts,
+    app_state: Res<AppState>,
+    egui_settings: Res<EguiSettings>,
+    previews: Query<(Entity, &BlenderPreviewObject, &GlobalTransform)>,
+    cameras: Query<(&Camera, &GlobalTransform), With<LauncherCamera>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let ui_scale = egui_settings.scale_factor as f32;
+    let ctx = contexts.ctx_mut();
+
+    for (entity, preview, transform) in previews.iter() {
+        let Some(Outline::Text(text)) = app_state
+            .file(preview.file)
+            .and_then(|file| file.meshes.get(preview.mesh))
+            .and_then(|object| object.outline.as_ref())
+        else {
+            continue;
+        };
+        let Some(viewport_position) =
+            camera.world_to_viewport(camera_transform, transform.translation())
+        else {
+            continue;
+        };
+
+        let position = viewport_to_egui(viewport_position, window, ui_scale);
+        egui::Area::new(("text_label", entity))
+            .fixed_pos(position)
+            .pivot(egui::Align2::LEFT_BOTTOM)
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(text.as_str()).heading());
+            });
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+
+use crate::{i18n::t, settings::Settings, OccupiedScreenSpace};
+
+/// How many times each event driven system actually ran,
+/// so we can see they stay idle when nothing is happening
+#[derive(Default, Resource)]
+pub struct SystemRuns {
+    frames: u64,
+    counts: BTreeMap<&'static str, u64>,
+}
+
+impl SystemRuns {
+    pub fn record(&mut self, system: &'static str) {
+        *self.counts.entry(system).or_default() += 1;
+    }
+}
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SystemRuns>()
+            .add_system(count_frames)
+            .add_system(debug_overlay_ui.run_if(|settings: Res<Settings>| settings.debug_overlay));
+    }
+}
+
+fn count_frames(mut system_runs: ResMut<SystemRuns>) {
+    // Skip change detection, nothing needs to react to the frame count
+    system_runs.bypass_change_detection().frames += 1;
+}
+
+/// Shows the run counts in the top left corner of the viewport
+fn debug_overlay_ui(
+    mut contexts: EguiContexts,
+    system_runs: Res<SystemRuns>,
+    occupied_screen_space: Res<OccupiedScreenSpace>,
+    egui_settings: Res<EguiSettings>,
+) {
+    let ui_scale = egui_settings.scale_factor as f32;
+    let offset = egui::vec2(
+        occupied_screen_space.left / ui_scale + 12.0,
+        occupied_screen_space.top / ui_scale + 12.0,
+    );
+
+    egui::Area::new("debug_overlay")
+        .anchor(egui::Align2::LEFT_TOP, offset)
+        .interactable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                egui::Grid::new("system_runs").show(ui, |ui| {
+                    ui.label(t!("debug.frames"));
+                    ui.monospace(system_runs.frames.to_string());
+                    ui.end_row();
+
+                    for (system, count) in system_runs.counts.iter() {
+                        ui.label(*system);
+                        ui.monospace(count.to_string());
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+}
@@ -0,0 +1,354 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    asset::HandleId,
+    prelude::*,
+    render::{
+        mesh::{Indices, VertexAttributeValues},
+        render_resource::PrimitiveTopology,
+    },
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+use futures_lite::future;
+
+use crate::{
+    analysis::{mesh_indices, mesh_positions},
+    i18n::t,
+    settings::Settings,
+    BlenderPreviewObject, OccupiedScreenSpace,
+};
+
+/// UV space is split into this many cells per side, vertices in different cells never merge
+const UV_CLUSTER_CELLS: f32 = 16.0;
+
+/// The original mesh of a preview showing a decimated copy
+#[derive(Component)]
+pub struct FullMesh {
+    pub handle: Handle<Mesh>,
+    /// Decimated triangles over original triangles
+    ratio: f32,
+}
+
+/// The user asked for the full mesh, don't decimate this preview again
+#[derive(Component)]
+struct KeepFullMesh;
+
+struct DecimatedMesh {
+    handle: Handle<Mesh>,
+    ratio: f32,
+}
+
+/// Decimated copies (and in-flight decimation tasks) keyed by the original mesh handle
+#[derive(Default, Resource)]
+struct DecimationCache {
+    results: HashMap<HandleId, DecimatedMesh>,
+    pending: HashMap<HandleId, Task<Option<(Mesh, f32)>>>,
+}
+
+pub struct DecimationPlugin;
+
+impl Plugin for DecimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DecimationCache>()
+            .add_system(
+                decimate_heavy_previews
+                    .run_if(|settings: Res<Settings>| settings.preview_decimation),
+            )
+            .add_system(decimation_badge_ui)
+            .add_system(drop_reloaded_decimations.run_if(on_event::<AssetEvent<Mesh>>()));
+    }
+}
+
+/// Puts the original back on previews whose mesh was reloaded, so the new one gets decimated
+fn drop_reloaded_decimations(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<Mesh>>,
+    mut cache: ResMut<DecimationCache>,
+    mut previews: Query<(Entity, &FullMesh, &mut Handle<Mesh>)>,
+) {
+    for event in asset_events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        cache.results.remove(&handle.id());
+        cache.pending.remove(&handle.id());
+        for (entity, full_mesh, mut mesh) in previews.iter_mut() {
+            if full_mesh.handle == *handle {
+                *mesh = full_mesh.handle.clone();
+                commands.entity(entity).remove::<FullMesh>();
+            }
+        }
+    }
+}
+
+/// Swaps loaded meshes over the triangle threshold for a simplified copy,
+/// made on a background thread the first time each mesh is seen
+fn decimate_heavy_previews(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut cache: ResMut<DecimationCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut previews: Query<
+        (Entity, &mut Handle<Mesh>),
+        (
+            With<BlenderPreviewObject>,
+            Without<FullMesh>,
+            Without<KeepFullMesh>,
+        ),
+    >,
+) {
+    let mut finished = Vec::new();
+    for (id, task) in cache.pending.iter_mut() {
+        if let Some(result) = future::block_on(future::poll_once(task)) {
+            finished.push((*id, result));
+        }
+    }
+    for (id, result) in finished {
+        cache.pending.remove(&id);
+        if let Some((mesh, ratio)) = result {
+            let handle = meshes.add(mesh);
+            cache.results.insert(id, DecimatedMesh { handle, ratio });
+        }
+    }
+
+    let thread_pool = AsyncComputeTaskPool::get();
+    for (entity, mut handle) in previews.iter_mut() {
+        let id = handle.id();
+        if let Some(decimated) = cache.results.get(&id) {
+            commands.entity(entity).insert(FullMesh {
+                handle: handle.clone(),
+                ratio: decimated.ratio,
+            });
+            *handle = decimated.handle.clone();
+            continue;
+        }
+        if cache.pending.contains_key(&id) {
+            continue;
+        }
+
+        // The mesh might still be loading, we'll try again next frame
+        let Some(mesh) = meshes.get(&*handle) else {
+            continue;
+        };
+        let triangles = mesh
+            .indices()
+            .map_or(mesh.count_vertices(), |indices| indices.len())
+            / 3;
+        if triangles <= settings.decimation_threshold {
+            continue;
+        }
+        let geometry = MeshGeometry::from_mesh(mesh);
+
+        let target_triangles = settings.decimation_threshold;
+        let task = thread_pool.spawn(async move {
+            let original_triangles = geometry.indices.len() / 3;
+            let decimated = cluster_vertices(&geometry, target_triangles)?;
+            let ratio = (decimated.indices.len() / 3) as f32 / original_triangles as f32;
+            Some((decimated.into_mesh(), ratio))
+        });
+        cache.pending.insert(id, task);
+    }
+}
+
+/// A corner badge while any preview is decimated, with a button to swap the originals back
+fn decimation_badge_ui(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    occupied_screen_space: Res<OccupiedScreenSpace>,
+    egui_settings: Res<EguiSettings>,
+    mut previews: Query<(Entity, &FullMesh, &mut Handle<Mesh>)>,
+) {
+    if previews.is_empty() {
+        return;
+    }
+
+    let ratio = previews
+        .iter()
+        .map(|(_, full_mesh, _)| full_mesh.ratio)
+        .sum::<f32>()
+        / previews.iter().len() as f32;
+    let ui_scale = egui_settings.scale_factor as f32;
+    let offset = egui::vec2(
+        -occupied_screen_space.right / ui_scale - 8.0,
+        -occupied_screen_space.bottom / ui_scale - 8.0,
+    );
+
+    let mut load_full = false;
+    egui::Area::new("decimation_badge")
+        .anchor(egui::Align2::RIGHT_BOTTOM, offset)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(t!(
+                        "decimate.badge",
+                        percent = format!("{:.0}", ratio * 100.0)
+                    ));
+                    load_full = ui.button(t!("decimate.load_full")).clicked();
+                });
+            });
+        });
+
+    if load_full {
+        for (entity, full_mesh, mut handle) in previews.iter_mut() {
+            *handle = full_mesh.handle.clone();
+            commands
+                .entity(entity)
+                .remove::<FullMesh>()
+                .insert(KeepFullMesh);
+        }
+    }
+}
+
+/// The attributes we keep when decimating, copied out of the asset for the background task
+struct MeshGeometry {
+    positions: Vec<[f32; 3]>,
+    normals: Option<Vec<[f32; 3]>>,
+    uvs: Option<Vec<[f32; 2]>>,
+    indices: Vec<u32>,
+}
+
+impl MeshGeometry {
+    fn from_mesh(mesh: &Mesh) -> Self {
+        let positions = mesh_positions(mesh);
+        let indices = mesh_indices(mesh, positions.len());
+        let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(normals)) => Some(normals.clone()),
+            _ => None,
+        };
+        let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            Some(VertexAttributeValues::Float32x2(uvs)) => Some(uvs.clone()),
+            _ => None,
+        };
+
+        Self {
+            positions,
+            normals,
+            uvs,
+            indices,
+        }
+    }
+
+    fn into_mesh(self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        if let Some(normals) = self.normals {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        }
+        if let Some(uvs) = self.uvs {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        }
+        mesh.set_indices(Some(Indices::U32(self.indices)));
+        mesh
+    }
+}
+
+/// Vertex clustering: snaps every vertex to a grid and merges the vertices sharing a cell,
+/// dropping the triangles that collapse. Vertices are also split by a coarse UV cell,
+/// so UV islands aren't averaged into each other and textures stay roughly in place.
+/// Returns `None` when the grid wouldn't remove anything.
+fn cluster_vertices(geometry: &MeshGeometry, target_triangles: usize) -> Option<MeshGeometry> {
+    let (min, max) = geometry.positions.iter().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), position| {
+            let position = Vec3::from_array(*position);
+            (min.min(position), max.max(position))
+        },
+    );
+    let extent = (max - min).max_element();
+    if !extent.is_finite() || extent <= 0.0 {
+        return None;
+    }
+
+    // Surfaces cross roughly resolution² cells, each cell ends up around two triangles
+    let resolution = ((target_triangles as f32 / 2.0).sqrt() * 1.5).max(4.0);
+    let cell_size = extent / resolution;
+
+    #[derive(Default)]
+    struct Cluster {
+        position: Vec3,
+        normal: Vec3,
+        uv: Vec2,
+        count: f32,
+    }
+
+    let mut cluster_ids: HashMap<(IVec3, IVec2), u32> = HashMap::new();
+    let mut clusters: Vec<Cluster> = Vec::new();
+    let remap: Vec<u32> = geometry
+        .positions
+        .iter()
+        .enumerate()
+        .map(|(index, position)| {
+            let position = Vec3::from_array(*position);
+            let uv = geometry
+                .uvs
+                .as_ref()
+                .and_then(|uvs| uvs.get(index))
+                .map_or(Vec2::ZERO, |uv| Vec2::from_array(*uv));
+            let cell = ((position - min) / cell_size).floor().as_ivec3();
+            let uv_cell = (uv * UV_CLUSTER_CELLS).floor().as_ivec2();
+
+            let id = *cluster_ids.entry((cell, uv_cell)).or_insert_with(|| {
+                clusters.push(Cluster::default());
+                clusters.len() as u32 - 1
+            });
+            let cluster = &mut clusters[id as usize];
+            cluster.position += position;
+            cluster.uv += uv;
+            if let Some(normal) = geometry
+                .normals
+                .as_ref()
+                .and_then(|normals| normals.get(index))
+            {
+                cluster.normal += Vec3::from_array(*normal);
+            }
+            cluster.count += 1.0;
+            id
+        })
+        .collect();
+
+    let mut seen_triangles = HashSet::new();
+    let mut indices = Vec::new();
+    for triangle in geometry.indices.chunks_exact(3) {
+        let Some([a, b, c]) = triangle
+            .iter()
+            .map(|index| remap.get(*index as usize).copied())
+            .collect::<Option<Vec<u32>>>()
+            .and_then(|corners| <[u32; 3]>::try_from(corners).ok())
+        else {
+            continue;
+        };
+        if a == b || b == c || c == a {
+            continue;
+        }
+        let mut key = [a, b, c];
+        key.sort_unstable();
+        if seen_triangles.insert(key) {
+            indices.extend([a, b, c]);
+        }
+    }
+
+    if indices.len() >= geometry.indices.len() {
+        return None;
+    }
+
+    Some(MeshGeometry {
+        positions: clusters
+            .iter()
+            .map(|cluster| (cluster.position / cluster.count).to_array())
+            .collect(),
+        normals: geometry.normals.as_ref().map(|_| {
+            clusters
+                .iter()
+                .map(|cluster| cluster.normal.normalize_or_zero().to_array())
+                .collect()
+        }),
+        uvs: geometry.uvs.as_ref().map(|_| {
+            clusters
+                .iter()
+                .map(|cluster| (cluster.uv / cluster.count).to_array())
+                .collect()
+        }),
+        indices,
+    })
+}
@@ -0,0 +1,120 @@
+use bevy::{
+    prelude::*,
+    render::camera::RenderTarget,
+    window::{WindowRef, WindowResolution},
+};
+
+use crate::{
+    commands::{Action, ActionEvent},
+    i18n::t,
+    OriginalCameraTransform,
+};
+
+/// The preview camera in the detached window.
+/// It isn't offset for the panels, those stay on the primary window.
+#[derive(Component)]
+pub struct DetachedCamera;
+
+/// The second window the preview was popped out into, if any
+#[derive(Default, Resource)]
+pub struct DetachedPreview {
+    window: Option<Entity>,
+    camera: Option<Entity>,
+}
+
+impl DetachedPreview {
+    pub fn window(&self) -> Option<Entity> {
+        self.window
+    }
+}
+
+pub struct DetachPlugin;
+
+impl Plugin for DetachPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DetachedPreview>()
+            .add_system(detach_preview.run_if(on_event::<ActionEvent>()))
+            .add_system(
+                follow_preview_camera
+                    .run_if(|detached: Res<DetachedPreview>| detached.window.is_some()),
+            )
+            .add_system(
+                reattach_closed_preview
+                    .run_if(|detached: Res<DetachedPreview>| detached.window.is_some()),
+            );
+    }
+}
+
+/// Opens a second window with its own camera looking at the same scene
+fn detach_preview(
+    mut commands: Commands,
+    mut action_events: EventReader<ActionEvent>,
+    mut detached: ResMut<DetachedPreview>,
+    original_camera_transform: Res<OriginalCameraTransform>,
+) {
+    if !action_events
+        .iter()
+        .any(|ActionEvent(action)| *action == Action::DetachPreview)
+    {
+        return;
+    }
+    // Already popped out, the window is still around
+    if detached.window.is_some() {
+        return;
+    }
+
+    let window = commands
+        .spawn(Window {
+            title: t!("detach.window_title").to_string(),
+            resolution: WindowResolution::new(1280.0, 720.0),
+            ..Default::default()
+        })
+        .id();
+    let camera = commands
+        .spawn((
+            DetachedCamera,
+            Camera3dBundle {
+                camera: Camera {
+                    target: RenderTarget::Window(WindowRef::Entity(window)),
+                    ..Default::default()
+                },
+                transform: **original_camera_transform,
+                ..Default::default()
+            },
+        ))
+        .id();
+
+    detached.window = Some(window);
+    detached.camera = Some(camera);
+}
+
+/// Keeps the detached camera on the same view as the main one, without the panel offset
+fn follow_preview_camera(
+    original_camera_transform: Res<OriginalCameraTransform>,
+    mut cameras: Query<&mut Transform, With<DetachedCamera>>,
+) {
+    for mut transform in cameras.iter_mut() {
+        if *transform != **original_camera_transform {
+            *transform = **original_camera_transform;
+        }
+    }
+}
+
+/// Closing the detached window puts the preview back in the main one
+fn reattach_closed_preview(
+    mut commands: Commands,
+    mut detached: ResMut<DetachedPreview>,
+    windows: Query<(), With<Window>>,
+) {
+    let Some(window) = detached.window else {
+        return;
+    };
+    if windows.contains(window) {
+        return;
+    }
+
+    if let Some(camera) = detached.camera.take() {
+        commands.entity(camera).despawn_recursive();
+    }
+    detached.window = None;
+}
@@ -0,0 +1,77 @@
+use bevy::{core_pipeline::bloom::BloomSettings, prelude::*};
+
+use crate::{settings::Settings, shading::ViewportShading, LauncherCamera};
+
+pub struct CameraEffectsPlugin;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            apply_camera_effects.run_if(
+                resource_changed::<Settings>().or_else(resource_changed::<ViewportShading>()),
+            ),
+        );
+    }
+}
+
+/// Adds or removes bloom on the preview camera to match the settings. The detached window's,
+/// the offscreen and the overlay cameras are left as they are.
+fn apply_camera_effects(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    shading: Res<ViewportShading>,
+    mut cameras: Query<(Entity, &mut Camera, Option<&mut BloomSettings>), With<LauncherCamera>>,
+) {
+    // Debug shading modes show raw data, glow would just get in the way
+    let bloom = settings.bloom && *shading == ViewportShading::Material;
+
+    for (entity, mut camera, bloom_settings) in cameras.iter_mut() {
+        // Bloom only has anything to work with on an HDR target
+        if camera.hdr != bloom {
+            camera.hdr = bloom;
+        }
+
+        match (bloom, bloom_settings) {
+            (true, Some(mut bloom_settings)) => {
+                if bloom_settings.intensity != settings.bloom_intensity {
+                    bloom_settings.intensity = settings.bloom_intensity;
+                }
+            }
+            (true, None) => {
+                commands.entity(entity).insert(BloomSettings {
+                    intensity: settings.bloom_intensity,
+                    ..Default::default()
+                });
+            }
+            (false, Some(_)) => {
+                commands.entity(entity).remove::<BloomSettings>();
+            }
+            (false, None) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bloom_only_goes_on_the_preview_camera() {
+        let mut app = App::new();
+        app.insert_resource(Settings {
+            bloom: true,
+            ..Default::default()
+        })
+        .init_resource::<ViewportShading>()
+        .add_plugin(CameraEffectsPlugin);
+        let preview = app.world.spawn((LauncherCamera, Camera::default())).id();
+        // Like the detached window's camera
+        let other = app.world.spawn(Camera::default()).id();
+        app.update();
+
+        assert!(app.world.get::<Camera>(preview).unwrap().hdr);
+        assert!(app.world.get::<BloomSettings>(preview).is_some());
+        assert!(!app.world.get::<Camera>(other).unwrap().hdr);
+        assert!(app.world.get::<BloomSettings>(other).is_none());
+    }
+}
@@ -0,0 +1,244 @@
+use std::{collections::HashMap, f32::consts::TAU};
+
+use bevy::prelude::*;
+use blend::Instance;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    curves::{line_mesh, to_bevy},
+    metadata::ObjectKind,
+    settings::Settings,
+    AppState, BlenderPreviewObject,
+};
+
+/// Segments in each circle of the circle, sphere, and cone shapes
+const CIRCLE_SEGMENTS: usize = 32;
+/// Arrow heads, relative to the display size
+const ARROW_HEAD: f32 = 0.1;
+
+/// How Blender draws an empty, its `OB_*` display types
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EmptyShape {
+    PlainAxes,
+    Arrows,
+    SingleArrow,
+    Circle,
+    Cube,
+    Sphere,
+    Cone,
+    /// Just the frame of the image
+    Image,
+}
+
+/// An empty's display type and size, which is the shape's radius before the object's scale
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EmptyDisplay {
+    pub shape: EmptyShape,
+    pub size: f32,
+}
+
+/// Reads how an empty is displayed, `None` for every other kind of object
+pub fn empty_display(obj: &Instance, kind: ObjectKind) -> Option<EmptyDisplay> {
+    if kind != ObjectKind::Empty {
+        return None;
+    }
+    let shape = match obj.get_u8("empty_drawtype") {
+        1 => EmptyShape::Arrows,
+        3 => EmptyShape::Circle,
+        4 => EmptyShape::SingleArrow,
+        5 => EmptyShape::Cube,
+        6 => EmptyShape::Sphere,
+        7 => EmptyShape::Cone,
+        8 => EmptyShape::Image,
+        _ => EmptyShape::PlainAxes,
+    };
+    let size = obj.get_f32("empty_drawsize");
+    Some(EmptyDisplay {
+        shape,
+        size: if size > 0.0 { size } else { 1.0 },
+    })
+}
+
+/// The wire shape drawn for an empty. It's a child of the empty's preview entity rather than
+/// its mesh, so it can be hidden without hiding the empty's children, and so it stays out of
+/// the bounds the camera fits the clipping planes to.
+#[derive(Component)]
+struct EmptyMarker;
+
+#[derive(Resource)]
+struct EmptyMarkerAssets {
+    material: Handle<StandardMaterial>,
+    meshes: HashMap<(EmptyShape, u32), Handle<Mesh>>,
+}
+
+pub struct EmptyMarkerPlugin;
+
+impl Plugin for EmptyMarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_empty_marker_assets)
+            .add_system(add_empty_markers)
+            .add_system(toggle_empty_markers.run_if(resource_changed::<Settings>()));
+    }
+}
+
+fn setup_empty_marker_assets(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(EmptyMarkerAssets {
+        // Blender's default wire color for unselected objects
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.1, 0.1, 0.1),
+            unlit: true,
+            ..Default::default()
+        }),
+        meshes: HashMap::new(),
+    });
+}
+
+/// Gives every newly spawned empty its marker
+fn add_empty_markers(
+    mut commands: Commands,
+    app_state: Res<AppState>,
+    settings: Res<Settings>,
+    mut assets: ResMut<EmptyMarkerAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    previews: Query<(Entity, &BlenderPreviewObject), Added<BlenderPreviewObject>>,
+) {
+    for (entity, preview) in previews.iter() {
+        let Some(display) = app_state
+            .file(preview.file)
+            .and_then(|file| file.meshes.get(preview.mesh))
+            .and_then(|object| object.empty)
+        else {
+            continue;
+        };
+
+        let mesh = assets
+            .meshes
+            .entry((display.shape, display.size.to_bits()))
+            .or_insert_with(|| meshes.add(empty_mesh(display)))
+            .clone();
+        let marker = commands
+            .spawn((
+                EmptyMarker,
+                PbrBundle {
+                    mesh,
+                    material: assets.material.clone(),
+                    visibility: marker_visibility(&settings),
+                    ..Default::default()
+                },
+            ))
+            .id();
+        commands.entity(entity).add_child(marker);
+    }
+}
+
+fn toggle_empty_markers(
+    settings: Res<Settings>,
+    mut markers: Query<&mut Visibility, With<EmptyMarker>>,
+) {
+    let visibility = marker_visibility(&settings);
+    for mut marker in markers.iter_mut() {
+        if *marker != visibility {
+            *marker = visibility;
+        }
+    }
+}
+
+fn marker_visibility(settings: &Settings) -> Visibility {
+    if settings.show_empties {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+/// The shape's lines in the empty's own space, built on Blender's axes like Blender draws them
+fn empty_mesh(display: EmptyDisplay) -> Mesh {
+    let size = display.size;
+    let axes = [Vec3::X, Vec3::Y, Vec3::Z];
+    let mut lines: Vec<[Vec3; 2]> = Vec::new();
+
+    match display.shape {
+        EmptyShape::PlainAxes => {
+            lines.extend(axes.map(|axis| [-axis * size, axis * size]));
+        }
+        EmptyShape::Arrows => {
+            for (index, axis) in axes.iter().enumerate() {
+                // Heads fan out towards the next axis
+                let side = axes[(index + 1) % 3];
+                lines.extend(arrow(*axis, side, size));
+            }
+        }
+        EmptyShape::SingleArrow => lines.extend(arrow(Vec3::Z, Vec3::X, size)),
+        EmptyShape::Circle => lines.extend(circle(Vec3::X, Vec3::Y, size)),
+        EmptyShape::Cube => {
+            let corner = |x: f32, y: f32, z: f32| Vec3::new(x, y, z) * size;
+            for a in [-1.0, 1.0] {
+                for b in [-1.0, 1.0] {
+                    lines.push([corner(-1.0, a, b), corner(1.0, a, b)]);
+                    lines.push([corner(a, -1.0, b), corner(a, 1.0, b)]);
+                    lines.push([corner(a, b, -1.0), corner(a, b, 1.0)]);
+                }
+            }
+        }
+        EmptyShape::Sphere => {
+            lines.extend(circle(Vec3::X, Vec3::Y, size));
+            lines.extend(circle(Vec3::X, Vec3::Z, size));
+            lines.extend(circle(Vec3::Y, Vec3::Z, size));
+        }
+        EmptyShape::Cone => {
+            // Points along Y, twice as long as it's wide
+            let tip = Vec3::Y * size * 2.0;
+            let base = circle(Vec3::X, Vec3::Z, size);
+            for (index, [start, _]) in base.iter().enumerate() {
+                if index % (CIRCLE_SEGMENTS / 4) == 0 {
+                    lines.push([*start, tip]);
+                }
+            }
+            lines.extend(base);
+        }
+        EmptyShape::Image => {
+            let half = size * 0.5;
+            let corners = [
+                Vec3::new(-half, -half, 0.0),
+                Vec3::new(half, -half, 0.0),
+                Vec3::new(half, half, 0.0),
+                Vec3::new(-half, half, 0.0),
+            ];
+            lines.extend((0..4).map(|index| [corners[index], corners[(index + 1) % 4]]));
+        }
+    }
+
+    line_mesh(
+        lines
+            .iter()
+            .flatten()
+            .map(|point| to_bevy(point.to_array()))
+            .collect(),
+    )
+}
+
+/// A line from the origin along `axis`, with a head bent towards `side`
+fn arrow(axis: Vec3, side: Vec3, size: f32) -> [[Vec3; 2]; 3] {
+    let tip = axis * size;
+    let back = tip - axis * size * ARROW_HEAD;
+    let spread = side * size * ARROW_HEAD * 0.5;
+    [
+        [Vec3::ZERO, tip],
+        [tip, back + spread],
+        [tip, back - spread],
+    ]
+}
+
+/// A circle around the origin in the plane of `u` and `v`
+fn circle(u: Vec3, v: Vec3, radius: f32) -> Vec<[Vec3; 2]> {
+    let point = |index: usize| {
+        let angle = index as f32 / CIRCLE_SEGMENTS as f32 * TAU;
+        (u * angle.cos() + v * angle.sin()) * radius
+    };
+    (0..CIRCLE_SEGMENTS)
+        .map(|index| [point(index), point(index + 1)])
+        .collect()
+}
@@ -0,0 +1,119 @@
+use std::{fs, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    metadata::{ImageInfo, MeshStats},
+    natural_sort::natural_cmp,
+    properties::properties_json,
+    units::{dimensions, SceneUnits},
+    BlendObject, File,
+};
+
+/// The JSON shape of an exported file's metadata
+#[derive(Serialize)]
+struct MetadataExport<'a> {
+    path: &'a str,
+    units: &'a SceneUnits,
+    objects: Vec<ObjectExport<'a>>,
+    materials: Vec<&'a str>,
+    texture_audit: TextureAuditExport<'a>,
+    /// What the parse had to read around, e.g. objects it skipped
+    warnings: Vec<WarningExport<'a>>,
+}
+
+#[derive(Serialize)]
+struct ObjectExport<'a> {
+    name: &'a str,
+    stats: Option<&'a MeshStats>,
+    scale: [f32; 3],
+    /// In the scene's units, meters unless the unit system is none
+    dimensions: Option<[f32; 3]>,
+    /// `object` and `mesh` maps of the custom properties, when there are any
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    custom_properties: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct WarningExport<'a> {
+    /// The datablock's code, like `OB`
+    code: &'a str,
+    name: &'a str,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct TextureAuditExport<'a> {
+    max_size: u32,
+    oversized: Vec<&'a ImageInfo>,
+    broken: Vec<&'a ImageInfo>,
+    images: &'a [ImageInfo],
+}
+
+fn custom_properties_json(object: &BlendObject) -> serde_json::Value {
+    let mut map = serde_json::Map::new();
+    if !object.properties.is_empty() {
+        map.insert("object".to_string(), properties_json(&object.properties));
+    }
+    if !object.data_properties.is_empty() {
+        map.insert("mesh".to_string(), properties_json(&object.data_properties));
+    }
+    if map.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::Value::Object(map)
+    }
+}
+
+/// Writes the parsed metadata of a file as pretty printed JSON.
+/// Objects and materials are sorted by name whatever the list shows, so exports of the
+/// same file only differ where its contents do.
+pub fn export_metadata(file: &File, max_texture_size: u32, path: &Path) -> Result<(), String> {
+    let mut objects: Vec<&BlendObject> = file.meshes.iter().collect();
+    objects.sort_by(|a, b| natural_cmp(&a.name, &b.name));
+    let mut materials: Vec<&str> = file.materials.iter().map(String::as_str).collect();
+    materials.sort_by(|a, b| natural_cmp(a, b));
+
+    let export = MetadataExport {
+        path: &file.path,
+        units: &file.units,
+        objects: objects
+            .into_iter()
+            .map(|object| ObjectExport {
+                name: &object.name,
+                stats: object.stats.as_ref(),
+                scale: object.scale,
+                dimensions: dimensions(object)
+                    .map(|size| size.map(|length| file.units.to_scene(length))),
+                custom_properties: custom_properties_json(object),
+            })
+            .collect(),
+        materials,
+        texture_audit: TextureAuditExport {
+            max_size: max_texture_size,
+            oversized: file
+                .images
+                .iter()
+                .filter(|image| image.exceeds(max_texture_size))
+                .collect(),
+            broken: file
+                .images
+                .iter()
+                .filter(|image| image.is_broken())
+                .collect(),
+            images: &file.images,
+        },
+        warnings: file
+            .parse_warnings
+            .iter()
+            .map(|warning| WarningExport {
+                code: &warning.code,
+                name: &warning.name,
+                message: warning.message(),
+            })
+            .collect(),
+    };
+
+    let json = serde_json::to_string_pretty(&export).map_err(|error| error.to_string())?;
+    fs::write(path, json).map_err(|error| error.to_string())
+}
@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{
+    commands::{Action, ActionEvent, Keybindings},
+    confirm::{Confirmation, DestructiveAction},
+    file_status::{FileOp, FileStatus},
+    i18n::t,
+    toasts::{Severity, Toasts},
+    AppState, ReparseFile,
+};
+
+/// Actions on a single file along with their toolbar icons, in toolbar order.
+/// The toolbar and the context menu of a file's row are built from this, and
+/// the command palette lists the same actions, so each is wired up once.
+/// They act on the selected file, so a row selects its file before sending one.
+pub const FILE_ACTIONS: [(Action, &str); 5] = [
+    (Action::ReloadFile, "🔄"),
+    (Action::OpenInBlender, "▶"),
+    (Action::RevealFile, "🗁"),
+    (Action::ExportMetadata, "💾"),
+    (Action::RemoveFile, "✖"),
+];
+
+pub struct FileActionsPlugin;
+
+impl Plugin for FileActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(run_file_actions.run_if(on_event::<ActionEvent>()));
+    }
+}
+
+/// The file actions without a plugin of their own. Opening and exporting have theirs.
+fn run_file_actions(
+    mut actions: EventReader<ActionEvent>,
+    app_state: Res<AppState>,
+    mut reparse: EventWriter<ReparseFile>,
+    mut confirmation: ResMut<Confirmation>,
+    mut toasts: ResMut<Toasts>,
+) {
+    for ActionEvent(action) in actions.iter() {
+        let Some(file) = app_state.selected_file() else {
+            continue;
+        };
+        // Shortcuts get here whatever the file is busy with, so say why nothing happens
+        if let Some(reason) = blocked(*action, file.status) {
+            let message = match action {
+                Action::RemoveFile => {
+                    t!("file_status.not_removed", path = file.path, reason = reason)
+                }
+                _ => t!(
+                    "file_status.not_reloaded",
+                    path = file.path,
+                    reason = reason
+                ),
+            };
+            toasts.push(Severity::Warning, message);
+            continue;
+        }
+        match action {
+            Action::ReloadFile => reparse.send(ReparseFile(file.id)),
+            Action::RevealFile => {
+                if let Err(error) = opener::reveal(&file.path) {
+                    warn!(path = %file.path, %error, "couldn't reveal file");
+                    toasts.push(
+                        Severity::Error,
+                        t!("toasts.reveal_failed", path = file.path, error = error),
+                    );
+                }
+            }
+            Action::RemoveFile => confirmation.request(DestructiveAction::RemoveFile(file.id)),
+            _ => {}
+        }
+    }
+}
+
+/// Why the file's status rules out `action` right now
+fn blocked(action: Action, status: FileStatus) -> Option<&'static str> {
+    let op = match action {
+        Action::ReloadFile => FileOp::Reload,
+        Action::RemoveFile => FileOp::Remove,
+        _ => return None,
+    };
+    status.blocked(op)
+}
+
+/// The name of an action with its shortcut, if it has one
+fn tooltip(keybindings: &Keybindings, action: Action) -> String {
+    let name = keybindings
+        .command(action)
+        .map_or("", |command| command.name());
+    match keybindings.chord(action) {
+        Some(chord) => format!("{} ({})", name, chord.label()),
+        None => name.to_string(),
+    }
+}
+
+/// Small icon buttons for the actions in `visible`, in registry order.
+/// Actions the file is too busy for are disabled.
+pub fn file_toolbar_ui(
+    ui: &mut egui::Ui,
+    keybindings: &Keybindings,
+    visible: &[Action],
+    status: FileStatus,
+) -> Option<Action> {
+    let mut clicked = None;
+    for (action, icon) in FILE_ACTIONS {
+        if !visible.contains(&action) {
+            continue;
+        }
+        let blocked = blocked(action, status);
+        if ui
+            .add_enabled(blocked.is_none(), egui::Button::new(icon).small())
+            .on_hover_text(tooltip(keybindings, action))
+            .on_disabled_hover_text(blocked.unwrap_or_default())
+            .clicked()
+        {
+            clicked = Some(action);
+        }
+    }
+    clicked
+}
+
+/// Every file action as a menu entry, whether or not the toolbar shows it
+pub fn file_context_menu_ui(
+    ui: &mut egui::Ui,
+    keybindings: &Keybindings,
+    status: FileStatus,
+) -> Option<Action> {
+    let mut clicked = None;
+    for (action, icon) in FILE_ACTIONS {
+        let name = keybindings
+            .command(action)
+            .map_or("", |command| command.name());
+        let mut button = egui::Button::new(format!("{} {}", icon, name));
+        if let Some(chord) = keybindings.chord(action) {
+            button = button.shortcut_text(chord.label());
+        }
+        let blocked = blocked(action, status);
+        if ui
+            .add_enabled(blocked.is_none(), button)
+            .on_disabled_hover_text(blocked.unwrap_or_default())
+            .clicked()
+        {
+            clicked = Some(action);
+            ui.close_menu();
+        }
+    }
+    clicked
+}
+
+/// Checkboxes for which actions the file toolbar shows
+pub fn file_toolbar_settings_ui(
+    ui: &mut egui::Ui,
+    keybindings: &Keybindings,
+    visible: &mut Vec<Action>,
+) -> bool {
+    let mut changed = false;
+    ui.horizontal_wrapped(|ui| {
+        ui.label(t!("settings.file_toolbar"));
+        for (action, icon) in FILE_ACTIONS {
+            let mut shown = visible.contains(&action);
+            if ui
+                .checkbox(&mut shown, icon)
+                .on_hover_text(tooltip(keybindings, action))
+                .changed()
+            {
+                if shown {
+                    visible.push(action);
+                } else {
+                    visible.retain(|visible| *visible != action);
+                }
+                changed = true;
+            }
+        }
+    });
+    changed
+}
@@ -0,0 +1,205 @@
+use crate::i18n::t;
+
+/// What a file is busy with. Every operation on a file checks this first and moves it along,
+/// so nothing can remove a file mid-parse or render a file that's reloading.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FileStatus {
+    #[default]
+    Idle,
+    /// Queued for or running its first parse
+    Parsing,
+    /// Queued for or running a parse after it's been parsed before
+    Reloading,
+    /// Generating thumbnails or running a render job
+    Rendering,
+    /// Gone from disk when we went to read it
+    Missing,
+    /// The last parse failed
+    Error,
+}
+
+/// Something the user can do to a file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileOp {
+    Parse,
+    Reload,
+    Render,
+    Spawn,
+    Remove,
+}
+
+impl FileOp {
+    /// Reading a file's metadata, a reload if it's been read before
+    pub fn load(parsed: bool) -> FileOp {
+        if parsed {
+            FileOp::Reload
+        } else {
+            FileOp::Parse
+        }
+    }
+}
+
+impl FileStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileStatus::Idle => t!("file_status.idle"),
+            FileStatus::Parsing => t!("file_status.parsing"),
+            FileStatus::Reloading => t!("file_status.reloading"),
+            FileStatus::Rendering => t!("file_status.rendering"),
+            FileStatus::Missing => t!("file_status.missing"),
+            FileStatus::Error => t!("file_status.error"),
+        }
+    }
+
+    /// Whether an operation is running, as opposed to the file just being in a bad way
+    pub fn is_busy(&self) -> bool {
+        matches!(
+            self,
+            FileStatus::Parsing | FileStatus::Reloading | FileStatus::Rendering
+        )
+    }
+
+    /// Why `op` can't run right now, `None` when it can
+    pub fn blocked(&self, op: FileOp) -> Option<&'static str> {
+        match (self, op) {
+            (FileStatus::Idle, _) => None,
+            // Showing the objects we already have doesn't get in the way of thumbnails
+            (FileStatus::Rendering, FileOp::Spawn) => None,
+            // Parsing again is how to look for the file again, or retry a failed parse
+            (
+                FileStatus::Missing | FileStatus::Error,
+                FileOp::Parse | FileOp::Reload | FileOp::Remove,
+            ) => None,
+            (FileStatus::Parsing, _) => Some(t!("file_status.blocked.parsing")),
+            (FileStatus::Reloading, _) => Some(t!("file_status.blocked.reloading")),
+            (FileStatus::Rendering, _) => Some(t!("file_status.blocked.rendering")),
+            (FileStatus::Missing, _) => Some(t!("file_status.blocked.missing")),
+            (FileStatus::Error, _) => Some(t!("file_status.blocked.error")),
+        }
+    }
+
+    /// The status while `op` runs, or why it can't run now.
+    /// Spawning and removing are over at once, so they leave the status as it is.
+    pub fn begin(self, op: FileOp) -> Result<FileStatus, &'static str> {
+        if let Some(reason) = self.blocked(op) {
+            return Err(reason);
+        }
+        Ok(match op {
+            FileOp::Parse => FileStatus::Parsing,
+            FileOp::Reload => FileStatus::Reloading,
+            FileOp::Render => FileStatus::Rendering,
+            FileOp::Spawn | FileOp::Remove => self,
+        })
+    }
+
+    /// The status once the running operation is over. Only a failed parse leaves the file
+    /// in `Error`, a failed render says nothing about the file.
+    pub fn finish(self, succeeded: bool) -> FileStatus {
+        match self {
+            FileStatus::Parsing | FileStatus::Reloading if !succeeded => FileStatus::Error,
+            FileStatus::Parsing | FileStatus::Reloading | FileStatus::Rendering => FileStatus::Idle,
+            status => status,
+        }
+    }
+
+    /// The status once the running operation was called off before it got anywhere
+    pub fn cancel(self) -> FileStatus {
+        match self {
+            FileStatus::Parsing | FileStatus::Reloading | FileStatus::Rendering => FileStatus::Idle,
+            status => status,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [FileStatus; 6] = [
+        FileStatus::Idle,
+        FileStatus::Parsing,
+        FileStatus::Reloading,
+        FileStatus::Rendering,
+        FileStatus::Missing,
+        FileStatus::Error,
+    ];
+    const OPS: [FileOp; 5] = [
+        FileOp::Parse,
+        FileOp::Reload,
+        FileOp::Render,
+        FileOp::Spawn,
+        FileOp::Remove,
+    ];
+
+    #[test]
+    fn idle_files_allow_everything() {
+        let status = FileStatus::Idle;
+        assert_eq!(status.begin(FileOp::Parse), Ok(FileStatus::Parsing));
+        assert_eq!(status.begin(FileOp::Reload), Ok(FileStatus::Reloading));
+        assert_eq!(status.begin(FileOp::Render), Ok(FileStatus::Rendering));
+        assert_eq!(status.begin(FileOp::Spawn), Ok(FileStatus::Idle));
+        assert_eq!(status.begin(FileOp::Remove), Ok(FileStatus::Idle));
+    }
+
+    #[test]
+    fn parsing_files_cant_be_touched() {
+        for status in [FileStatus::Parsing, FileStatus::Reloading] {
+            for op in OPS {
+                assert!(status.begin(op).is_err(), "{:?} allowed {:?}", status, op);
+            }
+        }
+    }
+
+    #[test]
+    fn rendering_files_can_only_be_spawned() {
+        let status = FileStatus::Rendering;
+        assert_eq!(status.begin(FileOp::Spawn), Ok(FileStatus::Rendering));
+        assert!(status.begin(FileOp::Render).is_err());
+        assert!(status.begin(FileOp::Reload).is_err());
+        assert!(status.begin(FileOp::Remove).is_err());
+    }
+
+    #[test]
+    fn missing_and_broken_files_can_be_retried_or_removed() {
+        for status in [FileStatus::Missing, FileStatus::Error] {
+            assert_eq!(status.begin(FileOp::Parse), Ok(FileStatus::Parsing));
+            assert_eq!(status.begin(FileOp::Reload), Ok(FileStatus::Reloading));
+            assert_eq!(status.begin(FileOp::Remove), Ok(status));
+            assert!(status.begin(FileOp::Spawn).is_err());
+            assert!(status.begin(FileOp::Render).is_err());
+        }
+    }
+
+    #[test]
+    fn finishing_goes_back_to_idle() {
+        for status in ALL.into_iter().filter(FileStatus::is_busy) {
+            assert_eq!(status.finish(true), FileStatus::Idle);
+            assert_eq!(status.cancel(), FileStatus::Idle);
+        }
+    }
+
+    #[test]
+    fn only_failed_parses_are_errors() {
+        assert_eq!(FileStatus::Parsing.finish(false), FileStatus::Error);
+        assert_eq!(FileStatus::Reloading.finish(false), FileStatus::Error);
+        assert_eq!(FileStatus::Rendering.finish(false), FileStatus::Idle);
+    }
+
+    #[test]
+    fn settled_states_stay_put() {
+        for status in ALL.into_iter().filter(|status| !status.is_busy()) {
+            assert_eq!(status.finish(true), status);
+            assert_eq!(status.finish(false), status);
+            assert_eq!(status.cancel(), status);
+        }
+    }
+
+    #[test]
+    fn blocked_agrees_with_begin() {
+        for status in ALL {
+            for op in OPS {
+                assert_eq!(status.blocked(op).is_some(), status.begin(op).is_err());
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+
+use crate::{
+    commands::{Action, ActionEvent},
+    i18n::t,
+    lighting::PreviewLight,
+    settings::Settings,
+    toasts::{Severity, ToastAction, Toasts},
+    OccupiedScreenSpace,
+};
+
+/// How long frames have to stay over the limit before we step in, in seconds
+const SLOW_FOR: f32 = 2.0;
+/// How long frames have to stay comfortably under the limit before quality comes back
+const RECOVERED_FOR: f32 = 5.0;
+/// What "comfortably" means, as a fraction of the limit. Turning things back on costs time,
+/// so coming back right at the limit would just trip the guard again.
+const RECOVERED_FRACTION: f64 = 0.5;
+
+/// Watches the frame time and turns off the expensive parts of the preview when something
+/// spawned makes the app unusable, so the panels stay reachable
+#[derive(Default, Resource)]
+pub struct FrameGuard {
+    /// Seconds the frame time has been over the limit, or under it while reduced
+    streak: f32,
+    reduced: Option<Reduced>,
+    /// The user asked for full quality back while frames were slow,
+    /// so we hold off until they speed up again
+    dismissed: bool,
+}
+
+impl FrameGuard {
+    /// Whether preview quality is reduced right now
+    pub fn is_reducing(&self) -> bool {
+        self.reduced.is_some()
+    }
+}
+
+/// What we turned off, to turn back on
+struct Reduced {
+    msaa: Msaa,
+    shadow_lights: Vec<Entity>,
+}
+
+/// What reducing preview quality touches
+#[derive(SystemParam)]
+struct PreviewQuality<'w, 's> {
+    msaa: ResMut<'w, Msaa>,
+    lights: Query<'w, 's, (Entity, &'static mut PointLight), With<PreviewLight>>,
+}
+
+impl PreviewQuality<'_, '_> {
+    fn reduce(&mut self) -> Reduced {
+        let msaa = *self.msaa;
+        if msaa != Msaa::Off {
+            *self.msaa = Msaa::Off;
+        }
+        let mut shadow_lights = Vec::new();
+        for (entity, mut light) in self.lights.iter_mut() {
+            if light.shadows_enabled {
+                light.shadows_enabled = false;
+                shadow_lights.push(entity);
+            }
+        }
+        Reduced {
+            msaa,
+            shadow_lights,
+        }
+    }
+
+    /// Turns back on what `reduce` turned off. Shadows may have been turned off in the settings
+    /// in the meantime, so they follow `shadows` instead.
+    fn restore(&mut self, reduced: Reduced, shadows: bool) {
+        if *self.msaa != reduced.msaa {
+            *self.msaa = reduced.msaa;
+        }
+        for entity in reduced.shadow_lights {
+            // Lights despawned in the meantime have nothing to restore
+            if let Ok((_, mut light)) = self.lights.get_mut(entity) {
+                light.shadows_enabled = shadows;
+            }
+        }
+    }
+}
+
+pub struct FrameGuardPlugin;
+
+impl Plugin for FrameGuardPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugin(FrameTimeDiagnosticsPlugin);
+        }
+        app.init_resource::<FrameGuard>()
+            .add_system(watch_frame_time)
+            .add_system(reduced_quality_ui.run_if(|guard: Res<FrameGuard>| guard.is_reducing()));
+    }
+}
+
+fn watch_frame_time(
+    time: Res<Time>,
+    diagnostics: Res<Diagnostics>,
+    settings: Res<Settings>,
+    mut guard: ResMut<FrameGuard>,
+    mut quality: PreviewQuality,
+    mut toasts: ResMut<Toasts>,
+) {
+    // Nothing reacts to the guard changing, so the streak can tick without flagging it
+    let guard = guard.bypass_change_detection();
+    if !settings.frame_guard {
+        if let Some(reduced) = guard.reduced.take() {
+            quality.restore(reduced, settings.lighting.shadows);
+        }
+        guard.streak = 0.0;
+        return;
+    }
+    let Some(frame_ms) = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed())
+    else {
+        return;
+    };
+    let limit = f64::from(settings.frame_guard_ms);
+    let recovered = frame_ms < limit * RECOVERED_FRACTION;
+    if recovered {
+        guard.dismissed = false;
+    }
+
+    match guard.reduced.take() {
+        None => {
+            guard.streak = if frame_ms > limit && !guard.dismissed {
+                guard.streak + time.delta_seconds()
+            } else {
+                0.0
+            };
+            if guard.streak < SLOW_FOR {
+                return;
+            }
+            guard.streak = 0.0;
+            let reduced = quality.reduce();
+            warn!(frame_ms, "frames are slow, reducing preview quality");
+            let mut what = Vec::new();
+            if !reduced.shadow_lights.is_empty() {
+                what.push(t!("frame_guard.shadows"));
+            }
+            if reduced.msaa != Msaa::Off {
+                what.push(t!("frame_guard.msaa"));
+            }
+            // Already as cheap as we can make it, there's nothing to turn back on later
+            if what.is_empty() {
+                guard.dismissed = true;
+                return;
+            }
+            toasts.push_with_action(
+                Severity::Warning,
+                t!("frame_guard.reduced", what = what.join(", ")),
+                ToastAction::ClearPreview,
+            );
+            guard.reduced = Some(reduced);
+        }
+        Some(reduced) => {
+            guard.streak = if recovered {
+                guard.streak + time.delta_seconds()
+            } else {
+                0.0
+            };
+            if guard.streak < RECOVERED_FOR {
+                guard.reduced = Some(reduced);
+                return;
+            }
+            guard.streak = 0.0;
+            quality.restore(reduced, settings.lighting.shadows);
+            info!(frame_ms, "frames are fast again, restoring preview quality");
+            toasts.push(Severity::Info, t!("frame_guard.restored"));
+        }
+    }
+}
+
+/// A note at the top of the viewport for as long as quality is reduced,
+/// with a way to clear what's slow or to get full quality back regardless
+fn reduced_quality_ui(
+    mut contexts: EguiContexts,
+    settings: Res<Settings>,
+    mut guard: ResMut<FrameGuard>,
+    mut quality: PreviewQuality,
+    mut actions: EventWriter<ActionEvent>,
+    occupied_screen_space: Res<OccupiedScreenSpace>,
+    egui_settings: Res<EguiSettings>,
+) {
+    let ui_scale = egui_settings.scale_factor as f32;
+    let offset = egui::vec2(
+        (occupied_screen_space.left - occupied_screen_space.right) / ui_scale / 2.0,
+        occupied_screen_space.top / ui_scale + 12.0,
+    );
+
+    let mut restore = false;
+    egui::Area::new("reduced_quality")
+        .anchor(egui::Align2::CENTER_TOP, offset)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(t!("frame_guard.active"));
+                    if ui.small_button(t!("actions.clear_preview")).clicked() {
+                        actions.send(ActionEvent(Action::ClearPreview));
+                    }
+                    restore = ui.small_button(t!("frame_guard.restore")).clicked();
+                });
+            });
+        });
+
+    if restore {
+        if let Some(reduced) = guard.reduced.take() {
+            quality.restore(reduced, settings.lighting.shadows);
+        }
+        guard.streak = 0.0;
+        guard.dismissed = true;
+    }
+}
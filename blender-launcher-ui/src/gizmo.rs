@@ -0,0 +1,393 @@
+use bevy::{math::Ray, prelude::*, render::primitives::Aabb, window::PrimaryWindow};
+use bevy_egui::EguiContexts;
+
+use crate::{
+    camera, measure::MeasureTool, overlays::overlay, placement::Placement,
+    preview_info::PreviewObjectInfo, settings::Settings, viewport::ViewportClick, AppState,
+    BlenderPreviewObject, LauncherCamera,
+};
+
+/// How long the handles are, as a fraction of their distance from the camera,
+/// so they stay the same size on screen however far away the object is
+const HANDLE_LENGTH: f32 = 0.15;
+/// Half the thickness of a handle and the size of the cube at its tip, as fractions of
+/// its length
+const SHAFT_RADIUS: f32 = 0.012;
+const TIP_SIZE: f32 = 0.07;
+/// How close the cursor has to be to a handle to grab it, in pixels
+const HANDLE_REACH: f32 = 8.0;
+/// What holding Shift scales dragging by
+const FINE_STEP: f32 = 0.1;
+
+const AXES: [(Vec3, Color); 3] = [
+    (Vec3::X, Color::rgb(0.9, 0.27, 0.27)),
+    (Vec3::Y, Color::rgb(0.43, 0.78, 0.27)),
+    (Vec3::Z, Color::rgb(0.27, 0.51, 0.94)),
+];
+const ACTIVE_COLOR: Color = Color::rgb(1.0, 0.82, 0.24);
+
+/// The preview picked in the viewport and the handles for moving it along the world axes
+#[derive(Default, Resource)]
+pub struct TranslateGizmo {
+    selected: Option<Entity>,
+    /// Index into `AXES` of the handle under the cursor
+    hovered: Option<usize>,
+    drag: Option<Drag>,
+    click: ViewportClick,
+}
+
+impl TranslateGizmo {
+    /// The preview entity the gizmo is on
+    pub fn selected(&self) -> Option<Entity> {
+        self.selected
+    }
+
+    /// Whether a handle is being dragged, which takes the mouse from orbiting
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+}
+
+struct Drag {
+    /// Index into `AXES`
+    axis: usize,
+    /// How far along the axis the cursor was last frame
+    last: f32,
+}
+
+/// One of the entities drawing the handles, on the overlay layer
+#[derive(Component)]
+struct GizmoHandle {
+    /// Index into `AXES`
+    axis: usize,
+    /// The cube at the end rather than the line to it
+    tip: bool,
+}
+
+#[derive(Resource)]
+struct GizmoMaterials {
+    axes: [Handle<StandardMaterial>; 3],
+    active: Handle<StandardMaterial>,
+}
+
+/// Moves a preview to a position typed into the inspector
+pub struct SetTranslation {
+    pub entity: Entity,
+    pub translation: Vec3,
+}
+
+pub struct GizmoPlugin;
+
+impl Plugin for GizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TranslateGizmo>()
+            .add_event::<SetTranslation>()
+            .add_startup_system(spawn_gizmo_handles)
+            // Grabbing a handle has to win over the orbit the same press would start
+            .add_system(translate_gizmo.before(camera::orbit_camera))
+            .add_system(place_gizmo_handles.after(translate_gizmo))
+            .add_system(set_translation.run_if(on_event::<SetTranslation>()));
+    }
+}
+
+/// The handles are six entities spawned once, moved onto whichever preview is picked
+fn spawn_gizmo_handles(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut material = |color: Color| {
+        materials.add(StandardMaterial {
+            base_color: color,
+            unlit: true,
+            ..Default::default()
+        })
+    };
+    let gizmo_materials = GizmoMaterials {
+        axes: AXES.map(|(_, color)| material(color)),
+        active: material(ACTIVE_COLOR),
+    };
+    let tip = meshes.add(shape::Cube { size: TIP_SIZE }.into());
+    for (index, (axis, _)) in AXES.iter().enumerate() {
+        // From the origin to 1 along the axis, with a unit scale
+        let across = (Vec3::ONE - *axis) * SHAFT_RADIUS;
+        let shaft = meshes.add(
+            shape::Box {
+                min_x: -across.x,
+                max_x: axis.x + across.x,
+                min_y: -across.y,
+                max_y: axis.y + across.y,
+                min_z: -across.z,
+                max_z: axis.z + across.z,
+            }
+            .into(),
+        );
+        for (mesh, is_tip) in [(shaft, false), (tip.clone(), true)] {
+            commands.spawn((
+                GizmoHandle {
+                    axis: index,
+                    tip: is_tip,
+                },
+                overlay(),
+                PbrBundle {
+                    mesh,
+                    material: gizmo_materials.axes[index].clone(),
+                    visibility: Visibility::Hidden,
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+    commands.insert_resource(gizmo_materials);
+}
+
+/// Picks previews on click, and drags the handles of the picked one
+#[allow(clippy::too_many_arguments)]
+fn translate_gizmo(
+    mut contexts: EguiContexts,
+    settings: Res<Settings>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    placement: Res<Placement>,
+    measure: Res<MeasureTool>,
+    mut gizmo: ResMut<TranslateGizmo>,
+    mut app_state: ResMut<AppState>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<LauncherCamera>>,
+    mut previews: Query<(
+        Entity,
+        &mut Transform,
+        &mut BlenderPreviewObject,
+        Option<&PreviewObjectInfo>,
+        &GlobalTransform,
+        Option<&Aabb>,
+    )>,
+) {
+    // Placing objects and measuring have the clicks to themselves
+    if placement.object().is_some() || measure.is_active() {
+        if gizmo.selected.is_some() {
+            *gizmo = TranslateGizmo::default();
+        }
+        return;
+    }
+    if gizmo
+        .selected
+        .is_some_and(|entity| !previews.contains(entity))
+    {
+        *gizmo = TranslateGizmo::default();
+    }
+    // Borrowing the fields separately below needs the resource itself
+    let gizmo = &mut *gizmo;
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single())
+    else {
+        return;
+    };
+    let cursor = window.cursor_position();
+    let ray = cursor.and_then(|cursor| camera.viewport_to_world(camera_transform, cursor));
+    let ctx = contexts.ctx_mut();
+    let over_panels = ctx.is_pointer_over_area() || ctx.is_using_pointer();
+
+    // The handles, from the picked preview's origin to each axis' tip, in viewport pixels.
+    // Hidden along with the other overlays, and out of reach then too.
+    let handles = gizmo
+        .selected
+        .filter(|_| settings.show_overlays)
+        .and_then(|entity| {
+            let (.., transform, _) = previews.get(entity).ok()?;
+            let origin = transform.translation();
+            let length = camera_transform.translation().distance(origin) * HANDLE_LENGTH;
+            let start = camera.world_to_viewport(camera_transform, origin)?;
+            let tips = AXES.map(|(axis, _)| {
+                camera.world_to_viewport(camera_transform, origin + axis * length)
+            });
+            Some((origin, start, tips))
+        });
+
+    if let Some(drag) = &mut gizmo.drag {
+        if !mouse_buttons.pressed(MouseButton::Left) {
+            gizmo.drag = None;
+        } else if let (Some(ray), Some(entity), Some((origin, ..))) = (ray, gizmo.selected, handles)
+        {
+            let (axis, _) = AXES[drag.axis];
+            if let Some(along) = along_axis(ray, origin, axis) {
+                let fine = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+                let step = (along - drag.last) * if fine { FINE_STEP } else { 1.0 };
+                // Fine steps don't follow the cursor, so the next step starts from here
+                drag.last = along;
+                if step != 0.0 {
+                    if let Ok((_, mut transform, mut preview, ..)) = previews.get_mut(entity) {
+                        transform.translation += axis * step;
+                        // `snap_to_ground` puts snapped previews back on this every frame
+                        preview.original_transform.translation += axis * step;
+                    }
+                }
+            }
+        }
+    } else {
+        let hovered = match (cursor, handles) {
+            (Some(cursor), Some((_, start, tips))) if !over_panels => (0..AXES.len())
+                .filter_map(|axis| Some((axis, distance_to_segment(cursor, start, tips[axis]?))))
+                .filter(|(_, distance)| *distance <= HANDLE_REACH)
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(axis, _)| axis),
+            _ => None,
+        };
+        gizmo.hovered = hovered;
+
+        if mouse_buttons.just_pressed(MouseButton::Left) && !over_panels {
+            match (hovered, ray, handles) {
+                (Some(axis), Some(ray), Some((origin, ..))) => {
+                    gizmo.drag = along_axis(ray, origin, AXES[axis].0)
+                        .map(|along| Drag { axis, last: along });
+                }
+                _ => gizmo.click.press(cursor, over_panels),
+            }
+        }
+        if mouse_buttons.just_released(MouseButton::Left) {
+            if gizmo.click.release(cursor) {
+                let picked = ray.and_then(|ray| {
+                    previews
+                        .iter()
+                        .filter_map(|(entity, _, _, info, transform, aabb)| {
+                            let distance = ray_hits_aabb(ray, transform, aabb?)?;
+                            Some((entity, info, distance))
+                        })
+                        .min_by(|(.., a), (.., b)| a.total_cmp(b))
+                });
+                // Clicking the background lets go of the object
+                gizmo.selected = picked.map(|(entity, ..)| entity);
+                // Found by name, the file may have been reparsed since the copy was spawned
+                let object = picked
+                    .and_then(|(_, info, _)| info)
+                    .and_then(|info| Some((info.file, info.index(&app_state)?)));
+                if let Some((file, index)) = object {
+                    if app_state.selected_object != Some((file, index)) {
+                        app_state.select_object(file, index);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Puts the handles on the picked preview, highlighting the one under the cursor or dragged
+fn place_gizmo_handles(
+    gizmo: Res<TranslateGizmo>,
+    settings: Res<Settings>,
+    materials: Res<GizmoMaterials>,
+    previews: Query<&GlobalTransform, With<BlenderPreviewObject>>,
+    cameras: Query<&GlobalTransform, With<LauncherCamera>>,
+    mut handles: Query<(
+        &GizmoHandle,
+        &mut Transform,
+        &mut Visibility,
+        &mut Handle<StandardMaterial>,
+    )>,
+) {
+    let placed = gizmo
+        .selected
+        .filter(|_| settings.show_overlays)
+        .and_then(|entity| previews.get(entity).ok())
+        .zip(cameras.get_single().ok())
+        .map(|(preview, camera)| {
+            let origin = preview.translation();
+            (
+                origin,
+                camera.translation().distance(origin) * HANDLE_LENGTH,
+            )
+        });
+    let active = gizmo.drag.as_ref().map(|drag| drag.axis).or(gizmo.hovered);
+
+    for (handle, mut transform, mut visibility, mut material) in handles.iter_mut() {
+        let Some((origin, length)) = placed else {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+            continue;
+        };
+        let (axis, _) = AXES[handle.axis];
+        let translation = if handle.tip {
+            origin + axis * length
+        } else {
+            origin
+        };
+        let target = Transform::from_translation(translation).with_scale(Vec3::splat(length));
+        if *transform != target {
+            *transform = target;
+        }
+        if *visibility != Visibility::Inherited {
+            *visibility = Visibility::Inherited;
+        }
+        let wanted = if active == Some(handle.axis) {
+            &materials.active
+        } else {
+            &materials.axes[handle.axis]
+        };
+        if *material != *wanted {
+            *material = wanted.clone();
+        }
+    }
+}
+
+fn set_translation(
+    mut events: EventReader<SetTranslation>,
+    mut previews: Query<(&mut Transform, &mut BlenderPreviewObject)>,
+) {
+    for event in events.iter() {
+        let Ok((mut transform, mut preview)) = previews.get_mut(event.entity) else {
+            continue;
+        };
+        // Snapped previews sit above their position, so move by the difference
+        let delta = event.translation - preview.original_transform.translation;
+        transform.translation += delta;
+        preview.original_transform.translation = event.translation;
+    }
+}
+
+/// How far along `axis` through `origin` the ray points, found by meeting the ray with the
+/// plane through the axis that faces it the most. `None` when looking straight down the axis.
+fn along_axis(ray: Ray, origin: Vec3, axis: Vec3) -> Option<f32> {
+    let normal = axis.cross(ray.direction).cross(axis);
+    let facing = normal.dot(ray.direction);
+    if facing.abs() <= f32::EPSILON {
+        return None;
+    }
+    let distance = normal.dot(origin - ray.origin) / facing;
+    let hit = ray.origin + ray.direction * distance;
+    Some((hit - origin).dot(axis))
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let along = if segment.length_squared() > f32::EPSILON {
+        ((point - start).dot(segment) / segment.length_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    point.distance(start + segment * along)
+}
+
+/// How far along the ray it enters the transformed bounding box, `None` if it misses
+pub fn ray_hits_aabb(ray: Ray, transform: &GlobalTransform, aabb: &Aabb) -> Option<f32> {
+    // In the box's own space the box is axis aligned, and distances along the ray stay the same
+    let inverse = transform.compute_matrix().inverse();
+    let origin = inverse.transform_point3(ray.origin);
+    let direction = inverse.transform_vector3(ray.direction);
+    let min = Vec3::from(aabb.min());
+    let max = Vec3::from(aabb.max());
+
+    let (mut near, mut far) = (f32::MIN, f32::MAX);
+    for axis in 0..3 {
+        if direction[axis].abs() <= f32::EPSILON {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let a = (min[axis] - origin[axis]) / direction[axis];
+        let b = (max[axis] - origin[axis]) / direction[axis];
+        near = near.max(a.min(b));
+        far = far.min(a.max(b));
+    }
+    (near <= far && far >= 0.0).then(|| near.max(0.0))
+}
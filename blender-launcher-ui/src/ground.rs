@@ -0,0 +1,109 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+
+use crate::{settings::Settings, BlenderPreviewObject};
+
+pub struct SnapToGroundPlugin;
+
+impl Plugin for SnapToGroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            snap_to_ground
+                .run_if(|settings: Res<Settings>| settings.snap_to_ground || settings.is_changed()),
+        );
+    }
+}
+
+/// Rests each preview on Y=0 while "Snap to ground" is on, and puts it back when it's off.
+/// Bevy adds the `Aabb` once the mesh has loaded, so objects still loading snap when they arrive.
+/// Previews parented to another one are in its space and move along with it, so only the
+/// roots are snapped.
+fn snap_to_ground(
+    settings: Res<Settings>,
+    mut previews: Query<(&mut Transform, &BlenderPreviewObject, Option<&Aabb>), Without<Parent>>,
+) {
+    for (mut transform, preview, aabb) in previews.iter_mut() {
+        let target = match aabb {
+            Some(aabb) if settings.snap_to_ground => {
+                let lowest = lowest_point(&preview.original_transform, aabb);
+                preview
+                    .original_transform
+                    .with_translation(preview.original_transform.translation - Vec3::Y * lowest)
+            }
+            _ => preview.original_transform,
+        };
+
+        if *transform != target {
+            *transform = target;
+        }
+    }
+}
+
+/// The lowest Y of the mesh's bounding box once it's been transformed
+pub fn lowest_point(transform: &Transform, aabb: &Aabb) -> f32 {
+    let center = Vec3::from(aabb.center);
+    let half_extents = Vec3::from(aabb.half_extents);
+
+    let mut lowest = f32::MAX;
+    for x in [-1.0, 1.0] {
+        for y in [-1.0, 1.0] {
+            for z in [-1.0, 1.0] {
+                let corner = center + half_extents * Vec3::new(x, y, z);
+                lowest = lowest.min(transform.transform_point(corner).y);
+            }
+        }
+    }
+    lowest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileId;
+
+    fn preview(transform: Transform) -> (BlenderPreviewObject, Transform, Aabb) {
+        (
+            BlenderPreviewObject {
+                file: FileId(0),
+                mesh: 0,
+                original_transform: transform,
+            },
+            transform,
+            Aabb::from_min_max(Vec3::splat(-1.0), Vec3::splat(1.0)),
+        )
+    }
+
+    #[test]
+    fn parented_previews_move_with_their_root() {
+        let mut app = App::new();
+        app.insert_resource(Settings {
+            snap_to_ground: true,
+            ..Default::default()
+        })
+        .add_plugin(SnapToGroundPlugin);
+        let root = app
+            .world
+            .spawn(preview(Transform::from_xyz(0.0, 3.0, 0.0)))
+            .id();
+        // Rotated and scaled in its parent's space, like a child from a scene spawn
+        let child_transform = Transform::from_xyz(2.0, -1.0, 0.0)
+            .with_rotation(Quat::from_rotation_z(0.5))
+            .with_scale(Vec3::splat(2.0));
+        let child = app.world.spawn(preview(child_transform)).id();
+        app.world.entity_mut(root).push_children(&[child]);
+        app.update();
+
+        assert_eq!(
+            *app.world.get::<Transform>(root).unwrap(),
+            Transform::from_xyz(0.0, 1.0, 0.0)
+        );
+        assert_eq!(*app.world.get::<Transform>(child).unwrap(), child_transform);
+
+        app.world.resource_mut::<Settings>().snap_to_ground = false;
+        app.update();
+        assert_eq!(
+            *app.world.get::<Transform>(root).unwrap(),
+            Transform::from_xyz(0.0, 3.0, 0.0)
+        );
+        assert_eq!(*app.world.get::<Transform>(child).unwrap(), child_transform);
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::FileId;
+
+/// How many preview states we keep around for undo
+const MAX_HISTORY: usize = 50;
+
+/// Everything needed to respawn a previewed object.
+/// We hold on to strong handles so undoing doesn't have to reload anything.
+#[derive(Clone)]
+pub struct PreviewDescriptor {
+    pub file: FileId,
+    pub mesh: usize,
+    /// The object's name and mesh datablock, what the spawned preview is known by
+    pub object: String,
+    pub datablock: String,
+    pub mesh_handle: Handle<Mesh>,
+    pub material_handle: Handle<StandardMaterial>,
+    pub transform: Transform,
+}
+
+/// The spawned objects and camera at one point in time
+#[derive(Clone)]
+pub struct PreviewState {
+    pub objects: Vec<PreviewDescriptor>,
+    pub camera: Transform,
+}
+
+/// A bounded undo/redo stack of preview states
+#[derive(Default, Resource)]
+pub struct SpawnHistory {
+    states: Vec<PreviewState>,
+    /// Index of the current state in `states`
+    cursor: usize,
+}
+
+impl SpawnHistory {
+    /// Records a new state, dropping anything that could have been redone
+    pub fn push(&mut self, state: PreviewState) {
+        if !self.states.is_empty() {
+            self.states.truncate(self.cursor + 1);
+        }
+        self.states.push(state);
+
+        if self.states.len() > MAX_HISTORY {
+            self.states.remove(0);
+        }
+        self.cursor = self.states.len() - 1;
+    }
+
+    /// Steps back and returns the state to restore
+    pub fn undo(&mut self) -> Option<&PreviewState> {
+        if self.cursor == 0 {
+            return None;
+        }
+        self.cursor -= 1;
+        self.states.get(self.cursor)
+    }
+
+    /// Steps forward and returns the state to restore
+    pub fn redo(&mut self) -> Option<&PreviewState> {
+        if self.cursor + 1 >= self.states.len() {
+            return None;
+        }
+        self.cursor += 1;
+        self.states.get(self.cursor)
+    }
+
+    /// Drops every state that references a removed file
+    pub fn remove_file(&mut self, file_id: FileId) {
+        let current = self.cursor;
+        let mut new_cursor = 0;
+        let mut kept = Vec::new();
+
+        for (index, state) in self.states.drain(..).enumerate() {
+            if state.objects.iter().any(|object| object.file == file_id) {
+                continue;
+            }
+            if index <= current {
+                new_cursor = kept.len();
+            }
+            kept.push(state);
+        }
+
+        self.states = kept;
+        self.cursor = new_cursor;
+    }
+}
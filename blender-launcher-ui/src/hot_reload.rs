@@ -0,0 +1,136 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+
+use crate::{
+    analysis::MeshAnalysisCache,
+    decimate::FullMesh,
+    i18n::t,
+    parse_queue::ParseQueue,
+    toasts::{Severity, Toasts},
+    AppState, BlenderPreviewObject, FileId, ReparseFile,
+};
+
+/// A preview whose mesh was reloaded, waiting on its file to be parsed again
+/// to find out whether its object is still there
+struct ReloadedPreview {
+    entity: Entity,
+    file: FileId,
+    object: String,
+    /// Set once the reparse shows up in the queue, so we don't resolve before it ran
+    reparsing: bool,
+}
+
+#[derive(Default, Resource)]
+struct ReloadedPreviews(Vec<ReloadedPreview>);
+
+/// Follows bevy_blender's reloads when the asset server watches for changes,
+/// which `Settings::hot_reload` turns on at startup
+pub struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReloadedPreviews>()
+            .add_system(refresh_modified_previews.run_if(on_event::<AssetEvent<Mesh>>()))
+            .add_system(
+                resolve_reloaded_previews
+                    .run_if(|reloaded: Res<ReloadedPreviews>| !reloaded.0.is_empty()),
+            );
+    }
+}
+
+/// Drops what was derived from a reloaded mesh, and reparses its file for the stats
+fn refresh_modified_previews(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<Mesh>>,
+    app_state: Res<AppState>,
+    mut analysis_cache: ResMut<MeshAnalysisCache>,
+    mut reloaded: ResMut<ReloadedPreviews>,
+    mut reparse: EventWriter<ReparseFile>,
+    previews: Query<(
+        Entity,
+        &BlenderPreviewObject,
+        &Handle<Mesh>,
+        Option<&FullMesh>,
+    )>,
+) {
+    for event in asset_events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+        analysis_cache.invalidate(handle);
+
+        for (entity, preview, mesh, full_mesh) in previews.iter() {
+            // Decimated previews are refreshed through their original mesh
+            let mesh = full_mesh.map_or(mesh, |full_mesh| &full_mesh.handle);
+            if mesh != handle || reloaded.0.iter().any(|waiting| waiting.entity == entity) {
+                continue;
+            }
+            // Bevy only computes bounds for entities without them
+            commands.entity(entity).remove::<Aabb>();
+
+            let Some(object) = app_state
+                .file(preview.file)
+                .and_then(|file| file.meshes.get(preview.mesh))
+            else {
+                continue;
+            };
+            if !reloaded
+                .0
+                .iter()
+                .any(|waiting| waiting.file == preview.file)
+            {
+                reparse.send(ReparseFile(preview.file));
+            }
+            reloaded.0.push(ReloadedPreview {
+                entity,
+                file: preview.file,
+                object: object.name.clone(),
+                reparsing: false,
+            });
+        }
+    }
+}
+
+/// Once a reloaded preview's file is parsed again, points the preview at its object's new
+/// place in the list, or despawns it when the object was deleted from the file
+fn resolve_reloaded_previews(
+    mut commands: Commands,
+    app_state: Res<AppState>,
+    parse_queue: Res<ParseQueue>,
+    mut toasts: ResMut<Toasts>,
+    mut reloaded: ResMut<ReloadedPreviews>,
+    mut previews: Query<&mut BlenderPreviewObject>,
+) {
+    reloaded.0.retain_mut(|waiting| {
+        if parse_queue.status(waiting.file).is_some() {
+            waiting.reparsing = true;
+            return true;
+        }
+        if !waiting.reparsing {
+            return true;
+        }
+
+        let Ok(mut preview) = previews.get_mut(waiting.entity) else {
+            return false;
+        };
+        let position = app_state.file(waiting.file).and_then(|file| {
+            file.meshes
+                .iter()
+                .position(|object| object.name == waiting.object)
+        });
+        match position {
+            Some(mesh) => {
+                if preview.mesh != mesh {
+                    preview.mesh = mesh;
+                }
+            }
+            None => {
+                commands.entity(waiting.entity).despawn_recursive();
+                toasts.push(
+                    Severity::Info,
+                    t!("hot_reload.removed", name = waiting.object),
+                );
+            }
+        }
+        false
+    });
+}
@@ -0,0 +1,162 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        OnceLock,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Languages the UI is translated to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// The language's name in itself, so it can be found without reading the current one
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    /// The `key = value` file with every string in this language
+    fn source(&self) -> &'static str {
+        match self {
+            Language::English => include_str!("../locales/en.lang"),
+            Language::German => include_str!("../locales/de.lang"),
+        }
+    }
+}
+
+// The UI reads these from every panel, so they're global rather than a resource
+// that'd have to be threaded through each function that draws text
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+static SHOW_KEYS: AtomicBool = AtomicBool::new(false);
+
+/// Switches the language used by `t!`. With `show_keys` every string shows its key instead,
+/// to check what's translated and find the key for a string.
+pub fn set_language(language: Language, show_keys: bool) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+    SHOW_KEYS.store(show_keys, Ordering::Relaxed);
+}
+
+fn current_language() -> Language {
+    Language::ALL
+        .get(LANGUAGE.load(Ordering::Relaxed) as usize)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Every language's strings, parsed on first use
+fn catalogs() -> &'static [HashMap<&'static str, &'static str>] {
+    static CATALOGS: OnceLock<Vec<HashMap<&'static str, &'static str>>> = OnceLock::new();
+    CATALOGS.get_or_init(|| {
+        Language::ALL
+            .iter()
+            .map(|language| parse_catalog(language.source()))
+            .collect()
+    })
+}
+
+/// One `key = value` per line, `#` starts a comment
+fn parse_catalog(source: &'static str) -> HashMap<&'static str, &'static str> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim(), value.trim()))
+        })
+        .collect()
+}
+
+/// The string for `key` in `language`, falling back to English and then to the key itself
+fn lookup(language: Language, key: &'static str) -> &'static str {
+    let catalogs = catalogs();
+    catalogs[language as usize]
+        .get(key)
+        .or_else(|| catalogs[Language::English as usize].get(key))
+        .copied()
+        .unwrap_or(key)
+}
+
+/// The string for `key` in the current language
+pub fn tr(key: &'static str) -> &'static str {
+    if SHOW_KEYS.load(Ordering::Relaxed) {
+        return key;
+    }
+    lookup(current_language(), key)
+}
+
+/// Like `tr`, filling in `{name}` placeholders so translations can order them however they like
+pub fn tr_args(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    if SHOW_KEYS.load(Ordering::Relaxed) {
+        return key.to_string();
+    }
+    fill(lookup(current_language(), key), args)
+}
+
+fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut filled = template.to_string();
+    for (name, value) in args {
+        filled = filled.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    filled
+}
+
+/// Translates a key, e.g. `t!("files.clear_all")` or `t!("files.found", count = 3)`
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::tr_args(
+            $key,
+            &[$((stringify!($name), &$value as &dyn std::fmt::Display)),+],
+        )
+    };
+}
+pub(crate) use t;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translations_only_use_english_keys() {
+        let catalogs = catalogs();
+        for language in Language::ALL {
+            for key in catalogs[language as usize].keys() {
+                assert!(
+                    catalogs[Language::English as usize].contains_key(key),
+                    "{:?} has {} but English doesn't",
+                    language,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn missing_keys_fall_back_to_english_then_the_key() {
+        let english = lookup(Language::English, "panel.inspector");
+        assert_ne!(english, "panel.inspector");
+        assert_eq!(lookup(Language::German, "no.such.key"), "no.such.key");
+    }
+
+    #[test]
+    fn placeholders_can_be_reordered() {
+        let filled = fill("{path}: {count}", &[("count", &3), ("path", &"a.blend")]);
+        assert_eq!(filled, "a.blend: 3");
+    }
+}
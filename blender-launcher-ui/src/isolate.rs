@@ -0,0 +1,243 @@
+use std::path::Path;
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use bevy_egui::egui;
+use futures_lite::future;
+
+use crate::{
+    asset_cache::AssetCache,
+    i18n::t,
+    mesh_builder::MeshData,
+    metadata::{id_name, open_blend},
+    preview_info::PreviewObjectInfo,
+    AppState, BlendObject, BlenderPreviewObject, FileId,
+};
+
+/// Shows only the faces of one material slot of a preview, or everything again with `None`
+pub struct IsolateSlot {
+    pub file: FileId,
+    pub mesh: usize,
+    pub slot: Option<usize>,
+}
+
+/// A preview hidden behind a copy that only has one slot's faces
+#[derive(Component)]
+pub struct IsolatedSlot {
+    pub slot: usize,
+    copy: Entity,
+}
+
+/// The copy standing in for an isolated preview. It isn't a `BlenderPreviewObject`,
+/// so it stays out of the analysis, export, and everything else that walks the previews.
+#[derive(Component)]
+struct IsolatedCopy {
+    source: Entity,
+}
+
+/// Meshes being rebuilt on a background thread, with the previews waiting on them
+#[derive(Default, Resource)]
+struct PendingIsolations {
+    tasks: Vec<(Vec<Entity>, usize, Task<Option<Mesh>>)>,
+}
+
+pub struct SlotIsolationPlugin;
+
+impl Plugin for SlotIsolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<IsolateSlot>()
+            .init_resource::<PendingIsolations>()
+            .add_system(start_isolations.run_if(on_event::<IsolateSlot>()))
+            .add_system(
+                finish_isolations
+                    .run_if(|pending: Res<PendingIsolations>| !pending.tasks.is_empty()),
+            )
+            .add_system(despawn_orphaned_copies);
+    }
+}
+
+fn start_isolations(
+    mut commands: Commands,
+    mut events: EventReader<IsolateSlot>,
+    app_state: Res<AppState>,
+    mut pending: ResMut<PendingIsolations>,
+    mut previews: Query<
+        (
+            Entity,
+            &PreviewObjectInfo,
+            &mut Visibility,
+            Option<&IsolatedSlot>,
+        ),
+        With<BlenderPreviewObject>,
+    >,
+) {
+    let thread_pool = AsyncComputeTaskPool::get();
+    for event in events.iter() {
+        let Some(event_file) = app_state.file(event.file) else {
+            continue;
+        };
+        let mut targets = Vec::new();
+        for (entity, info, mut visibility, isolated) in previews.iter_mut() {
+            if !info.is_object(event_file, event.mesh) {
+                continue;
+            }
+            if let Some(isolated) = isolated {
+                commands.entity(isolated.copy).despawn_recursive();
+                commands.entity(entity).remove::<IsolatedSlot>();
+                *visibility = Visibility::Inherited;
+            }
+            targets.push(entity);
+        }
+        // A newer request for the same preview wins over one still being built
+        pending
+            .tasks
+            .retain(|(entities, ..)| !entities.iter().any(|entity| targets.contains(entity)));
+
+        let (Some(slot), false) = (event.slot, targets.is_empty()) else {
+            continue;
+        };
+        let Some((file, object)) = app_state
+            .file(event.file)
+            .and_then(|file| Some((file, file.meshes.get(event.mesh)?)))
+        else {
+            continue;
+        };
+        let path = file.path.clone();
+        let name = object.name.clone();
+        let task = thread_pool.spawn(async move {
+            let blend = match open_blend(Path::new(&path)) {
+                Ok(blend) => blend,
+                Err(error) => {
+                    warn!(path = %path, %error, "couldn't reopen the file to isolate a material slot");
+                    return None;
+                }
+            };
+            let obj = blend
+                .instances_with_code(*b"OB")
+                .find(|obj| id_name(obj) == name)?;
+            if !obj.is_valid("data") {
+                return None;
+            }
+            match MeshData::from_mesh(&obj.get("data")) {
+                Ok(data) => Some(data.build(|face| face.material == slot)),
+                Err(error) => {
+                    warn!(path, object = name, error = error.describe(), "couldn't rebuild mesh");
+                    None
+                }
+            }
+        });
+        pending.tasks.push((targets, slot, task));
+    }
+}
+
+/// Swaps each waiting preview for a copy with the rebuilt mesh, in the slot's material
+#[allow(clippy::too_many_arguments)]
+fn finish_isolations(
+    mut commands: Commands,
+    mut pending: ResMut<PendingIsolations>,
+    app_state: Res<AppState>,
+    asset_server: Res<AssetServer>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut previews: Query<(&BlenderPreviewObject, &GlobalTransform, &mut Visibility)>,
+) {
+    let mut finished = Vec::new();
+    pending.tasks.retain_mut(|(entities, slot, task)| {
+        match future::block_on(future::poll_once(task)) {
+            Some(result) => {
+                finished.push((std::mem::take(entities), *slot, result));
+                false
+            }
+            None => true,
+        }
+    });
+
+    for (entities, slot, result) in finished {
+        let Some(mesh) = result else {
+            continue;
+        };
+        let mesh = meshes.add(mesh);
+        for entity in entities {
+            // The preview might have been cleared while the mesh was built
+            let Ok((preview, transform, mut visibility)) = previews.get_mut(entity) else {
+                continue;
+            };
+            let Some((file, object)) = app_state
+                .file(preview.file)
+                .and_then(|file| Some((file, file.meshes.get(preview.mesh)?)))
+            else {
+                continue;
+            };
+            let material = match object.materials.get(slot) {
+                Some(name) if file.materials.contains(name) => {
+                    asset_cache.material(&asset_server, &file.path, name)
+                }
+                _ => asset_cache.color_material(&mut materials, object.color),
+            };
+
+            let copy = commands
+                .spawn((
+                    IsolatedCopy { source: entity },
+                    PbrBundle {
+                        mesh: mesh.clone(),
+                        material,
+                        transform: transform.compute_transform(),
+                        ..Default::default()
+                    },
+                ))
+                .id();
+            commands.entity(entity).insert(IsolatedSlot { slot, copy });
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+/// Copies go with their preview, e.g. when it's cleared or respawned
+fn despawn_orphaned_copies(
+    mut commands: Commands,
+    copies: Query<(Entity, &IsolatedCopy)>,
+    previews: Query<(), With<IsolatedSlot>>,
+) {
+    for (entity, copy) in copies.iter() {
+        if !previews.contains(copy.source) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// "Isolate slot N" buttons for an object with more than one material slot,
+/// and "Show all" while one is isolated
+pub fn isolate_slot_ui(
+    ui: &mut egui::Ui,
+    file: FileId,
+    mesh: usize,
+    object: &BlendObject,
+    isolated: Option<usize>,
+    events: &mut EventWriter<IsolateSlot>,
+) {
+    if object.materials.len() < 2 {
+        return;
+    }
+    ui.horizontal_wrapped(|ui| {
+        for (slot, material) in object.materials.iter().enumerate() {
+            let label = t!("isolate.slot", slot = slot + 1, material = material);
+            if ui.selectable_label(isolated == Some(slot), label).clicked() {
+                events.send(IsolateSlot {
+                    file,
+                    mesh,
+                    slot: Some(slot),
+                });
+            }
+        }
+        if isolated.is_some() && ui.button(t!("isolate.show_all")).clicked() {
+            events.send(IsolateSlot {
+                file,
+                mesh,
+                slot: None,
+            });
+        }
+    });
+}
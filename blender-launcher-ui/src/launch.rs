@@ -0,0 +1,185 @@
+use std::{path::PathBuf, process::Command};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    builds::{BlenderInstall, BlenderInstalls},
+    commands::{Action, ActionEvent},
+    i18n::t,
+    settings::Settings,
+    toasts::{Severity, Toasts},
+    AppState, File,
+};
+
+/// Which Blender opens a file that doesn't have its own override
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OpenWithRule {
+    /// The newest install of the version the file was saved with, else the default one.
+    /// Opening an LTS project in a newer Blender re-saves it in a format the LTS can't read.
+    #[default]
+    MatchFileVersion,
+    /// Always the default install picked in the builds window
+    Default,
+}
+
+impl OpenWithRule {
+    pub const ALL: [OpenWithRule; 2] = [OpenWithRule::MatchFileVersion, OpenWithRule::Default];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OpenWithRule::MatchFileVersion => t!("launch.rule.match_file"),
+            OpenWithRule::Default => t!("launch.rule.default"),
+        }
+    }
+}
+
+/// The Blender a file opens with: its override, then whatever the rule in the settings picks
+pub fn effective_install<'a>(
+    file: &File,
+    installs: &'a BlenderInstalls,
+    settings: &Settings,
+) -> Option<&'a BlenderInstall> {
+    // An override pointing at a build that was since removed falls through to the rule
+    let override_install = file.blender_override.as_ref().and_then(|executable| {
+        installs
+            .iter()
+            .find(|install| install.executable == *executable)
+    });
+
+    let matching_install = match (settings.open_with_rule, file.version) {
+        (OpenWithRule::MatchFileVersion, Some(version)) => {
+            // Installs are sorted newest first
+            installs.iter().find(|install| {
+                install.version.is_some_and(|installed| {
+                    (installed.major, installed.minor) == (version.major, version.minor)
+                })
+            })
+        }
+        _ => None,
+    };
+
+    override_install
+        .or(matching_install)
+        .or_else(|| installs.preferred(settings))
+}
+
+pub struct LaunchPlugin;
+
+impl Plugin for LaunchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(open_in_blender.run_if(on_event::<ActionEvent>()));
+    }
+}
+
+/// Opens the selected file in its effective Blender install
+fn open_in_blender(
+    mut action_events: EventReader<ActionEvent>,
+    app_state: Res<AppState>,
+    installs: Res<BlenderInstalls>,
+    settings: Res<Settings>,
+    mut toasts: ResMut<Toasts>,
+) {
+    for ActionEvent(action) in action_events.iter() {
+        if *action != Action::OpenInBlender {
+            continue;
+        }
+        let Some(file) = app_state.selected_file() else {
+            continue;
+        };
+        let Some(install) = effective_install(file, &installs, &settings) else {
+            toasts.push(Severity::Warning, t!("launch.no_install"));
+            continue;
+        };
+
+        info!(path = %file.path, executable = %install.executable.display(), "opening in Blender");
+        if let Err(error) = Command::new(&install.executable).arg(&file.path).spawn() {
+            error!(path = %file.path, %error, "couldn't start Blender");
+            toasts.push(
+                Severity::Error,
+                t!("launch.failed", name = install.label(), error = error),
+            );
+        }
+    }
+}
+
+/// What the user did with the Open in Blender controls
+pub enum OpenInBlenderAction {
+    Open,
+    /// Pins the file to an install, or back to the rule with `None`
+    SetOverride(Option<PathBuf>),
+}
+
+/// The Open in Blender button, with a dropdown to pin the file to an install.
+/// The tooltip says which Blender it'll be, so there are no surprises.
+pub fn open_in_blender_ui(
+    ui: &mut egui::Ui,
+    file: &File,
+    installs: &BlenderInstalls,
+    settings: &Settings,
+) -> Option<OpenInBlenderAction> {
+    let mut action = None;
+    let install = effective_install(file, installs, settings);
+
+    let mut tooltip = match install {
+        Some(install) => t!(
+            "launch.tooltip",
+            name = install.label(),
+            path = install.executable.display()
+        ),
+        None => t!("launch.no_install").to_string(),
+    };
+    if let Some(version) = file.version {
+        tooltip.push('\n');
+        tooltip.push_str(&t!(
+            "launch.saved_with",
+            version = format!("{}.{}", version.major, version.minor)
+        ));
+    }
+
+    ui.horizontal(|ui| {
+        let response = ui
+            .add_enabled(install.is_some(), egui::Button::new(t!("launch.open")))
+            .on_hover_text(tooltip.as_str())
+            .on_disabled_hover_text(tooltip.as_str());
+        if response.clicked() {
+            action = Some(OpenInBlenderAction::Open);
+        }
+
+        let selected_text = match &file.blender_override {
+            None => t!("launch.automatic").to_string(),
+            Some(executable) => installs
+                .iter()
+                .find(|install| install.executable == *executable)
+                .map_or_else(
+                    || t!("launch.missing_install").to_string(),
+                    |install| install.label(),
+                ),
+        };
+        egui::ComboBox::from_id_source("open_with")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(file.blender_override.is_none(), t!("launch.automatic"))
+                    .clicked()
+                {
+                    action = Some(OpenInBlenderAction::SetOverride(None));
+                }
+                for install in installs.iter() {
+                    let selected = file.blender_override.as_ref() == Some(&install.executable);
+                    if ui
+                        .selectable_label(selected, install.label())
+                        .on_hover_text(install.executable.display().to_string())
+                        .clicked()
+                    {
+                        action = Some(OpenInBlenderAction::SetOverride(Some(
+                            install.executable.clone(),
+                        )));
+                    }
+                }
+            });
+    });
+
+    action
+}
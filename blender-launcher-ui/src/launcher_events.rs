@@ -0,0 +1,235 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{AppState, FileId, PreviewObjectInfo};
+
+/// A file joined the list, from a dialog, a drop, the session or [`AppState::add_file`].
+/// Its metadata is parsed right after, [`MetadataLoaded`] says when it's in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileAdded {
+    pub file: FileId,
+    pub path: String,
+}
+
+/// A file left the list, on its own or with the rest of a workspace
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileRemoved {
+    pub file: FileId,
+    /// Where it was, the file can't be looked up anymore
+    pub path: String,
+}
+
+/// A file's metadata was parsed or read from the cache, the first time or after a reload
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MetadataLoaded(pub FileId);
+
+/// A preview of an object was spawned, on its own or as part of a scene, grid or comparison
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObjectSpawned {
+    pub file: FileId,
+    /// The object's name, see [`PreviewObjectInfo`] on the entity for more
+    pub object: String,
+    pub entity: Entity,
+}
+
+/// A preview from an [`ObjectSpawned`] was despawned. The entity is already gone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObjectDespawned {
+    pub file: FileId,
+    pub object: String,
+    pub entity: Entity,
+}
+
+/// The selected file or object changed, to what [`AppState`] has selected now
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SelectionChanged {
+    pub file: Option<FileId>,
+    /// The (file, object) last spawned or picked, as in [`AppState::selected_object`]
+    pub object: Option<(FileId, usize)>,
+}
+
+pub struct LauncherEventsPlugin;
+
+impl Plugin for LauncherEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FileAdded>()
+            .add_event::<FileRemoved>()
+            .add_event::<MetadataLoaded>()
+            .add_event::<ObjectSpawned>()
+            .add_event::<ObjectDespawned>()
+            .add_event::<SelectionChanged>()
+            .add_system(emit_file_events.run_if(resource_changed::<AppState>()))
+            // After the commands from the update have spawned and despawned the previews
+            .add_system(emit_preview_events.in_base_set(CoreSet::PostUpdate));
+    }
+}
+
+/// Compares the file list and selection with what they were the last time around. Files
+/// are added and selections made from all over, this sees them all the same way.
+fn emit_file_events(
+    app_state: Res<AppState>,
+    mut added: EventWriter<FileAdded>,
+    mut removed: EventWriter<FileRemoved>,
+    mut selections: EventWriter<SelectionChanged>,
+    mut listed: Local<Vec<(FileId, String)>>,
+    mut selection: Local<SelectionChanged>,
+) {
+    let unchanged = listed.len() == app_state.files.len()
+        && listed
+            .iter()
+            .zip(app_state.files.iter())
+            .all(|((id, _), file)| *id == file.id);
+    if !unchanged {
+        removed.send_batch(
+            listed
+                .iter()
+                .filter(|(id, _)| app_state.file(*id).is_none())
+                .map(|(file, path)| FileRemoved {
+                    file: *file,
+                    path: path.clone(),
+                }),
+        );
+        added.send_batch(
+            app_state
+                .files
+                .iter()
+                .filter(|file| !listed.iter().any(|(id, _)| *id == file.id))
+                .map(|file| FileAdded {
+                    file: file.id,
+                    path: file.path.clone(),
+                }),
+        );
+        *listed = app_state
+            .files
+            .iter()
+            .map(|file| (file.id, file.path.clone()))
+            .collect();
+    }
+
+    let current = SelectionChanged {
+        file: app_state.selected_file,
+        object: app_state.selected_object,
+    };
+    if *selection != current {
+        selections.send(current.clone());
+        *selection = current;
+    }
+}
+
+/// Reports the previews that came and went. Remembers what each one was spawned from, which
+/// can't be read from an entity that's been despawned.
+fn emit_preview_events(
+    spawned: Query<(Entity, &PreviewObjectInfo), Added<PreviewObjectInfo>>,
+    mut despawned: RemovedComponents<PreviewObjectInfo>,
+    mut spawned_events: EventWriter<ObjectSpawned>,
+    mut despawned_events: EventWriter<ObjectDespawned>,
+    mut previews: Local<HashMap<Entity, (FileId, String)>>,
+) {
+    for entity in despawned.iter() {
+        if let Some((file, object)) = previews.remove(&entity) {
+            despawned_events.send(ObjectDespawned {
+                file,
+                object,
+                entity,
+            });
+        }
+    }
+    for (entity, info) in spawned.iter() {
+        previews.insert(entity, (info.file, info.object.clone()));
+        spawned_events.send(ObjectSpawned {
+            file: info.file,
+            object: info.object.clone(),
+            entity,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events<E: Clone + Send + Sync + 'static>(app: &App) -> Vec<E> {
+        let events = app.world.resource::<Events<E>>();
+        events.get_reader().iter(events).cloned().collect()
+    }
+
+    #[test]
+    fn reports_files_and_selection_as_they_change() {
+        let mut app = App::new();
+        app.init_resource::<AppState>()
+            .add_plugin(LauncherEventsPlugin);
+        let mut app_state = app.world.resource_mut::<AppState>();
+        let first = app_state.add_file("first.blend".to_string());
+        let second = app_state.add_file("second.blend".to_string());
+        app_state.select_object(second, 0);
+        app.update();
+        let added: Vec<FileId> = events::<FileAdded>(&app)
+            .into_iter()
+            .map(|added| added.file)
+            .collect();
+        assert_eq!(added, [first, second]);
+        assert_eq!(
+            events::<SelectionChanged>(&app),
+            [SelectionChanged {
+                file: Some(second),
+                object: Some((second, 0)),
+            }]
+        );
+
+        // Nothing changed, nothing new to report
+        app.world.resource_mut::<Events<FileAdded>>().clear();
+        app.world.resource_mut::<Events<SelectionChanged>>().clear();
+        app.world.resource_mut::<AppState>().set_changed();
+        app.update();
+        assert!(events::<FileAdded>(&app).is_empty());
+        assert!(events::<SelectionChanged>(&app).is_empty());
+
+        app.world
+            .resource_mut::<AppState>()
+            .files
+            .retain(|file| file.id != second);
+        app.update();
+        assert_eq!(
+            events::<FileRemoved>(&app),
+            [FileRemoved {
+                file: second,
+                path: "second.blend".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_previews_as_they_come_and_go() {
+        let mut app = App::new();
+        app.init_resource::<AppState>()
+            .add_plugin(LauncherEventsPlugin);
+        let entity = app
+            .world
+            .spawn(PreviewObjectInfo::new(
+                FileId(0),
+                "Cube",
+                "Cube",
+                Transform::default(),
+            ))
+            .id();
+        app.update();
+        assert_eq!(
+            events::<ObjectSpawned>(&app),
+            [ObjectSpawned {
+                file: FileId(0),
+                object: "Cube".to_string(),
+                entity,
+            }]
+        );
+
+        app.world.despawn(entity);
+        app.update();
+        assert_eq!(
+            events::<ObjectDespawned>(&app),
+            [ObjectDespawned {
+                file: FileId(0),
+                object: "Cube".to_string(),
+                entity,
+            }]
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use std::{collections::HashSet, fs, io, path::Path};
+
+use bevy::prelude::*;
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_cache::AssetCache,
+    commands::{Action, ActionEvent},
+    file_status::{FileOp, FileStatus},
+    history::{PreviewDescriptor, PreviewState, SpawnHistory},
+    i18n::t,
+    preview_info::PreviewObjectInfo,
+    spawn_preview,
+    toasts::{Severity, ToastAction, Toasts},
+    AppState, BlenderPreviewObject, OriginalCameraTransform,
+};
+
+/// Bumped when a field changes meaning. Fields a version doesn't know are ignored,
+/// so files from newer versions still open with what this one understands.
+const FORMAT_VERSION: u32 = 1;
+
+/// Where each previewed object is, in a file other tools can read
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Layout {
+    pub version: u32,
+    pub objects: Vec<LayoutObject>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            objects: Vec::new(),
+        }
+    }
+}
+
+/// One previewed object, with its transform in world space on Bevy's Y-up axes
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LayoutObject {
+    /// Path of the .blend file the object is from
+    pub file: String,
+    pub object: String,
+    pub translation: [f32; 3],
+    /// A quaternion, as x, y, z, w
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+impl LayoutObject {
+    fn new(file: &str, object: &str, transform: &Transform) -> Self {
+        Self {
+            file: file.to_string(),
+            object: object.to_string(),
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+            scale: transform.scale.to_array(),
+        }
+    }
+
+    fn transform(&self) -> Transform {
+        Transform {
+            translation: Vec3::from_array(self.translation),
+            rotation: Quat::from_array(self.rotation).normalize(),
+            scale: Vec3::from_array(self.scale),
+        }
+    }
+}
+
+impl Layout {
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Written by a newer version, so some of what's in it may be left out
+    pub fn is_newer(&self) -> bool {
+        self.version > FORMAT_VERSION
+    }
+}
+
+/// An imported layout waiting for its files to finish parsing before it's spawned
+#[derive(Default, Resource)]
+struct PendingLayout(Option<Vec<LayoutObject>>);
+
+pub struct LayoutPlugin;
+
+impl Plugin for LayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingLayout>()
+            .add_system(layout_actions.run_if(on_event::<ActionEvent>()))
+            .add_system(
+                spawn_pending_layout
+                    .after(layout_actions)
+                    .run_if(|pending: Res<PendingLayout>| pending.0.is_some()),
+            );
+    }
+}
+
+fn layout_actions(
+    mut actions: EventReader<ActionEvent>,
+    mut app_state: ResMut<AppState>,
+    mut pending: ResMut<PendingLayout>,
+    mut toasts: ResMut<Toasts>,
+    previews: Query<(&PreviewObjectInfo, &GlobalTransform)>,
+) {
+    for ActionEvent(action) in actions.iter() {
+        let result = match action {
+            Action::ExportLayout => export_dialog(&app_state, &previews, &mut toasts),
+            Action::ImportLayout => import_dialog(&mut app_state, &mut pending, &mut toasts),
+            _ => continue,
+        };
+        if let Err(error) = result {
+            error!(%error, "couldn't read or write layout");
+            toasts.push(Severity::Error, t!("layout.failed", error = error));
+        }
+    }
+}
+
+/// Asks where to save the previewed objects and writes them there
+fn export_dialog(
+    app_state: &AppState,
+    previews: &Query<(&PreviewObjectInfo, &GlobalTransform)>,
+    toasts: &mut Toasts,
+) -> io::Result<()> {
+    // Empties and the nodes standing in for skipped parents have nothing to respawn
+    let objects: Vec<LayoutObject> = previews
+        .iter()
+        .filter_map(|(info, transform)| {
+            let file = app_state.file(info.file)?;
+            let object = &file.meshes[info.index(app_state)?];
+            (object.stats.is_some() || object.outline.is_some()).then(|| {
+                LayoutObject::new(&file.path, &info.object, &transform.compute_transform())
+            })
+        })
+        .collect();
+    if objects.is_empty() {
+        toasts.push(Severity::Warning, t!("layout.nothing_to_export"));
+        return Ok(());
+    }
+    let Some(path) = FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .set_file_name("layout.json")
+        .save_file()
+    else {
+        return Ok(());
+    };
+
+    let count = objects.len();
+    Layout {
+        version: FORMAT_VERSION,
+        objects,
+    }
+    .write(&path)?;
+    toasts.push_with_action(
+        Severity::Success,
+        t!("layout.exported", count = count),
+        ToastAction::RevealFile(path),
+    );
+    Ok(())
+}
+
+/// Reads a layout and adds the files it needs to the list, reporting the ones that don't exist.
+/// It's spawned once they've been parsed.
+fn import_dialog(
+    app_state: &mut AppState,
+    pending: &mut PendingLayout,
+    toasts: &mut Toasts,
+) -> io::Result<()> {
+    let Some(path) = FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+        return Ok(());
+    };
+    let layout = Layout::read(&path)?;
+    if layout.is_newer() {
+        toasts.push(Severity::Warning, t!("layout.import_newer"));
+    }
+
+    let mut missing = Vec::new();
+    let mut seen = HashSet::new();
+    for object in layout.objects.iter() {
+        if !seen.insert(object.file.as_str()) {
+            continue;
+        }
+        if app_state.files.iter().any(|file| file.path == object.file) {
+            continue;
+        }
+        if !Path::new(&object.file).is_file() {
+            warn!(
+                path = object.file,
+                "couldn't find file from imported layout"
+            );
+            missing.push(object.file.clone());
+            continue;
+        }
+        // The parse queue picks up new files by itself
+        app_state.add_file(object.file.clone());
+    }
+    if !missing.is_empty() {
+        toasts.push(
+            Severity::Warning,
+            t!(
+                "layout.missing_files",
+                count = missing.len(),
+                files = missing.join(", ")
+            ),
+        );
+    }
+    pending.0 = Some(layout.objects);
+    Ok(())
+}
+
+/// Replaces the preview with the pending layout once its files have been parsed
+#[allow(clippy::too_many_arguments)]
+fn spawn_pending_layout(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    app_state: Res<AppState>,
+    mut pending: ResMut<PendingLayout>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut history: ResMut<SpawnHistory>,
+    mut toasts: ResMut<Toasts>,
+    original_camera_transform: Res<OriginalCameraTransform>,
+    blender_objects: Query<Entity, With<BlenderPreviewObject>>,
+) {
+    let Some(objects) = &pending.0 else {
+        return;
+    };
+    // Files added by the import are idle until the parse queue gets to them
+    let still_parsing = objects.iter().any(|object| {
+        app_state.files.iter().any(|file| {
+            file.path == object.file
+                && match file.status {
+                    FileStatus::Parsing | FileStatus::Reloading => true,
+                    FileStatus::Idle => !file.parsed,
+                    _ => false,
+                }
+        })
+    });
+    if still_parsing {
+        return;
+    }
+    let Some(objects) = pending.0.take() else {
+        return;
+    };
+
+    let mut descriptors = Vec::new();
+    let mut missing = Vec::new();
+    for object in objects.iter() {
+        let found = app_state
+            .files
+            .iter()
+            .find(|file| file.path == object.file)
+            .filter(|file| file.status.blocked(FileOp::Spawn).is_none())
+            .and_then(|file| {
+                let (index, mesh) = file
+                    .meshes
+                    .iter()
+                    .enumerate()
+                    .find(|(_, mesh)| mesh.name == object.object)?;
+                Some((file, index, mesh))
+            });
+        let Some((file, index, mesh)) = found else {
+            missing.push(object.object.as_str());
+            continue;
+        };
+        descriptors.push(PreviewDescriptor {
+            file: file.id,
+            mesh: index,
+            object: mesh.name.clone(),
+            datablock: mesh.mesh_datablock().to_string(),
+            mesh_handle: asset_cache.mesh(&asset_server, &file.path, mesh),
+            material_handle: asset_cache.object_material(&asset_server, &mut materials, file, mesh),
+            transform: object.transform(),
+        });
+    }
+
+    if !missing.is_empty() {
+        warn!(
+            count = missing.len(),
+            "objects from the layout are gone from their files"
+        );
+        toasts.push(
+            Severity::Warning,
+            t!(
+                "layout.missing_objects",
+                count = missing.len(),
+                objects = missing.join(", ")
+            ),
+        );
+    }
+    if descriptors.is_empty() {
+        return;
+    }
+
+    for blender_entity in blender_objects.iter() {
+        commands.entity(blender_entity).despawn_recursive();
+    }
+    for descriptor in descriptors.iter() {
+        spawn_preview(&mut commands, descriptor);
+    }
+    toasts.push(
+        Severity::Success,
+        t!("layout.imported", count = descriptors.len()),
+    );
+    history.push(PreviewState {
+        objects: descriptors,
+        camera: **original_camera_transform,
+    });
+}
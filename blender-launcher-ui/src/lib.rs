@@ -0,0 +1,2975 @@
+//! The Blender file launcher as a Bevy plugin, for running on its own or inside another app.
+//!
+//! [`BlenderLauncherPlugin`] brings the file list, the inspector and the preview. It goes after
+//! `DefaultPlugins`, and host apps pick its panels and whether it spawns and drives a camera:
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use blender_launcher_ui::{BlenderLauncherPlugin, LauncherPanels};
+//!
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugin(BlenderLauncherPlugin {
+//!         panels: LauncherPanels {
+//!             status_bar: false,
+//!             ..Default::default()
+//!         },
+//!         own_camera: false,
+//!     })
+//!     .run();
+//! ```
+//!
+//! What a host app can read and send:
+//! - [`AppState`], the resource with the file list and the selection. Files added with
+//!   [`AppState::add_file`] are parsed in the background.
+//! - [`LoadBlenderData`] and [`ReparseFile`] load a file's metadata again, [`RemoveFile`]
+//!   drops a file from the list.
+//! - [`SpawnEvent`] replaces the preview with one object of a file, [`SpawnSceneEvent`] with
+//!   the whole scene. Every preview has a [`BlenderPreviewObject`] and a
+//!   [`PreviewObjectInfo`] naming what it was spawned from.
+//! - [`ActionEvent`] runs any [`Action`] from the menus and shortcuts.
+//! - [`FileAdded`], [`FileRemoved`], [`MetadataLoaded`], [`ObjectSpawned`],
+//!   [`ObjectDespawned`] and [`SelectionChanged`] report what happened, whether the user did
+//!   it in the panels or a host app through the resources and events above.
+//! - [`OccupiedScreenSpace`], the room the docked panels take in the window.
+//! - [`Settings`], what the user set in the settings window. They're saved with the
+//!   launcher's own, so a host app shares them with the standalone launcher.
+//!
+//! The parts of the panels that work on their own are in [`panels`], for drawing into a host
+//! app's `Ui`. Spawn [`overlay_camera`] as a child of a host camera for the origin markers and
+//! the gizmo to show in its view.
+
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    render::{camera::Projection, mesh::Indices},
+    window::{FileDragAndDrop, PrimaryWindow, WindowResized},
+};
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiSettings};
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy_blender::*;
+
+mod about;
+mod accessibility;
+mod analysis;
+mod asset_cache;
+mod asset_paths;
+mod association;
+mod auto_preview;
+mod backfaces;
+mod bench;
+mod builds;
+mod camera;
+mod commands;
+mod compare;
+mod confirm;
+mod console;
+mod contact_sheet;
+mod crash;
+mod curves;
+mod debug;
+mod decimate;
+mod detach;
+mod effects;
+mod empties;
+mod export;
+mod file_actions;
+mod file_status;
+mod frame_guard;
+mod gizmo;
+mod ground;
+mod history;
+mod hot_reload;
+mod i18n;
+mod isolate;
+mod launch;
+mod launcher_events;
+mod layout;
+mod lighting;
+mod lod;
+mod logging;
+mod material_override;
+mod measure;
+mod menu_bar;
+mod mesh_builder;
+mod metadata;
+mod metadata_cache;
+mod natural_sort;
+mod object_list;
+mod object_table;
+mod object_tooltip;
+mod offscreen;
+mod origin;
+mod outliner;
+mod overlays;
+pub mod panels;
+mod parse_queue;
+mod parse_warnings;
+mod placement;
+mod preview_info;
+mod properties;
+mod quick_look;
+mod recent_spawns;
+mod render_output;
+mod render_queue;
+mod safe_mode;
+mod scenes;
+mod session;
+mod settings;
+mod shading;
+mod shared_workspace;
+mod shutdown;
+mod spawn_queue;
+pub mod standalone;
+mod status_bar;
+mod thumbnails;
+mod toasts;
+mod tray;
+mod units;
+mod viewport;
+mod workspace;
+
+use about::AboutPlugin;
+use accessibility::{status_label, Status};
+use analysis::{MeshAnalysisCache, MeshAnalysisPlugin};
+use asset_cache::{fall_back_to_display_colors, AssetCache};
+use asset_paths::{
+    asset_path_report_ui, asset_path_ui, AssetCheck, AssetPathsPlugin, ValidateAssetPaths,
+};
+use association::FileAssociationPlugin;
+use auto_preview::AutoPreviewPlugin;
+use backfaces::BackfacePlugin;
+use builds::{BlenderInstalls, BlenderVersion, BuildsPlugin};
+use camera::CameraPlugin;
+use commands::{CommandsPlugin, Keybindings};
+use compare::{
+    comparison_ui, ClearComparison, CompareEvent, ComparePlugin, CompareState, ComparisonAction,
+};
+use confirm::{ConfirmPlugin, Confirmation, DestructiveAction};
+use console::{console_ui, LogConsole};
+use contact_sheet::ContactSheetPlugin;
+use crash::CrashPlugin;
+use curves::{CurvePreviewPlugin, Outline};
+use debug::{DebugPlugin, SystemRuns};
+use decimate::{DecimationPlugin, FullMesh};
+use detach::DetachPlugin;
+use effects::CameraEffectsPlugin;
+use empties::{EmptyDisplay, EmptyMarkerPlugin};
+use file_actions::{file_context_menu_ui, file_toolbar_ui, FileActionsPlugin};
+use file_status::{FileOp, FileStatus};
+use frame_guard::FrameGuardPlugin;
+use gizmo::{GizmoPlugin, SetTranslation, TranslateGizmo};
+use ground::SnapToGroundPlugin;
+use history::{PreviewDescriptor, PreviewState, SpawnHistory};
+use hot_reload::HotReloadPlugin;
+use i18n::t;
+use isolate::{isolate_slot_ui, IsolateSlot, IsolatedSlot, SlotIsolationPlugin};
+use launch::{open_in_blender_ui, LaunchPlugin, OpenInBlenderAction};
+use launcher_events::LauncherEventsPlugin;
+use layout::LayoutPlugin;
+use lighting::{LightingPlugin, PreviewLight};
+use lod::{LodChain, LodPlugin, ObjectRow};
+use material_override::{MaterialOverride, MaterialOverridePlugin};
+use measure::MeasurePlugin;
+use menu_bar::{menu_bar_ui, open_recent, MenuBarPlugin, MenuCommand, MenuResponse, PanelToggles};
+use mesh_builder::BuiltInLoaderPlugin;
+use metadata::{BlendHeader, Bounds, IdUsers, ImageInfo, MeshStats};
+use metadata_cache::MetadataCachePlugin;
+use object_list::{chain_id, rows_ui, visible_rows};
+use object_table::{object_columns_ui, object_table_ui, ObjectListMode};
+use object_tooltip::object_tooltip_ui;
+use offscreen::OffscreenPlugin;
+use origin::OriginMarkerPlugin;
+use outliner::{breadcrumb_ui, outliner_ui, Crumb, OutlinerTargets};
+use overlays::OverlayPlugin;
+use parse_queue::{ParseQueue, ParseQueuePlugin, ParseStatus, ParseWarning};
+use parse_warnings::{parse_warnings_ui, warnings_badge_ui};
+use placement::{PlaceOnClick, Placement, PlacementPlugin};
+use preview_info::PreviewInfoPlugin;
+use properties::{properties_ui, CustomProperty};
+use quick_look::{QuickLook, QuickLookPlugin};
+use recent_spawns::{recent_spawns_ui, RecentSpawns};
+use render_output::RenderOutputPlugin;
+use render_queue::RenderQueuePlugin;
+use safe_mode::SafeModePlugin;
+use scenes::{scene_selector_ui, SceneChoice, SceneInfo};
+use session::SessionPlugin;
+use settings::{settings_ui, InstanceGrid, PanelLayout, SettingsPlugin};
+use shading::ShadingPlugin;
+use shutdown::ShutdownPlugin;
+use spawn_queue::{CancelSpawnQueue, SpawnQueue, SpawnQueuePlugin};
+use status_bar::{status_bar_ui, PreviewTotals, StatusSources};
+use thumbnails::{
+    row_thumbnail, thumbnail, thumbnails_ui, Subject, ThumbnailAction, ThumbnailsPlugin,
+    THUMBNAIL_SIZE,
+};
+use toasts::{ToastAction, ToastsPlugin};
+use tray::TrayPlugin;
+use units::{dimensions, ScaleIssues, SceneUnits};
+use workspace::{workspace_ui, WorkspaceAction, WorkspacePlugin, Workspaces};
+
+pub use commands::{Action, ActionEvent};
+pub use launcher_events::{
+    FileAdded, FileRemoved, MetadataLoaded, ObjectDespawned, ObjectSpawned, SelectionChanged,
+};
+pub use metadata::ObjectKind;
+pub use overlays::overlay_camera;
+pub use preview_info::PreviewObjectInfo;
+pub use safe_mode::SafeMode;
+pub use settings::Settings;
+pub use spawn_queue::SpawnSceneEvent;
+pub use toasts::{Severity, Toasts};
+
+/// Height of the top and bottom panels when they're collapsed to just their header
+const COLLAPSED_PANEL_HEIGHT: f32 = 24.0;
+/// Follows the name of the object Blender has active, in the list and the table
+const ACTIVE_MARKER: &str = " •";
+
+/// Space taken by the egui panels, in logical window pixels. The launcher's camera keeps the
+/// preview centered in what's left, a host camera can do the same with it.
+#[derive(Clone, Debug, Default, PartialEq, Resource)]
+pub struct OccupiedScreenSpace {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+const CAMERA_POSITION: Vec3 = Vec3::new(-2.0, 2.5, 5.0);
+const CAMERA_TARGET: Vec3 = Vec3::ZERO;
+
+/// The preview camera the launcher spawns when it owns the camera
+#[derive(Component)]
+struct LauncherCamera;
+
+#[derive(Resource, Deref, DerefMut)]
+struct OriginalCameraTransform(Transform);
+
+/// A spawned preview, and the (file, mesh) it came from. [`PreviewObjectInfo`] has the same
+/// by name, for anything that outlives a reparse.
+#[derive(Component)]
+pub struct BlenderPreviewObject {
+    file: FileId,
+    mesh: usize,
+    // Where it was spawned, before any adjustments like snapping to the ground
+    original_transform: Transform,
+}
+
+/// An object in a file's metadata
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BlendObject {
+    name: String,
+    kind: ObjectKind,
+    // Only mesh objects have stats
+    stats: Option<MeshStats>,
+    // Names of the materials in the object's slots
+    materials: Vec<String>,
+    // Modifier names, top of the stack first
+    modifiers: Vec<String>,
+    // Constraints as "Type (name)", top of the stack first
+    constraints: Vec<String>,
+    // Drivers in the object's own animation data
+    drivers: usize,
+    // Viewport display color, linear RGBA. What we preview with when there's no material.
+    color: [f32; 4],
+    // Custom properties on the object, and on its mesh data
+    properties: Vec<CustomProperty>,
+    data_properties: Vec<CustomProperty>,
+    // Scale on Blender's axes, the location is converted but this is kept as Blender shows it
+    scale: [f32; 3],
+    // Bounds of the mesh data, before the object's scale
+    bounds: Option<Bounds>,
+    // What we preview curve and text objects with, since they have no mesh to load
+    outline: Option<Outline>,
+    // How an empty is drawn, `None` for other kinds
+    empty: Option<EmptyDisplay>,
+    // Name of the mesh datablock a mesh object uses. Duplicating leaves it behind,
+    // e.g. the object "Crate.001" with the mesh "Crate".
+    mesh_data: Option<String>,
+    // Name of the object this one is parented to
+    parent: Option<String>,
+    // Transform relative to the parent, on Bevy's axes
+    transform: Transform,
+    // Object location, converted to Bevy's Y-up axes
+    location: Vec3,
+}
+
+impl BlendObject {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> ObjectKind {
+        self.kind
+    }
+
+    /// What bevy_blender knows the object's mesh as, its `#ME` label
+    fn mesh_datablock(&self) -> &str {
+        self.mesh_data.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Stable identifier for a file in the list, so events and selection
+/// keep pointing at the same file when the list is reordered or files are removed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FileId(u64);
+
+/// A file in the list, and its metadata once it's been parsed
+pub struct File {
+    id: FileId,
+    path: String,
+    meshes: Vec<BlendObject>,
+    materials: Vec<String>,
+    // Material name -> its user count in the file, which also counts users besides objects
+    material_ids: HashMap<String, IdUsers>,
+    // Material name -> its viewport display color
+    material_colors: HashMap<String, [f32; 4]>,
+    // Every mesh datablock with its user count, including ones no object uses
+    mesh_blocks: Vec<(String, IdUsers)>,
+    // Material name -> indices of the objects in `meshes` that use it
+    material_users: HashMap<String, Vec<usize>>,
+    images: Vec<ImageInfo>,
+    units: SceneUnits,
+    scenes: Vec<SceneInfo>,
+    // Index into `scenes` of the one the file was saved with open
+    active_scene: Option<usize>,
+    // What "Spawn scene" spawns, picked in the right panel
+    scene_choice: SceneChoice,
+    // The Blender version the file was saved with, read along with the metadata
+    version: Option<BlenderVersion>,
+    // Pointer size and byte order, `None` for compressed files
+    header: Option<BlendHeader>,
+    // Executable the user pinned this file to, otherwise `Settings::open_with_rule` decides
+    blender_override: Option<PathBuf>,
+    // Whether the metadata has been parsed at least once
+    parsed: bool,
+    // What the file is busy with, checked before anything else starts on it
+    status: FileStatus,
+    // Why the last parse failed, shown on the file's row
+    parse_error: Option<String>,
+    // Objects the last parse had to skip
+    parse_warnings: Vec<ParseWarning>,
+    // Object name -> what validating its asset path found, cleared on reparse
+    asset_checks: HashMap<String, AssetCheck>,
+    // Object or material name -> its thumbnail, `None` until the cached ones have been read
+    thumbnails: Option<HashMap<(Subject, String), egui::TextureHandle>>,
+    // Objects done and in total while thumbnails are being generated
+    thumbnail_progress: Option<(usize, usize)>,
+    // Objects grouped by the LOD pattern, `None` until grouped after a parse
+    lods: Option<Vec<LodChain>>,
+}
+
+/// The file list and what's selected in it
+#[derive(Default, Resource)]
+pub struct AppState {
+    selected_file: Option<FileId>,
+    // The (file, mesh) we last spawned, shown in the inspector
+    selected_object: Option<(FileId, usize)>,
+    // A (file, mesh) the left panel should scroll to on the next frame
+    scroll_to_object: Option<(FileId, usize)>,
+    // A (file, collection) picked in the outliner or a breadcrumb, in place of an object
+    selected_collection: Option<(FileId, String)>,
+    // A (file, collection) the outliner should open up to and scroll to on the next frame
+    scroll_to_collection: Option<(FileId, String)>,
+    // Material being tried on the selected object in place of its own
+    material_override: Option<String>,
+    // What the object lists are filtered by, matched against every object of a file
+    object_filter: String,
+    // The objects spawned last, for the quick list in the top panel
+    recent_spawns: RecentSpawns,
+    files: Vec<File>,
+    next_file_id: u64,
+}
+
+impl File {
+    pub fn id(&self) -> FileId {
+        self.id
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Every object in the file, in the order [`SpawnEvent`] indexes them. Empty until parsed.
+    pub fn objects(&self) -> &[BlendObject] {
+        &self.meshes
+    }
+
+    /// Whether the metadata has been parsed at least once
+    pub fn is_parsed(&self) -> bool {
+        self.parsed
+    }
+}
+
+impl AppState {
+    /// In list order
+    pub fn files(&self) -> &[File] {
+        &self.files
+    }
+
+    pub fn file(&self, id: FileId) -> Option<&File> {
+        self.files.iter().find(|file| file.id == id)
+    }
+
+    pub fn file_mut(&mut self, id: FileId) -> Option<&mut File> {
+        self.files.iter_mut().find(|file| file.id == id)
+    }
+
+    /// Moves the file into the status `op` runs in.
+    /// False if the file is gone or busy with something `op` would conflict with.
+    fn begin(&mut self, id: FileId, op: FileOp) -> bool {
+        let Some(file) = self.file_mut(id) else {
+            return false;
+        };
+        match file.status.begin(op) {
+            Ok(status) => {
+                file.status = status;
+                true
+            }
+            Err(reason) => {
+                debug!(path = %file.path, ?op, status = ?file.status, reason, "file is busy");
+                false
+            }
+        }
+    }
+
+    pub fn selected_file(&self) -> Option<&File> {
+        self.selected_file.and_then(|id| self.file(id))
+    }
+
+    /// The (file, object) last spawned or picked
+    pub fn selected_object(&self) -> Option<(FileId, usize)> {
+        self.selected_object
+    }
+
+    /// Selects a file, or nothing with `None`.
+    /// The selected object or collection only survives if it belongs to the new selection.
+    pub fn select_file(&mut self, file_id: Option<FileId>) {
+        self.selected_file = file_id;
+        if self
+            .selected_object
+            .is_some_and(|(object_file, _)| Some(object_file) != file_id)
+        {
+            self.selected_object = None;
+        }
+        if matches!(&self.selected_collection, Some((collection_file, _)) if Some(*collection_file) != file_id)
+        {
+            self.selected_collection = None;
+        }
+    }
+
+    /// Selects an object along with the file it belongs to
+    pub fn select_object(&mut self, file_id: FileId, mesh_id: usize) {
+        self.selected_file = Some(file_id);
+        self.selected_object = Some((file_id, mesh_id));
+        self.selected_collection = None;
+    }
+
+    /// Selects a collection along with the file it belongs to, in place of any object
+    pub fn select_collection(&mut self, file_id: FileId, name: String) {
+        self.selected_file = Some(file_id);
+        self.selected_object = None;
+        self.selected_collection = Some((file_id, name));
+    }
+
+    /// Adds a file to the end of the list and returns its new ID. It's parsed on the next
+    /// update, [`MetadataLoaded`] says when it's done.
+    pub fn add_file(&mut self, path: String) -> FileId {
+        let id = FileId(self.next_file_id);
+        self.next_file_id += 1;
+        self.files.push(File {
+            id,
+            path,
+            meshes: Vec::new(),
+            materials: Vec::new(),
+            material_ids: HashMap::new(),
+            material_colors: HashMap::new(),
+            mesh_blocks: Vec::new(),
+            material_users: HashMap::new(),
+            images: Vec::new(),
+            units: SceneUnits::default(),
+            scenes: Vec::new(),
+            active_scene: None,
+            scene_choice: SceneChoice::default(),
+            version: None,
+            header: None,
+            blender_override: None,
+            parsed: false,
+            status: FileStatus::Idle,
+            parse_error: None,
+            parse_warnings: Vec::new(),
+            asset_checks: HashMap::new(),
+            thumbnails: None,
+            thumbnail_progress: None,
+            lods: None,
+        });
+        id
+    }
+}
+
+/// Parses a file's metadata, or reads it from the metadata cache
+pub struct LoadBlenderData(pub FileId);
+/// Parses a file again even if the metadata cache has it
+pub struct ReparseFile(pub FileId);
+/// Spawns the object at an index of [`File::objects`], replacing the preview
+pub struct SpawnEvent(pub FileId, pub usize);
+/// Removes a file from the list, along with its previews
+pub struct RemoveFile(pub FileId);
+/// Spawns a mesh many times in a grid, replacing the preview
+struct SpawnGridEvent {
+    file: FileId,
+    mesh: usize,
+    grid: InstanceGrid,
+}
+
+/// Which of the docked panels the launcher draws
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Resource)]
+pub struct LauncherPanels {
+    /// The file list on the left
+    pub files: bool,
+    /// The inspector on the right
+    pub inspector: bool,
+    /// The menu bar and the recent spawns on top
+    pub top_bar: bool,
+    /// The status bar and the log console at the bottom
+    pub status_bar: bool,
+}
+
+impl Default for LauncherPanels {
+    fn default() -> Self {
+        Self {
+            files: true,
+            inspector: true,
+            top_bar: true,
+            status_bar: true,
+        }
+    }
+}
+
+/// Everything the launcher does, to add after `DefaultPlugins`. See the crate docs.
+pub struct BlenderLauncherPlugin {
+    pub panels: LauncherPanels,
+    /// Spawns the preview camera, orbits it on drags and keeps the preview clear of the
+    /// panels. Host apps with a camera of their own turn this off.
+    pub own_camera: bool,
+}
+
+impl Default for BlenderLauncherPlugin {
+    fn default() -> Self {
+        Self {
+            panels: LauncherPanels::default(),
+            own_camera: true,
+        }
+    }
+}
+
+impl Plugin for BlenderLauncherPlugin {
+    fn build(&self, app: &mut App) {
+        // Read while the settings, cache and session plugins are built. The standalone
+        // launcher inserts its own from the command line first.
+        app.init_resource::<SafeMode>();
+        // Host apps may already draw their own UI with egui
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugin(EguiPlugin);
+        }
+        if !app.is_plugin_added::<bevy_blender::BlenderPlugin>() {
+            app.add_plugin(bevy_blender::BlenderPlugin);
+        }
+        app.insert_resource(self.panels)
+            .insert_resource(OriginalCameraTransform(
+                Transform::from_translation(CAMERA_POSITION).looking_at(CAMERA_TARGET, Vec3::Y),
+            ))
+            .add_plugin(MeshAnalysisPlugin)
+            .add_plugin(SettingsPlugin)
+            .add_plugin(SafeModePlugin)
+            .add_plugin(CommandsPlugin)
+            .add_plugin(ToastsPlugin)
+            .add_plugin(ConfirmPlugin)
+            .add_plugin(DebugPlugin)
+            .add_plugin(ParseQueuePlugin)
+            .add_plugin(MetadataCachePlugin)
+            .add_plugin(SpawnQueuePlugin)
+            .add_plugin(PreviewInfoPlugin)
+            .add_plugin(LauncherEventsPlugin)
+            .add_plugin(AutoPreviewPlugin)
+            .add_plugin(BuiltInLoaderPlugin)
+            .add_plugin(AssetPathsPlugin)
+            .add_plugin(HotReloadPlugin)
+            .add_plugin(OffscreenPlugin)
+            .add_plugin(QuickLookPlugin)
+            .add_plugin(ThumbnailsPlugin)
+            .add_plugin(ContactSheetPlugin)
+            .add_plugin(MaterialOverridePlugin)
+            .add_plugin(LodPlugin)
+            .add_plugin(FileActionsPlugin)
+            .add_plugin(OverlayPlugin)
+            .add_plugin(OriginMarkerPlugin)
+            .add_plugin(SnapToGroundPlugin)
+            .add_plugin(ComparePlugin)
+            .add_plugin(DecimationPlugin)
+            .add_plugin(SlotIsolationPlugin)
+            .add_plugin(CurvePreviewPlugin)
+            .add_plugin(EmptyMarkerPlugin)
+            .add_plugin(BackfacePlugin)
+            .add_plugin(ShadingPlugin)
+            .add_plugin(CameraEffectsPlugin)
+            .add_plugin(LightingPlugin)
+            .add_plugin(FrameGuardPlugin)
+            .add_plugin(PlacementPlugin)
+            .add_plugin(GizmoPlugin)
+            .add_plugin(LayoutPlugin)
+            .add_plugin(MeasurePlugin)
+            .add_plugin(MenuBarPlugin)
+            .add_plugin(AboutPlugin)
+            .add_plugin(CrashPlugin)
+            .add_plugin(DetachPlugin)
+            .add_plugin(TrayPlugin)
+            .add_plugin(FileAssociationPlugin)
+            .add_plugin(BuildsPlugin)
+            .add_plugin(LaunchPlugin)
+            .add_plugin(RenderQueuePlugin)
+            .add_plugin(RenderOutputPlugin)
+            .add_plugin(ShutdownPlugin)
+            .init_resource::<AppState>()
+            .add_plugin(SessionPlugin)
+            .add_plugin(WorkspacePlugin)
+            .add_event::<LoadBlenderData>()
+            .add_event::<SpawnEvent>()
+            .add_event::<RemoveFile>()
+            .add_event::<SpawnGridEvent>()
+            .init_resource::<AssetCache>()
+            .init_resource::<SpawnHistory>()
+            .init_resource::<OccupiedScreenSpace>()
+            .init_resource::<LogConsole>()
+            .add_startup_system(setup_system)
+            // Everything but the UI only runs when there's something to do
+            .add_system(test_spawn.run_if(on_event::<SpawnEvent>()))
+            .add_system(spawn_grid.run_if(on_event::<SpawnGridEvent>()))
+            .add_system(
+                fall_back_to_display_colors
+                    .run_if(|asset_cache: Res<AssetCache>| asset_cache.is_loading_materials()),
+            )
+            .add_system(ui_example_system)
+            .add_system(handle_actions.run_if(on_event::<ActionEvent>()))
+            .add_system(handle_dropped_files.run_if(on_event::<FileDragAndDrop>()))
+            .add_system(remove_files.run_if(on_event::<RemoveFile>()))
+            .add_system(forget_removed_files.run_if(on_event::<FileRemoved>()));
+
+        if self.own_camera {
+            app.add_plugin(CameraPlugin)
+                .add_startup_system(spawn_camera)
+                .add_system(
+                    update_camera_transform_system
+                        .after(camera::apply_projection)
+                        .run_if(
+                            resource_changed::<OccupiedScreenSpace>()
+                                .or_else(resource_changed::<OriginalCameraTransform>())
+                                .or_else(on_event::<WindowResized>())
+                                // The offset depends on the FOV
+                                .or_else(|projections: Query<(), Changed<Projection>>| {
+                                    !projections.is_empty()
+                                }),
+                        ),
+                );
+        }
+    }
+}
+
+/// Every event the panels can send, grouped to stay under Bevy's system parameter limit.
+/// Add it to a system that draws [`file_browser_ui`] or [`inspector_ui`].
+#[derive(SystemParam)]
+pub struct PanelEvents<'w> {
+    spawn: EventWriter<'w, SpawnEvent>,
+    spawn_scene: EventWriter<'w, SpawnSceneEvent>,
+    spawn_grid: EventWriter<'w, SpawnGridEvent>,
+    compare: EventWriter<'w, CompareEvent>,
+    clear_comparison: EventWriter<'w, ClearComparison>,
+    cancel_spawn_queue: EventWriter<'w, CancelSpawnQueue>,
+    load_metadata: EventWriter<'w, LoadBlenderData>,
+    action: EventWriter<'w, ActionEvent>,
+    isolate_slot: EventWriter<'w, IsolateSlot>,
+    validate_asset_paths: EventWriter<'w, ValidateAssetPaths>,
+    workspace: EventWriter<'w, WorkspaceAction>,
+    quick_look: EventWriter<'w, QuickLook>,
+    thumbnails: EventWriter<'w, ThumbnailAction>,
+    material_override: EventWriter<'w, MaterialOverride>,
+    place_on_click: EventWriter<'w, PlaceOnClick>,
+    translate: EventWriter<'w, SetTranslation>,
+}
+
+/// The resources and previews the file browser and inspector read and update, besides the
+/// file list and settings. Add it to a system that draws [`file_browser_ui`] or [`inspector_ui`].
+#[derive(SystemParam)]
+pub struct PanelResources<'w, 's> {
+    keybindings: ResMut<'w, Keybindings>,
+    parse_queue: ResMut<'w, ParseQueue>,
+    confirmation: ResMut<'w, Confirmation>,
+    compare: ResMut<'w, CompareState>,
+    toasts: ResMut<'w, Toasts>,
+    installs: Res<'w, BlenderInstalls>,
+    placement: Res<'w, Placement>,
+    gizmo: Res<'w, TranslateGizmo>,
+    analysis_cache: Res<'w, MeshAnalysisCache>,
+    previews: Query<
+        'w,
+        's,
+        (
+            &'static BlenderPreviewObject,
+            Option<&'static PreviewObjectInfo>,
+            Option<&'static Handle<Mesh>>,
+            Option<&'static FullMesh>,
+            Option<&'static IsolatedSlot>,
+        ),
+    >,
+}
+
+impl PanelResources<'_, '_> {
+    /// How many previews are on screen and their triangles in total
+    fn preview_totals(&self, app_state: &AppState) -> PreviewTotals {
+        PreviewTotals {
+            objects: self.previews.iter().len(),
+            triangles: self
+                .previews
+                .iter()
+                .filter_map(|(preview, ..)| {
+                    let object = app_state.file(preview.file)?.meshes.get(preview.mesh)?;
+                    Some(object.stats.as_ref()?.triangles)
+                })
+                .sum(),
+        }
+    }
+}
+
+fn ui_example_system(
+    mut contexts: EguiContexts,
+    mut occupied_screen_space: ResMut<OccupiedScreenSpace>,
+    mut events: PanelEvents,
+    mut resources: PanelResources,
+    spawn_queue: Res<SpawnQueue>,
+    mut app_state: ResMut<AppState>,
+    mut settings: ResMut<Settings>,
+    (workspaces, mut safe_mode): (Res<Workspaces>, ResMut<SafeMode>),
+    (console, status_sources, panels): (Res<LogConsole>, StatusSources, Res<LauncherPanels>),
+    egui_settings: Res<EguiSettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let ctx = contexts.ctx_mut();
+    // egui measures panels in points, which are logical pixels times the UI scale
+    let ui_scale = egui_settings.scale_factor as f32;
+
+    // The saved panel sizes only apply the first time egui sees each panel
+    let layout = match windows.get_single() {
+        Ok(window) => settings
+            .panel_layout
+            .clamped(window.width() / ui_scale, window.height() / ui_scale),
+        Err(_) => settings.panel_layout.clone(),
+    };
+    let mut measured_layout = layout.clone();
+
+    let left_width = egui::SidePanel::left("left_panel")
+        .resizable(true)
+        .default_width(layout.left_width)
+        .show_animated(ctx, panels.files, |ui| {
+            file_browser_ui(
+                ui,
+                &mut app_state,
+                &mut settings,
+                &mut resources,
+                &mut events,
+            );
+            ui.allocate_rect(ui.available_rect_before_wrap(), egui::Sense::hover());
+        })
+        .map(|panel| panel.response.rect.width());
+    let right_width = egui::SidePanel::right("right_panel")
+        .resizable(true)
+        .default_width(layout.right_width)
+        .show_animated(ctx, panels.inspector, |ui| {
+            inspector_ui(
+                ui,
+                &mut app_state,
+                &mut settings,
+                &mut resources,
+                &mut events,
+            );
+            ui.allocate_rect(ui.available_rect_before_wrap(), egui::Sense::hover());
+        })
+        .map(|panel| panel.response.rect.width());
+
+    let mut top_collapsed = layout.top_collapsed;
+    let mut bottom_collapsed = layout.bottom_collapsed;
+    let top_height = collapsible_panel(
+        egui::TopBottomPanel::top("top_panel"),
+        egui::TopBottomPanel::top("top_panel_collapsed"),
+        top_collapsed,
+        layout.top_height,
+    )
+    .show_animated(ctx, panels.top_bar, |ui| {
+        let mut menu = MenuResponse::default();
+        egui::menu::bar(ui, |ui| {
+            collapse_button(ui, &mut top_collapsed);
+            menu = menu_bar_ui(
+                ui,
+                &resources.keybindings,
+                settings.bypass_change_detection(),
+                app_state.selected_object.is_some(),
+                PanelToggles {
+                    top_collapsed: &mut top_collapsed,
+                    bottom_collapsed: &mut bottom_collapsed,
+                },
+            );
+            if safe_mode.is_active() {
+                ui.separator();
+                status_label(ui, Status::Warning, t!("safe_mode.banner"));
+                if ui
+                    .small_button(t!("safe_mode.leave"))
+                    .on_hover_text(t!("safe_mode.leave.hint"))
+                    .clicked()
+                {
+                    safe_mode.leave();
+                }
+            }
+        });
+        if menu.settings_changed {
+            settings.set_changed();
+        }
+        match menu.command {
+            Some(MenuCommand::Action(action)) => events.action.send(ActionEvent(action)),
+            Some(MenuCommand::OpenRecent(path)) => {
+                open_recent(&mut app_state, &mut settings, &mut resources.toasts, path);
+            }
+            Some(MenuCommand::Compare) => {
+                if let Some(object) = app_state.selected_object {
+                    resources.compare.open_picker(object);
+                }
+            }
+            None => {}
+        }
+        if !top_collapsed {
+            ui.horizontal(|ui| {
+                // Switching would save this session over the workspace's saved one
+                ui.add_enabled_ui(!safe_mode.is_active(), |ui| {
+                    workspace_ui(
+                        ui,
+                        &workspaces,
+                        settings.workspace.as_deref(),
+                        &mut events.workspace,
+                        &mut resources.confirmation,
+                    );
+                });
+            });
+            if !app_state.recent_spawns.is_empty() {
+                if let Some((file_id, index)) = recent_spawns_ui(ui, &app_state) {
+                    app_state.select_object(file_id, index);
+                    app_state.scroll_to_object = Some((file_id, index));
+                    events.spawn.send(SpawnEvent(file_id, index));
+                }
+            }
+            let (parsed, total) = resources.parse_queue.progress();
+            if total > 0 {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::ProgressBar::new(parsed as f32 / total as f32)
+                            .desired_width(200.0)
+                            .text(t!("progress.parsed", done = parsed, total = total)),
+                    );
+                    if ui.button(t!("progress.cancel")).clicked() {
+                        for file_id in resources.parse_queue.cancel_queued() {
+                            if let Some(file) = app_state.file_mut(file_id) {
+                                file.status = file.status.cancel();
+                            }
+                        }
+                    }
+                });
+            }
+            let (loaded, total) = spawn_queue.progress();
+            if total > 0 {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::ProgressBar::new(loaded as f32 / total as f32)
+                            .desired_width(200.0)
+                            .text(t!("progress.loading", done = loaded, total = total)),
+                    );
+                    if ui.button(t!("progress.cancel")).clicked() {
+                        events.cancel_spawn_queue.send(CancelSpawnQueue);
+                    }
+                });
+            }
+            ui.allocate_rect(ui.available_rect_before_wrap(), egui::Sense::hover());
+        }
+    })
+    .map(|panel| panel.response.rect.height());
+
+    let bottom_height = collapsible_panel(
+        egui::TopBottomPanel::bottom("bottom_panel"),
+        egui::TopBottomPanel::bottom("bottom_panel_collapsed"),
+        bottom_collapsed,
+        layout.bottom_height,
+    )
+    .show_animated(ctx, panels.status_bar, |ui| {
+        ui.horizontal(|ui| {
+            collapse_button(ui, &mut bottom_collapsed);
+            let show_log = status_bar_ui(
+                ui,
+                &app_state,
+                &resources.parse_queue,
+                &spawn_queue,
+                &status_sources,
+                resources.preview_totals(&app_state),
+            );
+            if show_log {
+                bottom_collapsed = false;
+            }
+        });
+        if !bottom_collapsed {
+            console_ui(ui, &console, settings.log_console_monospace);
+        }
+    })
+    .map(|panel| panel.response.rect.height());
+
+    // Only flag a change when a panel actually moved, so the camera isn't updated every frame
+    occupied_screen_space.set_if_neq(OccupiedScreenSpace {
+        left: left_width.unwrap_or_default() * ui_scale,
+        right: right_width.unwrap_or_default() * ui_scale,
+        top: top_height.unwrap_or_default() * ui_scale,
+        bottom: bottom_height.unwrap_or_default() * ui_scale,
+    });
+
+    // Remember the sizes so the layout is restored on the next launch.
+    // Collapsed panels keep the height they had before collapsing, and panels a host app
+    // turned off keep the size they have in the standalone launcher.
+    if let Some(left_width) = left_width {
+        measured_layout.left_width = left_width;
+    }
+    if let Some(right_width) = right_width {
+        measured_layout.right_width = right_width;
+    }
+    measured_layout.top_collapsed = top_collapsed;
+    measured_layout.bottom_collapsed = bottom_collapsed;
+    if let Some(top_height) = top_height.filter(|_| !layout.top_collapsed) {
+        measured_layout.top_height = top_height;
+    }
+    if let Some(bottom_height) = bottom_height.filter(|_| !layout.bottom_collapsed) {
+        measured_layout.bottom_height = bottom_height;
+    }
+    if !panel_layout_matches(&measured_layout, &layout) {
+        settings.panel_layout = measured_layout;
+    }
+
+    // Give new users a hint what to do, centered over the viewport
+    if app_state.files.is_empty() {
+        let viewport_center = egui::vec2(
+            (left_width.unwrap_or_default() - right_width.unwrap_or_default()) * 0.5,
+            (top_height.unwrap_or_default() - bottom_height.unwrap_or_default()) * 0.5,
+        );
+        onboarding_ui(ctx, viewport_center, &mut events.action);
+    }
+}
+
+/// The file list with each file's objects, as a flat list, a table or an outliner.
+/// Clicking an object selects it and spawns a preview of it.
+pub fn file_browser_ui(
+    ui: &mut egui::Ui,
+    app_state: &mut ResMut<AppState>,
+    settings: &mut ResMut<Settings>,
+    resources: &mut PanelResources,
+    events: &mut PanelEvents,
+) {
+    ui.horizontal(|ui| {
+        ui.heading(t!("panel.left"));
+        if ui
+            .add_enabled(
+                !app_state.files.is_empty(),
+                egui::Button::new(t!("files.clear_all")),
+            )
+            .clicked()
+        {
+            resources
+                .confirmation
+                .request(DestructiveAction::ClearFiles);
+        }
+    });
+    ui.horizontal(|ui| {
+        // Copied out so the settings only change when the user changes them
+        let mut list_mode = settings.object_list;
+        ui.selectable_value(
+            &mut list_mode,
+            ObjectListMode::Buttons,
+            t!("files.as_buttons"),
+        );
+        ui.selectable_value(&mut list_mode, ObjectListMode::Table, t!("files.as_table"));
+        ui.selectable_value(
+            &mut list_mode,
+            ObjectListMode::Outliner,
+            t!("files.as_outliner"),
+        );
+        if list_mode != settings.object_list {
+            settings.object_list = list_mode;
+        }
+        if list_mode == ObjectListMode::Table {
+            let mut columns = settings.object_columns;
+            ui.menu_button(t!("files.columns"), |ui| {
+                object_columns_ui(ui, &mut columns);
+            });
+            if columns != settings.object_columns {
+                settings.object_columns = columns;
+            }
+        }
+    });
+
+    // Copied out like the list mode, and only written back when edited
+    let mut object_filter = app_state.object_filter.clone();
+    ui.add(
+        egui::TextEdit::singleline(&mut object_filter)
+            .hint_text(t!("files.filter_objects"))
+            .desired_width(f32::INFINITY),
+    );
+
+    // We store the original selected file later to check for changes
+    let original_file = app_state.selected_file;
+
+    // We keep the selected file outside the loop
+    // since we mutate `app_state` in loop
+    let mut selected_file = original_file;
+    let mut selected_object = None;
+    let mut selected_collection = None;
+    // (index, direction) of a file the user wants to move up or down the list
+    let mut move_file = None;
+    let file_count = app_state.files.len();
+
+    // Jumping to an object (e.g. from the materials list) selects its file and the object
+    // (only touched mutably when set, so AppState isn't flagged as changed every frame)
+    let scroll_to_object = match app_state.scroll_to_object {
+        Some(_) => app_state.scroll_to_object.take(),
+        None => None,
+    };
+    if let Some((file_id, _)) = scroll_to_object {
+        selected_file = Some(file_id);
+        selected_object = scroll_to_object;
+    }
+    let scroll_to_collection = match app_state.scroll_to_collection {
+        Some(_) => app_state.scroll_to_collection.take(),
+        None => None,
+    };
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        // Loop over all files and show UI for them
+        for (index, file) in app_state.files.iter().enumerate() {
+            // Is the file selected? Change the name to signify that.
+            let is_selected = original_file == Some(file.id);
+            let name = if is_selected {
+                format!("⭐ {}", &file.path)
+            } else {
+                file.path.to_string()
+            };
+
+            // Render the UI, the selected file uses the accent color
+            let mut file_button = egui::Button::new(name);
+            if is_selected {
+                file_button = file_button.fill(ui.visuals().selection.bg_fill);
+            }
+            ui.horizontal(|ui| {
+                // Reorder handles
+                if ui
+                    .add_enabled(index > 0, egui::Button::new("⏶").small())
+                    .on_hover_text(t!("files.move_up"))
+                    .clicked()
+                {
+                    move_file = Some((index, index - 1));
+                }
+                if ui
+                    .add_enabled(index + 1 < file_count, egui::Button::new("⏷").small())
+                    .on_hover_text(t!("files.move_down"))
+                    .clicked()
+                {
+                    move_file = Some((index, index + 1));
+                }
+
+                // The toolbar shows on the selected row, and on the hovered one
+                let hovered = ui.rect_contains_pointer(ui.max_rect());
+                let mut file_action = None;
+                let file_response = ui.add(file_button).context_menu(|ui| {
+                    file_action = file_context_menu_ui(ui, &resources.keybindings, file.status);
+                });
+                if file_response.clicked() {
+                    // Did we click?
+                    // Toggle the file as selected/unselected.
+                    debug!(path = %file.path, "selected file");
+                    if is_selected {
+                        selected_file = None;
+                    } else {
+                        selected_file = Some(file.id);
+                        // spawn_events.send(SpawnEvent(index));
+                        // Files parse as they're added, unless that was cancelled
+                        if !file.parsed {
+                            events.load_metadata.send(LoadBlenderData(file.id));
+                        }
+                    }
+                }
+                let spawn_blocked = file.status.blocked(FileOp::Spawn);
+                if let Some(active) = file.active_object() {
+                    if ui
+                        .add_enabled(spawn_blocked.is_none(), egui::Button::new("◉").small())
+                        .on_hover_text(t!("files.spawn_active", object = file.meshes[active].name))
+                        .on_disabled_hover_text(spawn_blocked.unwrap_or_default())
+                        .clicked()
+                    {
+                        selected_object = Some((file.id, active));
+                        events.spawn.send(SpawnEvent(file.id, active));
+                    }
+                }
+                match resources.parse_queue.status(file.id) {
+                    Some(ParseStatus::Queued) => {
+                        ui.label("⏳").on_hover_text(t!("files.queued"));
+                    }
+                    Some(ParseStatus::Parsing) => {
+                        ui.spinner().on_hover_text(t!("files.parsing"));
+                    }
+                    None => {}
+                }
+                match file.status {
+                    FileStatus::Rendering => {
+                        ui.spinner().on_hover_text(file.status.label());
+                    }
+                    FileStatus::Missing | FileStatus::Error => {
+                        let response = status_label(ui, Status::Error, file.status.label());
+                        if let Some(error) = &file.parse_error {
+                            response.on_hover_text(error.as_str());
+                        }
+                    }
+                    _ => {}
+                }
+                warnings_badge_ui(ui, file);
+                if is_selected || hovered {
+                    file_action = file_action.or(file_toolbar_ui(
+                        ui,
+                        &resources.keybindings,
+                        &settings.file_toolbar,
+                        file.status,
+                    ));
+                }
+                if let Some(action) = file_action {
+                    // File actions act on the selected file
+                    selected_file = Some(file.id);
+                    events.action.send(ActionEvent(action));
+                }
+            });
+
+            if settings.object_list == ObjectListMode::Table {
+                if let Some(mesh_index) = object_table_ui(
+                    ui,
+                    file,
+                    &settings,
+                    &object_filter,
+                    app_state.selected_object,
+                    scroll_to_object,
+                ) {
+                    selected_object = Some((file.id, mesh_index));
+                    events.spawn.send(SpawnEvent(file.id, mesh_index));
+                }
+                ui.spacing();
+                continue;
+            }
+
+            // One object's row, shared by the flat list and the outliner.
+            // `level` is the LOD chain and position of objects listed under a chain.
+            let active_object = file.active_object();
+            let spawn_blocked = file.status.blocked(FileOp::Spawn);
+            let mut object_row_ui =
+                |ui: &mut egui::Ui, mesh_index: usize, level: Option<(&LodChain, usize)>| {
+                    let mesh = &file.meshes[mesh_index];
+                    // Badge objects with problems so they're easy to spot
+                    let warnings = object_warnings(mesh, &settings);
+                    let mut label = if warnings.is_empty() {
+                        format!("{} {}", mesh.kind.icon(), &mesh.name)
+                    } else {
+                        format!("⚠ {} {}", mesh.kind.icon(), &mesh.name)
+                    };
+                    if active_object == Some(mesh_index) {
+                        label.push_str(ACTIVE_MARKER);
+                    }
+
+                    // Highlight the selected object
+                    let mut button = egui::Button::new(label);
+                    if app_state.selected_object == Some((file.id, mesh_index)) {
+                        button = button.fill(ui.visuals().selection.bg_fill);
+                    }
+
+                    let mut response = ui
+                        .horizontal(|ui| {
+                            if level.is_some() {
+                                ui.add_space(ui.spacing().indent);
+                            }
+                            row_thumbnail(ui, file, &mesh.name);
+                            color_swatch(ui, mesh.color);
+                            let response = ui
+                                .add_enabled(
+                                    mesh.kind.is_previewable() && spawn_blocked.is_none(),
+                                    button,
+                                )
+                                .on_disabled_hover_text(match spawn_blocked {
+                                    Some(reason) if mesh.kind.is_previewable() => {
+                                        reason.to_string()
+                                    }
+                                    _ => not_previewable(mesh.kind),
+                                });
+                            if let Some((chain, position)) = level {
+                                ui.weak(chain.level_stats(file, position));
+                            }
+                            response
+                        })
+                        .inner;
+                    if settings.object_tooltips {
+                        response = response.on_hover_ui(|ui| {
+                            object_tooltip_ui(ui, file, mesh, &warnings);
+                        });
+                    } else if !warnings.is_empty() {
+                        response = response.on_hover_text(warnings.join("\n"));
+                    }
+                    if scroll_to_object == Some((file.id, mesh_index)) {
+                        response.scroll_to_me(Some(egui::Align::Center));
+                    }
+                    if response.clicked() {
+                        selected_object = Some((file.id, mesh_index));
+                        events.spawn.send(SpawnEvent(file.id, mesh_index));
+                    }
+                    if mesh.kind == ObjectKind::Mesh {
+                        let quick_look = QuickLook {
+                            file: file.id,
+                            mesh: mesh_index,
+                        };
+                        // Space over a row, or its context menu
+                        if response.hovered() && ui.input(|i| i.key_pressed(egui::Key::Space)) {
+                            events.quick_look.send(quick_look);
+                        } else {
+                            response.context_menu(|ui| {
+                                if ui.button(t!("quick_look.open")).clicked() {
+                                    events.quick_look.send(quick_look);
+                                    ui.close_menu();
+                                }
+                            });
+                        }
+                    }
+                };
+
+            let scroll_to = scroll_to_object
+                .filter(|(file_id, _)| *file_id == file.id)
+                .map(|(_, mesh_index)| mesh_index);
+            if settings.object_list == ObjectListMode::Outliner {
+                let collection_of_file = |collection: &Option<(FileId, String)>| {
+                    collection
+                        .as_ref()
+                        .filter(|(file_id, _)| *file_id == file.id)
+                        .map(|(_, name)| name.as_str())
+                };
+                let targets = OutlinerTargets {
+                    selected_collection: collection_of_file(&app_state.selected_collection),
+                    scroll_to_object: scroll_to,
+                    scroll_to_collection: collection_of_file(&scroll_to_collection),
+                };
+                if let Some(name) =
+                    outliner_ui(ui, file, &object_filter, targets, &mut |ui, mesh_index| {
+                        object_row_ui(ui, mesh_index, None)
+                    })
+                {
+                    selected_collection = Some((file.id, name));
+                }
+                ui.spacing();
+                continue;
+            }
+
+            // Only the rows in view are laid out, so files with thousands of objects
+            // don't cost thousands of widgets a frame
+            let rows = visible_rows(ui.ctx(), file, &object_filter);
+            let scroll_to_row = scroll_to.and_then(|mesh_index| {
+                rows.iter()
+                    .position(|row| row.object(file) == Some(mesh_index))
+            });
+            rows_ui(ui, file.id, &rows, scroll_to_row, |ui, row| match *row {
+                ObjectRow::Object(mesh_index) => object_row_ui(ui, mesh_index, None),
+                ObjectRow::Chain(chain) => lod_chain_header_ui(ui, file, chain),
+                ObjectRow::Level { chain, position } => {
+                    let chain = &file.lods.as_deref().unwrap_or_default()[chain];
+                    object_row_ui(ui, chain.levels[position].1, Some((chain, position)));
+                }
+            });
+
+            ui.spacing();
+        }
+    });
+    // Update the state if we made changes
+    if object_filter != app_state.object_filter {
+        app_state.object_filter = object_filter;
+    }
+    if let Some((from, to)) = move_file {
+        app_state.files.swap(from, to);
+    }
+    if let Some((file_id, mesh_id)) = selected_object {
+        app_state.select_object(file_id, mesh_id);
+    } else if let Some((file_id, name)) = selected_collection {
+        app_state.select_collection(file_id, name);
+    } else if selected_file != original_file {
+        app_state.select_file(selected_file);
+    }
+}
+
+/// The actions for the selected file and object, and everything known about them: the
+/// inspector, the checks of the previewed meshes, the file's audits and the settings
+pub fn inspector_ui(
+    ui: &mut egui::Ui,
+    app_state: &mut ResMut<AppState>,
+    settings: &mut ResMut<Settings>,
+    resources: &mut PanelResources,
+    events: &mut PanelEvents,
+) {
+    ui.heading(t!("panel.right"));
+
+    // if ui.button("Spawn").clicked() {
+    //     spawn_events.send(SpawnEvent);
+    // }
+
+    if ui.button(t!("actions.select_file")).clicked() {
+        events.action.send(ActionEvent(Action::OpenFiles));
+    }
+    if ui.button(t!("actions.clear_preview")).clicked() {
+        events.action.send(ActionEvent(Action::ClearPreview));
+    }
+    if ui.button(t!("actions.detach_preview")).clicked() {
+        events.action.send(ActionEvent(Action::DetachPreview));
+    }
+    ui.horizontal(|ui| {
+        if ui
+            .button(t!("actions.export_layout"))
+            .on_hover_text(t!("actions.export_layout.hint"))
+            .clicked()
+        {
+            events.action.send(ActionEvent(Action::ExportLayout));
+        }
+        if ui.button(t!("actions.import_layout")).clicked() {
+            events.action.send(ActionEvent(Action::ImportLayout));
+        }
+    });
+    if ui.button(t!("actions.measure")).clicked() {
+        events.action.send(ActionEvent(Action::ToggleMeasure));
+    }
+    if ui.button(t!("actions.builds")).clicked() {
+        events.action.send(ActionEvent(Action::ShowBuilds));
+    }
+    if ui.button(t!("actions.render_queue")).clicked() {
+        events.action.send(ActionEvent(Action::ShowRenderQueue));
+    }
+    if let Some(file_id) = app_state.selected_file {
+        let blocked = app_state
+            .file(file_id)
+            .and_then(|file| file.status.blocked(FileOp::Spawn));
+        if ui
+            .add_enabled(
+                blocked.is_none(),
+                egui::Button::new(t!("actions.spawn_scene")),
+            )
+            .on_disabled_hover_text(blocked.unwrap_or_default())
+            .clicked()
+        {
+            events.spawn_scene.send(SpawnSceneEvent(file_id));
+        }
+        // Only written back on a change, a write every frame would mark the state changed
+        if let Some(file) = app_state.file(file_id) {
+            let mut choice = file.scene_choice;
+            scene_selector_ui(ui, file, &mut choice);
+            if choice != file.scene_choice {
+                if let Some(file) = app_state.file_mut(file_id) {
+                    file.scene_choice = choice;
+                }
+            }
+        }
+    }
+    if let Some(file) = app_state.selected_file() {
+        let file_id = file.id;
+        match open_in_blender_ui(ui, file, &resources.installs, &settings) {
+            Some(OpenInBlenderAction::Open) => {
+                events.action.send(ActionEvent(Action::OpenInBlender));
+            }
+            Some(OpenInBlenderAction::SetOverride(executable)) => {
+                if let Some(file) = app_state.file_mut(file_id) {
+                    file.blender_override = executable;
+                }
+            }
+            None => {}
+        }
+    }
+    if let Some(object) = app_state.selected_object {
+        if ui.button(t!("actions.compare_with")).clicked() {
+            resources.compare.open_picker(object);
+        }
+        let mut placing = resources.placement.object() == Some(object);
+        if ui
+            .toggle_value(&mut placing, t!("placement.place_on_click"))
+            .on_hover_text(t!("placement.hint"))
+            .changed()
+        {
+            events
+                .place_on_click
+                .send(PlaceOnClick(placing.then_some(object)));
+        }
+    }
+    if let Some((file_id, mesh_id)) = app_state.selected_object {
+        ui.collapsing(t!("grid.heading"), |ui| {
+            let mut grid = settings.instance_grid;
+            egui::Grid::new("instance_grid").show(ui, |ui| {
+                ui.label(t!("grid.rows"));
+                ui.add(egui::DragValue::new(&mut grid.rows).clamp_range(1..=100));
+                ui.end_row();
+                ui.label(t!("grid.columns"));
+                ui.add(egui::DragValue::new(&mut grid.columns).clamp_range(1..=100));
+                ui.end_row();
+                ui.label(t!("grid.spacing"));
+                ui.add(
+                    egui::DragValue::new(&mut grid.spacing)
+                        .speed(0.1)
+                        .clamp_range(0.0..=1000.0),
+                );
+                ui.end_row();
+            });
+            if grid != settings.instance_grid {
+                settings.instance_grid = grid;
+            }
+
+            if ui.button(t!("grid.spawn")).clicked() {
+                events.spawn_grid.send(SpawnGridEvent {
+                    file: file_id,
+                    mesh: mesh_id,
+                    grid,
+                });
+            }
+        });
+    }
+
+    ui.separator();
+    ui.heading(t!("panel.inspector"));
+
+    match comparison_ui(ui, &resources.compare, app_state) {
+        Some(ComparisonAction::Swap) => {
+            if let Some(swapped) = resources.compare.swapped() {
+                events.compare.send(swapped);
+            }
+        }
+        Some(ComparisonAction::Clear) => events.clear_comparison.send(ClearComparison),
+        None => {}
+    }
+
+    // Show the parsed stats for the selected object
+    let selected_object = app_state.selected_object.and_then(|(file_id, mesh_id)| {
+        let file = app_state.file(file_id)?;
+        Some((file, mesh_id, file.meshes.get(mesh_id)?))
+    });
+    // A step of the breadcrumb that was clicked, applied once we're done reading the state
+    let mut clicked_crumb = None;
+    let selected_collection = app_state
+        .selected_collection
+        .as_ref()
+        .and_then(|(file_id, name)| Some((app_state.file(*file_id)?, name.as_str())));
+    if let Some((file, mesh_id, object)) = selected_object {
+        let path = file.object_path(mesh_id);
+        // Objects with neither collections nor parents would only repeat their name
+        if path.len() > 1 {
+            clicked_crumb = breadcrumb_ui(ui, file, &path).map(|crumb| (file.id, crumb));
+        }
+        ui.label(object.name.as_str());
+        if object.mesh_datablock() != object.name {
+            ui.weak(t!("inspector.mesh_data", name = object.mesh_datablock()));
+        }
+        match &object.outline {
+            Some(Outline::Curves(_)) => {
+                ui.weak(format!("≈ {}", t!("inspector.curve_approximation")));
+            }
+            Some(Outline::Text(_)) => {
+                ui.weak(format!("≈ {}", t!("inspector.text_approximation")));
+            }
+            None => {}
+        }
+        if let Some([x, y, z]) = dimensions(object) {
+            ui.label(t!(
+                "inspector.dimensions",
+                x = file.units.format_length(x),
+                y = file.units.format_length(y),
+                z = file.units.format_length(z)
+            ));
+        }
+        // The copy picked in the viewport, typing a position moves it like the gizmo does
+        let picked = resources.gizmo.selected().and_then(|entity| {
+            let (preview, info, ..) = resources.previews.get(entity).ok()?;
+            let info = info?;
+            info.is_object(file, mesh_id)
+                .then_some((entity, preview, info))
+        });
+        if let Some((entity, preview, info)) = picked {
+            let mut translation = preview.original_transform.translation;
+            ui.horizontal(|ui| {
+                // Which of the copies it is, with more than one on screen
+                if info.instance > 0 {
+                    ui.weak(info.label());
+                }
+                ui.label(t!("gizmo.position"));
+                for (axis, label) in ["x", "y", "z"].into_iter().enumerate() {
+                    ui.add(
+                        egui::DragValue::new(&mut translation[axis])
+                            .prefix(format!("{}: ", label))
+                            .speed(0.01),
+                    );
+                }
+            });
+            if translation != preview.original_transform.translation {
+                events.translate.send(SetTranslation {
+                    entity,
+                    translation,
+                });
+            }
+        }
+        if object.kind == ObjectKind::Mesh {
+            asset_path_ui(
+                ui,
+                file,
+                object,
+                settings.asset_root.as_deref(),
+                &mut events.validate_asset_paths,
+            );
+        }
+        if let Some(view_layer) = file.excluding_view_layer(&object.name) {
+            ui.weak(t!("inspector.excluded", view_layer = view_layer));
+        }
+        if !object.constraints.is_empty() || object.drivers > 0 {
+            ui.label(t!(
+                "inspector.constraints",
+                count = object.constraints.len()
+            ))
+            .on_hover_text(object.constraints.join("\n"));
+            ui.label(t!("inspector.drivers", count = object.drivers));
+        }
+        if !object.properties.is_empty() || !object.data_properties.is_empty() {
+            ui.collapsing(t!("properties.heading"), |ui| {
+                let id = ui.make_persistent_id(("custom_properties", &object.name));
+                if !object.properties.is_empty() {
+                    ui.strong(t!("properties.object"));
+                    properties_ui(ui, id.with("object"), &object.properties);
+                }
+                if !object.data_properties.is_empty() {
+                    ui.strong(t!("properties.mesh"));
+                    properties_ui(ui, id.with("mesh"), &object.data_properties);
+                }
+            });
+        }
+        let scale_issues = ScaleIssues::of(object.scale, settings.scale_tolerance);
+        if scale_issues.any() {
+            status_label(ui, Status::Warning, scale_issues.describe(object.scale));
+        }
+        if let Some(stats) = &object.stats {
+            ui.label(t!(
+                "stats.summary",
+                vertices = stats.vertices,
+                edges = stats.edges,
+                faces = stats.faces
+            ));
+            ui.label(t!("stats.triangles", count = stats.triangles));
+            if stats.color_attributes.is_empty() {
+                ui.weak(t!("stats.no_color_attributes"));
+            } else {
+                ui.label(t!(
+                    "stats.color_attributes",
+                    names = stats.color_attributes.join(", ")
+                ));
+            }
+            let ngons = t!("stats.ngons", count = stats.ngons);
+            if stats.ngons > settings.ngon_warning_threshold {
+                status_label(ui, Status::Warning, ngons);
+            } else {
+                ui.label(ngons);
+            }
+            // Isolating works on the previews, so there's nothing to offer without one
+            let mut spawned = resources
+                .previews
+                .iter()
+                .filter(|(_, info, ..)| info.is_some_and(|info| info.is_object(file, mesh_id)))
+                .peekable();
+            if spawned.peek().is_some() {
+                let isolated =
+                    spawned.find_map(|(.., isolated)| isolated.map(|isolated| isolated.slot));
+                isolate_slot_ui(
+                    ui,
+                    file.id,
+                    mesh_id,
+                    object,
+                    isolated,
+                    &mut events.isolate_slot,
+                );
+            }
+        }
+    } else if let Some((file, name)) = selected_collection {
+        clicked_crumb =
+            breadcrumb_ui(ui, file, &file.collection_path(name)).map(|crumb| (file.id, crumb));
+        let count = file
+            .scene_collection()
+            .and_then(|root| root.find(name))
+            .map_or(0, |collection| {
+                let mut objects = Vec::new();
+                collection.all_objects(&mut objects);
+                // Objects can be linked into more than one of the nested collections
+                objects.sort_unstable();
+                objects.dedup();
+                objects.len()
+            });
+        ui.label(t!("inspector.collection_objects", count = count));
+    } else {
+        ui.weak(t!("inspector.nothing_selected"));
+    }
+    match clicked_crumb {
+        // Selecting happens in the left panel, along with bringing the row into view
+        Some((file_id, Crumb::Object(mesh_id))) => {
+            app_state.scroll_to_object = Some((file_id, mesh_id));
+        }
+        Some((file_id, Crumb::Collection(name))) => {
+            app_state.select_collection(file_id, name.clone());
+            app_state.scroll_to_collection = Some((file_id, name));
+            // Only the outliner has collections to scroll to
+            if settings.object_list != ObjectListMode::Outliner {
+                settings.object_list = ObjectListMode::Outliner;
+            }
+        }
+        None => {}
+    }
+    if let Some(file) = app_state.selected_file() {
+        if !file.parse_warnings.is_empty() {
+            let heading = t!("parse_warnings.heading", count = file.parse_warnings.len());
+            ui.collapsing(heading, |ui| {
+                parse_warnings_ui(ui, file, &mut resources.toasts)
+            });
+        }
+    }
+
+    // The real cost of what's on screen, e.g. when a mesh is tiled in a grid
+    let preview_totals = resources.preview_totals(app_state);
+    if preview_totals.objects > 1 {
+        ui.label(t!(
+            "inspector.preview_total",
+            objects = preview_totals.objects,
+            triangles = preview_totals.triangles
+        ));
+    }
+
+    // Show the geometry checks for each previewed mesh, once per mesh
+    let mut analyzed_meshes = HashSet::new();
+    // (decimated previews are analyzed on their original mesh)
+    let mesh_handles = resources
+        .previews
+        .iter()
+        .filter_map(|(_, _, mesh_handle, full_mesh, _)| {
+            full_mesh.map(|full_mesh| &full_mesh.handle).or(mesh_handle)
+        });
+    for mesh_handle in mesh_handles {
+        if !analyzed_meshes.insert(mesh_handle.id()) {
+            continue;
+        }
+        match resources.analysis_cache.get(mesh_handle) {
+            Some(analysis) => {
+                if analysis.passed() {
+                    status_label(ui, Status::Ok, t!("analysis.passed"));
+                } else {
+                    status_label(ui, Status::Warning, t!("analysis.issues"));
+                }
+                ui.label(t!(
+                    "analysis.size",
+                    vertices = analysis.vertex_count,
+                    triangles = analysis.triangle_count
+                ));
+                ui.label(t!(
+                    "analysis.loose_vertices",
+                    count = analysis.loose_vertices
+                ));
+                ui.label(t!(
+                    "analysis.non_manifold_edges",
+                    count = analysis.non_manifold_edges
+                ));
+                ui.label(t!(
+                    "analysis.zero_area_triangles",
+                    count = analysis.zero_area_triangles
+                ));
+                ui.label(t!(
+                    "analysis.duplicate_vertices",
+                    count = analysis.duplicate_vertices
+                ));
+
+                let offset = analysis.bounds_center;
+                let origin_offset = t!(
+                    "analysis.origin_offset",
+                    x = format!("{:.2}", offset.x),
+                    y = format!("{:.2}", offset.y),
+                    z = format!("{:.2}", offset.z)
+                );
+                if analysis.origin_outside_bounds() {
+                    status_label(ui, Status::Warning, origin_offset)
+                        .on_hover_text(t!("analysis.origin_outside"));
+                } else {
+                    ui.label(origin_offset);
+                }
+            }
+            None if resources.analysis_cache.is_pending(mesh_handle) => {
+                ui.label(t!("analysis.pending"));
+            }
+            None => {
+                ui.label(t!("analysis.waiting"));
+            }
+        }
+    }
+
+    // Clicking an object in the materials list jumps to it in the left panel
+    let mut scroll_to_object = None;
+
+    // Audit the textures of the selected file
+    if let Some(file) = app_state.selected_file() {
+        ui.separator();
+        file_header_ui(ui, file);
+        object_summary_ui(ui, file);
+        scale_audit_ui(ui, file, settings.scale_tolerance);
+        materials_ui(
+            ui,
+            file,
+            app_state.material_override.as_deref(),
+            &mut scroll_to_object,
+            &mut events.material_override,
+        );
+        orphan_data_ui(ui, file);
+        texture_audit_ui(ui, file, settings.max_texture_size);
+        asset_path_report_ui(
+            ui,
+            file,
+            settings.asset_root.as_deref(),
+            &mut events.validate_asset_paths,
+        );
+        thumbnails_ui(ui, file, &mut events.thumbnails);
+
+        if ui.button(t!("actions.export_metadata")).clicked() {
+            events.action.send(ActionEvent(Action::ExportMetadata));
+        }
+        if ui
+            .button(t!("actions.export_contact_sheet"))
+            .on_hover_text(t!("actions.export_contact_sheet.hint"))
+            .clicked()
+        {
+            events.action.send(ActionEvent(Action::ExportContactSheet));
+        }
+    }
+
+    if scroll_to_object.is_some() {
+        app_state.scroll_to_object = scroll_to_object;
+    }
+
+    ui.separator();
+    ui.collapsing(t!("settings.heading"), |ui| {
+        // We only flag the settings as changed when something was edited,
+        // otherwise they'd be saved every frame
+        let changed = settings_ui(
+            ui,
+            settings.bypass_change_detection(),
+            resources.keybindings.bypass_change_detection(),
+        );
+        if changed {
+            settings.set_changed();
+            resources.keybindings.set_changed();
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button(t!("association.register")).clicked() {
+                events.action.send(ActionEvent(Action::RegisterFileHandler));
+            }
+            if ui.button(t!("association.unregister")).clicked() {
+                events
+                    .action
+                    .send(ActionEvent(Action::UnregisterFileHandler));
+            }
+        });
+    });
+}
+
+/// The empty state shown before any files are added
+fn onboarding_ui(
+    ctx: &egui::Context,
+    offset: egui::Vec2,
+    action_events: &mut EventWriter<ActionEvent>,
+) {
+    egui::Area::new("onboarding")
+        .anchor(egui::Align2::CENTER_CENTER, offset)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.heading(t!("onboarding.heading"));
+                    ui.label(t!("onboarding.body"));
+                    ui.add_space(8.0);
+
+                    let open_button =
+                        egui::Button::new(egui::RichText::new(t!("onboarding.open")).heading());
+                    if ui.add(open_button).clicked() {
+                        action_events.send(ActionEvent(Action::OpenFiles));
+                    }
+                    if ui.button(t!("onboarding.scan")).clicked() {
+                        action_events.send(ActionEvent(Action::ScanFolder));
+                    }
+
+                    ui.add_space(8.0);
+                    ui.weak(t!("onboarding.drop_hint"));
+                });
+            });
+        });
+}
+
+/// Top and bottom panels swap to a separate thin panel when collapsed,
+/// so egui keeps remembering the expanded panel's size
+fn collapsible_panel(
+    panel: egui::TopBottomPanel,
+    collapsed_panel: egui::TopBottomPanel,
+    collapsed: bool,
+    height: f32,
+) -> egui::TopBottomPanel {
+    if collapsed {
+        collapsed_panel
+            .resizable(false)
+            .exact_height(COLLAPSED_PANEL_HEIGHT)
+    } else {
+        panel.resizable(true).default_height(height)
+    }
+}
+
+fn collapse_button(ui: &mut egui::Ui, collapsed: &mut bool) {
+    let icon = if *collapsed { "⏵" } else { "⏷" };
+    if ui.small_button(icon).clicked() {
+        *collapsed = !*collapsed;
+    }
+}
+
+/// Panel sizes jitter by fractions of a point, so we only care about visible differences
+fn panel_layout_matches(a: &PanelLayout, b: &PanelLayout) -> bool {
+    let close = |a: f32, b: f32| (a - b).abs() < 1.0;
+    close(a.left_width, b.left_width)
+        && close(a.right_width, b.right_width)
+        && close(a.top_height, b.top_height)
+        && close(a.bottom_height, b.bottom_height)
+        && a.top_collapsed == b.top_collapsed
+        && a.bottom_collapsed == b.bottom_collapsed
+}
+
+/// Everything about an object worth a warning badge in the object list
+fn object_warnings(object: &BlendObject, settings: &Settings) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let ngons = object.stats.as_ref().map_or(0, |stats| stats.ngons);
+    if ngons > settings.ngon_warning_threshold {
+        warnings.push(t!("object.ngon_count", count = ngons));
+    }
+    let scale_issues = ScaleIssues::of(object.scale, settings.scale_tolerance);
+    if scale_issues.any() {
+        warnings.push(scale_issues.describe(object.scale));
+    }
+    warnings
+}
+
+/// The collapsible row a LOD chain's levels are listed under, which `visible_rows` checks
+fn lod_chain_header_ui(ui: &mut egui::Ui, file: &File, chain: usize) {
+    let Some(chain) = file.lods.as_deref().and_then(|chains| chains.get(chain)) else {
+        return;
+    };
+    let warnings = chain.warnings(file);
+    let highest = chain.levels.last().map_or(0, |(level, _)| *level);
+    let id = chain_id(file, &chain.base);
+    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+        .show_header(ui, |ui| {
+            let label = t!("lod.chain", base = chain.base, highest = highest);
+            if warnings.is_empty() {
+                ui.label(label);
+            } else {
+                status_label(ui, Status::Warning, label).on_hover_text(warnings.join("\n"));
+            }
+        })
+        // The levels are regular object rows, drawn by the list after this
+        .body_unindented(|_| {});
+}
+
+/// A small square in an object's viewport display color
+fn color_swatch(ui: &mut egui::Ui, [r, g, b, _]: [f32; 4]) {
+    let size = ui.spacing().interact_size.y * 0.5;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::hover());
+    ui.painter()
+        .rect_filled(rect, 2.0, egui::Rgba::from_rgb(r, g, b));
+}
+
+/// Why an object in the list can't be clicked
+fn not_previewable(kind: ObjectKind) -> String {
+    t!("object.not_previewable", kind = kind.label())
+}
+
+/// What the file's header says about how it was written
+pub fn file_header_ui(ui: &mut egui::Ui, file: &File) {
+    match file.header {
+        Some(header) => {
+            ui.weak(t!(
+                "header.info",
+                // Headers only carry major and minor
+                version = format!("{}.{}", header.version.major, header.version.minor),
+                bits = u32::from(header.pointer_size) * 8,
+                endianness = header.endianness.label()
+            ));
+        }
+        // Compressed files start with the compression header instead
+        None if file.parsed => {
+            ui.weak(t!("header.compressed"));
+        }
+        None => {}
+    }
+}
+
+/// How many objects of each kind the file has, and how many of them we can't show
+pub fn object_summary_ui(ui: &mut egui::Ui, file: &File) {
+    let mut counts: Vec<(ObjectKind, usize)> = Vec::new();
+    for object in file.meshes.iter() {
+        match counts.iter_mut().find(|(kind, _)| *kind == object.kind) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((object.kind, 1)),
+        }
+    }
+    counts.sort();
+
+    ui.label(t!("summary.objects", count = file.meshes.len()))
+        .on_hover_text(
+            counts
+                .iter()
+                .map(|(kind, count)| format!("{} {}: {}", kind.icon(), kind.label(), count))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    let hidden: usize = counts
+        .iter()
+        .filter(|(kind, _)| !kind.is_previewable())
+        .map(|(_, count)| count)
+        .sum();
+    if hidden > 0 {
+        ui.weak(t!("summary.not_previewable", count = hidden));
+    }
+
+    // Rigs with lots of drivers can behave differently once linked into another file
+    let constraints: usize = file
+        .meshes
+        .iter()
+        .map(|object| object.constraints.len())
+        .sum();
+    let drivers: usize = file.meshes.iter().map(|object| object.drivers).sum();
+    if constraints > 0 || drivers > 0 {
+        ui.label(t!(
+            "summary.rigging",
+            constraints = constraints,
+            drivers = drivers
+        ));
+    }
+}
+
+/// Counts the objects with scale that has to be applied before export
+pub fn scale_audit_ui(ui: &mut egui::Ui, file: &File, tolerance: f32) {
+    let count = file
+        .meshes
+        .iter()
+        .filter(|object| ScaleIssues::of(object.scale, tolerance).any())
+        .count();
+    if count > 0 {
+        status_label(ui, Status::Warning, t!("scale.audit", count = count))
+            .on_hover_text(t!("scale.audit_hint"));
+    }
+}
+
+/// Lists every material with the objects using it
+/// Lists the file's materials with their users. Clicking a material's thumbnail
+/// tries it on the selected object.
+fn materials_ui(
+    ui: &mut egui::Ui,
+    file: &File,
+    material_override: Option<&str>,
+    scroll_to_object: &mut Option<(FileId, usize)>,
+    overrides: &mut EventWriter<MaterialOverride>,
+) {
+    ui.collapsing(t!("materials.heading"), |ui| {
+        if let Some(material) = material_override {
+            ui.horizontal(|ui| {
+                ui.label(t!("materials.overridden", material = material));
+                if ui.button(t!("materials.revert")).clicked() {
+                    overrides.send(MaterialOverride::Revert);
+                }
+            });
+        }
+        for material in &file.materials {
+            let users = file
+                .material_users
+                .get(material)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+
+            let badges = file
+                .material_ids
+                .get(material)
+                .map(IdUsers::badges)
+                .unwrap_or_default();
+            let id = ui.make_persistent_id(material);
+            egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                .show_header(ui, |ui| {
+                    if let Some(texture) = thumbnail(file, Subject::Material, material) {
+                        let size = egui::Vec2::splat(ui.spacing().interact_size.y);
+                        if ui
+                            .add(egui::ImageButton::new(texture, size))
+                            .on_hover_ui(|ui| {
+                                ui.image(texture, egui::Vec2::splat(THUMBNAIL_SIZE as f32));
+                                ui.label(t!("materials.apply"));
+                            })
+                            .clicked()
+                        {
+                            overrides.send(MaterialOverride::Apply {
+                                file: file.id,
+                                material: material.clone(),
+                            });
+                        }
+                    }
+                    ui.label(format!(
+                        "{}{}",
+                        t!("materials.users", material = material, count = users.len()),
+                        badges
+                    ));
+                })
+                .body(|ui| {
+                    for object_id in users {
+                        if ui.link(file.meshes[*object_id].name.as_str()).clicked() {
+                            *scroll_to_object = Some((file.id, *object_id));
+                        }
+                    }
+                });
+        }
+    });
+}
+
+/// Lists data that nothing in the file uses
+pub fn orphan_data_ui(ui: &mut egui::Ui, file: &File) {
+    ui.collapsing(t!("orphans.heading"), |ui| {
+        let unused_materials: Vec<&String> = file
+            .materials
+            .iter()
+            .filter(|material| !file.material_users.contains_key(*material))
+            .collect();
+
+        // Meshes only count once nothing but their fake user keeps them
+        let unused_meshes: Vec<&(String, IdUsers)> = file
+            .mesh_blocks
+            .iter()
+            .filter(|(_, users)| users.real_users() <= 0)
+            .collect();
+
+        if unused_materials.is_empty() && unused_meshes.is_empty() {
+            ui.label(t!("orphans.none"));
+            return;
+        }
+
+        if !unused_materials.is_empty() {
+            ui.label(t!("orphans.unused_materials"));
+        }
+        for material in unused_materials {
+            // Other datablocks, e.g. node groups or brushes, can still use it
+            let ids = file.material_ids.get(material).copied().unwrap_or_default();
+            ui.label(format!(
+                "{}{}",
+                t!("orphans.unused_material", material = material),
+                if ids.fake_user { " 🛡" } else { "" }
+            ))
+            .on_hover_text(t!("orphans.id_users", count = ids.real_users().max(0)));
+        }
+        if !unused_meshes.is_empty() {
+            ui.label(t!("orphans.unused_meshes"));
+        }
+        for (mesh, users) in unused_meshes {
+            ui.label(format!("{}{}", mesh, users.badges()));
+        }
+    });
+}
+
+/// Lists oversized and broken images with the materials that use them
+pub fn texture_audit_ui(ui: &mut egui::Ui, file: &File, max_texture_size: u32) {
+    ui.collapsing(t!("textures.heading"), |ui| {
+        let oversized: Vec<&ImageInfo> = file
+            .images
+            .iter()
+            .filter(|image| image.exceeds(max_texture_size))
+            .collect();
+
+        if oversized.is_empty() {
+            ui.label(t!("textures.none_oversized", size = max_texture_size));
+        } else {
+            egui::Grid::new("texture_audit_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong(t!("textures.image"));
+                    ui.strong(t!("textures.resolution"));
+                    ui.strong(t!("textures.materials"));
+                    ui.end_row();
+
+                    for image in oversized {
+                        let (width, height) = image.resolution.unwrap_or_default();
+                        ui.label(image.name.as_str());
+                        ui.label(format!("{}x{}", width, height));
+                        ui.label(image.materials.join(", "));
+                        ui.end_row();
+                    }
+                });
+        }
+
+        // Linked images that don't exist anymore
+        let broken: Vec<&ImageInfo> = file
+            .images
+            .iter()
+            .filter(|image| image.is_broken())
+            .collect();
+        if !broken.is_empty() {
+            ui.label(t!("textures.broken"));
+            for image in broken {
+                if let metadata::ImageSource::Linked { path, .. } = &image.source {
+                    status_label(
+                        ui,
+                        Status::Error,
+                        format!("{} ({})", image.name, path.display()),
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Runs actions sent by buttons, keyboard shortcuts, and the command palette
+fn handle_actions(
+    mut commands: Commands,
+    mut action_events: EventReader<ActionEvent>,
+    mut app_state: ResMut<AppState>,
+    mut toasts: ResMut<Toasts>,
+    mut history: ResMut<SpawnHistory>,
+    mut original_camera_transform: ResMut<OriginalCameraTransform>,
+    settings: Res<Settings>,
+    blender_objects: Query<Entity, With<BlenderPreviewObject>>,
+    // A host app's camera is its own to move
+    mut cameras: Query<&mut Transform, With<LauncherCamera>>,
+    mut system_runs: ResMut<SystemRuns>,
+) {
+    system_runs.record("handle_actions");
+    for ActionEvent(action) in action_events.iter() {
+        match action {
+            Action::OpenFiles => open_files_dialog(&mut app_state),
+            Action::ScanFolder => scan_folder_dialog(&mut app_state, &mut toasts),
+            Action::ClearPreview => {
+                for blender_entity in blender_objects.iter() {
+                    commands.entity(blender_entity).despawn_recursive();
+                }
+                history.push(PreviewState {
+                    objects: Vec::new(),
+                    camera: **original_camera_transform,
+                });
+            }
+            Action::Undo | Action::Redo => {
+                let state = if *action == Action::Undo {
+                    history.undo()
+                } else {
+                    history.redo()
+                };
+                let Some(state) = state else {
+                    continue;
+                };
+
+                // Swap the current preview for the one from history
+                for blender_entity in blender_objects.iter() {
+                    commands.entity(blender_entity).despawn_recursive();
+                }
+                for descriptor in state.objects.iter() {
+                    spawn_preview(&mut commands, descriptor);
+                }
+
+                // Restore the camera bookmark
+                **original_camera_transform = state.camera;
+                for mut camera_transform in cameras.iter_mut() {
+                    camera_transform.rotation = state.camera.rotation;
+                }
+            }
+            Action::ExportMetadata => {
+                if let Some(file) = app_state.selected_file() {
+                    export_metadata_dialog(file, settings.max_texture_size, &mut toasts);
+                }
+            }
+            // The command palette handles its own overlays
+            Action::ToggleHelp | Action::CommandPalette => {}
+            // Handled by their own plugins
+            Action::DetachPreview
+            | Action::RegisterFileHandler
+            | Action::UnregisterFileHandler
+            | Action::ShowBuilds
+            | Action::ShowRenderQueue
+            | Action::ExportContactSheet
+            | Action::ReloadFile
+            | Action::RevealFile
+            | Action::RemoveFile
+            | Action::ExportLayout
+            | Action::ImportLayout
+            | Action::ToggleMeasure
+            | Action::Quit
+            | Action::ShowAbout
+            | Action::OpenInBlender => {}
+        }
+    }
+}
+
+/// Lets the user pick .blend files and adds them to the file list
+fn open_files_dialog(app_state: &mut AppState) {
+    let files = FileDialog::new()
+        .add_filter("Blender", &["blend"])
+        .set_directory("/")
+        .pick_files();
+
+    if let Some(file_path_buffers) = files {
+        for file_path_buffer in file_path_buffers {
+            add_file(app_state, &file_path_buffer);
+        }
+    }
+}
+
+/// Adds a .blend file to the file list
+fn add_file(app_state: &mut AppState, file_path_buffer: &Path) {
+    let file_path_option = file_path_buffer.to_str();
+    if let Some(file_path) = file_path_option {
+        info!(path = file_path, "added file");
+        app_state.add_file(file_path.to_string());
+    }
+}
+
+/// Lets the user pick a folder and adds every .blend file inside it
+fn scan_folder_dialog(app_state: &mut AppState, toasts: &mut Toasts) {
+    let Some(folder) = FileDialog::new().set_directory("/").pick_folder() else {
+        return;
+    };
+
+    let blend_files = find_blend_files(&folder);
+    toasts.push(
+        Severity::Info,
+        t!(
+            "files.found",
+            count = blend_files.len(),
+            folder = folder.display()
+        ),
+    );
+    for blend_file in blend_files {
+        add_file(app_state, &blend_file);
+    }
+}
+
+/// Recursively finds .blend files (but not .blend1 backups) in a folder
+fn find_blend_files(folder: &Path) -> Vec<PathBuf> {
+    let mut blend_files = Vec::new();
+    let Ok(entries) = fs::read_dir(folder) else {
+        return blend_files;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            blend_files.extend(find_blend_files(&path));
+        } else if is_blend_file(&path) {
+            blend_files.push(path);
+        }
+    }
+
+    blend_files.sort();
+    blend_files
+}
+
+fn is_blend_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("blend"))
+}
+
+/// Removes files from the list, along with any selection or history pointing at them
+fn remove_files(
+    mut remove_events: EventReader<RemoveFile>,
+    mut app_state: ResMut<AppState>,
+    mut toasts: ResMut<Toasts>,
+    mut system_runs: ResMut<SystemRuns>,
+) {
+    system_runs.record("remove_files");
+    for RemoveFile(file_id) in remove_events.iter() {
+        let Some(file) = app_state.file(*file_id) else {
+            continue;
+        };
+        // Removing everything skips the files that are busy, so tell the user which
+        if let Some(reason) = file.status.blocked(FileOp::Remove) {
+            toasts.push(
+                Severity::Warning,
+                t!("file_status.not_removed", path = file.path, reason = reason),
+            );
+            continue;
+        }
+        // What's kept about it elsewhere goes with the `FileRemoved` this leads to
+        app_state.files.retain(|file| file.id != *file_id);
+
+        if app_state.selected_file == Some(*file_id) {
+            app_state.selected_file = None;
+        }
+        if matches!(app_state.selected_object, Some((file, _)) if file == *file_id) {
+            app_state.selected_object = None;
+        }
+        if matches!(app_state.scroll_to_object, Some((file, _)) if file == *file_id) {
+            app_state.scroll_to_object = None;
+        }
+        if matches!(&app_state.selected_collection, Some((file, _)) if file == file_id) {
+            app_state.selected_collection = None;
+        }
+        if matches!(&app_state.scroll_to_collection, Some((file, _)) if file == file_id) {
+            app_state.scroll_to_collection = None;
+        }
+    }
+}
+
+/// Drops the history, recent spawns and assets of files that left the list, however they left
+fn forget_removed_files(
+    mut removed: EventReader<FileRemoved>,
+    mut app_state: ResMut<AppState>,
+    mut history: ResMut<SpawnHistory>,
+    mut asset_cache: ResMut<AssetCache>,
+) {
+    for FileRemoved { file, path } in removed.iter() {
+        history.remove_file(*file);
+        app_state.recent_spawns.remove_file(*file);
+        // The same blend can be in the list twice, only drop its assets once it's gone
+        if !app_state.files.iter().any(|listed| listed.path == *path) {
+            asset_cache.invalidate_file(path);
+        }
+    }
+}
+
+/// Adds .blend files dropped onto the window
+fn handle_dropped_files(
+    mut drag_and_drop_events: EventReader<FileDragAndDrop>,
+    mut app_state: ResMut<AppState>,
+    mut system_runs: ResMut<SystemRuns>,
+) {
+    system_runs.record("handle_dropped_files");
+    for event in drag_and_drop_events.iter() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
+            if path_buf.is_dir() {
+                for blend_file in find_blend_files(path_buf) {
+                    add_file(&mut app_state, &blend_file);
+                }
+            } else if is_blend_file(path_buf) {
+                add_file(&mut app_state, path_buf);
+            }
+        }
+    }
+}
+
+fn export_metadata_dialog(file: &File, max_texture_size: u32, toasts: &mut Toasts) {
+    let export_path = FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .set_file_name("metadata.json")
+        .save_file();
+
+    if let Some(export_path) = export_path {
+        match export::export_metadata(file, max_texture_size, &export_path) {
+            Ok(()) => toasts.push_with_action(
+                Severity::Success,
+                t!("export.done"),
+                ToastAction::RevealFile(export_path),
+            ),
+            Err(error) => {
+                error!(path = %file.path, %error, "failed to export metadata");
+                toasts.push(Severity::Error, t!("export.failed", error = error));
+            }
+        }
+    }
+}
+
+fn test_spawn(
+    mut commands: Commands,
+    asset_server: ResMut<AssetServer>,
+    mut spawn_event: EventReader<SpawnEvent>,
+    mut app_state: ResMut<AppState>,
+    mut history: ResMut<SpawnHistory>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    original_camera_transform: Res<OriginalCameraTransform>,
+    blender_objects: Query<(Entity, Option<&PreviewObjectInfo>), With<BlenderPreviewObject>>,
+    mut system_runs: ResMut<SystemRuns>,
+) {
+    system_runs.record("test_spawn");
+
+    // Each spawn replaces the previous preview, so when several requests
+    // land in the same frame (e.g. a fast double click) only the last one matters
+    let Some(SpawnEvent(file_id, mesh_id)) = spawn_event.iter().last() else {
+        return;
+    };
+
+    // Get object data, unless the file is mid-reload and its objects are about to change
+    let Some(file) = app_state
+        .file(*file_id)
+        .filter(|file| file.status.blocked(FileOp::Spawn).is_none())
+    else {
+        return;
+    };
+    let Some(mesh) = file.meshes.get(*mesh_id) else {
+        return;
+    };
+
+    // Nothing to do if that's already the only thing on screen
+    let mut previews = blender_objects.iter();
+    if let (Some((_, Some(info))), None) = (previews.next(), previews.next()) {
+        if info.is_object(file, *mesh_id) {
+            return;
+        }
+    }
+
+    // Clear previous Blender objects
+    for (blender_entity, _) in blender_objects.iter() {
+        commands.entity(blender_entity).despawn_recursive();
+    }
+
+    // Spawn the Blender object, reusing handles if we've loaded it before
+    let descriptor = PreviewDescriptor {
+        file: *file_id,
+        mesh: *mesh_id,
+        object: mesh.name.clone(),
+        datablock: mesh.mesh_datablock().to_string(),
+        mesh_handle: asset_cache.mesh(&asset_server, &file.path, mesh),
+        material_handle: asset_cache.object_material(&asset_server, &mut materials, file, mesh),
+        // mesh: asset_server.load(blender_mesh!("demo.blend", "Suzanne")),
+        // material: asset_server.load(blender_material!("demo.blend", "Red")),
+        transform: Transform::default(),
+    };
+    spawn_preview(&mut commands, &descriptor);
+    let object = mesh.name.clone();
+
+    history.push(PreviewState {
+        objects: vec![descriptor],
+        camera: **original_camera_transform,
+    });
+    app_state.recent_spawns.record(*file_id, object);
+}
+
+/// Replaces the preview with a grid of instances that share one mesh and material
+fn spawn_grid(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut grid_events: EventReader<SpawnGridEvent>,
+    app_state: Res<AppState>,
+    mut history: ResMut<SpawnHistory>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    original_camera_transform: Res<OriginalCameraTransform>,
+    blender_objects: Query<Entity, With<BlenderPreviewObject>>,
+    mut system_runs: ResMut<SystemRuns>,
+) {
+    system_runs.record("spawn_grid");
+
+    let Some(event) = grid_events.iter().last() else {
+        return;
+    };
+    let Some(file) = app_state
+        .file(event.file)
+        .filter(|file| file.status.blocked(FileOp::Spawn).is_none())
+    else {
+        return;
+    };
+    let Some(mesh) = file.meshes.get(event.mesh) else {
+        return;
+    };
+
+    for blender_entity in blender_objects.iter() {
+        commands.entity(blender_entity).despawn_recursive();
+    }
+
+    let mesh_handle = asset_cache.mesh(&asset_server, &file.path, mesh);
+    let material_handle = asset_cache.object_material(&asset_server, &mut materials, file, mesh);
+    let objects: Vec<PreviewDescriptor> = event
+        .grid
+        .positions()
+        .map(|position| PreviewDescriptor {
+            file: event.file,
+            mesh: event.mesh,
+            object: mesh.name.clone(),
+            datablock: mesh.mesh_datablock().to_string(),
+            mesh_handle: mesh_handle.clone(),
+            material_handle: material_handle.clone(),
+            transform: Transform::from_translation(position),
+        })
+        .collect();
+    for descriptor in objects.iter() {
+        spawn_preview(&mut commands, descriptor);
+    }
+
+    history.push(PreviewState {
+        objects,
+        camera: **original_camera_transform,
+    });
+}
+
+/// Spawns a previewed Blender object from already loaded handles
+fn spawn_preview(commands: &mut Commands, descriptor: &PreviewDescriptor) -> Entity {
+    commands
+        .spawn((
+            BlenderPreviewObject {
+                file: descriptor.file,
+                mesh: descriptor.mesh,
+                original_transform: descriptor.transform,
+            },
+            PreviewObjectInfo::new(
+                descriptor.file,
+                &descriptor.object,
+                &descriptor.datablock,
+                descriptor.transform,
+            ),
+            PbrBundle {
+                mesh: descriptor.mesh_handle.clone(),
+                material: descriptor.material_handle.clone(),
+                transform: descriptor.transform,
+                ..Default::default()
+            },
+        ))
+        .id()
+}
+
+fn setup_system(mut commands: Commands, asset_server: ResMut<AssetServer>) {
+    // Spawn the Suzanne mesh with the Red material
+    // commands.spawn(PbrBundle {
+    //     mesh: asset_server.load(blender_mesh!("demo.blend", "Suzanne")),
+    //     material: asset_server.load(blender_material!("demo.blend", "Red")),
+    //     ..Default::default()
+    // });
+
+    // Placed by `apply_lighting` from the lighting settings
+    commands.spawn((
+        PreviewLight,
+        PointLightBundle {
+            point_light: PointLight {
+                intensity: 1500.0,
+                shadows_enabled: true,
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(4.0, 8.0, 4.0),
+            ..Default::default()
+        },
+    ));
+}
+
+fn spawn_camera(mut commands: Commands, original_camera_transform: Res<OriginalCameraTransform>) {
+    commands
+        .spawn((
+            LauncherCamera,
+            Camera3dBundle {
+                transform: **original_camera_transform,
+                ..Default::default()
+            },
+        ))
+        .with_children(|camera| {
+            camera.spawn(overlay_camera());
+        });
+}
+
+fn update_camera_transform_system(
+    occupied_screen_space: Res<OccupiedScreenSpace>,
+    original_camera_transform: Res<OriginalCameraTransform>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    // Only the launcher's own camera makes room for the panels, a host's is left alone
+    mut camera_query: Query<(&Projection, &mut Transform), With<LauncherCamera>>,
+    mut system_runs: ResMut<SystemRuns>,
+) {
+    system_runs.record("update_camera_transform_system");
+
+    let Ok((Projection::Perspective(camera_projection), mut transform)) =
+        camera_query.get_single_mut()
+    else {
+        return;
+    };
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let distance_to_target = (CAMERA_TARGET - original_camera_transform.translation).length();
+    let frustum_height = 2.0 * distance_to_target * (camera_projection.fov * 0.5).tan();
+    let frustum_width = frustum_height * camera_projection.aspect_ratio;
+
+    let left_taken = occupied_screen_space.left / window.width();
+    let right_taken = occupied_screen_space.right / window.width();
+    let top_taken = occupied_screen_space.top / window.height();
+    let bottom_taken = occupied_screen_space.bottom / window.height();
+    let translation = original_camera_transform.translation
+        + transform.rotation.mul_vec3(Vec3::new(
+            (right_taken - left_taken) * frustum_width * 0.5,
+            (top_taken - bottom_taken) * frustum_height * 0.5,
+            0.0,
+        ));
+    // Avoid flagging the transform as changed when nothing moved
+    if transform.translation != translation {
+        transform.translation = translation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auto_preview::AutoPreview;
+    use metadata_cache::MetadataCache;
+    use std::time::Duration;
+
+    const DEMO_BLEND: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/demo.blend");
+    const EMPTY_AND_SLOTS_BLEND: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/demo-empty-and-slots.blend"
+    );
+
+    /// Just enough of the app to run `test_spawn`
+    fn spawn_test_app() -> (App, FileId) {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>()
+            .init_resource::<AppState>()
+            .init_resource::<AssetCache>()
+            .init_resource::<SpawnHistory>()
+            .init_resource::<SystemRuns>()
+            .insert_resource(OriginalCameraTransform(Transform::default()))
+            .add_event::<SpawnEvent>()
+            .add_system(test_spawn);
+
+        let mut app_state = app.world.resource_mut::<AppState>();
+        let file_id = app_state.add_file("demo.blend".to_string());
+        app_state
+            .file_mut(file_id)
+            .unwrap()
+            .meshes
+            .push(BlendObject {
+                name: "Suzanne".to_string(),
+                kind: ObjectKind::Mesh,
+                stats: None,
+                materials: Vec::new(),
+                modifiers: Vec::new(),
+                constraints: Vec::new(),
+                drivers: 0,
+                color: [1.0; 4],
+                properties: Vec::new(),
+                data_properties: Vec::new(),
+                scale: [1.0; 3],
+                bounds: None,
+                outline: None,
+                empty: None,
+                mesh_data: None,
+                parent: None,
+                transform: Transform::IDENTITY,
+                location: Vec3::ZERO,
+            });
+
+        (app, file_id)
+    }
+
+    /// The app's parsing and spawning, with the real plugins and without a window or renderer
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>()
+            .init_resource::<Settings>()
+            .init_resource::<MetadataCache>()
+            .init_resource::<LogConsole>()
+            .init_resource::<Toasts>()
+            .init_resource::<SystemRuns>()
+            .init_resource::<AppState>()
+            .init_resource::<AssetCache>()
+            .init_resource::<SpawnHistory>()
+            .insert_resource(OriginalCameraTransform(Transform::default()))
+            .add_event::<LoadBlenderData>()
+            .add_event::<ReparseFile>()
+            .add_event::<SpawnEvent>()
+            .add_plugin(ParseQueuePlugin)
+            .add_plugin(SpawnQueuePlugin)
+            .add_plugin(PreviewInfoPlugin)
+            .add_plugin(LauncherEventsPlugin)
+            .add_system(test_spawn.run_if(on_event::<SpawnEvent>()));
+        app
+    }
+
+    /// Parses run on the task pool, so this waits for them a frame at a time
+    fn update_until(app: &mut App, done: impl Fn(&World) -> bool) {
+        for _ in 0..1000 {
+            app.update();
+            if done(&app.world) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("gave up waiting after 1000 frames");
+    }
+
+    /// Adds and parses the file at `path`
+    fn load_file(app: &mut App, path: &str) -> FileId {
+        let file_id = app
+            .world
+            .resource_mut::<AppState>()
+            .add_file(path.to_string());
+        app.world
+            .resource_mut::<Events<LoadBlenderData>>()
+            .send(LoadBlenderData(file_id));
+        update_until(app, |world| {
+            world
+                .resource::<AppState>()
+                .file(file_id)
+                .is_some_and(|file| file.parsed)
+        });
+        file_id
+    }
+
+    /// The (file, label) of every preview entity, sorted
+    fn previews(app: &mut App) -> Vec<(FileId, String)> {
+        let mut previews: Vec<(FileId, String)> = app
+            .world
+            .query_filtered::<&PreviewObjectInfo, With<BlenderPreviewObject>>()
+            .iter(&app.world)
+            .map(|info| (info.file, info.label()))
+            .collect();
+        previews.sort_by(|(_, a), (_, b)| a.cmp(b));
+        previews
+    }
+
+    #[test]
+    fn demo_file_parses_and_spawns() {
+        let mut app = headless_app();
+        let file_id = load_file(&mut app, DEMO_BLEND);
+
+        let file = app.world.resource::<AppState>().file(file_id).unwrap();
+        assert!(file.parse_error.is_none());
+        assert_eq!(file.meshes.len(), 13);
+        let suzanne = file
+            .meshes
+            .iter()
+            .position(|object| object.name == "Suzanne")
+            .unwrap();
+        app.world
+            .resource_mut::<Events<SpawnEvent>>()
+            .send(SpawnEvent(file_id, suzanne));
+        app.update();
+        assert_eq!(previews(&mut app), [(file_id, "Suzanne".to_string())]);
+
+        // The whole scene replaces it, every mesh object but no camera or light
+        app.world
+            .resource_mut::<Events<SpawnSceneEvent>>()
+            .send(SpawnSceneEvent(file_id));
+        app.update();
+        let names: Vec<String> = previews(&mut app)
+            .into_iter()
+            .map(|(file, name)| {
+                assert_eq!(file, file_id);
+                name
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "Cube",
+                "Cylinder",
+                "LittleSuzanne",
+                "LittlerSuzanne",
+                "Suzanne",
+                "SuzanneNoMat",
+                "SuzanneNodesMat",
+                "TransformCube",
+                "TransformCube_X",
+                "TransformCube_Y",
+                "TransformCube_Z",
+            ]
+        );
+    }
+
+    /// The (object, datablock) of every preview entity, sorted
+    fn preview_datablocks(app: &mut App) -> Vec<(String, String)> {
+        let mut previews: Vec<(String, String)> = app
+            .world
+            .query_filtered::<&PreviewObjectInfo, With<BlenderPreviewObject>>()
+            .iter(&app.world)
+            .map(|info| (info.object.clone(), info.datablock.clone()))
+            .collect();
+        previews.sort();
+        previews
+    }
+
+    #[test]
+    fn empties_and_objects_with_several_slots_spawn() {
+        let mut app = headless_app();
+        let file_id = load_file(&mut app, EMPTY_AND_SLOTS_BLEND);
+        let file = app.world.resource::<AppState>().file(file_id).unwrap();
+        assert!(file.parse_error.is_none());
+        let index = |name: &str| {
+            file.meshes
+                .iter()
+                .position(|object| object.name == name)
+                .unwrap()
+        };
+        let (empty, suzanne) = (index("Empty"), index("SuzanneTwoMats"));
+
+        // An empty has no mesh of its own, so it goes by its object's name
+        app.world
+            .resource_mut::<Events<SpawnEvent>>()
+            .send(SpawnEvent(file_id, empty));
+        app.update();
+        assert_eq!(
+            preview_datablocks(&mut app),
+            [("Empty".to_string(), "Empty".to_string())]
+        );
+
+        app.world
+            .resource_mut::<Events<SpawnEvent>>()
+            .send(SpawnEvent(file_id, suzanne));
+        app.update();
+        assert_eq!(
+            preview_datablocks(&mut app),
+            [("SuzanneTwoMats".to_string(), "Suzanne.003".to_string())]
+        );
+
+        // The scene has both, and still no camera or light
+        app.world
+            .resource_mut::<Events<SpawnSceneEvent>>()
+            .send(SpawnSceneEvent(file_id));
+        app.update();
+        let previews = preview_datablocks(&mut app);
+        assert_eq!(previews.len(), 11);
+        for (object, datablock) in [("Empty", "Empty"), ("SuzanneTwoMats", "Suzanne.003")] {
+            assert!(
+                previews
+                    .iter()
+                    .any(|preview| *preview == (object.to_string(), datablock.to_string())),
+                "{} isn't in {:?}",
+                object,
+                previews
+            );
+        }
+        assert!(!previews
+            .iter()
+            .any(|(object, _)| object == "Camera" || object == "Light"));
+    }
+
+    #[test]
+    fn auto_preview_only_spawns_the_last_selected_file() {
+        let mut app = headless_app();
+        app.init_resource::<SafeMode>()
+            .add_plugin(AutoPreviewPlugin);
+        app.world.resource_mut::<Settings>().auto_preview = AutoPreview::FirstMesh;
+        let mut app_state = app.world.resource_mut::<AppState>();
+        let first = app_state.add_file(DEMO_BLEND.to_string());
+        let second = app_state.add_file(DEMO_BLEND.to_string());
+        app_state.select_file(Some(first));
+        app.world
+            .resource_mut::<Events<LoadBlenderData>>()
+            .send_batch([LoadBlenderData(first), LoadBlenderData(second)]);
+
+        // Clicked past before its parse is done
+        app.update();
+        app.world
+            .resource_mut::<AppState>()
+            .select_file(Some(second));
+        update_until(&mut app, |world| {
+            let app_state = world.resource::<AppState>();
+            [first, second]
+                .iter()
+                .all(|id| app_state.file(*id).is_some_and(|file| file.parsed))
+        });
+        app.update();
+
+        assert_eq!(previews(&mut app), [(second, "Cube".to_string())]);
+        let app_state = app.world.resource::<AppState>();
+        assert_eq!(app_state.selected_file, Some(second));
+        assert!(matches!(app_state.selected_object, Some((file, _)) if file == second));
+    }
+
+    #[test]
+    fn deselecting_a_file_leaves_nothing_selected() {
+        let mut app_state = AppState::default();
+        let first = app_state.add_file("first.blend".to_string());
+        let second = app_state.add_file("second.blend".to_string());
+
+        // Picking an object selects its file too
+        app_state.select_object(second, 0);
+        assert_eq!(app_state.selected_file, Some(second));
+
+        // Toggling the file off clears everything, nothing falls back to the first file
+        app_state.select_file(None);
+        assert!(app_state.selected_file().is_none());
+        assert_eq!(app_state.selected_object, None);
+
+        // Switching files drops the other file's object
+        app_state.select_object(second, 0);
+        app_state.select_file(Some(first));
+        assert_eq!(app_state.selected_file().map(|file| file.id), Some(first));
+        assert_eq!(app_state.selected_object, None);
+    }
+
+    fn preview_count(app: &mut App) -> usize {
+        app.world
+            .query::<&BlenderPreviewObject>()
+            .iter(&app.world)
+            .count()
+    }
+
+    #[test]
+    fn duplicate_spawn_events_spawn_one_preview() {
+        let (mut app, file_id) = spawn_test_app();
+
+        for _ in 0..3 {
+            app.world
+                .resource_mut::<Events<SpawnEvent>>()
+                .send(SpawnEvent(file_id, 0));
+        }
+        app.update();
+        assert_eq!(preview_count(&mut app), 1);
+
+        // Asking for the object that's already previewed doesn't respawn it
+        let entity = app
+            .world
+            .query_filtered::<Entity, With<BlenderPreviewObject>>()
+            .single(&app.world);
+        app.world
+            .resource_mut::<Events<SpawnEvent>>()
+            .send(SpawnEvent(file_id, 0));
+        app.update();
+        let respawned = app
+            .world
+            .query_filtered::<Entity, With<BlenderPreviewObject>>()
+            .single(&app.world);
+        assert_eq!(entity, respawned);
+    }
+
+    #[test]
+    fn host_cameras_are_left_alone() {
+        let mut app = App::new();
+        app.init_resource::<OccupiedScreenSpace>()
+            .init_resource::<SystemRuns>()
+            .insert_resource(OriginalCameraTransform(Transform::from_translation(
+                CAMERA_POSITION,
+            )))
+            .add_system(update_camera_transform_system);
+        // Two cameras of the host's and none of the launcher's, nor a primary window
+        let host_transform = Transform::from_xyz(1.0, 2.0, 3.0);
+        for _ in 0..2 {
+            app.world.spawn((Projection::default(), host_transform));
+        }
+        app.update();
+        let moved = app
+            .world
+            .query::<&Transform>()
+            .iter(&app.world)
+            .any(|transform| *transform != host_transform);
+        assert!(!moved);
+    }
+}
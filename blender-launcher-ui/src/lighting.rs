@@ -0,0 +1,98 @@
+use bevy::{pbr::PointLightShadowMap, prelude::*, render::primitives::Aabb};
+
+use crate::{
+    frame_guard::FrameGuard,
+    settings::{LightingSettings, Settings},
+    BlenderPreviewObject, CAMERA_TARGET,
+};
+
+/// What the shadow map size can be picked from, Bevy's default is 1024
+pub const SHADOW_MAP_SIZES: [usize; 4] = [512, 1024, 2048, 4096];
+
+/// Brightness of the light at the default distance. Moving it further away scales this
+/// up by the square of the distance, so the previews stay as bright.
+const INTENSITY: f32 = 1500.0;
+
+/// The light of the preview, as opposed to the ones the offscreen studios bring
+#[derive(Component)]
+pub struct PreviewLight;
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(apply_lighting.run_if(|settings: Res<Settings>| {
+            settings.lighting.auto_distance || settings.is_changed()
+        }));
+    }
+}
+
+/// Places the preview light and sets up its shadows to match the settings,
+/// backing it off to cover the previews when auto distance is on
+fn apply_lighting(
+    settings: Res<Settings>,
+    frame_guard: Res<FrameGuard>,
+    previews: Query<(&GlobalTransform, &Aabb), With<BlenderPreviewObject>>,
+    mut shadow_map: ResMut<PointLightShadowMap>,
+    mut lights: Query<(&mut PointLight, &mut Transform), With<PreviewLight>>,
+) {
+    let lighting = &settings.lighting;
+    if shadow_map.size != lighting.shadow_map_size {
+        shadow_map.size = lighting.shadow_map_size;
+    }
+
+    let (target, distance, range) = match covering_sphere(previews.iter()) {
+        Some((center, radius)) if lighting.auto_distance => {
+            let distance = lighting.distance.max(radius * 2.0);
+            (center, distance, distance + radius * 2.0)
+        }
+        _ => (CAMERA_TARGET, lighting.distance, lighting.distance * 2.0),
+    };
+    let direction = Quat::from_euler(
+        EulerRot::YXZ,
+        lighting.azimuth_degrees.to_radians(),
+        -lighting.elevation_degrees.to_radians(),
+        0.0,
+    ) * Vec3::Z;
+    let translation = target + direction * distance;
+    // Point lights fall off with the square of the distance
+    let intensity = INTENSITY * (distance / LightingSettings::default().distance).powi(2);
+    // The frame guard turns shadows back on itself once frames are fast again
+    let shadows = lighting.shadows && !frame_guard.is_reducing();
+
+    // Only touch the light when something changed, this runs every frame with auto distance
+    for (mut light, mut transform) in lights.iter_mut() {
+        if transform.translation != translation {
+            *transform = Transform::from_translation(translation).looking_at(target, Vec3::Y);
+        }
+        if light.shadows_enabled != shadows || light.intensity != intensity || light.range != range
+        {
+            light.shadows_enabled = shadows;
+            light.intensity = intensity;
+            light.range = range;
+        }
+    }
+}
+
+/// A sphere around every preview's bounding sphere, `None` when nothing is previewed
+fn covering_sphere<'a>(
+    previews: impl Iterator<Item = (&'a GlobalTransform, &'a Aabb)>,
+) -> Option<(Vec3, f32)> {
+    let spheres: Vec<(Vec3, f32)> = previews
+        .map(|(transform, aabb)| {
+            let center = transform.transform_point(aabb.center.into());
+            let radius =
+                (Vec3::from(aabb.half_extents) * transform.compute_transform().scale).length();
+            (center, radius)
+        })
+        .collect();
+    if spheres.is_empty() {
+        return None;
+    }
+    let center = spheres.iter().map(|(center, _)| *center).sum::<Vec3>() / spheres.len() as f32;
+    let radius = spheres
+        .iter()
+        .map(|(sphere, radius)| center.distance(*sphere) + radius)
+        .fold(0.0, f32::max);
+    Some((center, radius))
+}
@@ -0,0 +1,210 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use regex::Regex;
+
+use crate::{
+    i18n::t, launcher_events::MetadataLoaded, metadata::ObjectKind, settings::Settings, AppState,
+    File, FileId,
+};
+
+/// Our studio's convention, `Asset_LOD0`, `Asset_LOD1`, ...
+pub const DEFAULT_LOD_PATTERN: &str = r"^(?P<base>.+)_LOD(?P<level>\d+)$";
+
+/// Mesh objects whose names only differ in their LOD level
+pub struct LodChain {
+    pub base: String,
+    /// (level, index into `File::meshes`), lowest level first
+    pub levels: Vec<(u32, usize)>,
+}
+
+/// How an object list lays out a file's objects, with LOD chains grouped under their base name
+pub enum ObjectRow {
+    Object(usize),
+    /// The header of a chain, an index into `File::lods`
+    Chain(usize),
+    /// One level of a chain, following its header
+    Level {
+        chain: usize,
+        position: usize,
+    },
+}
+
+impl ObjectRow {
+    /// The index into `File::meshes` of the object on this row, `None` for a chain's header
+    pub fn object(&self, file: &File) -> Option<usize> {
+        match *self {
+            ObjectRow::Object(index) => Some(index),
+            ObjectRow::Chain(_) => None,
+            ObjectRow::Level { chain, position } => {
+                let chains = file.lods.as_deref().unwrap_or_default();
+                Some(chains[chain].levels[position].1)
+            }
+        }
+    }
+}
+
+pub struct LodPlugin;
+
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            group_lods.run_if(on_event::<MetadataLoaded>().or_else(resource_changed::<Settings>())),
+        );
+    }
+}
+
+/// Compiles the pattern from the settings, which needs a `base` and a `level` group
+pub fn compile_lod_pattern(source: &str) -> Result<Regex, String> {
+    let regex = Regex::new(source).map_err(|error| error.to_string())?;
+    let names: Vec<&str> = regex.capture_names().flatten().collect();
+    if !names.contains(&"base") || !names.contains(&"level") {
+        return Err(t!("settings.lod_pattern.missing_groups").to_string());
+    }
+    Ok(regex)
+}
+
+/// Groups the objects of newly parsed files, and of every file when the pattern changes
+fn group_lods(
+    settings: Res<Settings>,
+    mut app_state: ResMut<AppState>,
+    mut loaded: EventReader<MetadataLoaded>,
+    mut pattern: Local<Option<(String, Option<Regex>)>>,
+) {
+    let changed = pattern
+        .as_ref()
+        .map_or(true, |(source, _)| *source != settings.lod_pattern);
+    if changed {
+        let regex = compile_lod_pattern(&settings.lod_pattern)
+            .map_err(|error| warn!(%error, "invalid LOD pattern, not grouping LODs"))
+            .ok();
+        *pattern = Some((settings.lod_pattern.clone(), regex));
+    }
+    let loaded: HashSet<FileId> = loaded.iter().map(|MetadataLoaded(file)| *file).collect();
+    if !changed && loaded.is_empty() {
+        return;
+    }
+
+    let regex = pattern.as_ref().and_then(|(_, regex)| regex.as_ref());
+    for file in app_state.files.iter_mut() {
+        if file.parsed && (changed || loaded.contains(&file.id)) {
+            file.lods = Some(
+                regex
+                    .map(|regex| lod_chains(file, regex))
+                    .unwrap_or_default(),
+            );
+        }
+    }
+}
+
+fn lod_chains(file: &File, pattern: &Regex) -> Vec<LodChain> {
+    let mut chains: Vec<LodChain> = Vec::new();
+    for (index, object) in file.meshes.iter().enumerate() {
+        if object.kind != ObjectKind::Mesh {
+            continue;
+        }
+        let Some(captures) = pattern.captures(&object.name) else {
+            continue;
+        };
+        let (Some(base), Some(level)) = (
+            captures.name("base"),
+            captures
+                .name("level")
+                .and_then(|level| level.as_str().parse::<u32>().ok()),
+        ) else {
+            continue;
+        };
+        match chains.iter_mut().find(|chain| chain.base == base.as_str()) {
+            Some(chain) => chain.levels.push((level, index)),
+            None => chains.push(LodChain {
+                base: base.as_str().to_string(),
+                levels: vec![(level, index)],
+            }),
+        }
+    }
+    for chain in chains.iter_mut() {
+        chain.levels.sort_unstable();
+    }
+    chains
+}
+
+/// The file's objects in list order. A chain shows up where its first object is,
+/// with all of its levels right after it.
+pub fn object_rows(file: &File) -> Vec<ObjectRow> {
+    let chains = file.lods.as_deref().unwrap_or_default();
+    let mut chain_of: HashMap<usize, usize> = HashMap::new();
+    for (chain_index, chain) in chains.iter().enumerate() {
+        for (_, object) in chain.levels.iter() {
+            chain_of.insert(*object, chain_index);
+        }
+    }
+
+    let mut rows = Vec::with_capacity(file.meshes.len() + chains.len());
+    for index in 0..file.meshes.len() {
+        let Some(&chain) = chain_of.get(&index) else {
+            rows.push(ObjectRow::Object(index));
+            continue;
+        };
+        let first = chains[chain].levels.iter().map(|(_, object)| *object).min();
+        if first == Some(index) {
+            rows.push(ObjectRow::Chain(chain));
+            rows.extend(
+                (0..chains[chain].levels.len())
+                    .map(|position| ObjectRow::Level { chain, position }),
+            );
+        }
+    }
+    rows
+}
+
+fn triangles(file: &File, object: usize) -> usize {
+    file.meshes[object]
+        .stats
+        .as_ref()
+        .map_or(0, |stats| stats.triangles)
+}
+
+impl LodChain {
+    /// Gaps in the levels, and levels with more triangles than the one before them
+    pub fn warnings(&self, file: &File) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let highest = self.levels.last().map_or(0, |(level, _)| *level);
+        for level in 0..=highest {
+            if !self.levels.iter().any(|(present, _)| *present == level) {
+                warnings.push(t!("lod.missing", level = level));
+            }
+        }
+        for pair in self.levels.windows(2) {
+            let [(lower, lower_object), (higher, higher_object)] = pair else {
+                continue;
+            };
+            if lower == higher {
+                warnings.push(t!("lod.duplicate", level = lower));
+            } else if triangles(file, *higher_object) > triangles(file, *lower_object) {
+                warnings.push(t!("lod.more_triangles", higher = higher, lower = lower));
+            }
+        }
+        warnings
+    }
+
+    /// Triangles of a level, and how many that is of the level before it
+    pub fn level_stats(&self, file: &File, position: usize) -> String {
+        let (_, object) = self.levels[position];
+        let count = triangles(file, object);
+        match position
+            .checked_sub(1)
+            .map(|previous| self.levels[previous])
+        {
+            Some((previous_level, previous_object)) if triangles(file, previous_object) > 0 => {
+                let ratio = count as f32 / triangles(file, previous_object) as f32;
+                t!(
+                    "lod.level_stats",
+                    triangles = count,
+                    percent = format!("{:.0}", ratio * 100.0),
+                    previous = previous_level
+                )
+            }
+            _ => t!("lod.triangles", triangles = count),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    fmt::{self, Write},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing::{field::Field, Event, Level, Subscriber};
+use tracing_subscriber::{field::Visit, layer::Context, prelude::*, EnvFilter, Layer};
+
+use crate::{console::LogConsole, crash::remember_log_line};
+
+/// How much we log, from the `--log-level` flag or the settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        LogLevel::ALL
+            .into_iter()
+            .find(|level| level.label().eq_ignore_ascii_case(value))
+    }
+}
+
+/// Reads `--log-level <level>` or `--log-level=<level>` from the command line
+pub fn cli_log_level() -> Option<LogLevel> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--log-level") {
+            Some("") => args.next(),
+            Some(value) => value.strip_prefix('=').map(str::to_string),
+            None => continue,
+        };
+        return match value.as_deref().and_then(LogLevel::parse) {
+            Some(level) => Some(level),
+            None => {
+                eprintln!(
+                    "Unknown --log-level {:?}, expected error/warn/info/debug/trace",
+                    value
+                );
+                None
+            }
+        };
+    }
+    None
+}
+
+/// Installs the global logger.
+/// We set it up ourselves rather than using Bevy's `LogPlugin`, so warnings and errors
+/// can also be sent to the in-app console. `RUST_LOG` still overrides `level`.
+pub fn init_logging(level: LogLevel) -> LogReceiver {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("{},wgpu=error,naga=warn", level.label())));
+
+    let (sender, receiver) = mpsc::channel();
+    let console_layer = ConsoleLayer {
+        sender: Mutex::new(sender),
+    };
+
+    let result = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(console_layer)
+        .with(CrashLogLayer)
+        .try_init();
+    if let Err(error) = result {
+        eprintln!("Couldn't set up logging: {}", error);
+    }
+
+    LogReceiver(Mutex::new(receiver))
+}
+
+/// Warnings and errors waiting to be shown in the log console
+#[derive(Resource)]
+pub struct LogReceiver(Mutex<Receiver<String>>);
+
+pub struct LoggingPlugin {
+    pub level: LogLevel,
+}
+
+impl Plugin for LoggingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(init_logging(self.level))
+            .add_system(mirror_logs_to_console);
+    }
+}
+
+fn mirror_logs_to_console(receiver: Res<LogReceiver>, mut console: ResMut<LogConsole>) {
+    let Ok(receiver) = receiver.0.lock() else {
+        return;
+    };
+    for line in receiver.try_iter() {
+        console.push(line);
+    }
+}
+
+/// Forwards warnings and errors as single lines, e.g. `WARN parse: failed path=a.blend`
+struct ConsoleLayer {
+    sender: Mutex<Sender<String>>,
+}
+
+impl<S: Subscriber> Layer<S> for ConsoleLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        // Levels compare by verbosity, so this is WARN and ERROR
+        if *metadata.level() > Level::WARN {
+            return;
+        }
+
+        if let Ok(sender) = self.sender.lock() {
+            // The console might be gone while shutting down, that's fine
+            let _ = sender.send(event_line(event));
+        }
+    }
+}
+
+/// Keeps the latest lines of every level that gets through the filter, for crash reports
+struct CrashLogLayer;
+
+impl<S: Subscriber> Layer<S> for CrashLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        remember_log_line(event_line(event));
+    }
+}
+
+fn event_line(event: &Event<'_>) -> String {
+    let metadata = event.metadata();
+    let mut visitor = LineVisitor::default();
+    event.record(&mut visitor);
+    format!(
+        "{} {}: {}{}",
+        metadata.level(),
+        metadata.target(),
+        visitor.message,
+        visitor.fields
+    )
+}
+
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for LineVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+}
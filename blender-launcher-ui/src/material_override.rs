@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use crate::{
+    asset_cache::AssetCache,
+    preview_info::PreviewObjectInfo,
+    shading::{lit_material, set_lit_material, OriginalMaterial},
+    AppState, BlenderPreviewObject, FileId,
+};
+
+/// Tries a material of a file on the selected object until it's reverted or respawned
+pub enum MaterialOverride {
+    Apply { file: FileId, material: String },
+    Revert,
+}
+
+/// The material a preview had before an override replaced it
+#[derive(Component)]
+struct OverriddenMaterial(Handle<StandardMaterial>);
+
+pub struct MaterialOverridePlugin;
+
+impl Plugin for MaterialOverridePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MaterialOverride>()
+            .add_system(
+                apply_material_overrides
+                    .run_if(on_event::<MaterialOverride>())
+                    .after(forget_respawned_override),
+            )
+            .add_system(
+                forget_respawned_override
+                    .run_if(|app_state: Res<AppState>| app_state.material_override.is_some()),
+            );
+    }
+}
+
+fn apply_material_overrides(
+    mut commands: Commands,
+    mut events: EventReader<MaterialOverride>,
+    mut app_state: ResMut<AppState>,
+    asset_server: Res<AssetServer>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut previews: Query<
+        (
+            Entity,
+            &PreviewObjectInfo,
+            &mut Handle<StandardMaterial>,
+            Option<&mut OriginalMaterial>,
+            Option<&OverriddenMaterial>,
+        ),
+        With<BlenderPreviewObject>,
+    >,
+) {
+    for event in events.iter() {
+        match event {
+            MaterialOverride::Apply { file, material } => {
+                let Some((selected_file, selected)) = app_state
+                    .selected_object
+                    .and_then(|(file, index)| Some((app_state.file(file)?, index)))
+                else {
+                    continue;
+                };
+                let Some(path) = app_state.file(*file).map(|file| file.path.clone()) else {
+                    continue;
+                };
+                let handle = asset_cache.material(&asset_server, &path, material);
+                let mut applied = false;
+                for (entity, info, mut current, mut shaded, overridden) in previews.iter_mut() {
+                    if !info.is_object(selected_file, selected) {
+                        continue;
+                    }
+                    // Trying one material after another still reverts to the first
+                    if overridden.is_none() {
+                        let original = lit_material(&current, shaded.as_deref()).clone();
+                        commands.entity(entity).insert(OverriddenMaterial(original));
+                    }
+                    set_lit_material(&mut current, shaded.as_deref_mut(), handle.clone());
+                    applied = true;
+                }
+                if applied {
+                    app_state.material_override = Some(material.clone());
+                }
+            }
+            MaterialOverride::Revert => {
+                for (entity, _, mut current, mut shaded, overridden) in previews.iter_mut() {
+                    if let Some(OverriddenMaterial(original)) = overridden {
+                        set_lit_material(&mut current, shaded.as_deref_mut(), original.clone());
+                        commands.entity(entity).remove::<OverriddenMaterial>();
+                    }
+                }
+                app_state.material_override = None;
+            }
+        }
+    }
+}
+
+/// Respawning or clearing the preview drops the override along with the entities
+fn forget_respawned_override(
+    mut app_state: ResMut<AppState>,
+    overridden: Query<(), With<OverriddenMaterial>>,
+) {
+    if overridden.is_empty() {
+        app_state.material_override = None;
+    }
+}
@@ -0,0 +1,332 @@
+use bevy::{math::Ray, prelude::*, render::primitives::Aabb, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+
+use crate::{
+    analysis::{mesh_indices, mesh_positions},
+    commands::{Action, ActionEvent},
+    gizmo::ray_hits_aabb,
+    i18n::t,
+    placement::{ground_hit, Placement},
+    units::SceneUnits,
+    viewport::{viewport_to_egui, ViewportClick},
+    AppState, BlenderPreviewObject, LauncherCamera, OccupiedScreenSpace,
+};
+
+/// How close a vertex has to be to the cursor for a picked point to snap to it, in pixels
+const VERTEX_SNAP: f32 = 10.0;
+/// What holding Shift rounds the direction of a measurement to, in degrees
+const ANGLE_STEP: f32 = 15.0;
+
+const LINE_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 210, 60);
+
+/// Distances measured between points picked in the viewport, kept until they're deleted
+#[derive(Default, Resource)]
+pub struct MeasureTool {
+    active: bool,
+    /// The first point of the measurement being picked
+    pending: Option<Vec3>,
+    measurements: Vec<Measurement>,
+    click: ViewportClick,
+}
+
+impl MeasureTool {
+    /// Whether clicks in the viewport pick points to measure between
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+struct Measurement {
+    start: Vec3,
+    end: Vec3,
+    /// The units of the file the first point was picked on
+    units: SceneUnits,
+}
+
+impl Measurement {
+    fn label(&self) -> String {
+        self.units.format_length(self.start.distance(self.end))
+    }
+}
+
+pub struct MeasurePlugin;
+
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeasureTool>()
+            .add_system(toggle_measuring.run_if(on_event::<ActionEvent>()))
+            .add_system(
+                pick_measure_points
+                    .after(toggle_measuring)
+                    .run_if(|tool: Res<MeasureTool>| tool.active),
+            )
+            .add_system(
+                measurements_ui
+                    .run_if(|tool: Res<MeasureTool>| tool.active || !tool.measurements.is_empty()),
+            );
+    }
+}
+
+fn toggle_measuring(mut actions: EventReader<ActionEvent>, mut tool: ResMut<MeasureTool>) {
+    for ActionEvent(action) in actions.iter() {
+        if *action == Action::ToggleMeasure {
+            tool.active = !tool.active;
+            tool.pending = None;
+        }
+    }
+}
+
+/// Picks a point on the previews, or the ground past them, on each click.
+/// Every second point finishes a measurement.
+#[allow(clippy::too_many_arguments)]
+fn pick_measure_points(
+    mut contexts: EguiContexts,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    app_state: Res<AppState>,
+    placement: Res<Placement>,
+    meshes: Res<Assets<Mesh>>,
+    mut tool: ResMut<MeasureTool>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<LauncherCamera>>,
+    previews: Query<(
+        &BlenderPreviewObject,
+        &GlobalTransform,
+        &Handle<Mesh>,
+        &Aabb,
+    )>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        // The first press drops a half picked measurement, the next stops measuring
+        if tool.pending.take().is_none() {
+            tool.active = false;
+        }
+        return;
+    }
+    // Placing objects has the clicks to itself
+    if placement.object().is_some() {
+        return;
+    }
+    let ctx = contexts.ctx_mut();
+    let over_panels = ctx.is_pointer_over_area() || ctx.is_using_pointer();
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single())
+    else {
+        return;
+    };
+    let cursor = window.cursor_position();
+
+    if !tool.click.clicked(&mouse_buttons, cursor, over_panels) {
+        return;
+    }
+    let Some(cursor) = cursor else {
+        return;
+    };
+    let Some(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+        return;
+    };
+
+    // The closest preview under the cursor, snapped to its closest vertex on screen
+    let picked = previews
+        .iter()
+        .filter(|(_, transform, _, aabb)| ray_hits_aabb(ray, transform, aabb).is_some())
+        .filter_map(|(preview, transform, handle, _)| {
+            let mesh = meshes.get(handle)?;
+            let (distance, point) = ray_hits_mesh(ray, transform, mesh)?;
+            let point =
+                closest_vertex(cursor, camera, camera_transform, transform, mesh).unwrap_or(point);
+            Some((distance, point, preview.file))
+        })
+        .min_by(|(a, ..), (b, ..)| a.total_cmp(b));
+    let units_of = |file| app_state.file(file).map(|file| file.units);
+    let (point, units) = match picked {
+        Some((_, point, file)) => (point, units_of(file)),
+        None => match ground_hit(ray) {
+            Some(point) => (point, app_state.selected_file().map(|file| file.units)),
+            None => return,
+        },
+    };
+
+    match tool.pending.take() {
+        None => tool.pending = Some(point),
+        Some(start) => {
+            let snapping = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+            let end = if snapping {
+                snap_angle(start, point)
+            } else {
+                point
+            };
+            tool.measurements.push(Measurement {
+                start,
+                end,
+                units: units.unwrap_or_default(),
+            });
+        }
+    }
+}
+
+/// Where the ray first meets one of the mesh's triangles, as (distance along the ray, point)
+fn ray_hits_mesh(ray: Ray, transform: &GlobalTransform, mesh: &Mesh) -> Option<(f32, Vec3)> {
+    let matrix = transform.compute_matrix();
+    let positions = mesh_positions(mesh);
+    let indices = mesh_indices(mesh, positions.len());
+    indices
+        .chunks_exact(3)
+        .filter_map(|triangle| {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| {
+                let position = positions.get(index as usize).copied().unwrap_or_default();
+                matrix.transform_point3(Vec3::from(position))
+            });
+            ray_hits_triangle(ray, a, b, c)
+        })
+        .min_by(|a, b| a.total_cmp(b))
+        .map(|distance| (distance, ray.origin + ray.direction * distance))
+}
+
+/// How far along the ray it meets the triangle, from either side (Möller–Trumbore)
+fn ray_hits_triangle(ray: Ray, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = ray.direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() <= f32::EPSILON {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let to_origin = ray.origin - a;
+    let u = to_origin.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = to_origin.cross(edge1);
+    let v = ray.direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge2.dot(q) * inverse;
+    (distance > 0.0).then_some(distance)
+}
+
+/// The mesh's vertex closest to the cursor on screen, if one is within `VERTEX_SNAP`
+fn closest_vertex(
+    cursor: Vec2,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    transform: &GlobalTransform,
+    mesh: &Mesh,
+) -> Option<Vec3> {
+    let matrix = transform.compute_matrix();
+    mesh_positions(mesh)
+        .into_iter()
+        .filter_map(|position| {
+            let point = matrix.transform_point3(Vec3::from(position));
+            let on_screen = camera.world_to_viewport(camera_transform, point)?;
+            Some((on_screen.distance(cursor), point))
+        })
+        .filter(|(distance, _)| *distance <= VERTEX_SNAP)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, point)| point)
+}
+
+/// Rounds the direction from `start` to `end` to `ANGLE_STEP`s of yaw and pitch,
+/// keeping the length
+fn snap_angle(start: Vec3, end: Vec3) -> Vec3 {
+    let offset = end - start;
+    let length = offset.length();
+    if length <= f32::EPSILON {
+        return end;
+    }
+    let step = ANGLE_STEP.to_radians();
+    let yaw = (offset.x.atan2(offset.z) / step).round() * step;
+    let pitch = ((offset.y / length).clamp(-1.0, 1.0).asin() / step).round() * step;
+    start
+        + Vec3::new(
+            pitch.cos() * yaw.sin(),
+            pitch.sin(),
+            pitch.cos() * yaw.cos(),
+        ) * length
+}
+
+/// The lines and labels over the viewport, and a window listing the measurements
+fn measurements_ui(
+    mut contexts: EguiContexts,
+    egui_settings: Res<EguiSettings>,
+    mut tool: ResMut<MeasureTool>,
+    occupied_screen_space: Res<OccupiedScreenSpace>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<LauncherCamera>>,
+) {
+    let ctx = contexts.ctx_mut();
+    if let (Ok(window), Ok((camera, camera_transform))) =
+        (windows.get_single(), cameras.get_single())
+    {
+        let ui_scale = egui_settings.scale_factor as f32;
+        let to_screen = |point: Vec3| {
+            let point = camera.world_to_viewport(camera_transform, point)?;
+            Some(viewport_to_egui(point, window, ui_scale))
+        };
+        let painter = ctx.layer_painter(egui::LayerId::background());
+        for measurement in tool.measurements.iter() {
+            let (Some(start), Some(end)) =
+                (to_screen(measurement.start), to_screen(measurement.end))
+            else {
+                continue;
+            };
+            painter.line_segment([start, end], (2.0, LINE_COLOR));
+            painter.circle_filled(start, 3.0, LINE_COLOR);
+            painter.circle_filled(end, 3.0, LINE_COLOR);
+            painter.text(
+                start + (end - start) / 2.0,
+                egui::Align2::CENTER_BOTTOM,
+                measurement.label(),
+                egui::FontId::proportional(14.0),
+                LINE_COLOR,
+            );
+        }
+        if let Some(pending) = tool.pending.and_then(to_screen) {
+            painter.circle_stroke(pending, 5.0, (2.0, LINE_COLOR));
+        }
+    }
+
+    // Out of the way of the panels, in the corner of the viewport
+    let ui_scale = egui_settings.scale_factor as f32;
+    let offset = egui::vec2(
+        -occupied_screen_space.right / ui_scale - 12.0,
+        -occupied_screen_space.bottom / ui_scale - 12.0,
+    );
+    let mut delete = None;
+    let mut clear = false;
+    let mut active = tool.active;
+    egui::Window::new(t!("measure.heading"))
+        .id(egui::Id::new("measurements"))
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, offset)
+        .show(ctx, |ui| {
+            ui.toggle_value(&mut active, t!("measure.active"))
+                .on_hover_text(t!("measure.hint"));
+            for (index, measurement) in tool.measurements.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(measurement.label());
+                    if ui
+                        .small_button("🗑")
+                        .on_hover_text(t!("measure.delete"))
+                        .clicked()
+                    {
+                        delete = Some(index);
+                    }
+                });
+            }
+            if tool.measurements.len() > 1 {
+                clear = ui.button(t!("measure.clear")).clicked();
+            }
+        });
+
+    if clear {
+        tool.measurements.clear();
+    } else if let Some(index) = delete {
+        tool.measurements.remove(index);
+    }
+    if active != tool.active {
+        tool.active = active;
+        tool.pending = None;
+    }
+}
@@ -0,0 +1,232 @@
+use std::path::Path;
+
+use bevy::{app::AppExit, prelude::*};
+use bevy_egui::egui;
+
+use crate::{
+    commands::{Action, ActionEvent, Keybindings},
+    confirm::{confirms_quit, Confirmation, DestructiveAction},
+    i18n::t,
+    launcher_events::FileAdded,
+    render_queue::RenderProcesses,
+    settings::{Settings, Theme},
+    toasts::{Severity, Toasts},
+    AppState,
+};
+
+/// How many files File ▸ Recent remembers
+const MAX_RECENT_FILES: usize = 10;
+
+/// A menu entry that needs more than the settings the menus edit in place
+pub enum MenuCommand {
+    Action(Action),
+    /// Adds the file back to the list if it's gone, and selects it
+    OpenRecent(String),
+    /// Opens the picker for what to compare the selected object with
+    Compare,
+}
+
+#[derive(Default)]
+pub struct MenuResponse {
+    pub command: Option<MenuCommand>,
+    pub settings_changed: bool,
+}
+
+/// The collapsible panels, which View can show and hide
+pub struct PanelToggles<'a> {
+    pub top_collapsed: &'a mut bool,
+    pub bottom_collapsed: &'a mut bool,
+}
+
+pub struct MenuBarPlugin;
+
+impl Plugin for MenuBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(remember_recent_files.run_if(on_event::<FileAdded>()))
+            .add_system(quit.run_if(on_event::<ActionEvent>()));
+    }
+}
+
+/// Puts files added to the list at the top of the recent ones, newest first
+fn remember_recent_files(mut added: EventReader<FileAdded>, mut settings: ResMut<Settings>) {
+    for FileAdded { path, .. } in added.iter() {
+        let recent = &mut settings.recent_files;
+        recent.retain(|recent| recent != path);
+        recent.insert(0, path.clone());
+        recent.truncate(MAX_RECENT_FILES);
+    }
+}
+
+fn quit(
+    mut actions: EventReader<ActionEvent>,
+    mut exits: EventWriter<AppExit>,
+    settings: Res<Settings>,
+    render_processes: Res<RenderProcesses>,
+    mut confirmation: ResMut<Confirmation>,
+) {
+    if actions
+        .iter()
+        .any(|ActionEvent(action)| *action == Action::Quit)
+    {
+        if confirms_quit(&settings, &render_processes) {
+            confirmation.request(DestructiveAction::Quit);
+        } else {
+            exits.send(AppExit);
+        }
+    }
+}
+
+/// Selects a recent file, adding it back to the list first if it was removed.
+/// Files that no longer exist are dropped from the recent ones.
+pub fn open_recent(
+    app_state: &mut AppState,
+    settings: &mut Settings,
+    toasts: &mut Toasts,
+    path: String,
+) {
+    if let Some(file) = app_state.files.iter().find(|file| file.path == path) {
+        let file_id = file.id;
+        app_state.select_file(Some(file_id));
+        return;
+    }
+    if !Path::new(&path).is_file() {
+        warn!(path, "recent file is gone");
+        toasts.push(Severity::Warning, t!("menu.recent_missing", path = &path));
+        settings.recent_files.retain(|recent| *recent != path);
+        return;
+    }
+    info!(path, "added recent file");
+    let file_id = app_state.add_file(path);
+    app_state.select_file(Some(file_id));
+}
+
+/// File, View, Tools and Help, for a `egui::menu::bar`. The entries send the same actions as
+/// the buttons and shortcuts elsewhere, and show their shortcuts.
+pub fn menu_bar_ui(
+    ui: &mut egui::Ui,
+    keybindings: &Keybindings,
+    settings: &mut Settings,
+    can_compare: bool,
+    panels: PanelToggles,
+) -> MenuResponse {
+    let mut response = MenuResponse::default();
+    let mut command = None;
+
+    ui.menu_button(t!("menu.file"), |ui| {
+        action_button(ui, keybindings, Action::OpenFiles, &mut command);
+        action_button(ui, keybindings, Action::ScanFolder, &mut command);
+        ui.menu_button(t!("menu.recent"), |ui| {
+            if settings.recent_files.is_empty() {
+                ui.weak(t!("menu.no_recent"));
+            }
+            for path in settings.recent_files.iter() {
+                let name = Path::new(path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.clone());
+                if ui.button(name).on_hover_text(path).clicked() {
+                    command = Some(MenuCommand::OpenRecent(path.clone()));
+                    ui.close_menu();
+                }
+            }
+            if !settings.recent_files.is_empty() {
+                ui.separator();
+                if ui.button(t!("menu.clear_recent")).clicked() {
+                    settings.recent_files.clear();
+                    response.settings_changed = true;
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.separator();
+        action_button(ui, keybindings, Action::ImportLayout, &mut command);
+        ui.menu_button(t!("menu.export"), |ui| {
+            action_button(ui, keybindings, Action::ExportMetadata, &mut command);
+            action_button(ui, keybindings, Action::ExportContactSheet, &mut command);
+            action_button(ui, keybindings, Action::ExportLayout, &mut command);
+        });
+        ui.separator();
+        action_button(ui, keybindings, Action::Quit, &mut command);
+    });
+
+    ui.menu_button(t!("menu.view"), |ui| {
+        let mut top_shown = !*panels.top_collapsed;
+        if ui.checkbox(&mut top_shown, t!("panel.top")).changed() {
+            *panels.top_collapsed = !top_shown;
+        }
+        let mut bottom_shown = !*panels.bottom_collapsed;
+        if ui.checkbox(&mut bottom_shown, t!("panel.bottom")).changed() {
+            *panels.bottom_collapsed = !bottom_shown;
+        }
+        action_button(ui, keybindings, Action::DetachPreview, &mut command);
+        ui.separator();
+        ui.menu_button(t!("settings.theme"), |ui| {
+            for (theme, label) in [
+                (Theme::Dark, t!("theme.dark")),
+                (Theme::Light, t!("theme.light")),
+                (Theme::System, t!("theme.system")),
+            ] {
+                response.settings_changed |=
+                    ui.radio_value(&mut settings.theme, theme, label).changed();
+            }
+        });
+        ui.separator();
+        for (shown, label) in [
+            (&mut settings.show_overlays, t!("settings.show_overlays")),
+            (&mut settings.show_origins, t!("settings.show_origins")),
+            (&mut settings.show_empties, t!("settings.show_empties")),
+            (
+                &mut settings.highlight_backfaces,
+                t!("settings.highlight_backfaces"),
+            ),
+            (&mut settings.debug_overlay, t!("settings.debug_overlay")),
+        ] {
+            response.settings_changed |= ui.checkbox(shown, label).changed();
+        }
+    });
+
+    ui.menu_button(t!("menu.tools"), |ui| {
+        action_button(ui, keybindings, Action::ShowRenderQueue, &mut command);
+        action_button(ui, keybindings, Action::ShowBuilds, &mut command);
+        action_button(ui, keybindings, Action::ToggleMeasure, &mut command);
+        if ui
+            .add_enabled(can_compare, egui::Button::new(t!("actions.compare_with")))
+            .on_disabled_hover_text(t!("menu.compare_needs_object"))
+            .clicked()
+        {
+            command = Some(MenuCommand::Compare);
+            ui.close_menu();
+        }
+        ui.separator();
+        action_button(ui, keybindings, Action::CommandPalette, &mut command);
+    });
+
+    ui.menu_button(t!("menu.help"), |ui| {
+        action_button(ui, keybindings, Action::ToggleHelp, &mut command);
+        ui.separator();
+        action_button(ui, keybindings, Action::ShowAbout, &mut command);
+    });
+
+    response.command = command;
+    response
+}
+
+/// An entry named after its command, with the shortcut it's bound to
+fn action_button(
+    ui: &mut egui::Ui,
+    keybindings: &Keybindings,
+    action: Action,
+    clicked: &mut Option<MenuCommand>,
+) {
+    let name = keybindings
+        .command(action)
+        .map_or("", |command| command.name());
+    let mut button = egui::Button::new(name);
+    if let Some(chord) = keybindings.chord(action) {
+        button = button.shortcut_text(chord.label());
+    }
+    if ui.add(button).clicked() {
+        *clicked = Some(MenuCommand::Action(action));
+        ui.close_menu();
+    }
+}
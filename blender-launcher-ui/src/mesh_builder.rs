@@ -0,0 +1,569 @@
+use std::path::Path;
+
+use bevy::{
+    asset::HandleId,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use blend::Instance;
+use futures_lite::future;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_cache::AssetCache,
+    curves::to_bevy,
+    i18n::t,
+    metadata::{face_spans, id_name, open_blend, vertex_positions, OB_MESH},
+    settings::Settings,
+    toasts::{Severity, Toasts},
+};
+
+/// Blender's `CD_MLOOPUV` layer type, UV maps before 3.5
+const CD_MLOOPUV: i32 = 16;
+/// Blender's `CD_PROP_INT32` layer type, which holds face corners and material indices
+/// since 3.4–3.6
+const CD_PROP_INT32: i32 = 11;
+/// Blender's `CD_PROP_FLOAT2` layer type, UV maps since 3.5
+const CD_PROP_FLOAT2: i32 = 49;
+/// Blender's `CD_PROP_BOOL` layer type, which holds `sharp_face` since 3.6
+const CD_PROP_BOOL: i32 = 50;
+/// Largest face we triangulate, in case a broken file claims a huge corner count
+const MAX_FACE_CORNERS: usize = 65536;
+
+/// What turns an object into a preview mesh
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MeshLoader {
+    /// bevy_blender's `file.blend#MEName` assets, the same path a game would load
+    #[default]
+    BevyBlender,
+    /// Our own reader, which follows the object to its mesh data instead of going by name
+    BuiltIn,
+}
+
+impl MeshLoader {
+    pub const ALL: [MeshLoader; 2] = [MeshLoader::BevyBlender, MeshLoader::BuiltIn];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MeshLoader::BevyBlender => t!("loader.bevy_blender"),
+            MeshLoader::BuiltIn => t!("loader.built_in"),
+        }
+    }
+}
+
+/// Why an `ME` block couldn't be turned into a mesh
+#[derive(Clone, Debug, PartialEq)]
+pub enum MeshDataError {
+    /// The faces have no vertex positions to point at
+    MissingPositions,
+    /// There are faces but no face corners
+    MissingCorners,
+    /// A face reaches past the corners, or a corner past the vertices
+    Malformed { face: usize },
+}
+
+impl MeshDataError {
+    pub fn describe(&self) -> String {
+        match self {
+            MeshDataError::MissingPositions => t!("loader.missing_positions").to_string(),
+            MeshDataError::MissingCorners => t!("loader.missing_corners").to_string(),
+            MeshDataError::Malformed { face } => t!("loader.malformed", face = face),
+        }
+    }
+}
+
+/// The face data of an `ME` block, on Blender's axes, enough to build a mesh ourselves
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshData {
+    pub positions: Vec<[f32; 3]>,
+    pub faces: Vec<Face>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Face {
+    /// Indices into `positions`, in Blender's winding order
+    pub vertices: Vec<u32>,
+    /// UV layer 0 at each corner, empty when the mesh has no UV map
+    pub uvs: Vec<[f32; 2]>,
+    /// The face's material slot
+    pub material: usize,
+    /// Shade smooth rather than flat
+    pub smooth: bool,
+}
+
+impl MeshData {
+    /// Reads the vertices and faces of an `ME` block. Before 3.6 faces are `MPoly` structs
+    /// indexing `MLoop` corners, after that an offset array indexing a `.corner_vert`
+    /// attribute. Material indices moved from `MPoly::mat_nr` to a `material_index`
+    /// attribute in 3.4, smooth shading from `MPoly::flag` to a `sharp_face` attribute in 3.6.
+    pub fn from_mesh(mesh: &Instance) -> Result<Self, MeshDataError> {
+        let positions: Vec<[f32; 3]> = vertex_positions(mesh)
+            .chunks_exact(3)
+            .map(|vertex| [vertex[0], vertex[1], vertex[2]])
+            .collect();
+        let ldata = mesh.get("ldata");
+        let pdata = mesh.get("pdata");
+
+        let spans = face_spans(mesh);
+        let corners: Vec<i32> = if mesh.is_valid("mloop") {
+            mesh.get_iter("mloop")
+                .map(|corner| corner.get_i32("v"))
+                .collect()
+        } else if spans.is_empty() {
+            Vec::new()
+        } else {
+            int_layer(&ldata, ".corner_vert").unwrap_or_default()
+        };
+
+        if !spans.is_empty() {
+            if positions.is_empty() {
+                return Err(MeshDataError::MissingPositions);
+            }
+            if corners.is_empty() {
+                return Err(MeshDataError::MissingCorners);
+            }
+        }
+        let materials = int_layer(&pdata, "material_index");
+        let sharp = layer(&pdata, CD_PROP_BOOL, "sharp_face").map(|layer| layer.get_u8_vec("data"));
+        let uvs = uv_layer(&ldata).filter(|uvs| uvs.len() == corners.len());
+
+        let mut faces = Vec::with_capacity(spans.len());
+        for (index, span) in spans.into_iter().enumerate() {
+            let range = span.start..span.start + span.corners.min(MAX_FACE_CORNERS);
+            let vertices: Vec<u32> = corners
+                .get(range.clone())
+                .ok_or(MeshDataError::Malformed { face: index })?
+                .iter()
+                .map(|vertex| *vertex as u32)
+                .collect();
+            if vertices
+                .iter()
+                .any(|vertex| *vertex as usize >= positions.len())
+            {
+                return Err(MeshDataError::Malformed { face: index });
+            }
+            faces.push(Face {
+                vertices,
+                uvs: uvs
+                    .as_ref()
+                    .map(|uvs| uvs[range].to_vec())
+                    .unwrap_or_default(),
+                material: materials
+                    .as_ref()
+                    .and_then(|materials| materials.get(index))
+                    .map_or(span.material, |slot| (*slot).max(0) as usize),
+                smooth: sharp
+                    .as_ref()
+                    .and_then(|sharp| sharp.get(index))
+                    .map_or(span.smooth, |sharp| *sharp == 0),
+            });
+        }
+
+        Ok(MeshData { positions, faces })
+    }
+
+    /// Faces using each material slot, indexed by slot
+    pub fn slot_face_counts(&self) -> Vec<usize> {
+        let mut counts = Vec::new();
+        for face in self.faces.iter() {
+            if counts.len() <= face.material {
+                counts.resize(face.material + 1, 0);
+            }
+            counts[face.material] += 1;
+        }
+        counts
+    }
+
+    /// A triangle mesh on Bevy's axes of the faces `keep` accepts, with a vertex per face
+    /// corner so UVs and flat faces keep their seams. Faces are fanned from their first
+    /// corner, which is exact for the convex faces most meshes are made of.
+    pub fn build(&self, keep: impl Fn(&Face) -> bool) -> Mesh {
+        let positions: Vec<Vec3> = self
+            .positions
+            .iter()
+            .map(|position| Vec3::from(to_bevy(*position)))
+            .collect();
+        let face_normals: Vec<Vec3> = self
+            .faces
+            .iter()
+            .map(|face| face_normal(&positions, &face.vertices))
+            .collect();
+        // Smooth faces share their vertices' normals, weighted by the area of every face
+        // around them, kept or not, so isolating faces doesn't change how they shade
+        let mut vertex_normals = vec![Vec3::ZERO; positions.len()];
+        for (face, normal) in self.faces.iter().zip(face_normals.iter()) {
+            for vertex in face.vertices.iter() {
+                vertex_normals[*vertex as usize] += *normal;
+            }
+        }
+
+        let mut mesh_positions: Vec<[f32; 3]> = Vec::new();
+        let mut normals: Vec<[f32; 3]> = Vec::new();
+        let mut uvs: Vec<[f32; 2]> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        for (face, face_normal) in self.faces.iter().zip(face_normals.iter()) {
+            if face.vertices.len() < 3 || !keep(face) {
+                continue;
+            }
+            let first = mesh_positions.len() as u32;
+            for (corner, vertex) in face.vertices.iter().enumerate() {
+                let vertex = *vertex as usize;
+                mesh_positions.push(positions[vertex].to_array());
+                let normal = if face.smooth {
+                    vertex_normals[vertex].normalize_or_zero()
+                } else {
+                    face_normal.normalize_or_zero()
+                };
+                normals.push(normal.to_array());
+                // Blender's V runs up from the bottom of the image, Bevy's down from the top
+                let [u, v] = face.uvs.get(corner).copied().unwrap_or_default();
+                uvs.push([u, 1.0 - v]);
+            }
+            // The axis swap is a rotation, so Blender's counter-clockwise winding holds
+            for corner in 1..face.vertices.len() as u32 - 1 {
+                indices.extend([first, first + corner, first + corner + 1]);
+            }
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, mesh_positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
+
+/// Newell's method, which handles faces that aren't quite planar. Its length is twice the
+/// face's area.
+fn face_normal(positions: &[Vec3], vertices: &[u32]) -> Vec3 {
+    let mut normal = Vec3::ZERO;
+    for (index, vertex) in vertices.iter().enumerate() {
+        let current = positions[*vertex as usize];
+        let next = positions[vertices[(index + 1) % vertices.len()] as usize];
+        normal += Vec3::new(
+            (current.y - next.y) * (current.z + next.z),
+            (current.z - next.z) * (current.x + next.x),
+            (current.x - next.x) * (current.y + next.y),
+        );
+    }
+    normal
+}
+
+/// A layer of a `CustomData`, by type and name
+fn layer<'a>(custom_data: &Instance<'a>, kind: i32, name: &str) -> Option<Instance<'a>> {
+    if custom_data.get_i32("totlayer") <= 0 || !custom_data.is_valid("layers") {
+        return None;
+    }
+    custom_data
+        .get_iter("layers")
+        .find(|layer| layer.get_i32("type") == kind && layer.get_string("name") == name)
+        .filter(|layer| layer.is_valid("data"))
+}
+
+/// An `int` attribute from a `CustomData`, by name
+fn int_layer(custom_data: &Instance, name: &str) -> Option<Vec<i32>> {
+    layer(custom_data, CD_PROP_INT32, name).map(|layer| layer.get_i32_vec("data"))
+}
+
+/// The first UV map in the face corner `CustomData`, whichever kind of layer it's stored in
+fn uv_layer(ldata: &Instance) -> Option<Vec<[f32; 2]>> {
+    if ldata.get_i32("totlayer") <= 0 || !ldata.is_valid("layers") {
+        return None;
+    }
+    let layer = ldata
+        .get_iter("layers")
+        .find(|layer| matches!(layer.get_i32("type"), CD_MLOOPUV | CD_PROP_FLOAT2))
+        .filter(|layer| layer.is_valid("data"))?;
+    if layer.get_i32("type") == CD_MLOOPUV {
+        // `MLoopUV` structs, a UV and selection flags
+        Some(
+            layer
+                .get_iter("data")
+                .map(|corner| match corner.get_f32_vec("uv").as_slice() {
+                    [u, v, ..] => [*u, *v],
+                    _ => [0.0, 0.0],
+                })
+                .collect(),
+        )
+    } else {
+        Some(
+            layer
+                .get_f32_vec("data")
+                .chunks_exact(2)
+                .map(|uv| [uv[0], uv[1]])
+                .collect(),
+        )
+    }
+}
+
+/// Reads an object's mesh straight from its file, following the object to its `ME` block
+pub fn load_object_mesh(path: &str, object: &str) -> Result<Mesh, String> {
+    let blend = open_blend(Path::new(path))?;
+    let obj = blend
+        .instances_with_code(*b"OB")
+        .find(|obj| id_name(obj) == object)
+        .ok_or_else(|| t!("loader.no_object", name = object))?;
+    if obj.get_i16("type") != OB_MESH || !obj.is_valid("data") {
+        return Err(t!("loader.not_a_mesh", name = object));
+    }
+    let data = MeshData::from_mesh(&obj.get("data")).map_err(|error| error.describe())?;
+    Ok(data.build(|_| true))
+}
+
+/// Meshes the built-in loader is reading, with the handle each one goes into
+#[derive(Default, Resource)]
+struct BuiltInLoads {
+    tasks: Vec<(HandleId, String, String, Task<Result<Mesh, String>>)>,
+}
+
+pub struct BuiltInLoaderPlugin;
+
+impl Plugin for BuiltInLoaderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuiltInLoads>()
+            .add_system(use_chosen_loader.run_if(resource_changed::<Settings>()))
+            .add_system(
+                start_built_in_loads
+                    .run_if(|asset_cache: Res<AssetCache>| asset_cache.has_pending_builds()),
+            )
+            .add_system(
+                finish_built_in_loads.run_if(|loads: Res<BuiltInLoads>| !loads.tasks.is_empty()),
+            );
+    }
+}
+
+fn use_chosen_loader(settings: Res<Settings>, mut asset_cache: ResMut<AssetCache>) {
+    if asset_cache.loader() != settings.mesh_loader {
+        asset_cache.set_loader(settings.mesh_loader);
+    }
+}
+
+fn start_built_in_loads(mut asset_cache: ResMut<AssetCache>, mut loads: ResMut<BuiltInLoads>) {
+    let thread_pool = AsyncComputeTaskPool::get();
+    for (id, path, name) in asset_cache.take_pending_builds() {
+        let (task_path, task_name) = (path.clone(), name.clone());
+        let task = thread_pool.spawn(async move { load_object_mesh(&task_path, &task_name) });
+        loads.tasks.push((id, path, name, task));
+    }
+}
+
+fn finish_built_in_loads(
+    mut loads: ResMut<BuiltInLoads>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut toasts: ResMut<Toasts>,
+) {
+    let mut finished = Vec::new();
+    loads.tasks.retain_mut(|(id, path, name, task)| {
+        match future::block_on(future::poll_once(task)) {
+            Some(result) => {
+                finished.push((*id, std::mem::take(path), std::mem::take(name), result));
+                false
+            }
+            None => true,
+        }
+    });
+
+    for (id, path, name, result) in finished {
+        match result {
+            // The previews already hold handles to this id, so they pick the mesh up
+            Ok(mesh) => meshes.set_untracked(id, mesh),
+            Err(error) => {
+                warn!(path, object = name, %error, "built-in loader couldn't read mesh");
+                toasts.push(
+                    Severity::Error,
+                    t!("loader.failed", name = name, error = error),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, time::Duration};
+
+    use bevy::{asset::LoadState, render::mesh::VertexAttributeValues};
+
+    use super::*;
+    use crate::metadata::MeshStats;
+
+    const MONKEY_BLEND: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/test-monkey.blend");
+    const DEMO_BLEND: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/demo.blend");
+    /// demo.blend with its faces as the `poly_offset_indices` Blender 3.6 writes instead of
+    /// `mpoly`, made by `assets/make_fixtures.py`
+    const DEMO_3_6_FACES_BLEND: &str =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/assets/demo-3.6-faces.blend");
+
+    /// A unit cube with its top and bottom in slot 1 and its sides in slot 0
+    fn two_slot_cube() -> MeshData {
+        let positions = (0..8)
+            .map(|corner| {
+                [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1].map(|axis| axis as f32)
+            })
+            .collect();
+        let face = |vertices: [u32; 4], material| Face {
+            vertices: vertices.to_vec(),
+            uvs: Vec::new(),
+            material,
+            smooth: false,
+        };
+        MeshData {
+            positions,
+            faces: vec![
+                face([0, 2, 3, 1], 1),
+                face([4, 5, 7, 6], 1),
+                face([0, 1, 5, 4], 0),
+                face([1, 3, 7, 5], 0),
+                face([3, 2, 6, 7], 0),
+                face([2, 0, 4, 6], 0),
+            ],
+        }
+    }
+
+    fn positions(mesh: &Mesh) -> Vec<[f32; 3]> {
+        match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => positions.clone(),
+            _ => panic!("mesh has no positions"),
+        }
+    }
+
+    fn triangles(mesh: &Mesh) -> usize {
+        mesh.indices().map_or(0, |indices| indices.len() / 3)
+    }
+
+    /// Vertices by position, since the loaders split them along different seams
+    fn distinct_positions(mesh: &Mesh) -> usize {
+        positions(mesh)
+            .iter()
+            .map(|position| position.map(f32::to_bits))
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    #[test]
+    fn isolating_a_slot_keeps_only_its_faces() {
+        let cube = two_slot_cube();
+        assert_eq!(cube.slot_face_counts(), [4, 2]);
+
+        assert_eq!(triangles(&cube.build(|_| true)), 6 * 2);
+
+        // Top and bottom sit at Blender's z = 0 and z = 1, which is Bevy's y
+        let caps = cube.build(|face| face.material == 1);
+        assert_eq!(triangles(&caps), 2 * 2);
+        assert!(positions(&caps)
+            .iter()
+            .all(|[_, y, _]| *y == 0.0 || *y == 1.0));
+
+        assert_eq!(triangles(&cube.build(|face| face.material == 2)), 0);
+    }
+
+    #[test]
+    fn flat_normals_face_out_of_the_cube() {
+        let mesh = two_slot_cube().build(|_| true);
+        let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        else {
+            panic!("mesh has no normals");
+        };
+        let center = Vec3::new(0.5, 0.5, -0.5);
+        for (position, normal) in positions(&mesh).iter().zip(normals) {
+            let outward = Vec3::from(*position) - center;
+            assert!((outward.dot(Vec3::from(*normal)) - 0.5).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn reads_every_face_of_a_mesh_block() {
+        let blend = open_blend(Path::new(MONKEY_BLEND)).unwrap();
+        let mesh = blend.instances_with_code(*b"ME").next().unwrap();
+        let stats = MeshStats::from_mesh(&mesh);
+        let data = MeshData::from_mesh(&mesh).unwrap();
+
+        assert_eq!(data.positions.len(), stats.vertices);
+        assert_eq!(data.faces.len(), stats.faces);
+        assert_eq!(triangles(&data.build(|_| true)), stats.triangles);
+    }
+
+    #[test]
+    fn face_offsets_read_the_same_as_polygons() {
+        // (name, triangles, ngons, faces built) of each mesh
+        let meshes = |path: &str| -> Vec<(String, usize, usize, usize)> {
+            let blend = open_blend(Path::new(path)).unwrap();
+            let meshes = blend
+                .instances_with_code(*b"ME")
+                .map(|mesh| {
+                    let stats = MeshStats::from_mesh(&mesh);
+                    let data = MeshData::from_mesh(&mesh).unwrap();
+                    (
+                        id_name(&mesh),
+                        stats.triangles,
+                        stats.ngons,
+                        data.faces.len(),
+                    )
+                })
+                .collect();
+            meshes
+        };
+        let offsets = meshes(DEMO_3_6_FACES_BLEND);
+        assert_eq!(offsets, meshes(DEMO_BLEND));
+
+        let cylinder = offsets
+            .iter()
+            .find(|(name, ..)| name == "Cylinder")
+            .unwrap();
+        // 32 sides and the two caps
+        assert_eq!(cylinder, &("Cylinder".to_string(), 124, 2, 34));
+    }
+
+    #[test]
+    fn built_in_loader_matches_bevy_blender() {
+        let name = {
+            let blend = open_blend(Path::new(MONKEY_BLEND)).unwrap();
+            let obj = blend
+                .instances_with_code(*b"OB")
+                .find(|obj| obj.get_i16("type") == OB_MESH)
+                .unwrap();
+            id_name(&obj)
+        };
+        let built_in = load_object_mesh(MONKEY_BLEND, &name).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin {
+                asset_folder: concat!(env!("CARGO_MANIFEST_DIR"), "/assets").to_string(),
+                ..Default::default()
+            })
+            .add_asset::<Mesh>()
+            .add_asset::<Image>()
+            .add_asset::<StandardMaterial>()
+            .add_plugin(bevy_blender::BlenderPlugin);
+        let handle: Handle<Mesh> = app
+            .world
+            .resource::<AssetServer>()
+            .load(format!("test-monkey.blend#ME{}", name));
+        for _ in 0..500 {
+            app.update();
+            match app.world.resource::<AssetServer>().get_load_state(&handle) {
+                LoadState::Loaded | LoadState::Failed => break,
+                _ => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+        let meshes = app.world.resource::<Assets<Mesh>>();
+        let bevy_blender = meshes
+            .get(&handle)
+            .expect("bevy_blender didn't load the mesh");
+
+        assert_eq!(
+            distinct_positions(&built_in),
+            distinct_positions(bevy_blender)
+        );
+    }
+
+    #[test]
+    fn loading_a_missing_object_names_it() {
+        let error = load_object_mesh(MONKEY_BLEND, "Not in the file").unwrap_err();
+        assert!(error.contains("Not in the file"));
+    }
+}
@@ -0,0 +1,1015 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufReader, Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    log::warn,
+    math::{EulerRot, Mat4, Quat, Vec3, Vec4},
+    prelude::Transform,
+};
+use blend::{Blend, Instance};
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+
+use crate::{builds::BlenderVersion, i18n::t};
+
+/// Blender's `OB_MESH` object type
+pub const OB_MESH: i16 = 1;
+/// Blender's `CD_PROP_FLOAT3` layer type, which holds vertex positions since 3.5
+const CD_PROP_FLOAT3: i32 = 48;
+/// Blender's `CD_PROP_INT32_2D` layer type, which holds edges since 3.6
+const CD_PROP_INT32_2D: i32 = 46;
+/// `ME_SMOOTH`, the smooth shading bit of `MPoly::flag` before 3.6
+const ME_SMOOTH: u8 = 1;
+/// Longest modifier stack we follow, in case a broken file links the list in a loop
+const MAX_MODIFIERS: usize = 1024;
+/// Blender's `Object::rotmode` values that aren't an Euler order
+const ROT_MODE_QUAT: i16 = 0;
+const ROT_MODE_AXIS_ANGLE: i16 = -1;
+/// Maps Blender's Z-up axes onto Bevy's Y-up ones, `[x, y, z]` to `[x, z, -y]`
+const BLENDER_TO_BEVY: Mat4 = Mat4::from_cols(Vec4::X, Vec4::NEG_Z, Vec4::Y, Vec4::W);
+/// Longest constraint stack or driver list we follow, for the same reason
+const MAX_CONSTRAINTS: usize = 1024;
+const MAX_DRIVERS: usize = 65536;
+/// Blender's `IMA_SRC_GENERATED` image source, blank and grid textures made in Blender
+const IMA_SRC_GENERATED: i16 = 4;
+/// Blender's `IMA_SRC_VIEWER` image source, used for render results and compositor viewers
+const IMA_SRC_VIEWER: i16 = 5;
+/// Blender's `IMA_SRC_TILED` image source, UDIMs with a file per tile
+const IMA_SRC_TILED: i16 = 6;
+/// The first UDIM tile, which is the only one when a tiled image doesn't list its tiles
+const FIRST_UDIM_TILE: u32 = 1001;
+/// Blender's `CD_PROP_BYTE_COLOR` (formerly `CD_MLOOPCOL`) and `CD_PROP_COLOR` layer types
+const CD_COLOR_TYPES: [i32; 2] = [17, 47];
+
+/// What an object is, from its `type` field
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ObjectKind {
+    Empty,
+    Mesh,
+    Curve,
+    Surface,
+    Text,
+    Metaball,
+    Light,
+    Camera,
+    Speaker,
+    LightProbe,
+    Lattice,
+    Armature,
+    GreasePencil,
+    Curves,
+    PointCloud,
+    Volume,
+    /// A type this build doesn't know about, with its raw value
+    Other(i16),
+}
+
+impl ObjectKind {
+    /// Maps the `OB_*` constants from `DNA_object_types.h`
+    pub fn from_type(object_type: i16) -> Self {
+        match object_type {
+            0 => ObjectKind::Empty,
+            OB_MESH => ObjectKind::Mesh,
+            2 => ObjectKind::Curve,
+            3 => ObjectKind::Surface,
+            4 => ObjectKind::Text,
+            5 => ObjectKind::Metaball,
+            10 => ObjectKind::Light,
+            11 => ObjectKind::Camera,
+            12 => ObjectKind::Speaker,
+            13 => ObjectKind::LightProbe,
+            22 => ObjectKind::Lattice,
+            25 => ObjectKind::Armature,
+            // The legacy and the 4.3 Grease Pencil
+            26 | 30 => ObjectKind::GreasePencil,
+            27 => ObjectKind::Curves,
+            28 => ObjectKind::PointCloud,
+            29 => ObjectKind::Volume,
+            other => ObjectKind::Other(other),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ObjectKind::Empty => t!("object_kind.empty"),
+            ObjectKind::Mesh => t!("object_kind.mesh"),
+            ObjectKind::Curve => t!("object_kind.curve"),
+            ObjectKind::Surface => t!("object_kind.surface"),
+            ObjectKind::Text => t!("object_kind.text"),
+            ObjectKind::Metaball => t!("object_kind.metaball"),
+            ObjectKind::Light => t!("object_kind.light"),
+            ObjectKind::Camera => t!("object_kind.camera"),
+            ObjectKind::Speaker => t!("object_kind.speaker"),
+            ObjectKind::LightProbe => t!("object_kind.light_probe"),
+            ObjectKind::Lattice => t!("object_kind.lattice"),
+            ObjectKind::Armature => t!("object_kind.armature"),
+            ObjectKind::GreasePencil => t!("object_kind.grease_pencil"),
+            ObjectKind::Curves => t!("object_kind.curves"),
+            ObjectKind::PointCloud => t!("object_kind.point_cloud"),
+            ObjectKind::Volume => t!("object_kind.volume"),
+            ObjectKind::Other(_) => t!("object_kind.other"),
+        }
+    }
+
+    /// A glyph for the object list, standing in for Blender's outliner icons
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ObjectKind::Empty => "✚",
+            ObjectKind::Mesh => "⬟",
+            ObjectKind::Curve => "〰",
+            ObjectKind::Surface => "◒",
+            ObjectKind::Text => "🗛",
+            ObjectKind::Metaball => "⚫",
+            ObjectKind::Light => "💡",
+            ObjectKind::Camera => "📷",
+            ObjectKind::Speaker => "🔊",
+            ObjectKind::LightProbe => "◎",
+            ObjectKind::Lattice => "▦",
+            ObjectKind::Armature => "☗",
+            ObjectKind::GreasePencil => "✏",
+            ObjectKind::Curves => "〃",
+            ObjectKind::PointCloud => "⁘",
+            ObjectKind::Volume => "☁",
+            ObjectKind::Other(_) => "?",
+        }
+    }
+
+    /// Whether we have anything to show for the object: meshes load,
+    /// curves and text get outlines, and empties get markers
+    pub fn is_previewable(&self) -> bool {
+        matches!(
+            self,
+            ObjectKind::Empty | ObjectKind::Mesh | ObjectKind::Curve | ObjectKind::Text
+        )
+    }
+}
+
+/// Opens a blend file by memory-mapping it, falling back to a buffered read when the file
+/// can't be mapped (e.g. some network drives). This doesn't save memory: the blend crate
+/// reads all of its input into its own buffer, so either way the whole file ends up on
+/// the heap. `benches/memory.rs` compares the two.
+pub fn open_blend(path: &Path) -> Result<Blend, String> {
+    let file = fs::File::open(path).map_err(|error| error.to_string())?;
+
+    // SAFETY: the map is only read while parsing, and dropped before we return.
+    // If another process truncates the file mid-parse we can get a SIGBUS,
+    // which is the same tradeoff every mmap based reader makes.
+    let blend = match unsafe { Mmap::map(&file) } {
+        Ok(mmap) => Blend::new(&mmap[..]),
+        Err(error) => {
+            warn!(path = %path.display(), %error, "couldn't memory-map, reading instead");
+            Blend::new(BufReader::new(file))
+        }
+    };
+
+    blend.map_err(|error| format!("{:?}", error))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Endianness::Little => t!("header.little_endian"),
+            Endianness::Big => t!("header.big_endian"),
+        }
+    }
+}
+
+/// How a file was written, from its header
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlendHeader {
+    /// In bytes, 4 for files saved by 32-bit builds
+    pub pointer_size: u8,
+    pub endianness: Endianness,
+    /// The Blender version the file was last saved with
+    pub version: BlenderVersion,
+}
+
+/// Reads the header at the start of a file's bytes.
+/// Compressed files don't start with a header, so they give `None`.
+pub fn parse_header(bytes: &[u8]) -> Option<BlendHeader> {
+    let rest = bytes.strip_prefix(b"BLENDER")?;
+
+    // `BLENDER_v402` up to 4.x (pointer size, endianness, version),
+    // `BLENDER17-01v0500` from 5.0 on (header size, format, endianness, version),
+    // which only exists with 64-bit pointers
+    let (pointer_size, endianness, digits) = match rest.first()? {
+        b'_' => (4, rest.get(1)?, rest.get(2..5)?),
+        b'-' => (8, rest.get(1)?, rest.get(2..5)?),
+        digit if digit.is_ascii_digit() => (8, rest.get(5)?, rest.get(6..10)?),
+        _ => return None,
+    };
+    let endianness = match endianness {
+        b'v' => Endianness::Little,
+        b'V' => Endianness::Big,
+        _ => return None,
+    };
+    let number: u32 = std::str::from_utf8(digits).ok()?.parse().ok()?;
+    Some(BlendHeader {
+        pointer_size,
+        endianness,
+        version: BlenderVersion {
+            major: number / 100,
+            minor: number % 100,
+            patch: 0,
+        },
+    })
+}
+
+/// The header of the file at `path`, see `parse_header`
+pub fn read_header(path: &Path) -> Option<BlendHeader> {
+    let mut header = [0; 17];
+    let read = fs::File::open(path).ok()?.read(&mut header).ok()?;
+    parse_header(&header[..read])
+}
+
+/// Counts parsed from a mesh (`ME`) block
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MeshStats {
+    pub vertices: usize,
+    pub edges: usize,
+    pub faces: usize,
+    /// Triangles after triangulation (each polygon becomes `loops - 2` triangles)
+    pub triangles: usize,
+    /// Faces with more than 4 vertices
+    pub ngons: usize,
+    /// Names of the color attributes stored on the vertices or face corners
+    pub color_attributes: Vec<String>,
+    /// Vertices that no edge uses. Only the file knows about them, the runtime mesh is built
+    /// from the faces.
+    #[serde(default)]
+    pub loose_vertices: usize,
+}
+
+impl MeshStats {
+    /// Reads the stats from an `ME` block using only its face corner counts,
+    /// so we never have to triangulate any vertex data
+    pub fn from_mesh(mesh: &Instance) -> Self {
+        let mut stats = MeshStats {
+            vertices: mesh.get_i32("totvert").max(0) as usize,
+            edges: mesh.get_i32("totedge").max(0) as usize,
+            faces: mesh.get_i32("totpoly").max(0) as usize,
+            color_attributes: color_attributes(mesh),
+            ..Default::default()
+        };
+        if let Some(edges) = edge_vertices(mesh) {
+            stats.loose_vertices = count_loose_vertices(stats.vertices, &edges);
+        }
+
+        for face in face_spans(mesh) {
+            stats.triangles += face.corners.saturating_sub(2);
+            if face.corners > 4 {
+                stats.ngons += 1;
+            }
+        }
+
+        stats
+    }
+}
+
+/// The vertex indices at the ends of each edge, from `MEdge` structs before 3.6 and an
+/// `.edge_verts` attribute after. `None` when the mesh has edges we can't find.
+fn edge_vertices(mesh: &Instance) -> Option<Vec<i32>> {
+    if mesh.is_valid("medge") {
+        return Some(
+            mesh.get_iter("medge")
+                .flat_map(|edge| [edge.get_i32("v1"), edge.get_i32("v2")])
+                .collect(),
+        );
+    }
+    let edata = mesh.get("edata");
+    let edges = if edata.get_i32("totlayer") > 0 && edata.is_valid("layers") {
+        edata
+            .get_iter("layers")
+            .find(|layer| {
+                layer.get_i32("type") == CD_PROP_INT32_2D
+                    && layer.get_string("name") == ".edge_verts"
+            })
+            .filter(|layer| layer.is_valid("data"))
+            .map(|layer| layer.get_i32_vec("data"))
+    } else {
+        None
+    };
+    edges.or_else(|| (mesh.get_i32("totedge") <= 0).then(Vec::new))
+}
+
+/// How many of a mesh's vertices aren't at either end of any of the edges, given as pairs
+/// of vertex indices. Out of range indices are ignored.
+pub fn count_loose_vertices(vertex_count: usize, edge_vertices: &[i32]) -> usize {
+    let mut used = vec![false; vertex_count];
+    for vertex in edge_vertices {
+        if let Some(used) = usize::try_from(*vertex)
+            .ok()
+            .and_then(|vertex| used.get_mut(vertex))
+        {
+            *used = true;
+        }
+    }
+    used.iter().filter(|used| !**used).count()
+}
+
+/// Color layers in the mesh's vertex (`vdata`) and face corner (`ldata`) `CustomData`
+fn color_attributes(mesh: &Instance) -> Vec<String> {
+    let mut names = Vec::new();
+    for custom_data in ["vdata", "ldata"] {
+        let custom_data = mesh.get(custom_data);
+        if custom_data.get_i32("totlayer") <= 0 || !custom_data.is_valid("layers") {
+            continue;
+        }
+        for layer in custom_data.get_iter("layers") {
+            if CD_COLOR_TYPES.contains(&layer.get_i32("type")) {
+                names.push(layer.get_string("name"));
+            }
+        }
+    }
+    names
+}
+
+/// Where an image block gets its pixels from
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageSource {
+    /// The image data is stored inside the .blend file
+    Packed,
+    /// The image is a file on disk next to (or relative to) the .blend file
+    Linked {
+        path: PathBuf,
+        /// UDIM tile numbers of a tiled image, whose path has a `<UDIM>` or `<UVTILE>` token
+        /// standing in for each tile's file
+        #[serde(default)]
+        tiles: Vec<u32>,
+        /// Whether every file behind the image is there
+        exists: bool,
+    },
+    /// Generated in Blender, like a blank or UV grid texture, so there's no file behind it
+    Generated,
+}
+
+/// The files behind a linked image, one for each tile of a tiled image
+pub fn image_files(path: &Path, tiles: &[u32]) -> Vec<PathBuf> {
+    if tiles.is_empty() {
+        return vec![path.to_path_buf()];
+    }
+    let template = path.to_string_lossy();
+    tiles
+        .iter()
+        .map(|tile| {
+            let index = tile.saturating_sub(FIRST_UDIM_TILE);
+            let uv_tile = format!("u{}_v{}", index % 10 + 1, index / 10 + 1);
+            PathBuf::from(
+                template
+                    .replace("<UDIM>", &tile.to_string())
+                    .replace("<UVTILE>", &uv_tile),
+            )
+        })
+        .collect()
+}
+
+/// Whether every file behind a linked image is on disk
+pub fn image_files_exist(path: &Path, tiles: &[u32]) -> bool {
+    image_files(path, tiles).iter().all(|file| file.is_file())
+}
+
+/// An image (`IM`) block and the materials that use it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImageInfo {
+    pub name: String,
+    pub source: ImageSource,
+    /// Width and height in pixels, if we could read the image header
+    pub resolution: Option<(u32, u32)>,
+    pub materials: Vec<String>,
+}
+
+impl ImageInfo {
+    /// Linked images whose file can't be found on disk
+    pub fn is_broken(&self) -> bool {
+        matches!(self.source, ImageSource::Linked { exists: false, .. })
+    }
+
+    /// Is either side of the image larger than `max_size`?
+    pub fn exceeds(&self, max_size: u32) -> bool {
+        self.resolution
+            .is_some_and(|(width, height)| width.max(height) > max_size)
+    }
+}
+
+/// `LIB_FAKEUSER` in `ID::flag`, the shield button in Blender
+const LIB_FAKEUSER: i16 = 1 << 9;
+
+/// How many users a datablock has, from its `ID` header
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdUsers {
+    /// `ID::us`, which counts the fake user too
+    pub users: i32,
+    pub fake_user: bool,
+}
+
+impl IdUsers {
+    pub fn of(block: &Instance) -> Self {
+        let id = block.get("id");
+        Self {
+            users: id.get_i32("us"),
+            fake_user: id.get_i16("flag") & LIB_FAKEUSER != 0,
+        }
+    }
+
+    /// Users besides the fake one. Data at 0 is only kept by its fake user, if at all.
+    pub fn real_users(&self) -> i32 {
+        self.users - self.fake_user as i32
+    }
+
+    /// A shield for fake user data and a tag for orphans, to follow the name in lists
+    pub fn badges(&self) -> String {
+        let mut badges = String::new();
+        if self.fake_user {
+            badges.push_str(" 🛡");
+        }
+        if self.real_users() <= 0 {
+            badges.push_str(&format!(" [{}]", t!("datablock.no_users")));
+        }
+        badges
+    }
+}
+
+/// Grabs the name of a datablock without the 2 letter code prefix blend adds (e.g. `OBCube`)
+pub fn id_name(block: &Instance) -> String {
+    let mut name = block.get("id").get_string("name");
+    if name.len() >= 2 && name.is_char_boundary(2) {
+        name.split_off(2)
+    } else {
+        name
+    }
+}
+
+/// Walks a Blender `ListBase` by following each item's `next` pointer
+pub fn list_iter<'a>(list: &Instance<'a>) -> impl Iterator<Item = Instance<'a>> {
+    let mut next = list.is_valid("first").then(|| list.get("first"));
+    std::iter::from_fn(move || {
+        let current = next.take()?;
+        if current.is_valid("next") {
+            next = Some(current.get("next"));
+        }
+        Some(current)
+    })
+}
+
+/// Names of the materials in an object's slots.
+/// Slots can be linked to the object itself or to its mesh data, so we check both.
+pub fn material_slots(obj: &Instance) -> Vec<String> {
+    let mut materials = Vec::new();
+    let mut collect_slots = |block: &Instance| {
+        if block.get_i16("totcol") <= 0 || !block.is_valid("mat") {
+            return;
+        }
+        for material in block.get_iter("mat") {
+            let name = id_name(&material);
+            if !materials.contains(&name) {
+                materials.push(name);
+            }
+        }
+    };
+
+    collect_slots(obj);
+    if obj.get_i16("type") == OB_MESH && obj.is_valid("data") {
+        collect_slots(&obj.get("data"));
+    }
+
+    materials
+}
+
+/// Axis aligned bounds of a mesh in its own space, on Blender's Z-up axes
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Bounds {
+    pub fn size(&self) -> [f32; 3] {
+        [0, 1, 2].map(|axis| self.max[axis] - self.min[axis])
+    }
+}
+
+/// A mesh's vertex positions as flat x, y, z triples. Before 3.5 they're `MVert` structs
+/// in `mvert`, after that a `position` attribute in the vertex `CustomData`.
+pub fn vertex_positions(mesh: &Instance) -> Vec<f32> {
+    if mesh.is_valid("mvert") {
+        return mesh
+            .get_iter("mvert")
+            .flat_map(|vertex| vertex.get_f32_vec("co"))
+            .collect();
+    }
+    let vdata = mesh.get("vdata");
+    if vdata.get_i32("totlayer") <= 0 || !vdata.is_valid("layers") {
+        return Vec::new();
+    }
+    vdata
+        .get_iter("layers")
+        .find(|layer| {
+            layer.get_i32("type") == CD_PROP_FLOAT3 && layer.get_string("name") == "position"
+        })
+        .filter(|layer| layer.is_valid("data"))
+        .map(|layer| layer.get_f32_vec("data"))
+        .unwrap_or_default()
+}
+
+/// Where a face's corners are in the mesh's corner array, and how it's shaded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FaceSpan {
+    /// The face's first corner
+    pub start: usize,
+    pub corners: usize,
+    /// The face's material slot, before 3.4 moved it to a `material_index` attribute
+    pub material: usize,
+    /// Shade smooth, before 3.6 moved it to a `sharp_face` attribute
+    pub smooth: bool,
+}
+
+/// A mesh's faces. Before 3.6 they're `MPoly` structs in `mpoly`, after that an offset
+/// array in `poly_offset_indices` with one more entry than there are faces.
+pub fn face_spans(mesh: &Instance) -> Vec<FaceSpan> {
+    if mesh.is_valid("mpoly") {
+        return mesh
+            .get_iter("mpoly")
+            .map(|poly| FaceSpan {
+                start: poly.get_i32("loopstart").max(0) as usize,
+                corners: poly.get_i32("totloop").max(0) as usize,
+                material: poly.get_i16("mat_nr").max(0) as usize,
+                smooth: poly.get_u8("flag") & ME_SMOOTH != 0,
+            })
+            .collect();
+    }
+    // Empty meshes have no face data at all
+    if !mesh.is_valid("poly_offset_indices") {
+        return Vec::new();
+    }
+    mesh.get_i32_vec("poly_offset_indices")
+        .windows(2)
+        .map(|pair| {
+            let start = pair[0].max(0) as usize;
+            FaceSpan {
+                start,
+                corners: (pair[1].max(0) as usize).saturating_sub(start),
+                material: 0,
+                smooth: true,
+            }
+        })
+        .collect()
+}
+
+/// Bounds of a mesh's vertices
+pub fn mesh_bounds(mesh: &Instance) -> Option<Bounds> {
+    let positions = vertex_positions(mesh);
+    let mut vertices = positions.chunks_exact(3);
+    let first = vertices.next()?;
+    let mut bounds = Bounds {
+        min: [first[0], first[1], first[2]],
+        max: [first[0], first[1], first[2]],
+    };
+    for vertex in vertices {
+        for axis in 0..3 {
+            bounds.min[axis] = bounds.min[axis].min(vertex[axis]);
+            bounds.max[axis] = bounds.max[axis].max(vertex[axis]);
+        }
+    }
+    Some(bounds)
+}
+
+/// The object's viewport display color, linear RGBA
+pub fn object_color(obj: &Instance) -> [f32; 4] {
+    match obj.get_f32_vec("color").as_slice() {
+        [r, g, b, a, ..] => [*r, *g, *b, *a],
+        _ => [1.0; 4],
+    }
+}
+
+/// A material's viewport display color, its `r`, `g`, `b`, and `a` fields
+pub fn material_color(material: &Instance) -> [f32; 4] {
+    ["r", "g", "b", "a"].map(|channel| material.get_f32(channel))
+}
+
+/// The object's scale, on Blender's axes. The field is still called `size` in the file,
+/// it was only renamed to `scale` in Blender's source.
+pub fn object_scale(obj: &Instance) -> [f32; 3] {
+    match obj.get_f32_vec("size").as_slice() {
+        [x, y, z, ..] => [*x, *y, *z],
+        _ => [1.0; 3],
+    }
+}
+
+/// The object's transform relative to its parent, on Bevy's axes. Parented objects include
+/// the parent inverse Blender keeps from when they were parented, so they land where Blender
+/// shows them as children of the parent's entity. Delta transforms and constraints are ignored.
+pub fn object_transform(obj: &Instance) -> Transform {
+    let vec3 = |field: &str, default: Vec3| match obj.get_f32_vec(field).as_slice() {
+        [x, y, z, ..] => Vec3::new(*x, *y, *z),
+        _ => default,
+    };
+
+    let rotation = match obj.get_i16("rotmode") {
+        ROT_MODE_QUAT => match obj.get_f32_vec("quat").as_slice() {
+            [w, x, y, z, ..] => Quat::from_xyzw(*x, *y, *z, *w).normalize(),
+            _ => Quat::IDENTITY,
+        },
+        ROT_MODE_AXIS_ANGLE => {
+            let axis = vec3("rotAxis", Vec3::Y);
+            if axis.length_squared() > 0.0 {
+                Quat::from_axis_angle(axis.normalize(), obj.get_f32("rotAngle"))
+            } else {
+                Quat::IDENTITY
+            }
+        }
+        // Blender names the order the axes are applied in, glam the order they're multiplied
+        order => {
+            let [x, y, z] = vec3("rot", Vec3::ZERO).to_array();
+            match order {
+                2 => Quat::from_euler(EulerRot::YZX, y, z, x),
+                3 => Quat::from_euler(EulerRot::ZXY, z, x, y),
+                4 => Quat::from_euler(EulerRot::XZY, x, z, y),
+                5 => Quat::from_euler(EulerRot::YXZ, y, x, z),
+                6 => Quat::from_euler(EulerRot::XYZ, x, y, z),
+                _ => Quat::from_euler(EulerRot::ZYX, z, y, x),
+            }
+        }
+    };
+    let basis = Mat4::from_scale_rotation_translation(
+        Vec3::from(object_scale(obj)),
+        rotation,
+        vec3("loc", Vec3::ZERO),
+    );
+
+    // `parentinv` is a column-major 4x4
+    let parent_inverse = obj.get_f32_vec("parentinv");
+    let local = if obj.is_valid("parent") && parent_inverse.len() >= 16 {
+        Mat4::from_cols_slice(&parent_inverse) * basis
+    } else {
+        basis
+    };
+    Transform::from_matrix(BLENDER_TO_BEVY * local * BLENDER_TO_BEVY.transpose())
+}
+
+/// Name of the object this one is parented to
+pub fn parent_name(obj: &Instance) -> Option<String> {
+    obj.is_valid("parent").then(|| id_name(&obj.get("parent")))
+}
+
+/// Names of the modifiers on an object, top of the stack first.
+/// `modifiers` is a `ListBase`, so this walks its `next` pointers.
+pub fn modifier_names(obj: &Instance) -> Vec<String> {
+    let modifiers = obj.get("modifiers");
+    if !modifiers.is_valid("first") {
+        return Vec::new();
+    }
+
+    let mut names = Vec::new();
+    let mut modifier = modifiers.get("first");
+    loop {
+        names.push(modifier.get_string("name"));
+        if names.len() >= MAX_MODIFIERS || !modifier.is_valid("next") {
+            break;
+        }
+        modifier = modifier.get("next");
+    }
+    names
+}
+
+/// The constraints on an object as "Type (name)", top of the stack first.
+/// Bone constraints live on the pose and aren't included.
+pub fn constraint_names(obj: &Instance) -> Vec<String> {
+    list_iter(&obj.get("constraints"))
+        .take(MAX_CONSTRAINTS)
+        .map(|constraint| {
+            format!(
+                "{} ({})",
+                constraint_type_name(constraint.get_i16("type")),
+                constraint.get_string("name")
+            )
+        })
+        .collect()
+}
+
+/// Blender's UI names for the `CONSTRAINT_TYPE_*` values
+fn constraint_type_name(constraint_type: i16) -> &'static str {
+    match constraint_type {
+        1 => "Child Of",
+        2 => "Track To",
+        3 => "Inverse Kinematics",
+        4 => "Follow Path",
+        5 => "Limit Rotation",
+        6 => "Limit Location",
+        7 => "Limit Scale",
+        8 => "Copy Rotation",
+        9 => "Copy Location",
+        10 => "Copy Scale",
+        11 => "Python",
+        12 => "Action",
+        13 => "Locked Track",
+        14 => "Limit Distance",
+        15 => "Stretch To",
+        16 => "Floor",
+        17 => "Rigid Body Joint",
+        18 => "Clamp To",
+        19 => "Transformation",
+        20 => "Shrinkwrap",
+        21 => "Damped Track",
+        22 => "Spline IK",
+        23 => "Copy Transforms",
+        24 => "Maintain Volume",
+        25 => "Pivot",
+        26 => "Follow Track",
+        27 => "Camera Solver",
+        28 => "Object Solver",
+        29 => "Transform Cache",
+        30 => "Armature",
+        _ => "Unknown",
+    }
+}
+
+/// How many drivers the object's own animation data has. Drivers on its mesh,
+/// materials, or shape keys belong to those datablocks and aren't counted.
+pub fn driver_count(obj: &Instance) -> usize {
+    if !obj.is_valid("adt") {
+        return 0;
+    }
+    list_iter(&obj.get("adt").get("drivers"))
+        .take(MAX_DRIVERS)
+        .count()
+}
+
+/// Finds every image in the file, its resolution, and which materials reference it
+pub fn parse_images(blend: &Blend, blend_path: &Path) -> Vec<ImageInfo> {
+    let material_users = image_material_users(blend);
+    let blend_dir = blend_path.parent().unwrap_or_else(|| Path::new(""));
+
+    blend
+        .instances_with_code(*b"IM")
+        // Render results and viewers don't have any real pixels to audit
+        .filter(|image| image.get_i16("source") != IMA_SRC_VIEWER)
+        .map(|image| {
+            let name = id_name(&image);
+
+            let source_type = image.get_i16("source");
+            let (source, resolution) = if image.is_valid("packedfile") {
+                let data = image.get("packedfile").get_u8_vec("data");
+                (ImageSource::Packed, probe_image_bytes(&data))
+            } else if source_type == IMA_SRC_GENERATED {
+                let size = (image.get_i32("gen_x"), image.get_i32("gen_y"));
+                let resolution = (size.0 > 0 && size.1 > 0).then(|| (size.0 as u32, size.1 as u32));
+                (ImageSource::Generated, resolution)
+            } else {
+                let path = resolve_blend_path(&image.get_string("filepath"), blend_dir);
+                let tiles = if source_type == IMA_SRC_TILED {
+                    udim_tiles(&image)
+                } else {
+                    Vec::new()
+                };
+                let files = image_files(&path, &tiles);
+                let exists = files.iter().all(|file| file.is_file());
+                // Every tile of a UDIM set is meant to have the same size
+                let resolution = files
+                    .iter()
+                    .find_map(|file| image::image_dimensions(file).ok());
+                (
+                    ImageSource::Linked {
+                        path,
+                        tiles,
+                        exists,
+                    },
+                    resolution,
+                )
+            };
+
+            ImageInfo {
+                materials: material_users.get(&name).cloned().unwrap_or_default(),
+                name,
+                source,
+                resolution,
+            }
+        })
+        .collect()
+}
+
+/// The tile numbers a tiled image lists, in the order Blender keeps them
+fn udim_tiles(image: &Instance) -> Vec<u32> {
+    let tiles: Vec<u32> = list_iter(&image.get("tiles"))
+        .map(|tile| tile.get_i32("tile_number"))
+        .filter(|tile| *tile > 0)
+        .map(|tile| tile as u32)
+        .collect();
+    if tiles.is_empty() {
+        vec![FIRST_UDIM_TILE]
+    } else {
+        tiles
+    }
+}
+
+/// Reads just enough of an in-memory image to know its size
+fn probe_image_bytes(data: &[u8]) -> Option<(u32, u32)> {
+    image::io::Reader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+/// Blender stores paths relative to the .blend file with a `//` prefix
+fn resolve_blend_path(path: &str, blend_dir: &Path) -> PathBuf {
+    match path.strip_prefix("//") {
+        Some(relative) => blend_dir.join(relative),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Maps image names to the materials that use them in their node trees
+fn image_material_users(blend: &Blend) -> HashMap<String, Vec<String>> {
+    let mut users: HashMap<String, Vec<String>> = HashMap::new();
+
+    for material in blend.instances_with_code(*b"MA") {
+        if !material.is_valid("nodetree") {
+            continue;
+        }
+
+        let material_name = id_name(&material);
+        for node in list_iter(&material.get("nodetree").get("nodes")) {
+            if !node.is_valid("id") {
+                continue;
+            }
+
+            // Nodes can point at any kind of datablock, we only want images
+            let id_name_raw = node.get("id").get("id").get_string("name");
+            if let Some(image_name) = id_name_raw.strip_prefix("IM") {
+                let materials = users.entry(image_name.to_string()).or_default();
+                if !materials.contains(&material_name) {
+                    materials.push(material_name.clone());
+                }
+            }
+        }
+    }
+
+    users
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The `OB_*` values from `DNA_object_types.h`, so a new or renumbered type doesn't
+    /// quietly land in the wrong bucket
+    const OBJECT_TYPES: [(i16, ObjectKind); 18] = [
+        (0, ObjectKind::Empty),
+        (1, ObjectKind::Mesh),
+        (2, ObjectKind::Curve),
+        (3, ObjectKind::Surface),
+        (4, ObjectKind::Text),
+        (5, ObjectKind::Metaball),
+        (10, ObjectKind::Light),
+        (11, ObjectKind::Camera),
+        (12, ObjectKind::Speaker),
+        (13, ObjectKind::LightProbe),
+        (22, ObjectKind::Lattice),
+        (25, ObjectKind::Armature),
+        (26, ObjectKind::GreasePencil),
+        (27, ObjectKind::Curves),
+        (28, ObjectKind::PointCloud),
+        (29, ObjectKind::Volume),
+        (30, ObjectKind::GreasePencil),
+        (31, ObjectKind::Other(31)),
+    ];
+
+    #[test]
+    fn headers_give_pointer_size_endianness_and_version() {
+        let version = |major, minor| BlenderVersion {
+            major,
+            minor,
+            patch: 0,
+        };
+        for (header, pointer_size, endianness, saved_with) in [
+            (&b"BLENDER-v301"[..], 8, Endianness::Little, version(3, 1)),
+            (&b"BLENDER_v249"[..], 4, Endianness::Little, version(2, 49)),
+            (&b"BLENDER_V250"[..], 4, Endianness::Big, version(2, 50)),
+            (&b"BLENDER-V279"[..], 8, Endianness::Big, version(2, 79)),
+            (
+                &b"BLENDER17-01v0500"[..],
+                8,
+                Endianness::Little,
+                version(5, 0),
+            ),
+        ] {
+            assert_eq!(
+                parse_header(header),
+                Some(BlendHeader {
+                    pointer_size,
+                    endianness,
+                    version: saved_with,
+                }),
+                "{}",
+                String::from_utf8_lossy(header)
+            );
+        }
+    }
+
+    #[test]
+    fn compressed_and_broken_headers_give_nothing() {
+        for header in [
+            &b""[..],
+            b"BLENDER",
+            b"BLENDER-x301",
+            b"BLENDER*v301",
+            b"BLENDER-vabc",
+            // The start of a zstd frame, what compressed files begin with
+            b"\x28\xb5\x2f\xfd",
+        ] {
+            assert_eq!(parse_header(header), None, "{:?}", header);
+        }
+    }
+
+    #[test]
+    fn object_types_map_to_their_kind() {
+        for (object_type, kind) in OBJECT_TYPES {
+            assert_eq!(
+                ObjectKind::from_type(object_type),
+                kind,
+                "type {}",
+                object_type
+            );
+        }
+    }
+
+    #[test]
+    fn only_meshes_curves_text_and_empties_are_previewable() {
+        let previewable: Vec<ObjectKind> = OBJECT_TYPES
+            .iter()
+            .map(|(_, kind)| *kind)
+            .filter(ObjectKind::is_previewable)
+            .collect();
+        assert_eq!(
+            previewable,
+            [
+                ObjectKind::Empty,
+                ObjectKind::Mesh,
+                ObjectKind::Curve,
+                ObjectKind::Text
+            ]
+        );
+    }
+
+    #[test]
+    fn udim_paths_expand_to_a_file_per_tile() {
+        assert_eq!(
+            image_files(Path::new("/textures/wood.png"), &[]),
+            [PathBuf::from("/textures/wood.png")]
+        );
+        assert_eq!(
+            image_files(Path::new("/textures/skin.<UDIM>.png"), &[1001, 1002, 1011]),
+            [
+                PathBuf::from("/textures/skin.1001.png"),
+                PathBuf::from("/textures/skin.1002.png"),
+                PathBuf::from("/textures/skin.1011.png"),
+            ]
+        );
+        assert_eq!(
+            image_files(Path::new("/textures/skin_<UVTILE>.png"), &[1001, 1012]),
+            [
+                PathBuf::from("/textures/skin_u1_v1.png"),
+                PathBuf::from("/textures/skin_u2_v2.png"),
+            ]
+        );
+    }
+
+    #[test]
+    fn generated_images_are_never_broken() {
+        let image = |source| ImageInfo {
+            name: "Image".to_string(),
+            source,
+            resolution: None,
+            materials: Vec::new(),
+        };
+        assert!(!image(ImageSource::Generated).is_broken());
+        assert!(image(ImageSource::Linked {
+            path: PathBuf::from("/missing/skin.<UDIM>.png"),
+            tiles: vec![1001],
+            exists: false,
+        })
+        .is_broken());
+    }
+
+    #[test]
+    fn loose_vertices_are_the_ones_no_edge_uses() {
+        // A triangle, a wire edge hanging off it, and vertices 4 and 5 on their own
+        let edges = [0, 1, 1, 2, 2, 0, 2, 3];
+        assert_eq!(count_loose_vertices(6, &edges), 2);
+        assert_eq!(count_loose_vertices(4, &edges), 0);
+        assert_eq!(count_loose_vertices(3, &[]), 3);
+        // Broken indices don't count as using anything
+        assert_eq!(count_loose_vertices(2, &[0, -1, 0, 7]), 1);
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::{
+    parse_queue::{ParseQueue, ParsedFile},
+    safe_mode::SafeMode,
+};
+
+/// How much of each end of a file goes into its fingerprint
+const FINGERPRINT_CHUNK: u64 = 1024 * 1024;
+
+/// Identifies a file's contents without hashing all of it. Blend files keep their header
+/// at the start and `DNA1` and `ENDB` at the end, so edits nearly always change one of them.
+/// Unlike the modified time, sync tools that preserve timestamps can't fool it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    len: u64,
+    hash: u64,
+}
+
+impl Fingerprint {
+    /// Hashes the first and last megabyte of the file along with its length
+    pub fn of(path: &Path) -> io::Result<Self> {
+        let mut file = fs::File::open(path)?;
+        let len = file.metadata()?.len();
+
+        let mut hasher = Xxh3::new();
+        let mut chunk = Vec::with_capacity(FINGERPRINT_CHUNK as usize);
+        file.by_ref()
+            .take(FINGERPRINT_CHUNK)
+            .read_to_end(&mut chunk)?;
+        hasher.update(&chunk);
+
+        // Small files fit in the first chunk
+        if len > FINGERPRINT_CHUNK {
+            chunk.clear();
+            file.seek(SeekFrom::Start(
+                len.saturating_sub(FINGERPRINT_CHUNK).max(FINGERPRINT_CHUNK),
+            ))?;
+            file.read_to_end(&mut chunk)?;
+            hasher.update(&chunk);
+        }
+
+        Ok(Self {
+            len,
+            hash: hasher.digest(),
+        })
+    }
+
+    /// Short enough to name a folder after
+    pub fn key(&self) -> String {
+        format!("{:016x}-{:x}", self.hash, self.len)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    fingerprint: Fingerprint,
+    parsed: ParsedFile,
+}
+
+/// The cached entries, shared with the parse tasks so they can check the cache themselves
+pub type SharedEntries = Arc<RwLock<HashMap<PathBuf, CacheEntry>>>;
+
+/// Metadata of every file parsed before, keyed by canonical path.
+/// Lets large libraries show up straight away instead of being parsed again on each start.
+#[derive(Default, Resource)]
+pub struct MetadataCache {
+    entries: SharedEntries,
+    /// Entries changed since the cache was last written
+    dirty: bool,
+}
+
+impl MetadataCache {
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn entries(&self) -> SharedEntries {
+        self.entries.clone()
+    }
+
+    pub fn insert(&mut self, path: PathBuf, fingerprint: Fingerprint, parsed: ParsedFile) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.insert(
+                path,
+                CacheEntry {
+                    fingerprint,
+                    parsed,
+                },
+            );
+            self.dirty = true;
+        }
+    }
+}
+
+/// The cached metadata for `path`, if its contents still match the fingerprint
+pub fn cached(
+    entries: &SharedEntries,
+    path: &Path,
+    fingerprint: Fingerprint,
+) -> Option<ParsedFile> {
+    let entries = entries.read().ok()?;
+    entries
+        .get(path)
+        .filter(|entry| entry.fingerprint == fingerprint)
+        .map(|entry| entry.parsed.clone())
+}
+
+pub struct MetadataCachePlugin;
+
+impl Plugin for MetadataCachePlugin {
+    fn build(&self, app: &mut App) {
+        // A broken cache could be what safe mode is avoiding
+        let safe_mode = app
+            .world
+            .get_resource::<SafeMode>()
+            .is_some_and(SafeMode::is_active);
+        let cache = if safe_mode {
+            MetadataCache::default()
+        } else {
+            load_metadata_cache()
+        };
+        app.insert_resource(cache)
+            .add_system(save_metadata_cache.run_if(
+                |cache: Res<MetadataCache>, queue: Res<ParseQueue>, safe_mode: Res<SafeMode>| {
+                    // Once per batch rather than after every file
+                    cache.is_dirty() && !queue.is_busy() && !safe_mode.is_active()
+                },
+            ));
+    }
+}
+
+fn metadata_cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("blender-launcher").join("metadata.json"))
+}
+
+pub fn load_metadata_cache() -> MetadataCache {
+    let Some(contents) = metadata_cache_path().and_then(|path| fs::read_to_string(path).ok())
+    else {
+        return MetadataCache::default();
+    };
+
+    // An outdated or broken cache only means parsing everything again
+    let entries: HashMap<PathBuf, CacheEntry> =
+        serde_json::from_str(&contents).unwrap_or_else(|error| {
+            warn!(%error, "discarding the metadata cache");
+            HashMap::new()
+        });
+    MetadataCache {
+        entries: Arc::new(RwLock::new(entries)),
+        dirty: false,
+    }
+}
+
+pub fn write_metadata_cache(cache: &MetadataCache) -> io::Result<()> {
+    let Some(path) = metadata_cache_path() else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let entries = cache
+        .entries
+        .read()
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "metadata cache lock poisoned"))?;
+    fs::write(&path, serde_json::to_string(&*entries)?)
+}
+
+fn save_metadata_cache(mut cache: ResMut<MetadataCache>) {
+    if let Err(error) = write_metadata_cache(&cache) {
+        warn!(%error, "couldn't save the metadata cache");
+    }
+    cache.dirty = false;
+}
@@ -0,0 +1,103 @@
+use std::{cmp::Ordering, iter::Peekable, str::Chars};
+
+/// Compares names the way people read them, so `Crate_2` comes before `Crate_10`.
+///
+/// Runs of ASCII digits compare by their value, with fewer leading zeros first when the
+/// values are equal (`Crate_1` before `Crate_01`). Everything else compares one character
+/// at a time, ignoring case. Names that only differ in case fall back to a plain comparison,
+/// so two different names never compare equal and sorting is the same on every run.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        let ordering = match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char)) if a_char.is_ascii_digit() && b_char.is_ascii_digit() => {
+                compare_numbers(&digits(&mut a_chars), &digits(&mut b_chars))
+            }
+            (Some(a_char), Some(b_char)) => {
+                a_chars.next();
+                b_chars.next();
+                a_char.to_lowercase().cmp(b_char.to_lowercase())
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Takes the run of digits at the front
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+/// Compares runs of digits of any length by value, without parsing them into an integer
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let a_value = a.trim_start_matches('0');
+    let b_value = b.trim_start_matches('0');
+    a_value
+        .len()
+        .cmp(&b_value.len())
+        .then_with(|| a_value.cmp(b_value))
+        .then_with(|| a.len().cmp(&b.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        names.sort_by(|a, b| natural_cmp(a, b));
+        names
+    }
+
+    #[test]
+    fn numbers_sort_by_value() {
+        assert_eq!(
+            sorted(&["Crate_10", "Crate_2", "Crate_1", "Crate"]),
+            ["Crate", "Crate_1", "Crate_2", "Crate_10"]
+        );
+        assert_eq!(
+            sorted(&["Cube.010", "Cube.002", "Cube.1"]),
+            ["Cube.1", "Cube.002", "Cube.010"]
+        );
+        // Longer than any integer type
+        assert_eq!(
+            natural_cmp("a99999999999999999999999", "a100000000000000000000000"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn case_is_ignored_until_it_breaks_a_tie() {
+        assert_eq!(
+            sorted(&["suzanne", "Cube", "Suzanne", "camera"]),
+            ["camera", "Cube", "Suzanne", "suzanne"]
+        );
+        assert_eq!(natural_cmp("Cube", "Cube"), Ordering::Equal);
+    }
+
+    #[test]
+    fn leading_zeros_break_ties_only() {
+        assert_eq!(natural_cmp("Crate_1", "Crate_01"), Ordering::Less);
+        assert_eq!(natural_cmp("Crate_01", "Crate_2"), Ordering::Less);
+        assert_eq!(natural_cmp("Crate_0", "Crate_00"), Ordering::Less);
+    }
+
+    #[test]
+    fn shorter_names_come_first() {
+        assert_eq!(
+            sorted(&["TransformCube_X", "TransformCube", "Transform"]),
+            ["Transform", "TransformCube", "TransformCube_X"]
+        );
+        assert_eq!(natural_cmp("", "a"), Ordering::Less);
+    }
+}
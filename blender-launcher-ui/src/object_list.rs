@@ -0,0 +1,132 @@
+use bevy_egui::egui;
+
+use crate::{
+    i18n::t,
+    lod::{object_rows, ObjectRow},
+    File, FileId,
+};
+
+/// Rows a file's object list shows before the rest hide behind "show all"
+pub const INITIAL_ROWS: usize = 200;
+
+/// How tall the list of a file gets once everything is shown, in rows.
+/// It scrolls on its own past that and only lays out the rows in view.
+const EXPANDED_ROWS: f32 = 30.0;
+
+/// Whether the object name matches what's typed in the filter, ignoring case
+pub fn matches_filter(name: &str, filter: &str) -> bool {
+    filter.is_empty() || name.to_lowercase().contains(&filter.to_lowercase())
+}
+
+/// The id `egui` keeps a chain's open state under. Not derived from a `Ui`, since the header
+/// may be laid out inside the list's own scroll area.
+pub fn chain_id(file: &File, base: &str) -> egui::Id {
+    egui::Id::new(("lod_chain", &file.path, base))
+}
+
+/// The rows of the file's object list that are showing, before the cap. Levels of closed
+/// chains are left out. While filtering, matching levels show whether or not their chain is
+/// open, and chains without a match are left out entirely.
+pub fn visible_rows(ctx: &egui::Context, file: &File, filter: &str) -> Vec<ObjectRow> {
+    let chains = file.lods.as_deref().unwrap_or_default();
+    let level_matches = |chain: usize, position: usize| {
+        let (_, object) = chains[chain].levels[position];
+        matches_filter(&file.meshes[object].name, filter)
+    };
+    object_rows(file)
+        .into_iter()
+        .filter(|row| match *row {
+            ObjectRow::Object(index) => matches_filter(&file.meshes[index].name, filter),
+            ObjectRow::Chain(_) if filter.is_empty() => true,
+            ObjectRow::Chain(chain) => {
+                (0..chains[chain].levels.len()).any(|position| level_matches(chain, position))
+            }
+            ObjectRow::Level { chain, .. } if filter.is_empty() => {
+                egui::collapsing_header::CollapsingState::load_with_default_open(
+                    ctx,
+                    chain_id(file, &chains[chain].base),
+                    false,
+                )
+                .is_open()
+            }
+            ObjectRow::Level { chain, position } => level_matches(chain, position),
+        })
+        .collect()
+}
+
+fn show_all_id(ui: &egui::Ui, file: FileId) -> egui::Id {
+    ui.make_persistent_id(("show_all_objects", file.0))
+}
+
+/// Whether the user expanded the file's list past `INITIAL_ROWS`.
+/// Only lives as long as the app, like the table's sort.
+pub fn showing_all(ui: &egui::Ui, file: FileId) -> bool {
+    ui.data(|data| data.get_temp(show_all_id(ui, file)))
+        .unwrap_or(false)
+}
+
+pub fn set_showing_all(ui: &egui::Ui, file: FileId, show_all: bool) {
+    ui.data_mut(|data| data.insert_temp(show_all_id(ui, file), show_all));
+}
+
+/// "Show all" under a capped list, or "show fewer" under an expanded one
+pub fn show_all_ui(ui: &mut egui::Ui, file: FileId, rows: usize) {
+    if rows <= INITIAL_ROWS {
+        return;
+    }
+    let show_all = showing_all(ui, file);
+    let label = if show_all {
+        t!("files.show_fewer", count = INITIAL_ROWS)
+    } else {
+        t!("files.show_all", count = rows - INITIAL_ROWS)
+    };
+    if ui.small_button(label).clicked() {
+        set_showing_all(ui, file, !show_all);
+    }
+}
+
+/// Lays out `rows` of the same height, all of them when there are few enough,
+/// otherwise in a scroll area of their own that only lays out the ones in view.
+/// `scroll_to` is a row to bring into view, which also expands a capped list to reach it.
+pub fn rows_ui<T>(
+    ui: &mut egui::Ui,
+    file: FileId,
+    rows: &[T],
+    scroll_to: Option<usize>,
+    mut row_ui: impl FnMut(&mut egui::Ui, &T),
+) {
+    if scroll_to.is_some_and(|row| row >= INITIAL_ROWS) {
+        set_showing_all(ui, file, true);
+    }
+    if rows.len() <= INITIAL_ROWS {
+        for row in rows {
+            row_ui(ui, row);
+        }
+        return;
+    }
+    if !showing_all(ui, file) {
+        for row in &rows[..INITIAL_ROWS] {
+            row_ui(ui, row);
+        }
+        show_all_ui(ui, file, rows.len());
+        return;
+    }
+
+    let row_height = ui.spacing().interact_size.y;
+    let mut scroll_area = egui::ScrollArea::vertical()
+        .id_source(("object_rows", file.0))
+        .max_height(row_height * EXPANDED_ROWS)
+        .auto_shrink([false, true]);
+    if let Some(row) = scroll_to {
+        // Rows out of view aren't laid out, so they can't scroll themselves into view
+        let offset = row as f32 * (row_height + ui.spacing().item_spacing.y);
+        scroll_area = scroll_area
+            .vertical_scroll_offset((offset - row_height * EXPANDED_ROWS / 2.0).max(0.0));
+    }
+    scroll_area.show_rows(ui, row_height, rows.len(), |ui, range| {
+        for row in &rows[range] {
+            row_ui(ui, row);
+        }
+    });
+    show_all_ui(ui, file, rows.len());
+}
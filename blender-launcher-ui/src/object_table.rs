@@ -0,0 +1,255 @@
+use std::cmp::Ordering;
+
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::t,
+    natural_sort::natural_cmp,
+    not_previewable,
+    object_list::{matches_filter, set_showing_all, show_all_ui, showing_all, INITIAL_ROWS},
+    object_tooltip::object_tooltip_ui,
+    object_warnings,
+    settings::Settings,
+    BlendObject, File, FileId, ACTIVE_MARKER,
+};
+
+/// How each file's objects are listed in the file panel
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObjectListMode {
+    /// One button per object, friendlier for browsing
+    #[default]
+    Buttons,
+    /// A sortable table with stats, for audits
+    Table,
+    /// A tree of the collections and parenting, like Blender's outliner
+    Outliner,
+}
+
+/// The optional columns of the object table, the name is always shown
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObjectColumns {
+    pub kind: bool,
+    pub vertices: bool,
+    pub faces: bool,
+    pub materials: bool,
+    pub modifiers: bool,
+}
+
+impl Default for ObjectColumns {
+    fn default() -> Self {
+        Self {
+            kind: true,
+            vertices: true,
+            faces: true,
+            materials: true,
+            modifiers: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Column {
+    #[default]
+    Name,
+    Kind,
+    Vertices,
+    Faces,
+    Materials,
+    Modifiers,
+}
+
+impl Column {
+    fn label(&self) -> &'static str {
+        match self {
+            Column::Name => t!("table.name"),
+            Column::Kind => t!("table.kind"),
+            Column::Vertices => t!("table.vertices"),
+            Column::Faces => t!("table.faces"),
+            Column::Materials => t!("table.materials"),
+            Column::Modifiers => t!("table.modifiers"),
+        }
+    }
+
+    fn is_visible(&self, columns: &ObjectColumns) -> bool {
+        match self {
+            Column::Name => true,
+            Column::Kind => columns.kind,
+            Column::Vertices => columns.vertices,
+            Column::Faces => columns.faces,
+            Column::Materials => columns.materials,
+            Column::Modifiers => columns.modifiers,
+        }
+    }
+
+    fn compare(&self, a: &BlendObject, b: &BlendObject) -> Ordering {
+        let vertices = |object: &BlendObject| object.stats.as_ref().map(|stats| stats.vertices);
+        let faces = |object: &BlendObject| object.stats.as_ref().map(|stats| stats.faces);
+        match self {
+            Column::Name => natural_cmp(&a.name, &b.name),
+            Column::Kind => a.kind.cmp(&b.kind),
+            Column::Vertices => vertices(a).cmp(&vertices(b)),
+            Column::Faces => faces(a).cmp(&faces(b)),
+            Column::Materials => a.materials.len().cmp(&b.materials.len()),
+            Column::Modifiers => a.modifiers.len().cmp(&b.modifiers.len()),
+        }
+    }
+}
+
+const COLUMNS: [Column; 6] = [
+    Column::Name,
+    Column::Kind,
+    Column::Vertices,
+    Column::Faces,
+    Column::Materials,
+    Column::Modifiers,
+];
+
+/// Which column a file's table is sorted by. Only lives as long as the app,
+/// so it's kept in egui's memory rather than the settings.
+#[derive(Clone, Copy, Default)]
+struct TableSort {
+    column: Column,
+    descending: bool,
+}
+
+/// Checkboxes for the optional columns
+pub fn object_columns_ui(ui: &mut egui::Ui, columns: &mut ObjectColumns) {
+    ui.checkbox(&mut columns.kind, Column::Kind.label());
+    ui.checkbox(&mut columns.vertices, Column::Vertices.label());
+    ui.checkbox(&mut columns.faces, Column::Faces.label());
+    ui.checkbox(&mut columns.materials, Column::Materials.label());
+    ui.checkbox(&mut columns.modifiers, Column::Modifiers.label());
+}
+
+/// A file's objects matching `filter` as a table sorted by the clicked header, capped like the
+/// buttons. Returns the object whose row was clicked, to be selected and spawned just like
+/// with the buttons.
+pub fn object_table_ui(
+    ui: &mut egui::Ui,
+    file: &File,
+    settings: &Settings,
+    filter: &str,
+    selected_object: Option<(FileId, usize)>,
+    scroll_to_object: Option<(FileId, usize)>,
+) -> Option<usize> {
+    let sort_id = ui.make_persistent_id(("object_table_sort", file.id.0));
+    let mut sort: TableSort = ui.data(|data| data.get_temp(sort_id)).unwrap_or_default();
+    let visible: Vec<Column> = COLUMNS
+        .into_iter()
+        .filter(|column| column.is_visible(&settings.object_columns))
+        .collect();
+
+    // Filtered and sorted over every object, then capped
+    let mut order: Vec<usize> = (0..file.meshes.len())
+        .filter(|index| matches_filter(&file.meshes[*index].name, filter))
+        .collect();
+    order.sort_by(|a, b| {
+        let ordering = sort.column.compare(&file.meshes[*a], &file.meshes[*b]);
+        if sort.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    let matching = order.len();
+    let scroll_to_row = scroll_to_object
+        .filter(|(file_id, _)| *file_id == file.id)
+        .and_then(|(_, mesh_index)| order.iter().position(|index| *index == mesh_index));
+    if scroll_to_row.is_some_and(|row| row >= INITIAL_ROWS) {
+        set_showing_all(ui, file.id, true);
+    }
+    if !showing_all(ui, file.id) {
+        order.truncate(INITIAL_ROWS);
+    }
+
+    let active_object = file.active_object();
+    let mut clicked = None;
+    egui::Grid::new(("object_table", file.id.0))
+        .striped(true)
+        .show(ui, |ui| {
+            for column in visible.iter() {
+                let arrow = match (sort.column == *column, sort.descending) {
+                    (false, _) => "",
+                    (true, false) => " ⏶",
+                    (true, true) => " ⏷",
+                };
+                if ui
+                    .add(egui::Button::new(format!("{}{}", column.label(), arrow)).frame(false))
+                    .clicked()
+                {
+                    // Clicking the sorted column again flips the direction
+                    sort = TableSort {
+                        column: *column,
+                        descending: sort.column == *column && !sort.descending,
+                    };
+                }
+            }
+            ui.end_row();
+
+            for mesh_index in order {
+                let object = &file.meshes[mesh_index];
+                let selected = selected_object == Some((file.id, mesh_index));
+                let warnings = object_warnings(object, settings);
+
+                for column in visible.iter() {
+                    let mut text = match column {
+                        Column::Name if !warnings.is_empty() => {
+                            format!("⚠ {} {}", object.kind.icon(), object.name)
+                        }
+                        Column::Name => format!("{} {}", object.kind.icon(), object.name),
+                        Column::Kind => object.kind.label().to_string(),
+                        Column::Vertices => count(object.stats.as_ref().map(|s| s.vertices)),
+                        Column::Faces => count(object.stats.as_ref().map(|s| s.faces)),
+                        Column::Materials => object.materials.len().to_string(),
+                        Column::Modifiers => object.modifiers.len().to_string(),
+                    };
+                    if *column == Column::Name && active_object == Some(mesh_index) {
+                        text.push_str(ACTIVE_MARKER);
+                    }
+                    // Every cell selects, so the whole row is one target
+                    let mut response = ui
+                        .add_enabled(
+                            object.kind.is_previewable(),
+                            egui::SelectableLabel::new(selected, text),
+                        )
+                        .on_disabled_hover_text(not_previewable(object.kind));
+                    match column {
+                        Column::Name if settings.object_tooltips => {
+                            response = response.on_hover_ui(|ui| {
+                                object_tooltip_ui(ui, file, object, &warnings);
+                            });
+                        }
+                        Column::Name if !warnings.is_empty() => {
+                            response = response.on_hover_text(warnings.join("\n"));
+                        }
+                        Column::Materials if !object.materials.is_empty() => {
+                            response = response.on_hover_text(object.materials.join("\n"));
+                        }
+                        Column::Modifiers if !object.modifiers.is_empty() => {
+                            response = response.on_hover_text(object.modifiers.join("\n"));
+                        }
+                        _ => {}
+                    }
+                    if *column == Column::Name && scroll_to_object == Some((file.id, mesh_index)) {
+                        response.scroll_to_me(Some(egui::Align::Center));
+                    }
+                    if response.clicked() {
+                        clicked = Some(mesh_index);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+
+    show_all_ui(ui, file.id, matching);
+
+    ui.data_mut(|data| data.insert_temp(sort_id, sort));
+    clicked
+}
+
+/// Only meshes have vertex and face counts
+fn count(value: Option<usize>) -> String {
+    value.map_or_else(|| "–".to_string(), |value| value.to_string())
+}
@@ -0,0 +1,64 @@
+use bevy_egui::egui;
+
+use crate::{
+    accessibility::{status_label, Status},
+    i18n::t,
+    thumbnails::{thumbnail, Subject, THUMBNAIL_SIZE},
+    units::dimensions,
+    BlendObject, File,
+};
+
+/// A glance at what the inspector would show for an object, for hovering its row.
+/// Only put together while the row is hovered, egui doesn't call `on_hover_ui` otherwise.
+pub fn object_tooltip_ui(
+    ui: &mut egui::Ui,
+    file: &File,
+    object: &BlendObject,
+    warnings: &[String],
+) {
+    ui.horizontal(|ui| {
+        if let Some(texture) = thumbnail(file, Subject::Object, &object.name) {
+            ui.image(texture, egui::Vec2::splat(THUMBNAIL_SIZE as f32 / 2.0));
+        }
+        ui.vertical(|ui| {
+            ui.strong(format!("{} {}", object.kind.icon(), object.name));
+            ui.weak(object.kind.label());
+
+            // Back on Blender's Z-up axes, the way the N panel shows it
+            let location = object.location;
+            ui.label(t!(
+                "tooltip.location",
+                x = file.units.format_length(location.x),
+                y = file.units.format_length(-location.z),
+                z = file.units.format_length(location.y)
+            ));
+            if let Some([x, y, z]) = dimensions(object) {
+                ui.label(t!(
+                    "inspector.dimensions",
+                    x = file.units.format_length(x),
+                    y = file.units.format_length(y),
+                    z = file.units.format_length(z)
+                ));
+            }
+            if let Some(stats) = &object.stats {
+                ui.label(t!(
+                    "tooltip.polygons",
+                    faces = stats.faces,
+                    triangles = stats.triangles
+                ));
+            }
+            if object.materials.is_empty() {
+                ui.label(t!("tooltip.no_materials"));
+            } else {
+                ui.label(t!(
+                    "tooltip.materials",
+                    materials = object.materials.join(", ")
+                ));
+            }
+            ui.label(t!("tooltip.modifiers", count = object.modifiers.len()));
+            for warning in warnings {
+                status_label(ui, Status::Warning, warning);
+            }
+        });
+    });
+}
@@ -0,0 +1,359 @@
+use std::{
+    num::NonZeroU32,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    asset::HandleId,
+    core_pipeline::clear_color::ClearColorConfig,
+    ecs::system::SystemParam,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        main_graph::node::CAMERA_DRIVER,
+        primitives::Aabb,
+        render_asset::RenderAssets,
+        render_graph::{self, NodeRunError, RenderGraph, RenderGraphContext},
+        render_resource::{
+            Buffer, BufferDescriptor, BufferUsages, Extent3d, ImageCopyBuffer, ImageDataLayout,
+            Maintain, MapMode, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        renderer::{RenderContext, RenderDevice},
+        view::RenderLayers,
+        RenderApp, RenderSet,
+    },
+};
+
+/// Vertical field of view of offscreen cameras, narrow so objects don't look distorted
+const FOV: f32 = std::f32::consts::PI / 6.0;
+/// Behind the rendered object, a neutral grey that works for light and dark materials
+const BACKGROUND: Color = Color::rgb(0.18, 0.18, 0.2);
+/// wgpu copies textures to buffers in rows padded to a multiple of this
+const COPY_ROW_ALIGNMENT: u32 = 256;
+const READBACK_NODE: &str = "offscreen_readback";
+
+/// A camera rendering into an image rather than the preview window.
+/// The systems driving the preview camera leave these alone.
+#[derive(Component)]
+pub struct OffscreenCamera;
+
+/// A single object rendered on its own, far enough away from the preview that neither
+/// lights the other, and on its own render layer so neither camera sees the other's objects
+pub struct Studio {
+    pub camera: Entity,
+    pub object: Entity,
+    pub light: Entity,
+}
+
+impl Studio {
+    pub fn spawn(
+        commands: &mut Commands,
+        origin: Vec3,
+        layer: u8,
+        target: Handle<Image>,
+        mesh: Handle<Mesh>,
+        material: Handle<StandardMaterial>,
+    ) -> Self {
+        let layers = RenderLayers::layer(layer);
+        let camera = commands
+            .spawn((
+                OffscreenCamera,
+                layers,
+                Camera3dBundle {
+                    camera: Camera {
+                        target: RenderTarget::Image(target),
+                        // Before the preview, so a frame shown in egui is never half drawn
+                        order: -1,
+                        ..Default::default()
+                    },
+                    camera_3d: Camera3d {
+                        clear_color: ClearColorConfig::Custom(BACKGROUND),
+                        ..Default::default()
+                    },
+                    projection: Projection::Perspective(PerspectiveProjection {
+                        fov: FOV,
+                        ..Default::default()
+                    }),
+                    transform: Transform::from_translation(origin + Vec3::Z)
+                        .looking_at(origin, Vec3::Y),
+                    ..Default::default()
+                },
+            ))
+            .id();
+        let object = commands
+            .spawn((
+                layers,
+                PbrBundle {
+                    mesh,
+                    material,
+                    transform: Transform::from_translation(origin),
+                    ..Default::default()
+                },
+            ))
+            .id();
+        let light = commands
+            .spawn((
+                layers,
+                PointLightBundle {
+                    transform: Transform::from_translation(origin + Vec3::ONE),
+                    ..Default::default()
+                },
+            ))
+            .id();
+        Self {
+            camera,
+            object,
+            light,
+        }
+    }
+
+    pub fn despawn(&self, commands: &mut Commands) {
+        for entity in [self.camera, self.object, self.light] {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// A square image a camera can render into and egui can show
+pub fn render_target(size: u32) -> Image {
+    let size = Extent3d {
+        width: size,
+        height: size,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..Default::default()
+    };
+    // Fills the image with zeroes
+    image.resize(size);
+    image
+}
+
+/// Center and bounding sphere radius of a mesh, `None` for a mesh without positions
+pub fn mesh_sphere(mesh: &Mesh) -> Option<(Vec3, f32)> {
+    let Aabb {
+        center,
+        half_extents,
+    } = mesh.compute_aabb()?;
+    Some((center.into(), Vec3::from(half_extents).length().max(0.001)))
+}
+
+/// What moving a studio's camera and light around touches
+#[derive(SystemParam)]
+pub struct StudioRig<'w, 's> {
+    transforms: Query<'w, 's, &'static mut Transform>,
+    projections: Query<'w, 's, &'static mut Projection>,
+    lights: Query<'w, 's, &'static mut PointLight>,
+}
+
+impl StudioRig<'_, '_> {
+    /// Points a studio's camera and light at a mesh with the given bounds from `yaw` and
+    /// `pitch` (in radians), close enough that the bounds fill the image
+    pub fn frame(
+        &mut self,
+        studio: &Studio,
+        origin: Vec3,
+        (center, radius): (Vec3, f32),
+        (yaw, pitch): (f32, f32),
+    ) {
+        let distance = radius / (FOV * 0.5).sin();
+        let direction = Quat::from_euler(EulerRot::YXZ, yaw, -pitch, 0.0) * Vec3::Z;
+        let eye = origin + direction * distance;
+
+        // The mesh's center sits on the origin, wherever it is in the blend
+        if let Ok(mut transform) = self.transforms.get_mut(studio.object) {
+            transform.translation = origin - center;
+        }
+        if let Ok(mut transform) = self.transforms.get_mut(studio.camera) {
+            *transform = Transform::from_translation(eye).looking_at(origin, Vec3::Y);
+        }
+        if let Ok(mut projection) = self.projections.get_mut(studio.camera) {
+            if let Projection::Perspective(perspective) = &mut *projection {
+                perspective.near = (distance - radius).max(distance * 0.01);
+                perspective.far = distance + radius * 2.0;
+            }
+        }
+        // A headlight, a little above the camera. Its brightness goes with the distance
+        // squared so big and small objects come out the same.
+        if let Ok(mut transform) = self.transforms.get_mut(studio.light) {
+            transform.translation = eye + Vec3::Y * distance * 0.5;
+        }
+        if let Ok(mut light) = self.lights.get_mut(studio.light) {
+            light.intensity = 20.0 * distance * distance;
+            light.range = distance * 3.0;
+        }
+    }
+}
+
+/// Copies offscreen images back from the GPU, for saving them.
+/// Requests go to the render world every frame, and are cleared at the start of the next.
+#[derive(Clone, Default, Resource, ExtractResource)]
+pub struct ImageReadback {
+    requests: Vec<Handle<Image>>,
+    /// RGBA pixels of the copied images, filled in by the render world
+    finished: Arc<Mutex<Vec<(HandleId, Vec<u8>)>>>,
+}
+
+impl ImageReadback {
+    /// Reads the image back after this frame renders. The caller keeps the image alive.
+    pub fn request(&mut self, image: &Handle<Image>) {
+        self.requests.push(image.clone_weak());
+    }
+
+    /// The pixels of a requested image, once they're back
+    pub fn take(&mut self, image: &Handle<Image>) -> Option<Vec<u8>> {
+        let mut finished = self.finished.lock().unwrap();
+        let index = finished.iter().position(|(id, _)| *id == image.id())?;
+        Some(finished.swap_remove(index).1)
+    }
+}
+
+pub struct OffscreenPlugin;
+
+impl Plugin for OffscreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ImageReadback>()
+            .add_plugin(ExtractResourcePlugin::<ImageReadback>::default())
+            .add_system(clear_readback_requests.in_base_set(CoreSet::First));
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<ReadbackBuffers>()
+            .add_system(prepare_readback_buffers.in_set(RenderSet::Prepare))
+            .add_system(map_readback_buffers.in_set(RenderSet::Cleanup));
+        let mut graph = render_app.world.resource_mut::<RenderGraph>();
+        graph.add_node(READBACK_NODE, ReadbackNode);
+        graph.add_node_edge(CAMERA_DRIVER, READBACK_NODE);
+    }
+}
+
+/// Requests were extracted at the end of the last frame
+fn clear_readback_requests(mut readback: ResMut<ImageReadback>) {
+    if !readback.requests.is_empty() {
+        readback.requests.clear();
+    }
+}
+
+struct ReadbackBuffer {
+    image: Handle<Image>,
+    buffer: Buffer,
+    width: u32,
+    height: u32,
+    padded_row: u32,
+}
+
+/// Buffers the images of this frame's requests are copied into, in the render world
+#[derive(Default, Resource)]
+struct ReadbackBuffers(Vec<ReadbackBuffer>);
+
+fn prepare_readback_buffers(
+    readback: Res<ImageReadback>,
+    gpu_images: Res<RenderAssets<Image>>,
+    render_device: Res<RenderDevice>,
+    mut buffers: ResMut<ReadbackBuffers>,
+) {
+    buffers.0.clear();
+    for image in readback.requests.iter() {
+        let Some(gpu_image) = gpu_images.get(image) else {
+            continue;
+        };
+        let (width, height) = (gpu_image.size.x as u32, gpu_image.size.y as u32);
+        let padded_row = (width * 4).div_ceil(COPY_ROW_ALIGNMENT) * COPY_ROW_ALIGNMENT;
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("offscreen_readback_buffer"),
+            size: u64::from(padded_row * height),
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        buffers.0.push(ReadbackBuffer {
+            image: image.clone_weak(),
+            buffer,
+            width,
+            height,
+            padded_row,
+        });
+    }
+}
+
+/// Copies the requested images into their buffers, once the cameras have drawn them
+struct ReadbackNode;
+
+impl render_graph::Node for ReadbackNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let gpu_images = world.resource::<RenderAssets<Image>>();
+        for readback in world.resource::<ReadbackBuffers>().0.iter() {
+            let Some(gpu_image) = gpu_images.get(&readback.image) else {
+                continue;
+            };
+            render_context.command_encoder().copy_texture_to_buffer(
+                gpu_image.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &readback.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(readback.padded_row),
+                        rows_per_image: None,
+                    },
+                },
+                Extent3d {
+                    width: readback.width,
+                    height: readback.height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Waits for the copies to finish and hands the pixels to the main world.
+/// Frames without requests have nothing to wait on.
+fn map_readback_buffers(
+    readback: Res<ImageReadback>,
+    render_device: Res<RenderDevice>,
+    mut buffers: ResMut<ReadbackBuffers>,
+) {
+    for copied in buffers.0.drain(..) {
+        let slice = copied.buffer.slice(..);
+        slice.map_async(MapMode::Read, |_| {});
+        render_device.poll(Maintain::Wait);
+
+        // Drops the row padding, and swaps the render target's BGRA for RGBA
+        let mut pixels = Vec::with_capacity((copied.width * copied.height * 4) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(copied.padded_row as usize) {
+                for bgra in row[..(copied.width * 4) as usize].chunks(4) {
+                    pixels.extend_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+                }
+            }
+        }
+        copied.buffer.unmap();
+        readback
+            .finished
+            .lock()
+            .unwrap()
+            .push((copied.image.id(), pixels));
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+
+use bevy::{prelude::*, render::render_resource::PrimitiveTopology};
+
+use crate::{overlays::overlay, settings::Settings, BlenderPreviewObject, LauncherCamera};
+
+/// How big the marker is relative to its distance from the camera
+const MARKER_SCREEN_SCALE: f32 = 0.08;
+
+/// An axis cross drawn at a preview's origin
+#[derive(Component)]
+struct OriginMarker;
+
+#[derive(Resource)]
+struct OriginMarkerAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct OriginMarkerPlugin;
+
+impl Plugin for OriginMarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_origin_marker_assets)
+            .add_system(
+                update_origin_markers.run_if(|settings: Res<Settings>| {
+                    settings.show_origins || settings.is_changed()
+                }),
+            );
+    }
+}
+
+fn setup_origin_marker_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // One line per axis, colored X red, Y green, Z blue
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    let axes = [
+        (Vec3::X, Color::RED),
+        (Vec3::Y, Color::GREEN),
+        (Vec3::Z, Color::BLUE),
+    ];
+    let positions: Vec<[f32; 3]> = axes
+        .iter()
+        .flat_map(|(axis, _)| [[0.0; 3], axis.to_array()])
+        .collect();
+    let colors: Vec<[f32; 4]> = axes
+        .iter()
+        .flat_map(|(_, color)| [color.as_linear_rgba_f32(); 2])
+        .collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+
+    commands.insert_resource(OriginMarkerAssets {
+        mesh: meshes.add(mesh),
+        material: materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            ..Default::default()
+        }),
+    });
+}
+
+/// Adds or removes markers to match the setting,
+/// and keeps them a constant size on screen
+fn update_origin_markers(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    assets: Res<OriginMarkerAssets>,
+    previews: Query<Entity, With<BlenderPreviewObject>>,
+    mut markers: Query<(Entity, &Parent, &mut Transform, &GlobalTransform), With<OriginMarker>>,
+    cameras: Query<&GlobalTransform, With<LauncherCamera>>,
+) {
+    if !settings.show_origins {
+        for (marker, ..) in markers.iter() {
+            commands.entity(marker).despawn();
+        }
+        return;
+    }
+
+    let marked: HashSet<Entity> = markers.iter().map(|(_, parent, ..)| parent.get()).collect();
+    for preview in previews.iter().filter(|preview| !marked.contains(preview)) {
+        let marker = commands
+            .spawn((
+                OriginMarker,
+                // Always visible, even when the origin is inside the geometry
+                overlay(),
+                PbrBundle {
+                    mesh: assets.mesh.clone(),
+                    material: assets.material.clone(),
+                    ..Default::default()
+                },
+            ))
+            .id();
+        commands.entity(preview).add_child(marker);
+    }
+
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    for (_, _, mut transform, global_transform) in markers.iter_mut() {
+        let distance = camera
+            .translation()
+            .distance(global_transform.translation());
+        let scale = Vec3::splat((distance * MARKER_SCREEN_SCALE).max(0.01));
+        if transform.scale != scale {
+            transform.scale = scale;
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy_egui::egui::{self, collapsing_header::CollapsingState};
+
+use crate::{object_list::matches_filter, scenes::CollectionInfo, File};
+
+/// Deepest parenting we follow, in case a broken file parents objects in a loop
+const MAX_PARENT_DEPTH: usize = 64;
+
+/// A step of the path from the scene down to an object or collection
+#[derive(Clone, Debug, PartialEq)]
+pub enum Crumb {
+    Collection(String),
+    /// An index into `File::meshes`
+    Object(usize),
+}
+
+impl File {
+    /// The collection tree of the chosen scene, `None` for files without collections
+    pub fn scene_collection(&self) -> Option<&CollectionInfo> {
+        self.scenes
+            .get(self.scene_choice.scene)
+            .and_then(|scene| scene.collection.as_ref())
+    }
+
+    /// Index of the object the given one is parented to
+    pub fn parent_of(&self, object: usize) -> Option<usize> {
+        let parent = self.meshes.get(object)?.parent.as_deref()?;
+        self.meshes.iter().position(|object| object.name == parent)
+    }
+
+    /// The collections and parents an object is nested in, outermost first and ending with
+    /// the object itself. Parented objects go by the collection of their topmost parent,
+    /// which is where the outliner shows them.
+    pub fn object_path(&self, object: usize) -> Vec<Crumb> {
+        let mut objects = vec![object];
+        while let Some(parent) = objects.last().and_then(|last| self.parent_of(*last)) {
+            if objects.len() > MAX_PARENT_DEPTH || objects.contains(&parent) {
+                break;
+            }
+            objects.push(parent);
+        }
+        objects.reverse();
+
+        let topmost = self.meshes[objects[0]].name.as_str();
+        let mut path = self.collection_path_where(|collection| {
+            collection.objects.iter().any(|name| name == topmost)
+        });
+        path.extend(objects.into_iter().map(Crumb::Object));
+        path
+    }
+
+    /// The collections a collection is nested in, outermost first and ending with itself
+    pub fn collection_path(&self, name: &str) -> Vec<Crumb> {
+        self.collection_path_where(|collection| collection.name == name)
+    }
+
+    /// The path to the first collection `found` is true for, leaving out the scene's own
+    fn collection_path_where(&self, found: impl Fn(&CollectionInfo) -> bool) -> Vec<Crumb> {
+        let mut path = Vec::new();
+        if let Some(collection) = self.scene_collection() {
+            find_collection(collection, &found, 0, &mut path);
+        }
+        path.into_iter()
+            .skip(1)
+            .map(|name| Crumb::Collection(name.to_string()))
+            .collect()
+    }
+}
+
+/// Pushes the names from `collection` down to the first collection `found` is true for,
+/// and returns whether there was one
+fn find_collection<'a>(
+    collection: &'a CollectionInfo,
+    found: &impl Fn(&CollectionInfo) -> bool,
+    depth: usize,
+    path: &mut Vec<&'a str>,
+) -> bool {
+    path.push(&collection.name);
+    if found(collection)
+        || (depth < MAX_PARENT_DEPTH
+            && collection
+                .children
+                .iter()
+                .any(|child| find_collection(child, found, depth + 1, path)))
+    {
+        return true;
+    }
+    path.pop();
+    false
+}
+
+/// The selected collection and what the outliner should bring into view
+#[derive(Clone, Copy, Default)]
+pub struct OutlinerTargets<'a> {
+    pub selected_collection: Option<&'a str>,
+    pub scroll_to_object: Option<usize>,
+    pub scroll_to_collection: Option<&'a str>,
+}
+
+/// Who's parented to whom in a file, and what the outliner is filtered by
+struct Hierarchy<'a> {
+    file: &'a File,
+    filter: &'a str,
+    /// Index into `File::meshes` of each object's parent
+    parents: HashMap<usize, usize>,
+    children: HashMap<usize, Vec<usize>>,
+    targets: OutlinerTargets<'a>,
+}
+
+impl<'a> Hierarchy<'a> {
+    fn new(file: &'a File, filter: &'a str, targets: OutlinerTargets<'a>) -> Self {
+        let by_name: HashMap<&str, usize> = file
+            .meshes
+            .iter()
+            .enumerate()
+            .map(|(index, object)| (object.name.as_str(), index))
+            .collect();
+        let mut parents = HashMap::new();
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (index, object) in file.meshes.iter().enumerate() {
+            let Some(&parent) = object.parent.as_deref().and_then(|name| by_name.get(name)) else {
+                continue;
+            };
+            parents.insert(index, parent);
+            children.entry(parent).or_default().push(index);
+        }
+        Self {
+            file,
+            filter,
+            parents,
+            children,
+            targets,
+        }
+    }
+
+    /// Whether the object or anything parented to it (within `objects`) matches the filter
+    fn shows(&self, object: usize, objects: &HashSet<usize>, depth: usize) -> bool {
+        matches_filter(&self.file.meshes[object].name, self.filter)
+            || (depth < MAX_PARENT_DEPTH
+                && self
+                    .children_in(object, objects)
+                    .any(|child| self.shows(child, objects, depth + 1)))
+    }
+
+    fn children_in<'b>(
+        &'b self,
+        object: usize,
+        objects: &'b HashSet<usize>,
+    ) -> impl Iterator<Item = usize> + 'b {
+        self.children
+            .get(&object)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|child| objects.contains(child))
+    }
+
+    /// Whether the object to scroll to is parented to `object`, however far down
+    fn leads_to_scroll_target(&self, object: usize) -> bool {
+        let Some(mut current) = self.targets.scroll_to_object else {
+            return false;
+        };
+        for _ in 0..MAX_PARENT_DEPTH {
+            match self.parents.get(&current) {
+                Some(&parent) if parent == object => return true,
+                Some(&parent) => current = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+}
+
+/// The file's objects as a tree, the chosen scene's collections with the objects in them and
+/// objects under the ones they're parented to, like Blender's outliner. Files without
+/// collections only get the parenting. `row_ui` lays out an object's own row.
+/// Returns the collection whose row was clicked.
+pub fn outliner_ui(
+    ui: &mut egui::Ui,
+    file: &File,
+    filter: &str,
+    targets: OutlinerTargets,
+    row_ui: &mut impl FnMut(&mut egui::Ui, usize),
+) -> Option<String> {
+    let hierarchy = Hierarchy::new(file, filter, targets);
+    match file.scene_collection() {
+        Some(collection) => collection_ui(ui, &hierarchy, collection, 0, row_ui),
+        None => {
+            let objects: HashSet<usize> = (0..file.meshes.len()).collect();
+            objects_ui(ui, &hierarchy, "", &objects, row_ui);
+            None
+        }
+    }
+}
+
+fn collection_ui(
+    ui: &mut egui::Ui,
+    hierarchy: &Hierarchy,
+    collection: &CollectionInfo,
+    depth: usize,
+    row_ui: &mut impl FnMut(&mut egui::Ui, usize),
+) -> Option<String> {
+    let file = hierarchy.file;
+    let names: HashSet<&str> = collection.objects.iter().map(String::as_str).collect();
+    let objects: HashSet<usize> = file
+        .meshes
+        .iter()
+        .enumerate()
+        .filter(|(_, object)| names.contains(object.name.as_str()))
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut nested = Vec::new();
+    collection.all_objects(&mut nested);
+    let filtering = !hierarchy.filter.is_empty();
+    if filtering
+        && !nested
+            .iter()
+            .any(|name| matches_filter(name, hierarchy.filter))
+    {
+        return None;
+    }
+    let targets = hierarchy.targets;
+    let is_target = targets.scroll_to_collection == Some(collection.name.as_str());
+    let leads_to_target = targets
+        .scroll_to_object
+        .is_some_and(|target| nested.contains(&file.meshes[target].name.as_str()))
+        || targets.scroll_to_collection.is_some_and(|target| {
+            collection
+                .children
+                .iter()
+                .any(|child| child.find(target).is_some())
+        });
+
+    // The scene's own collection is always there, so it isn't worth a row
+    if depth == 0 {
+        return collection_contents_ui(ui, hierarchy, collection, &objects, depth, row_ui);
+    }
+    let id = egui::Id::new(("outliner_collection", &file.path, &collection.name));
+    let header = |ui: &mut egui::Ui| {
+        let selected = targets.selected_collection == Some(collection.name.as_str());
+        let response = ui.selectable_label(selected, format!("🗀 {}", collection.name));
+        ui.weak(nested.len().to_string());
+        if is_target {
+            response.scroll_to_me(Some(egui::Align::Center));
+        }
+        response.clicked()
+    };
+    // Filtering shows every match, whatever was collapsed
+    let (clicked, nested_clicked) = if filtering {
+        let clicked = ui.horizontal(header).inner;
+        let nested_clicked = ui
+            .indent(id, |ui| {
+                collection_contents_ui(ui, hierarchy, collection, &objects, depth, row_ui)
+            })
+            .inner;
+        (clicked, nested_clicked)
+    } else {
+        let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, true);
+        if leads_to_target {
+            state.set_open(true);
+        }
+        let (_, header, body) = state
+            .show_header(ui, header)
+            .body(|ui| collection_contents_ui(ui, hierarchy, collection, &objects, depth, row_ui));
+        (header.inner, body.and_then(|body| body.inner))
+    };
+    if clicked {
+        Some(collection.name.clone())
+    } else {
+        nested_clicked
+    }
+}
+
+fn collection_contents_ui(
+    ui: &mut egui::Ui,
+    hierarchy: &Hierarchy,
+    collection: &CollectionInfo,
+    objects: &HashSet<usize>,
+    depth: usize,
+    row_ui: &mut impl FnMut(&mut egui::Ui, usize),
+) -> Option<String> {
+    let mut clicked = None;
+    for child in collection.children.iter() {
+        clicked = collection_ui(ui, hierarchy, child, depth + 1, row_ui).or(clicked);
+    }
+    objects_ui(ui, hierarchy, &collection.name, objects, row_ui);
+    clicked
+}
+
+/// The path to an object or collection, each step but the last a link to it.
+/// Returns the step that was clicked.
+pub fn breadcrumb_ui(ui: &mut egui::Ui, file: &File, path: &[Crumb]) -> Option<Crumb> {
+    let mut clicked = None;
+    ui.horizontal_wrapped(|ui| {
+        for (index, crumb) in path.iter().enumerate() {
+            if index > 0 {
+                ui.weak("▸");
+            }
+            let label = match crumb {
+                Crumb::Collection(name) => format!("🗀 {}", name),
+                Crumb::Object(object) => {
+                    let object = &file.meshes[*object];
+                    format!("{} {}", object.kind.icon(), object.name)
+                }
+            };
+            if index + 1 == path.len() {
+                ui.strong(label);
+            } else if ui.link(label).clicked() {
+                clicked = Some(crumb.clone());
+            }
+        }
+    });
+    clicked
+}
+
+/// The objects of a collection, each under its parent when that's in the collection too
+fn objects_ui(
+    ui: &mut egui::Ui,
+    hierarchy: &Hierarchy,
+    collection: &str,
+    objects: &HashSet<usize>,
+    row_ui: &mut impl FnMut(&mut egui::Ui, usize),
+) {
+    let mut roots: Vec<usize> = objects
+        .iter()
+        .copied()
+        .filter(|object| {
+            hierarchy
+                .parents
+                .get(object)
+                .map_or(true, |parent| !objects.contains(parent))
+        })
+        .collect();
+    // In file order, like the flat list
+    roots.sort_unstable();
+    for object in roots {
+        object_ui(ui, hierarchy, collection, objects, object, 0, row_ui);
+    }
+}
+
+fn object_ui(
+    ui: &mut egui::Ui,
+    hierarchy: &Hierarchy,
+    collection: &str,
+    objects: &HashSet<usize>,
+    object: usize,
+    depth: usize,
+    row_ui: &mut impl FnMut(&mut egui::Ui, usize),
+) {
+    if !hierarchy.shows(object, objects, depth) {
+        return;
+    }
+    let mut children: Vec<usize> = hierarchy.children_in(object, objects).collect();
+    children.sort_unstable();
+    if children.is_empty() || depth >= MAX_PARENT_DEPTH {
+        row_ui(ui, object);
+        return;
+    }
+
+    // Objects can be in several collections, and get a node in each
+    let id = egui::Id::new((
+        "outliner_object",
+        &hierarchy.file.path,
+        collection,
+        &hierarchy.file.meshes[object].name,
+    ));
+    let children_ui = |ui: &mut egui::Ui, row_ui: &mut _| {
+        for child in children {
+            object_ui(ui, hierarchy, collection, objects, child, depth + 1, row_ui);
+        }
+    };
+    if !hierarchy.filter.is_empty() {
+        row_ui(ui, object);
+        ui.indent(id, |ui| children_ui(ui, row_ui));
+        return;
+    }
+    let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, false);
+    if hierarchy.leads_to_scroll_target(object) {
+        state.set_open(true);
+    }
+    state
+        .show_header(ui, |ui| row_ui(ui, object))
+        .body(|ui| children_ui(ui, row_ui));
+}
@@ -0,0 +1,87 @@
+use bevy::{
+    core_pipeline::{clear_color::ClearColorConfig, tonemapping::Tonemapping},
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::view::RenderLayers,
+};
+
+use crate::settings::Settings;
+
+/// Render layer of the helpers drawn over the previews, like origin markers and the gizmo.
+/// Quick look and the thumbnails have layers 1 and 2.
+pub const OVERLAY_LAYER: u8 = 3;
+
+/// The preview camera's second pass, which draws the overlay layer on top of what the
+/// preview camera drew. It's a child of the preview camera, so it's always on the same view.
+#[derive(Component)]
+pub struct OverlayCamera;
+
+/// Puts a helper on the overlay layer. Only the overlay pass draws it, so it's never hidden
+/// inside the geometry, and it doesn't cast shadows onto the previews.
+pub fn overlay() -> impl Bundle {
+    (RenderLayers::layer(OVERLAY_LAYER), NotShadowCaster)
+}
+
+/// To spawn as a child of the preview camera
+pub fn overlay_camera() -> impl Bundle {
+    (
+        OverlayCamera,
+        RenderLayers::layer(OVERLAY_LAYER),
+        Camera3dBundle {
+            camera: Camera {
+                order: 1,
+                ..Default::default()
+            },
+            camera_3d: Camera3d {
+                // Keeps the previews, and starts from a fresh depth buffer so nothing covers
+                // the overlays
+                clear_color: ClearColorConfig::None,
+                ..Default::default()
+            },
+            // The preview camera already tonemapped what's under the overlays
+            tonemapping: Tonemapping::None,
+            ..Default::default()
+        },
+    )
+}
+
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(follow_preview_camera);
+    }
+}
+
+/// Keeps the overlay pass on the preview camera's projection, and off while overlays are hidden
+fn follow_preview_camera(
+    settings: Res<Settings>,
+    previews: Query<(&Camera, &Projection), Without<OverlayCamera>>,
+    mut overlays: Query<(&Parent, &mut Camera, &mut Projection), With<OverlayCamera>>,
+) {
+    for (parent, mut camera, mut projection) in overlays.iter_mut() {
+        let Ok((preview_camera, preview_projection)) = previews.get(parent.get()) else {
+            continue;
+        };
+        if camera.is_active != settings.show_overlays {
+            camera.is_active = settings.show_overlays;
+        }
+        // Both passes draw into the window's textures, which only works when they agree on HDR
+        if camera.hdr != preview_camera.hdr {
+            camera.hdr = preview_camera.hdr;
+        }
+        // The aspect ratio is kept up to date for each camera on its own
+        if let (Projection::Perspective(preview), Projection::Perspective(overlay)) =
+            (preview_projection, &*projection)
+        {
+            if (overlay.fov, overlay.near, overlay.far) != (preview.fov, preview.near, preview.far)
+            {
+                if let Projection::Perspective(overlay) = &mut *projection {
+                    overlay.fov = preview.fov;
+                    overlay.near = preview.near;
+                    overlay.far = preview.far;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! The parts of the launcher's panels that work on their own, for drawing into any `Ui`.
+//! Anything they'd do to the preview is up to the caller, like sending a [`crate::SpawnEvent`]
+//! for the object [`recent_spawns_ui`] returns. The whole file browser and inspector need
+//! the launcher's plugins, and [`PanelResources`] and [`PanelEvents`] in the drawing system.
+
+pub use crate::{
+    console::{console_ui, LogConsole},
+    file_browser_ui, file_header_ui, inspector_ui, object_summary_ui,
+    object_tooltip::object_tooltip_ui,
+    orphan_data_ui,
+    parse_warnings::{parse_warnings_ui, warnings_badge_ui},
+    recent_spawns::recent_spawns_ui,
+    scale_audit_ui,
+    scenes::{scene_selector_ui, SceneChoice},
+    texture_audit_ui, PanelEvents, PanelResources,
+};
@@ -0,0 +1,1038 @@
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt, fs,
+    panic::AssertUnwindSafe,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use blend::{Blend, Instance};
+use futures_lite::future;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    builds::BlenderVersion,
+    console::LogConsole,
+    crash::catch_expected_panic,
+    curves::object_outline,
+    debug::SystemRuns,
+    empties::empty_display,
+    file_status::{FileOp, FileStatus},
+    i18n::t,
+    launcher_events::MetadataLoaded,
+    metadata::{
+        constraint_names, driver_count, id_name, image_files_exist, material_color, material_slots,
+        mesh_bounds, modifier_names, object_color, object_scale, object_transform, open_blend,
+        parent_name, parse_images, read_header, BlendHeader, Endianness, IdUsers, ImageInfo,
+        ImageSource, MeshStats, ObjectKind, OB_MESH,
+    },
+    metadata_cache::{cached, Fingerprint, MetadataCache, SharedEntries},
+    natural_sort::natural_cmp,
+    properties::custom_properties,
+    scenes::{active_scene, parse_scenes, SceneInfo},
+    settings::Settings,
+    toasts::{Severity, ToastAction, Toasts},
+    units::SceneUnits,
+    AppState, BlendObject, File, FileId, LoadBlenderData, ReparseFile,
+};
+
+/// Where a file is in the parse queue
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseStatus {
+    Queued,
+    Parsing,
+}
+
+/// Something in a file we read around instead of failing the whole parse
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParseWarning {
+    /// Code of the datablock it's about, `OB` for objects and `IM` for images
+    pub code: String,
+    /// The datablock's name, or its position among the blocks with its code when even
+    /// that can't be read
+    pub name: String,
+    pub kind: ParseWarningKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ParseWarningKind {
+    /// Reading it panicked, so it was left out. Holds the panic message.
+    Skipped(String),
+    /// An object type this build doesn't know, listed but never previewed
+    UnknownObjectType(i16),
+    /// Packed image data we can't make sense of, e.g. compressed or in an unusual format
+    UnreadablePackedImage,
+}
+
+impl ParseWarning {
+    pub fn message(&self) -> String {
+        match &self.kind {
+            ParseWarningKind::Skipped(error) => t!("parse.warning.skipped", error = error),
+            ParseWarningKind::UnknownObjectType(value) => {
+                t!("parse.warning.unknown_type", value = value)
+            }
+            ParseWarningKind::UnreadablePackedImage => t!("parse.warning.packed_image").to_string(),
+        }
+    }
+}
+
+/// Why a file couldn't be parsed at all
+#[derive(Clone, Debug)]
+pub enum ParseError {
+    /// Not a blend file, or one the blend crate can't make sense of
+    Open(String),
+    /// Reading the file went wrong outside of any one object, e.g. in a truncated block
+    Corrupt(String),
+    /// Saved by a big-endian build (old PowerPC Macs and the like), which the blend crate
+    /// would read as garbage
+    BigEndian,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Open(error) => write!(f, "{}", t!("parse.error.open", error = error)),
+            ParseError::Corrupt(error) => {
+                write!(f, "{}", t!("parse.error.corrupt", error = error))
+            }
+            ParseError::BigEndian => write!(f, "{}", t!("parse.error.big_endian")),
+        }
+    }
+}
+
+/// Everything we read out of a blend file on a background thread
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ParsedFile {
+    meshes: Vec<BlendObject>,
+    materials: Vec<String>,
+    material_ids: HashMap<String, IdUsers>,
+    material_colors: HashMap<String, [f32; 4]>,
+    mesh_blocks: Vec<(String, IdUsers)>,
+    images: Vec<ImageInfo>,
+    units: SceneUnits,
+    scenes: Vec<SceneInfo>,
+    active_scene: Option<usize>,
+    version: Option<BlenderVersion>,
+    #[serde(default)]
+    header: Option<BlendHeader>,
+    #[serde(default)]
+    warnings: Vec<ParseWarning>,
+}
+
+/// How many of each kind of datablock a parse found
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct BlockCounts {
+    pub objects: usize,
+    pub meshes: usize,
+    pub materials: usize,
+    pub images: usize,
+    pub scenes: usize,
+}
+
+impl ParsedFile {
+    pub fn block_counts(&self) -> BlockCounts {
+        BlockCounts {
+            objects: self.meshes.len(),
+            meshes: self.mesh_blocks.len(),
+            materials: self.materials.len(),
+            images: self.images.len(),
+            scenes: self.scenes.len(),
+        }
+    }
+
+    pub fn skipped_objects(&self) -> usize {
+        self.warnings
+            .iter()
+            .filter(|warning| matches!(warning.kind, ParseWarningKind::Skipped(_)))
+            .count()
+    }
+
+    /// Puts the objects and materials in natural name order instead of block order
+    pub fn sort_by_name(&mut self) {
+        self.meshes.sort_by(|a, b| natural_cmp(&a.name, &b.name));
+        self.materials.sort_by(|a, b| natural_cmp(a, b));
+    }
+}
+
+/// A finished parse, and what to store in the metadata cache
+struct ParseResult {
+    parsed: ParsedFile,
+    /// Set when the file was parsed rather than read from the cache
+    cache_key: Option<(PathBuf, Fingerprint)>,
+}
+
+/// Parses blend files in the background, at most `Settings::parse_concurrency` at a time
+#[derive(Default, Resource)]
+pub struct ParseQueue {
+    queued: VecDeque<FileId>,
+    running: HashMap<FileId, Task<Result<ParseResult, ParseError>>>,
+    /// Queued files that skip the metadata cache
+    forced: HashSet<FileId>,
+    /// Files added to the current batch, reset once everything is parsed
+    total: usize,
+    finished: usize,
+    /// Files with an ID below this have already been queued automatically
+    next_unseen_id: u64,
+}
+
+impl ParseQueue {
+    pub fn status(&self, file_id: FileId) -> Option<ParseStatus> {
+        if self.running.contains_key(&file_id) {
+            Some(ParseStatus::Parsing)
+        } else if self.queued.contains(&file_id) {
+            Some(ParseStatus::Queued)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_busy(&self) -> bool {
+        !self.queued.is_empty() || !self.running.is_empty()
+    }
+
+    /// (parsed, total) for the current batch
+    pub fn progress(&self) -> (usize, usize) {
+        (self.finished, self.total)
+    }
+
+    /// Drops every parse that hasn't started yet, running ones finish normally.
+    /// Returns the files that were dropped.
+    pub fn cancel_queued(&mut self) -> Vec<FileId> {
+        self.total -= self.queued.len();
+        self.forced.clear();
+        self.queued.drain(..).collect()
+    }
+
+    /// Drops every parse. Parses already running finish on their thread,
+    /// but their results are thrown away.
+    pub fn cancel_all(&mut self) {
+        self.cancel_queued();
+        self.running.clear();
+        self.total = 0;
+        self.finished = 0;
+    }
+
+    fn enqueue(&mut self, file_id: FileId) {
+        if self.status(file_id).is_none() {
+            self.queued.push_back(file_id);
+            self.total += 1;
+        }
+    }
+}
+
+pub struct ParseQueuePlugin;
+
+impl Plugin for ParseQueuePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParseQueue>()
+            .add_system(
+                queue_parses.run_if(
+                    on_event::<LoadBlenderData>()
+                        .or_else(on_event::<ReparseFile>())
+                        .or_else(resource_changed::<AppState>()),
+                ),
+            )
+            .add_system(
+                run_parses
+                    .after(queue_parses)
+                    .run_if(|queue: Res<ParseQueue>| queue.is_busy()),
+            );
+    }
+}
+
+/// Queues newly added files, and any file we were explicitly asked to parse
+fn queue_parses(
+    mut load_events: EventReader<LoadBlenderData>,
+    mut reparse_events: EventReader<ReparseFile>,
+    mut queue: ResMut<ParseQueue>,
+    mut app_state: ResMut<AppState>,
+) {
+    // File IDs only ever go up, so anything at or past the last ID we saw is new
+    let next_unseen_id = queue.next_unseen_id;
+    let new_files: Vec<FileId> = app_state
+        .files
+        .iter()
+        .filter(|file| file.id.0 >= next_unseen_id)
+        .map(|file| file.id)
+        .collect();
+    for file_id in new_files {
+        if app_state.begin(file_id, FileOp::Parse) {
+            queue.enqueue(file_id);
+        }
+    }
+    queue.next_unseen_id = app_state.next_file_id;
+
+    // Files that are already queued or parsing are busy, so they're left alone.
+    // That also means a running parse is never asked to skip the cache after the fact.
+    for LoadBlenderData(file_id) in load_events.iter() {
+        let op = FileOp::load(app_state.file(*file_id).is_some_and(|file| file.parsed));
+        if app_state.begin(*file_id, op) {
+            queue.enqueue(*file_id);
+        }
+    }
+    for ReparseFile(file_id) in reparse_events.iter() {
+        let op = FileOp::load(app_state.file(*file_id).is_some_and(|file| file.parsed));
+        if app_state.begin(*file_id, op) {
+            queue.forced.insert(*file_id);
+            queue.enqueue(*file_id);
+        }
+    }
+}
+
+/// Starts queued parses as slots free up and applies the finished ones
+fn run_parses(
+    mut queue: ResMut<ParseQueue>,
+    mut app_state: ResMut<AppState>,
+    mut metadata_cache: ResMut<MetadataCache>,
+    settings: Res<Settings>,
+    mut console: ResMut<LogConsole>,
+    mut toasts: ResMut<Toasts>,
+    mut loaded: EventWriter<MetadataLoaded>,
+    mut system_runs: ResMut<SystemRuns>,
+) {
+    system_runs.record("run_parses");
+
+    let mut finished = Vec::new();
+    for (file_id, task) in queue.running.iter_mut() {
+        if let Some(result) = future::block_on(future::poll_once(task)) {
+            finished.push((*file_id, result));
+        }
+    }
+
+    for (file_id, result) in finished {
+        queue.running.remove(&file_id);
+        queue.finished += 1;
+
+        let ParseResult {
+            mut parsed,
+            cache_key,
+        } = match result {
+            Ok(result) => result,
+            Err(error) => {
+                if let Some(file) = app_state.file_mut(file_id) {
+                    console.push(format!("Couldn't parse {}: {}", file.path, error));
+                    toasts.push(
+                        Severity::Error,
+                        t!("parse.failed", path = file.path, error = error),
+                    );
+                    file.status = file.status.finish(false);
+                    file.parse_error = Some(error.to_string());
+                }
+                continue;
+            }
+        };
+
+        if let Some((path, fingerprint)) = cache_key {
+            metadata_cache.insert(path, fingerprint, parsed.clone());
+        }
+
+        // The file might have been removed while it was parsing
+        let Some(file) = app_state.file_mut(file_id) else {
+            continue;
+        };
+        // The cache keeps the file's own order, so the setting applies to cached files too
+        if settings.sort_by_name {
+            parsed.sort_by_name();
+        }
+        apply_parsed_file(file, parsed);
+        file.status = file.status.finish(true);
+        loaded.send(MetadataLoaded(file_id));
+        if !file.parse_warnings.is_empty() {
+            toasts.push(
+                Severity::Warning,
+                t!(
+                    "parse.warnings",
+                    count = file.parse_warnings.len(),
+                    path = file.path
+                ),
+            );
+        }
+
+        console.push(format!(
+            "Parsed {} objects from {}",
+            file.meshes.len(),
+            file.path
+        ));
+        if queue.total == 1 {
+            toasts.push_with_action(
+                Severity::Info,
+                t!(
+                    "parse.file_done",
+                    count = file.meshes.len(),
+                    path = file.path
+                ),
+                ToastAction::ShowLog,
+            );
+        }
+    }
+
+    let thread_pool = AsyncComputeTaskPool::get();
+    while queue.running.len() < settings.parse_concurrency.max(1) {
+        let Some(file_id) = queue.queued.pop_front() else {
+            break;
+        };
+        let forced = queue.forced.remove(&file_id);
+        // Removed before it got a slot
+        let Some(file) = app_state.file_mut(file_id) else {
+            queue.total -= 1;
+            continue;
+        };
+        if !Path::new(&file.path).exists() {
+            warn!(path = %file.path, "blend file is missing");
+            toasts.push(
+                Severity::Warning,
+                t!("file_status.missing_toast", path = file.path),
+            );
+            file.status = FileStatus::Missing;
+            queue.total -= 1;
+            continue;
+        }
+
+        console.push(format!("Loading file metadata {}", file.path));
+        let path = file.path.clone();
+        let cache = (settings.metadata_cache && !forced).then(|| metadata_cache.entries());
+        let task = thread_pool.spawn(async move { parse_or_read_cache(&path, cache) });
+        queue.running.insert(file_id, task);
+    }
+
+    // The batch is done, start counting from zero next time
+    if !queue.is_busy() {
+        if queue.total > 1 {
+            toasts.push_with_action(
+                Severity::Info,
+                t!("parse.batch_done", count = queue.finished),
+                ToastAction::ShowLog,
+            );
+        }
+        queue.total = 0;
+        queue.finished = 0;
+    }
+}
+
+/// Reads the file's metadata from the cache when its contents haven't changed, else parses it.
+/// Without a cache every file is parsed and nothing is stored.
+fn parse_or_read_cache(
+    path: &str,
+    cache: Option<SharedEntries>,
+) -> Result<ParseResult, ParseError> {
+    let Some(cache) = cache else {
+        return Ok(ParseResult {
+            parsed: parse_blend_file(path)?,
+            cache_key: None,
+        });
+    };
+
+    let key = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    let fingerprint = match Fingerprint::of(Path::new(path)) {
+        Ok(fingerprint) => fingerprint,
+        Err(error) => {
+            warn!(path, %error, "couldn't fingerprint blend file, skipping the cache");
+            return Ok(ParseResult {
+                parsed: parse_blend_file(path)?,
+                cache_key: None,
+            });
+        }
+    };
+
+    if let Some(mut parsed) = cached(&cache, &key, fingerprint) {
+        debug!(path, "read metadata from the cache");
+        // Linked images can come and go without the blend file changing
+        for image in parsed.images.iter_mut() {
+            if let ImageSource::Linked {
+                path,
+                tiles,
+                exists,
+            } = &mut image.source
+            {
+                *exists = image_files_exist(path, tiles);
+            }
+        }
+        return Ok(ParseResult {
+            parsed,
+            cache_key: None,
+        });
+    }
+
+    Ok(ParseResult {
+        parsed: parse_blend_file(path)?,
+        cache_key: Some((key, fingerprint)),
+    })
+}
+
+/// The message a panic was raised with, all the blend crate tells us about what went wrong
+pub fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown error".to_string()
+    }
+}
+
+/// Reads the objects, materials, and images out of a blend file.
+/// Truncated or otherwise damaged files make the blend crate panic on field lookups,
+/// so this never lets a panic out: an object that can't be read is skipped with a warning,
+/// and anything else fails the parse with a `ParseError`.
+pub fn parse_blend_file(path: &str) -> Result<ParsedFile, ParseError> {
+    // Everything logged while parsing carries the path, e.g. `parse{path=a.blend}: ...`
+    let _span = info_span!("parse", path).entered();
+    let started = Instant::now();
+
+    // Checked up front, the blend crate only reads little-endian files
+    let header = read_header(Path::new(path));
+    if header.is_some_and(|header| header.endianness == Endianness::Big) {
+        error!(stage = "open", "big-endian blend file");
+        return Err(ParseError::BigEndian);
+    }
+    let blend = catch_expected_panic(|| open_blend(Path::new(path)))
+        .unwrap_or_else(|panic| Err(panic_message(panic.as_ref())))
+        .map_err(|error| {
+            error!(stage = "open", %error, "couldn't load blend file");
+            ParseError::Open(error)
+        })?;
+    let parsed = catch_expected_panic(AssertUnwindSafe(|| read_blend(&blend, path, header)))
+        .map_err(|panic| {
+            let error = panic_message(panic.as_ref());
+            error!(stage = "read", %error, "blend file is corrupt");
+            ParseError::Corrupt(error)
+        })?;
+
+    // The same numbers `--bench` reports, so a slow load in a log can be compared against it
+    let blocks = parsed.block_counts();
+    info!(
+        objects = blocks.objects,
+        meshes = blocks.meshes,
+        materials = blocks.materials,
+        images = blocks.images,
+        scenes = blocks.scenes,
+        warnings = parsed.warnings.len(),
+        duration_ms = started.elapsed().as_secs_f64() * 1000.0,
+        "parsed blend file"
+    );
+    Ok(parsed)
+}
+
+fn read_blend(blend: &Blend, path: &str, header: Option<BlendHeader>) -> ParsedFile {
+    let mut materials = Vec::new();
+    let mut material_ids = HashMap::new();
+    let mut material_colors = HashMap::new();
+    for material in blend.instances_with_code(*b"MA") {
+        let name = id_name(&material);
+        material_ids.insert(name.clone(), IdUsers::of(&material));
+        material_colors.insert(name.clone(), material_color(&material));
+        materials.push(name);
+    }
+    let mesh_blocks: Vec<(String, IdUsers)> = blend
+        .instances_with_code(*b"ME")
+        .map(|mesh| (id_name(&mesh), IdUsers::of(&mesh)))
+        .collect();
+    let images = parse_images(blend, Path::new(path));
+
+    // Loop through all the objects in the Blender file, one bad object shouldn't lose us the rest
+    let mut meshes = Vec::new();
+    let mut warnings = Vec::new();
+    for (index, obj) in blend.instances_with_code(*b"OB").enumerate() {
+        match catch_expected_panic(AssertUnwindSafe(|| parse_object(&obj))) {
+            Ok(object) => meshes.push(object),
+            Err(panic) => {
+                // The name is in the same block, so it may well be unreadable too
+                let name = catch_expected_panic(AssertUnwindSafe(|| object_name(&obj)))
+                    .unwrap_or_else(|_| format!("#{}", index));
+                let message = panic_message(panic.as_ref());
+                warn!(object = %name, error = %message, "skipped unreadable object");
+                warnings.push(ParseWarning {
+                    code: "OB".to_string(),
+                    name,
+                    kind: ParseWarningKind::Skipped(message),
+                });
+            }
+        }
+    }
+    for object in &meshes {
+        if let ObjectKind::Other(value) = object.kind {
+            warnings.push(ParseWarning {
+                code: "OB".to_string(),
+                name: object.name.clone(),
+                kind: ParseWarningKind::UnknownObjectType(value),
+            });
+        }
+    }
+    for image in &images {
+        if matches!(image.source, ImageSource::Packed) && image.resolution.is_none() {
+            warnings.push(ParseWarning {
+                code: "IM".to_string(),
+                name: image.name.clone(),
+                kind: ParseWarningKind::UnreadablePackedImage,
+            });
+        }
+    }
+
+    let scenes = parse_scenes(blend);
+    ParsedFile {
+        meshes,
+        materials,
+        material_ids,
+        material_colors,
+        mesh_blocks,
+        images,
+        units: SceneUnits::from_blend(blend),
+        active_scene: active_scene(blend, &scenes),
+        scenes,
+        version: header.map(|header| header.version),
+        header,
+        warnings,
+    }
+}
+
+fn object_name(obj: &Instance) -> String {
+    let mut name_raw = obj.get("id").get_string("name");
+
+    // blend crate prefixes the names with OB, so we remove that if we find it
+    let should_remove = name_raw.starts_with("OB");
+    if should_remove {
+        name_raw.split_off(2).to_string()
+    } else {
+        name_raw
+    }
+}
+
+fn parse_object(obj: &Instance) -> BlendObject {
+    // Grab the names of each object (or "layer" like Photoshop)
+    let loc = obj.get_f32_vec("loc");
+    let name = object_name(obj);
+
+    // Only mesh objects point to an `ME` block we can count polygons in
+    let (stats, bounds, data_properties, mesh_data) =
+        if obj.get_i16("type") == OB_MESH && obj.is_valid("data") {
+            let mesh = obj.get("data");
+            (
+                Some(MeshStats::from_mesh(&mesh)),
+                mesh_bounds(&mesh),
+                custom_properties(&mesh),
+                Some(id_name(&mesh)),
+            )
+        } else {
+            (None, None, Vec::new(), None)
+        };
+
+    // Store the object (aka "mesh") names alongside the file data
+    // so we can select and load them
+    debug!(object = %name, ?loc, "found object");
+    // Blender is Z-up, Bevy is Y-up
+    let location = match loc.as_slice() {
+        [x, y, z, ..] => Vec3::new(*x, *z, -*y),
+        _ => Vec3::ZERO,
+    };
+    let kind = ObjectKind::from_type(obj.get_i16("type"));
+    BlendObject {
+        name,
+        kind,
+        stats,
+        materials: material_slots(obj),
+        modifiers: modifier_names(obj),
+        constraints: constraint_names(obj),
+        drivers: driver_count(obj),
+        color: object_color(obj),
+        properties: custom_properties(obj),
+        data_properties,
+        scale: object_scale(obj),
+        bounds,
+        outline: object_outline(obj, kind),
+        empty: empty_display(obj, kind),
+        mesh_data,
+        parent: parent_name(obj),
+        transform: object_transform(obj),
+        location,
+    }
+}
+
+fn apply_parsed_file(file: &mut File, parsed: ParsedFile) {
+    file.materials = parsed.materials;
+    file.material_ids = parsed.material_ids;
+    file.material_colors = parsed.material_colors;
+    file.mesh_blocks = parsed.mesh_blocks;
+    file.images = parsed.images;
+    file.units = parsed.units;
+    file.scenes = parsed.scenes;
+    file.active_scene = parsed.active_scene;
+    // Start out on the scene Blender had open, later parses keep what the user picked
+    if !file.parsed {
+        file.scene_choice.scene = parsed.active_scene.unwrap_or_default();
+    }
+    file.version = parsed.version;
+    file.header = parsed.header;
+    // Replace rather than append, reparsing a file must not duplicate its objects
+    file.meshes = parsed.meshes;
+    file.parsed = true;
+    file.parse_warnings = parsed.warnings;
+    file.parse_error = None;
+    file.asset_checks.clear();
+    // Read again from the cache folder of the new contents
+    file.thumbnails = None;
+    file.lods = None;
+
+    // Cross reference which objects use each material
+    file.material_users.clear();
+    for (object_id, object) in file.meshes.iter().enumerate() {
+        for material in &object.materials {
+            file.material_users
+                .entry(material.clone())
+                .or_default()
+                .push(object_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::empties::{EmptyDisplay, EmptyShape};
+
+    const DEMO_BLEND: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/demo.blend");
+    const MONKEY_BLEND: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/test-monkey.blend");
+    /// demo.blend with the Cylinder turned into an empty and SuzanneNoMat given two slots,
+    /// written by `assets/make_fixtures.py`
+    const EMPTY_AND_SLOTS_BLEND: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/demo-empty-and-slots.blend"
+    );
+    /// demo.blend as a 32-bit Blender would save it, with only the objects and what they use
+    const DEMO_32_BIT_BLEND: &str =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/assets/demo-32-bit.blend");
+
+    #[test]
+    fn reparsing_a_file_doesnt_duplicate_objects() {
+        let mut app_state = AppState::default();
+        let file_id = app_state.add_file(DEMO_BLEND.to_string());
+
+        for _ in 0..2 {
+            let parsed = parse_blend_file(DEMO_BLEND).unwrap();
+            apply_parsed_file(app_state.file_mut(file_id).unwrap(), parsed);
+        }
+
+        let file = app_state.file(file_id).unwrap();
+        assert_eq!(file.meshes.len(), 13);
+        assert_eq!(file.materials.len(), 8);
+        // Material users point into `meshes`, so they'd go out of range if it doubled
+        for users in file.material_users.values() {
+            assert!(users.iter().all(|object_id| *object_id < file.meshes.len()));
+        }
+    }
+
+    #[test]
+    fn only_mesh_objects_get_mesh_stats() {
+        for path in [DEMO_BLEND, MONKEY_BLEND] {
+            let parsed = parse_blend_file(path).unwrap();
+            assert!(parsed.warnings.is_empty(), "{:?}", parsed.warnings);
+            assert!(parsed
+                .meshes
+                .iter()
+                .any(|object| object.kind == ObjectKind::Mesh));
+            for object in parsed.meshes.iter() {
+                assert_eq!(
+                    object.stats.is_some(),
+                    object.kind == ObjectKind::Mesh,
+                    "{} in {} is a {:?}",
+                    object.name,
+                    path,
+                    object.kind
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn demo_file_contents() {
+        let parsed = parse_blend_file(DEMO_BLEND).unwrap();
+        let counts = parsed.block_counts();
+        assert_eq!(
+            (
+                counts.objects,
+                counts.meshes,
+                counts.materials,
+                counts.scenes
+            ),
+            (13, 11, 8, 1)
+        );
+        assert!(parsed.warnings.is_empty(), "{:?}", parsed.warnings);
+
+        // In the order the file stores them: name, kind and parent
+        let objects: Vec<(&str, ObjectKind, Option<&str>)> = parsed
+            .meshes
+            .iter()
+            .map(|object| (object.name.as_str(), object.kind, object.parent.as_deref()))
+            .collect();
+        assert_eq!(
+            objects,
+            [
+                ("Camera", ObjectKind::Camera, None),
+                ("Cube", ObjectKind::Mesh, None),
+                ("Cylinder", ObjectKind::Mesh, None),
+                ("Light", ObjectKind::Light, None),
+                ("LittlerSuzanne", ObjectKind::Mesh, Some("LittleSuzanne")),
+                ("LittleSuzanne", ObjectKind::Mesh, Some("Suzanne")),
+                ("Suzanne", ObjectKind::Mesh, None),
+                ("SuzanneNodesMat", ObjectKind::Mesh, None),
+                ("SuzanneNoMat", ObjectKind::Mesh, None),
+                ("TransformCube", ObjectKind::Mesh, None),
+                ("TransformCube_X", ObjectKind::Mesh, Some("TransformCube")),
+                ("TransformCube_Y", ObjectKind::Mesh, Some("TransformCube")),
+                ("TransformCube_Z", ObjectKind::Mesh, Some("TransformCube")),
+            ]
+        );
+        // Each object has at most one slot
+        let slots: Vec<Option<&str>> = parsed
+            .meshes
+            .iter()
+            .map(|object| {
+                assert!(object.materials.len() <= 1, "{}", object.name);
+                object.materials.first().map(String::as_str)
+            })
+            .collect();
+        assert_eq!(
+            slots,
+            [
+                None,
+                Some("Green"),
+                Some("Green"),
+                None,
+                Some("Blue"),
+                Some("Green"),
+                Some("Red"),
+                Some("NodesMaterial"),
+                None,
+                Some("White"),
+                Some("Red"),
+                Some("Green"),
+                Some("Blue"),
+            ]
+        );
+
+        // Duplicated objects keep pointing at a mesh named after the original
+        let mesh_data: Vec<(&str, Option<&str>)> = parsed
+            .meshes
+            .iter()
+            .filter(|object| object.name.contains("Suzanne"))
+            .map(|object| (object.name.as_str(), object.mesh_data.as_deref()))
+            .collect();
+        assert_eq!(
+            mesh_data,
+            [
+                ("LittlerSuzanne", Some("Suzanne.002")),
+                ("LittleSuzanne", Some("Suzanne.001")),
+                ("Suzanne", Some("Suzanne")),
+                ("SuzanneNodesMat", Some("Suzanne.004")),
+                ("SuzanneNoMat", Some("Suzanne.003")),
+            ]
+        );
+        assert_eq!(parsed.meshes[0].mesh_data, None);
+
+        let cube = parsed.meshes[1].stats.as_ref().unwrap();
+        assert_eq!((cube.vertices, cube.faces, cube.triangles), (8, 6, 12));
+        let suzanne = parsed.meshes[6].stats.as_ref().unwrap();
+        assert_eq!((suzanne.vertices, suzanne.faces), (507, 500));
+        // Every vertex in the file is part of a face
+        for object in parsed.meshes.iter() {
+            let loose = object
+                .stats
+                .as_ref()
+                .map_or(0, |stats| stats.loose_vertices);
+            assert_eq!(loose, 0, "{}", object.name);
+        }
+
+        assert_eq!(
+            parsed.materials,
+            [
+                "Blue",
+                "Dots Stroke",
+                "Green",
+                "MetallicRed",
+                "NodesMaterial",
+                "Red",
+                "RoughBlue",
+                "White"
+            ]
+        );
+
+        // One scene, with every object in its one collection
+        assert_eq!(parsed.scenes.len(), 1);
+        assert_eq!(parsed.active_scene, Some(0));
+        let scene = &parsed.scenes[0];
+        assert_eq!(scene.name, "Scene");
+        let master = scene.collection.as_ref().unwrap();
+        assert!(master.objects.is_empty());
+        assert_eq!(master.children.len(), 1);
+        let collection = &master.children[0];
+        assert_eq!(collection.name, "Collection");
+        assert_eq!(collection.objects.len(), 13);
+        assert!(collection.children.is_empty());
+        assert_eq!(scene.objects.as_ref().map(Vec::len), Some(13));
+    }
+
+    #[test]
+    fn empties_and_objects_with_several_slots() {
+        let parsed = parse_blend_file(EMPTY_AND_SLOTS_BLEND).unwrap();
+        assert!(parsed.warnings.is_empty(), "{:?}", parsed.warnings);
+        assert_eq!(parsed.meshes.len(), 13);
+
+        let empty = parsed
+            .meshes
+            .iter()
+            .find(|object| object.name == "Empty")
+            .unwrap();
+        assert_eq!(empty.kind, ObjectKind::Empty);
+        assert!(empty.stats.is_none());
+        assert!(empty.materials.is_empty());
+        assert_eq!(empty.mesh_data, None);
+        assert_eq!(
+            empty.empty,
+            Some(EmptyDisplay {
+                shape: EmptyShape::PlainAxes,
+                size: 1.0
+            })
+        );
+
+        // Both slots, in slot order, while every other object keeps its one
+        let suzanne = parsed
+            .meshes
+            .iter()
+            .find(|object| object.name == "SuzanneTwoMats")
+            .unwrap();
+        assert_eq!(suzanne.kind, ObjectKind::Mesh);
+        assert_eq!(suzanne.materials, ["Red", "Blue"]);
+        assert_eq!(suzanne.mesh_data.as_deref(), Some("Suzanne.003"));
+        let stats = suzanne.stats.as_ref().unwrap();
+        assert_eq!((stats.vertices, stats.faces), (507, 500));
+        for object in parsed.meshes.iter() {
+            if object.name != "SuzanneTwoMats" {
+                assert!(object.materials.len() <= 1, "{}", object.name);
+            }
+        }
+    }
+
+    /// Parses `bytes` from a temporary file, which must return whatever they hold
+    fn parse_bytes(name: &str, bytes: &[u8]) -> Result<ParsedFile, ParseError> {
+        let path = std::env::temp_dir().join(format!(
+            "blender-launcher-{}-{}.blend",
+            std::process::id(),
+            name
+        ));
+        fs::write(&path, bytes).unwrap();
+        let result = parse_blend_file(path.to_str().unwrap());
+        let _ = fs::remove_file(&path);
+        result
+    }
+
+    #[test]
+    fn files_that_arent_blends_fail_to_open() {
+        for (name, bytes) in [
+            ("empty", &b""[..]),
+            ("text", &b"definitely not a blend file"[..]),
+        ] {
+            assert!(
+                matches!(parse_bytes(name, bytes), Err(ParseError::Open(_))),
+                "{} opened",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_files_dont_panic() {
+        let bytes = fs::read(DEMO_BLEND).unwrap();
+        for len in [
+            12,
+            100,
+            4096,
+            bytes.len() / 2,
+            bytes.len() - 1000,
+            bytes.len() - 1,
+        ] {
+            let _ = parse_bytes(&format!("truncated-{}", len), &bytes[..len]);
+        }
+    }
+
+    #[test]
+    fn flipped_bytes_dont_panic() {
+        let original = fs::read(DEMO_BLEND).unwrap();
+        // Every nth byte after the header rather than random noise, so failures reproduce
+        for stride in [1009, 8191, 65521, 262139] {
+            let mut bytes = original.clone();
+            for index in (12..bytes.len()).step_by(stride) {
+                bytes[index] ^= 0xff;
+            }
+            let _ = parse_bytes(&format!("flipped-{}", stride), &bytes);
+        }
+    }
+
+    #[test]
+    fn wrong_pointer_size_claims_dont_panic() {
+        // `BLENDER-v301` is 64-bit pointers (`-`), claim 32-bit ones instead
+        let mut bytes = fs::read(DEMO_BLEND).unwrap();
+        bytes[7] = b'_';
+        let _ = parse_bytes("pointer-size", &bytes);
+    }
+
+    #[test]
+    fn files_with_32_bit_pointers_read_like_64_bit_ones() {
+        let parsed = parse_blend_file(DEMO_32_BIT_BLEND).unwrap();
+        assert!(parsed.warnings.is_empty(), "{:?}", parsed.warnings);
+        let header = parsed.header.as_ref().unwrap();
+        assert_eq!(header.pointer_size, 4);
+        assert_eq!(header.endianness, Endianness::Little);
+
+        // Everything about the objects, compared with the 64-bit file
+        let objects = |parsed: &ParsedFile| -> Vec<_> {
+            parsed
+                .meshes
+                .iter()
+                .map(|object| {
+                    (
+                        object.name.clone(),
+                        object.kind,
+                        object.parent.clone(),
+                        object.mesh_data.clone(),
+                        object.materials.clone(),
+                        object.stats.as_ref().map(|stats| {
+                            (stats.vertices, stats.edges, stats.faces, stats.triangles)
+                        }),
+                        object.transform,
+                    )
+                })
+                .collect()
+        };
+        let original = parse_blend_file(DEMO_BLEND).unwrap();
+        assert_eq!(objects(&parsed), objects(&original));
+        assert_eq!(parsed.materials, original.materials);
+        assert_eq!(parsed.scenes.len(), 1);
+        let collection = &parsed.scenes[0].collection.as_ref().unwrap().children[0];
+        assert_eq!(collection.name, "Collection");
+        assert_eq!(collection.objects.len(), 13);
+    }
+
+    #[test]
+    fn big_endian_files_are_rejected() {
+        // `v` is little-endian, `V` big-endian
+        let mut bytes = fs::read(DEMO_BLEND).unwrap();
+        bytes[8] = b'V';
+        assert!(matches!(
+            parse_bytes("big-endian", &bytes),
+            Err(ParseError::BigEndian)
+        ));
+    }
+
+    #[test]
+    fn parsed_files_keep_their_header() {
+        let parsed = parse_blend_file(DEMO_BLEND).unwrap();
+        let header = parsed.header.unwrap();
+        assert_eq!(header.pointer_size, 8);
+        assert_eq!(header.endianness, Endianness::Little);
+        assert_eq!(parsed.version, Some(header.version));
+    }
+}
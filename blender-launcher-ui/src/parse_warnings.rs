@@ -0,0 +1,62 @@
+use bevy_egui::egui;
+
+use crate::{
+    accessibility::{status_label, Status},
+    i18n::t,
+    toasts::{Severity, Toasts},
+    File,
+};
+
+/// The warning count on a file row, colored like any other warning
+pub fn warnings_badge_ui(ui: &mut egui::Ui, file: &File) {
+    let count = file.parse_warnings.len();
+    if count > 0 {
+        status_label(ui, Status::Warning, count)
+            .on_hover_text(t!("parse_warnings.badge", count = count));
+    }
+}
+
+/// What the last parse read around, one row per warning with the block it's about
+pub fn parse_warnings_ui(ui: &mut egui::Ui, file: &File, toasts: &mut Toasts) {
+    egui::Grid::new(("parse_warnings", file.id.0))
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for warning in &file.parse_warnings {
+                ui.monospace(warning.code.as_str());
+                ui.label(warning.name.as_str());
+                ui.label(warning.message());
+                ui.end_row();
+            }
+        });
+    if ui
+        .button(t!("parse_warnings.copy"))
+        .on_hover_text(t!("parse_warnings.copy.hint"))
+        .clicked()
+    {
+        ui.output_mut(|output| output.copied_text = warnings_report(file));
+        toasts.push(Severity::Success, t!("parse_warnings.copied"));
+    }
+}
+
+/// The warnings with the versions involved, fenced for pasting into a Markdown issue
+fn warnings_report(file: &File) -> String {
+    let saved_with = file
+        .version
+        .as_ref()
+        .map_or_else(|| "unknown".to_string(), |version| version.to_string());
+    let mut report = format!(
+        "blender-launcher: {}\nsaved with Blender: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        saved_with
+    );
+    for warning in &file.parse_warnings {
+        report.push_str(&format!(
+            "{} {}: {}\n",
+            warning.code,
+            warning.name,
+            warning.message()
+        ));
+    }
+    format!("```\n{report}```")
+}
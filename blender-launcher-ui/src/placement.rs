@@ -0,0 +1,287 @@
+use bevy::{math::Ray, prelude::*, render::primitives::Aabb, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+
+use crate::{
+    asset_cache::AssetCache,
+    file_status::FileOp,
+    ground::lowest_point,
+    history::{PreviewDescriptor, PreviewState, SpawnHistory},
+    i18n::t,
+    overlays::overlay,
+    preview_info::PreviewObjectInfo,
+    settings::Settings,
+    shading::{lit_material, OriginalMaterial},
+    spawn_preview,
+    viewport::ViewportClick,
+    AppState, BlenderPreviewObject, FileId, LauncherCamera, OccupiedScreenSpace,
+    OriginalCameraTransform,
+};
+
+/// Placing stops this far out, the ground gets too thin to aim at near the horizon
+const MAX_DISTANCE: f32 = 1000.0;
+/// How see-through the ghost following the cursor is
+const GHOST_ALPHA: f32 = 0.35;
+
+/// "Place on click": while on, every click on the ground adds another copy of the chosen
+/// object there, on top of what's already in the preview
+#[derive(Default, Resource)]
+pub struct Placement {
+    /// The (file, object) each click places, `None` while placing is off
+    object: Option<(FileId, usize)>,
+    click: ViewportClick,
+}
+
+impl Placement {
+    pub fn object(&self) -> Option<(FileId, usize)> {
+        self.object
+    }
+}
+
+/// Starts placing the given (file, object) on click, or stops with `None`
+pub struct PlaceOnClick(pub Option<(FileId, usize)>);
+
+/// The translucent copy of the object that follows the cursor until it's placed
+#[derive(Component)]
+struct PlacementGhost;
+
+pub struct PlacementPlugin;
+
+impl Plugin for PlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Placement>()
+            .add_event::<PlaceOnClick>()
+            .add_system(toggle_placement.run_if(on_event::<PlaceOnClick>()))
+            .add_system(
+                place_on_click
+                    .after(toggle_placement)
+                    .run_if(|placement: Res<Placement>| placement.object.is_some()),
+            )
+            .add_system(
+                placement_hint_ui.run_if(|placement: Res<Placement>| placement.object.is_some()),
+            );
+    }
+}
+
+/// Swaps the ghost for one of the newly chosen object, or takes it away
+fn toggle_placement(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut place_events: EventReader<PlaceOnClick>,
+    mut placement: ResMut<Placement>,
+    app_state: Res<AppState>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    ghosts: Query<Entity, With<PlacementGhost>>,
+) {
+    let Some(PlaceOnClick(object)) = place_events.iter().last() else {
+        return;
+    };
+    for ghost in ghosts.iter() {
+        commands.entity(ghost).despawn_recursive();
+    }
+    placement.object = *object;
+    placement.click = ViewportClick::default();
+
+    let Some((file_id, mesh_id)) = *object else {
+        return;
+    };
+    let Some(mesh) = app_state
+        .file(file_id)
+        .and_then(|file| Some((file, file.meshes.get(mesh_id)?)))
+        .map(|(file, mesh)| asset_cache.mesh(&asset_server, &file.path, mesh))
+    else {
+        placement.object = None;
+        return;
+    };
+    // Shown once the cursor is over the ground
+    commands.spawn((
+        PlacementGhost,
+        overlay(),
+        PbrBundle {
+            mesh,
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgba(0.6, 0.8, 1.0, GHOST_ALPHA),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..Default::default()
+            }),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+    ));
+}
+
+/// Moves the ghost to the point of the ground under the cursor and places a copy of the
+/// object there on each click, until Escape
+#[allow(clippy::too_many_arguments)]
+fn place_on_click(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut contexts: EguiContexts,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    settings: Res<Settings>,
+    app_state: Res<AppState>,
+    mut placement: ResMut<Placement>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut history: ResMut<SpawnHistory>,
+    original_camera_transform: Res<OriginalCameraTransform>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<LauncherCamera>>,
+    mut ghosts: Query<
+        (Entity, &mut Transform, &mut Visibility, Option<&Aabb>),
+        With<PlacementGhost>,
+    >,
+    previews: Query<(
+        &BlenderPreviewObject,
+        &PreviewObjectInfo,
+        &Handle<Mesh>,
+        &Handle<StandardMaterial>,
+        Option<&OriginalMaterial>,
+    )>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        placement.object = None;
+        for (ghost, ..) in ghosts.iter() {
+            commands.entity(ghost).despawn_recursive();
+        }
+        return;
+    }
+
+    // Clicks on the panels, and drags that started on them, are egui's
+    let ctx = contexts.ctx_mut();
+    let over_panels = ctx.is_pointer_over_area() || ctx.is_using_pointer();
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let hit =
+        cursor
+            .zip(cameras.get_single().ok())
+            .and_then(|(cursor, (camera, camera_transform))| {
+                // Cursor and viewport positions both start at the bottom left
+                ground_hit(camera.viewport_to_world(camera_transform, cursor)?)
+            });
+
+    // Rest the ghost where the placed copy will end up, on its lowest point with snap to ground
+    let ground = |aabb: Option<&Aabb>, point: Vec3| {
+        let transform = Transform::from_translation(point);
+        match aabb {
+            Some(aabb) if settings.snap_to_ground => {
+                transform.with_translation(point - Vec3::Y * lowest_point(&transform, aabb))
+            }
+            _ => transform,
+        }
+    };
+    for (_, mut transform, mut visibility, aabb) in ghosts.iter_mut() {
+        let shown = match hit {
+            Some(point) if !over_panels => {
+                let target = ground(aabb, point);
+                if *transform != target {
+                    *transform = target;
+                }
+                Visibility::Inherited
+            }
+            _ => Visibility::Hidden,
+        };
+        if *visibility != shown {
+            *visibility = shown;
+        }
+    }
+
+    if !placement.click.clicked(&mouse_buttons, cursor, over_panels) {
+        return;
+    }
+    let Some(point) = hit else {
+        return;
+    };
+
+    let Some((file_id, mesh_id)) = placement.object else {
+        return;
+    };
+    let Some(file) = app_state
+        .file(file_id)
+        .filter(|file| file.status.blocked(FileOp::Spawn).is_none())
+    else {
+        return;
+    };
+    let Some(mesh) = file.meshes.get(mesh_id) else {
+        return;
+    };
+    // `snap_to_ground` lifts the placed copy off the ground once its mesh has bounds
+    let descriptor = PreviewDescriptor {
+        file: file_id,
+        mesh: mesh_id,
+        object: mesh.name.clone(),
+        datablock: mesh.mesh_datablock().to_string(),
+        mesh_handle: asset_cache.mesh(&asset_server, &file.path, mesh),
+        material_handle: asset_cache.object_material(&asset_server, &mut materials, file, mesh),
+        transform: Transform::from_translation(point),
+    };
+    spawn_preview(&mut commands, &descriptor);
+
+    // Undo takes back one placement at a time
+    let mut objects: Vec<PreviewDescriptor> = previews
+        .iter()
+        .map(
+            |(preview, info, mesh_handle, material_handle, original)| PreviewDescriptor {
+                file: preview.file,
+                mesh: preview.mesh,
+                object: info.object.clone(),
+                datablock: info.datablock.clone(),
+                mesh_handle: mesh_handle.clone(),
+                // Undoing brings back the materials, the shading mode reapplies itself
+                material_handle: lit_material(material_handle, original).clone(),
+                transform: preview.original_transform,
+            },
+        )
+        .collect();
+    objects.push(descriptor);
+    history.push(PreviewState {
+        objects,
+        camera: **original_camera_transform,
+    });
+}
+
+/// Where a ray meets the ground at Y=0, `None` if it doesn't within `MAX_DISTANCE`
+pub fn ground_hit(ray: Ray) -> Option<Vec3> {
+    let distance = -ray.origin.y / ray.direction.y;
+    (distance.is_finite() && distance > 0.0 && distance <= MAX_DISTANCE)
+        .then(|| ray.origin + ray.direction * distance)
+}
+
+/// A note at the bottom of the viewport for as long as placing is on
+fn placement_hint_ui(
+    mut contexts: EguiContexts,
+    app_state: Res<AppState>,
+    placement: Res<Placement>,
+    mut place_events: EventWriter<PlaceOnClick>,
+    occupied_screen_space: Res<OccupiedScreenSpace>,
+    egui_settings: Res<EguiSettings>,
+) {
+    let Some(object) = placement
+        .object
+        .and_then(|(file_id, mesh_id)| app_state.file(file_id)?.meshes.get(mesh_id))
+    else {
+        return;
+    };
+    let ui_scale = egui_settings.scale_factor as f32;
+    let offset = egui::vec2(
+        (occupied_screen_space.left - occupied_screen_space.right) / ui_scale / 2.0,
+        -occupied_screen_space.bottom / ui_scale - 12.0,
+    );
+
+    egui::Area::new("placement_hint")
+        .anchor(egui::Align2::CENTER_BOTTOM, offset)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(t!("placement.active", name = &object.name));
+                    if ui.small_button(t!("placement.stop")).clicked() {
+                        place_events.send(PlaceOnClick(None));
+                    }
+                });
+            });
+        });
+}
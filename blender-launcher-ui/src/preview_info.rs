@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::{AppState, File, FileId};
+
+/// What a preview was spawned from, by name. Unlike the entity, or the object's index in a
+/// file that's been reparsed since, it means the same thing across reloads and sessions.
+#[derive(Clone, Component, Debug)]
+pub struct PreviewObjectInfo {
+    pub file: FileId,
+    pub object: String,
+    /// The mesh datablock, what bevy_blender loaded the preview's mesh by
+    pub datablock: String,
+    /// Where it was spawned, before it was moved around
+    pub spawn_transform: Transform,
+    /// Tells copies of the same object apart, counting from 0. A new copy takes the lowest
+    /// number that's free, so the ones already there keep theirs.
+    pub instance: usize,
+}
+
+impl PreviewObjectInfo {
+    pub fn new(file: FileId, object: &str, datablock: &str, spawn_transform: Transform) -> Self {
+        Self {
+            file,
+            object: object.to_string(),
+            datablock: datablock.to_string(),
+            spawn_transform,
+            instance: 0,
+        }
+    }
+
+    /// The object's name, plus the instance counter for copies, e.g. `Cube`, `Cube (2)`
+    pub fn label(&self) -> String {
+        match self.instance {
+            0 => self.object.clone(),
+            instance => format!("{} ({})", self.object, instance + 1),
+        }
+    }
+
+    /// Where the object is in its file now, which can change with every parse
+    pub fn index(&self, app_state: &AppState) -> Option<usize> {
+        app_state
+            .file(self.file)?
+            .meshes
+            .iter()
+            .position(|object| object.name == self.object)
+    }
+
+    /// Was this spawned from the object at `index` in `file`?
+    pub fn is_object(&self, file: &File, index: usize) -> bool {
+        self.file == file.id
+            && file
+                .meshes
+                .get(index)
+                .is_some_and(|object| object.name == self.object)
+    }
+}
+
+pub struct PreviewInfoPlugin;
+
+impl Plugin for PreviewInfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(number_instances);
+    }
+}
+
+/// Gives each new preview an instance number that no other copy of its object has.
+/// Spawners leave it at 0, they can't see what else was spawned in the same frame.
+fn number_instances(
+    mut infos: ParamSet<(
+        Query<Entity, Added<PreviewObjectInfo>>,
+        Query<(Entity, &mut PreviewObjectInfo)>,
+    )>,
+) {
+    let added: HashSet<Entity> = infos.p0().iter().collect();
+    if added.is_empty() {
+        return;
+    }
+    let mut infos = infos.p1();
+    let mut taken: HashSet<(FileId, String, usize)> = infos
+        .iter()
+        .filter(|(entity, _)| !added.contains(entity))
+        .map(|(_, info)| (info.file, info.object.clone(), info.instance))
+        .collect();
+    for (entity, mut info) in infos.iter_mut() {
+        if !added.contains(&entity) {
+            continue;
+        }
+        let instance = (0..)
+            .find(|instance| !taken.contains(&(info.file, info.object.clone(), *instance)))
+            .unwrap_or_default();
+        if info.instance != instance {
+            info.instance = instance;
+        }
+        taken.insert((info.file, info.object.clone(), instance));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn(app: &mut App, object: &str) -> Entity {
+        app.world
+            .spawn(PreviewObjectInfo::new(
+                FileId(0),
+                object,
+                object,
+                Transform::default(),
+            ))
+            .id()
+    }
+
+    fn instances(app: &mut App, object: &str) -> Vec<usize> {
+        let mut instances: Vec<usize> = app
+            .world
+            .query::<&PreviewObjectInfo>()
+            .iter(&app.world)
+            .filter(|info| info.object == object)
+            .map(|info| info.instance)
+            .collect();
+        instances.sort_unstable();
+        instances
+    }
+
+    #[test]
+    fn copies_of_an_object_get_their_own_instance() {
+        let mut app = App::new();
+        app.add_plugin(PreviewInfoPlugin);
+
+        // Spawned in the same frame, like a grid
+        let copies: Vec<Entity> = (0..3).map(|_| spawn(&mut app, "Cube")).collect();
+        spawn(&mut app, "Suzanne");
+        app.update();
+        assert_eq!(instances(&mut app, "Cube"), [0, 1, 2]);
+        assert_eq!(instances(&mut app, "Suzanne"), [0]);
+        let mut labels: Vec<String> = app
+            .world
+            .query::<&PreviewObjectInfo>()
+            .iter(&app.world)
+            .filter(|info| info.object == "Cube")
+            .map(PreviewObjectInfo::label)
+            .collect();
+        labels.sort();
+        assert_eq!(labels, ["Cube", "Cube (2)", "Cube (3)"]);
+
+        // The others keep their numbers, the next copy takes the free one
+        let removed = app
+            .world
+            .get::<PreviewObjectInfo>(copies[1])
+            .unwrap()
+            .instance;
+        app.world.despawn(copies[1]);
+        let added = spawn(&mut app, "Cube");
+        app.update();
+        assert_eq!(instances(&mut app, "Cube"), [0, 1, 2]);
+        let info = app.world.get::<PreviewObjectInfo>(added).unwrap();
+        assert_eq!(info.instance, removed);
+    }
+}
@@ -0,0 +1,239 @@
+use bevy_egui::egui;
+use blend::Instance;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::t,
+    metadata::{id_name, list_iter},
+};
+
+/// Blender's `IDP_*` property types
+const IDP_STRING: u8 = 0;
+const IDP_INT: u8 = 1;
+const IDP_FLOAT: u8 = 2;
+const IDP_ARRAY: u8 = 5;
+const IDP_GROUP: u8 = 6;
+const IDP_ID: u8 = 7;
+const IDP_DOUBLE: u8 = 8;
+const IDP_BOOLEAN: u8 = 10;
+/// Deepest group nesting we follow
+const MAX_DEPTH: usize = 32;
+/// Most items we read from one group or array, the rest are dropped
+const MAX_ITEMS: usize = 4096;
+/// Array items and string characters shown before the rest sit behind "expand"
+const PREVIEW_ITEMS: usize = 16;
+const PREVIEW_CHARS: usize = 200;
+
+/// A custom property (`IDProperty`) on a datablock
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CustomProperty {
+    pub name: String,
+    pub value: PropertyValue,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i32),
+    Float(f64),
+    String(String),
+    /// A pointer to another datablock, by name
+    Id(Option<String>),
+    Array(Vec<PropertyValue>),
+    Group(Vec<CustomProperty>),
+    /// A type we don't read, with its `IDP_*` value
+    Unsupported(u8),
+}
+
+impl PropertyValue {
+    /// The value as it goes into the metadata export
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            PropertyValue::Bool(value) => (*value).into(),
+            PropertyValue::Int(value) => (*value).into(),
+            PropertyValue::Float(value) => (*value).into(),
+            PropertyValue::String(value) => value.as_str().into(),
+            PropertyValue::Id(name) => name.as_deref().into(),
+            PropertyValue::Array(items) => items.iter().map(PropertyValue::to_json).collect(),
+            PropertyValue::Group(properties) => properties_json(properties),
+            PropertyValue::Unsupported(_) => serde_json::Value::Null,
+        }
+    }
+
+    /// The value as text, what clicking it copies
+    fn to_text(&self) -> String {
+        match self {
+            PropertyValue::String(value) => value.clone(),
+            PropertyValue::Id(name) => name.clone().unwrap_or_default(),
+            other => other.to_json().to_string(),
+        }
+    }
+}
+
+/// A property list as a JSON object keyed by name
+pub fn properties_json(properties: &[CustomProperty]) -> serde_json::Value {
+    serde_json::Value::Object(
+        properties
+            .iter()
+            .map(|property| (property.name.clone(), property.value.to_json()))
+            .collect(),
+    )
+}
+
+/// Reads the custom properties of a datablock from its `ID::properties` group
+pub fn custom_properties(block: &Instance) -> Vec<CustomProperty> {
+    let id = block.get("id");
+    if !id.is_valid("properties") {
+        return Vec::new();
+    }
+    match read_property(&id.get("properties"), 0) {
+        PropertyValue::Group(properties) => properties,
+        _ => Vec::new(),
+    }
+}
+
+fn read_property(property: &Instance, depth: usize) -> PropertyValue {
+    let data = property.get("data");
+    // Small values live right in `data.val` (and `data.val2` for doubles),
+    // anything bigger behind `data.pointer`
+    let val = data.get_i32("val") as u32;
+    let len = property.get_i32("len").max(0) as usize;
+
+    match property.get_u8("type") {
+        IDP_INT => PropertyValue::Int(val as i32),
+        IDP_FLOAT => PropertyValue::Float(f32::from_bits(val) as f64),
+        IDP_DOUBLE => {
+            let high = data.get_i32("val2") as u32;
+            PropertyValue::Float(f64::from_bits(((high as u64) << 32) | val as u64))
+        }
+        IDP_BOOLEAN => PropertyValue::Bool(val != 0),
+        IDP_STRING => {
+            let mut bytes = pointer_bytes(&data);
+            bytes.truncate(len);
+            // `len` counts the terminating null
+            while bytes.last() == Some(&0) {
+                bytes.pop();
+            }
+            PropertyValue::String(String::from_utf8_lossy(&bytes).to_string())
+        }
+        IDP_ARRAY => read_array(&pointer_bytes(&data), property.get_u8("subtype"), len),
+        IDP_GROUP if depth < MAX_DEPTH => PropertyValue::Group(
+            list_iter(&data.get("group"))
+                .take(MAX_ITEMS)
+                .map(|child| CustomProperty {
+                    name: child.get_string("name"),
+                    value: read_property(&child, depth + 1),
+                })
+                .collect(),
+        ),
+        IDP_GROUP => PropertyValue::Group(Vec::new()),
+        IDP_ID => PropertyValue::Id(
+            data.is_valid("pointer")
+                .then(|| id_name(&data.get("pointer"))),
+        ),
+        other => PropertyValue::Unsupported(other),
+    }
+}
+
+fn pointer_bytes(data: &Instance) -> Vec<u8> {
+    if data.is_valid("pointer") {
+        data.get_u8_vec("pointer")
+    } else {
+        Vec::new()
+    }
+}
+
+/// Array items are packed in the file's byte order, little-endian on every platform Blender
+/// still supports
+fn read_array(bytes: &[u8], subtype: u8, len: usize) -> PropertyValue {
+    let items = |size: usize| bytes.chunks_exact(size).take(len.min(MAX_ITEMS));
+    let items: Vec<PropertyValue> = match subtype {
+        IDP_INT => items(4)
+            .map(|chunk| PropertyValue::Int(i32::from_le_bytes(chunk.try_into().unwrap())))
+            .collect(),
+        IDP_FLOAT => items(4)
+            .map(|chunk| PropertyValue::Float(f32::from_le_bytes(chunk.try_into().unwrap()) as f64))
+            .collect(),
+        IDP_DOUBLE => items(8)
+            .map(|chunk| PropertyValue::Float(f64::from_le_bytes(chunk.try_into().unwrap())))
+            .collect(),
+        IDP_BOOLEAN => items(1)
+            .map(|chunk| PropertyValue::Bool(chunk[0] != 0))
+            .collect(),
+        other => return PropertyValue::Unsupported(other),
+    };
+    PropertyValue::Array(items)
+}
+
+/// Custom properties as a collapsible tree. Clicking a value copies it.
+/// Long arrays and strings only show their start until expanded, so they can't stall the panel.
+pub fn properties_ui(ui: &mut egui::Ui, id: egui::Id, properties: &[CustomProperty]) {
+    for (index, property) in properties.iter().enumerate() {
+        property_ui(ui, id.with(index), &property.name, &property.value);
+    }
+}
+
+fn property_ui(ui: &mut egui::Ui, id: egui::Id, name: &str, value: &PropertyValue) {
+    match value {
+        PropertyValue::Group(properties) => {
+            egui::CollapsingHeader::new(name)
+                .id_source(id)
+                .show(ui, |ui| properties_ui(ui, id, properties));
+        }
+        PropertyValue::Array(items) => {
+            egui::CollapsingHeader::new(format!("{} [{}]", name, items.len()))
+                .id_source(id)
+                .show(ui, |ui| {
+                    let expanded = expand_toggle(ui, id, items.len() > PREVIEW_ITEMS);
+                    let shown = if expanded { items.len() } else { PREVIEW_ITEMS };
+                    for (index, item) in items.iter().take(shown).enumerate() {
+                        property_ui(ui, id.with(index), &index.to_string(), item);
+                    }
+                });
+        }
+        value => {
+            ui.horizontal(|ui| {
+                ui.label(name);
+                let text = value.to_text();
+                let long = text.chars().count() > PREVIEW_CHARS;
+                let shown = if long && !ui.data(|data| data.get_temp(id).unwrap_or(false)) {
+                    let start: String = text.chars().take(PREVIEW_CHARS).collect();
+                    format!("{}…", start)
+                } else {
+                    text.clone()
+                };
+                let response = match value {
+                    PropertyValue::Unsupported(_) => ui.weak(t!("properties.unsupported")),
+                    _ => ui
+                        .add(
+                            egui::Label::new(egui::RichText::new(shown).monospace())
+                                .sense(egui::Sense::click()),
+                        )
+                        .on_hover_text(t!("properties.copy")),
+                };
+                if response.clicked() {
+                    ui.output_mut(|output| output.copied_text = text);
+                }
+                expand_toggle(ui, id, long);
+            });
+        }
+    }
+}
+
+/// An "expand" / "collapse" link when `needed`, returns whether it's expanded
+fn expand_toggle(ui: &mut egui::Ui, id: egui::Id, needed: bool) -> bool {
+    if !needed {
+        return false;
+    }
+    let mut expanded = ui.data(|data| data.get_temp(id).unwrap_or(false));
+    let label = if expanded {
+        t!("properties.collapse")
+    } else {
+        t!("properties.expand")
+    };
+    if ui.small_button(label).clicked() {
+        expanded = !expanded;
+        ui.data_mut(|data| data.insert_temp(id, expanded));
+    }
+    expanded
+}
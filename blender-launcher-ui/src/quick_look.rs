@@ -0,0 +1,210 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    asset_cache::AssetCache,
+    i18n::t,
+    offscreen::{mesh_sphere, render_target, Studio, StudioRig},
+    AppState, FileId,
+};
+
+/// Size of the offscreen image, in pixels
+const IMAGE_SIZE: u32 = 512;
+/// Render layer of the quick look studio, the preview is on the default layer 0
+const LAYER: u8 = 1;
+/// Where the studio is set up, out of range of the preview's light
+const ORIGIN: Vec3 = Vec3::new(0.0, -10_000.0, 0.0);
+/// Radians the camera turns per point dragged over the image
+const ORBIT_SPEED: f32 = 0.01;
+
+/// Renders one object on its own in a modal, leaving the preview as it is
+pub struct QuickLook {
+    pub file: FileId,
+    pub mesh: usize,
+}
+
+struct OpenQuickLook {
+    name: String,
+    image: Handle<Image>,
+    texture: egui::TextureId,
+    studio: Studio,
+    mesh: Handle<Mesh>,
+    /// Center and radius, once the mesh has loaded
+    bounds: Option<(Vec3, f32)>,
+    yaw: f32,
+    pitch: f32,
+}
+
+#[derive(Default, Resource)]
+struct QuickLookState {
+    open: Option<OpenQuickLook>,
+    /// The camera needs to move to the current yaw and pitch
+    moved: bool,
+}
+
+pub struct QuickLookPlugin;
+
+impl Plugin for QuickLookPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<QuickLook>()
+            .init_resource::<QuickLookState>()
+            .add_system(open_quick_look.run_if(on_event::<QuickLook>()))
+            .add_system(frame_quick_look.run_if(|state: Res<QuickLookState>| {
+                state
+                    .open
+                    .as_ref()
+                    .is_some_and(|open| open.bounds.is_none())
+                    || state.moved
+            }))
+            .add_system(quick_look_ui.run_if(|state: Res<QuickLookState>| state.open.is_some()));
+    }
+}
+
+/// Sets up the studio for the object, replacing one that's already open
+#[allow(clippy::too_many_arguments)]
+fn open_quick_look(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut events: EventReader<QuickLook>,
+    app_state: Res<AppState>,
+    asset_server: Res<AssetServer>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut state: ResMut<QuickLookState>,
+) {
+    let Some(event) = events.iter().last() else {
+        return;
+    };
+    let Some((file, object)) = app_state
+        .file(event.file)
+        .and_then(|file| Some((file, file.meshes.get(event.mesh)?)))
+    else {
+        return;
+    };
+
+    if let Some(open) = state.open.take() {
+        close(&mut commands, &mut contexts, &mut images, open);
+    }
+
+    let image = images.add(render_target(IMAGE_SIZE));
+    let mesh = asset_cache.mesh(&asset_server, &file.path, object);
+    let material = asset_cache.object_material(&asset_server, &mut materials, file, object);
+    let studio = Studio::spawn(
+        &mut commands,
+        ORIGIN,
+        LAYER,
+        image.clone(),
+        mesh.clone(),
+        material,
+    );
+    state.open = Some(OpenQuickLook {
+        name: object.name.clone(),
+        texture: contexts.add_image(image.clone_weak()),
+        image,
+        studio,
+        mesh,
+        bounds: None,
+        // Looking down at the front a little, like the preview does
+        yaw: 0.4,
+        pitch: 0.3,
+    });
+}
+
+/// Fits the camera once the mesh is loaded, and follows the orbit after that
+fn frame_quick_look(
+    meshes: Res<Assets<Mesh>>,
+    mut state: ResMut<QuickLookState>,
+    mut rig: StudioRig,
+) {
+    let state = &mut *state;
+    let Some(open) = state.open.as_mut() else {
+        return;
+    };
+    if open.bounds.is_none() {
+        let Some(mesh) = meshes.get(&open.mesh) else {
+            return;
+        };
+        open.bounds = mesh_sphere(mesh);
+    }
+    if let Some(bounds) = open.bounds {
+        rig.frame(&open.studio, ORIGIN, bounds, (open.yaw, open.pitch));
+    }
+    state.moved = false;
+}
+
+/// The modal with the rendered image. Dragging over it orbits the camera.
+fn quick_look_ui(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut images: ResMut<Assets<Image>>,
+    mut state: ResMut<QuickLookState>,
+) {
+    let Some(open) = state.open.as_mut() else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+    let mut closed = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+    let mut drag = egui::Vec2::ZERO;
+
+    // Blocks the panels underneath, like the confirmation modal
+    let screen_rect = ctx.screen_rect();
+    egui::Area::new("quick_look_backdrop")
+        .order(egui::Order::Middle)
+        .fixed_pos(screen_rect.min)
+        .show(ctx, |ui| {
+            closed |= ui
+                .allocate_response(screen_rect.size(), egui::Sense::click())
+                .clicked();
+            ui.painter()
+                .rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(96));
+        });
+    egui::Area::new("quick_look_modal")
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            egui::Frame::window(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(&open.name);
+                    closed |= ui.button(t!("quick_look.close")).clicked();
+                });
+                let size = egui::Vec2::splat(IMAGE_SIZE as f32);
+                let response =
+                    ui.add(egui::Image::new(open.texture, size).sense(egui::Sense::drag()));
+                drag = response.drag_delta();
+                if open.bounds.is_none() {
+                    ui.weak(t!("quick_look.loading"));
+                } else {
+                    ui.weak(t!("quick_look.hint"));
+                }
+            });
+        });
+
+    if closed {
+        if let Some(open) = state.open.take() {
+            close(&mut commands, &mut contexts, &mut images, open);
+        }
+        return;
+    }
+    if drag != egui::Vec2::ZERO {
+        open.yaw -= drag.x * ORBIT_SPEED;
+        // Short of straight up or down, where the camera's up vector flips
+        let limit = FRAC_PI_2 - 0.05;
+        open.pitch = (open.pitch + drag.y * ORBIT_SPEED).clamp(-limit, limit);
+        state.moved = true;
+    }
+}
+
+/// Frees the render target and despawns the studio
+fn close(
+    commands: &mut Commands,
+    contexts: &mut EguiContexts,
+    images: &mut Assets<Image>,
+    open: OpenQuickLook,
+) {
+    contexts.remove_image(&open.image);
+    images.remove(&open.image);
+    open.studio.despawn(commands);
+}
@@ -0,0 +1,83 @@
+use bevy_egui::egui;
+
+use crate::{file_status::FileOp, i18n::t, AppState, FileId};
+
+/// How many objects the quick list in the top panel holds
+pub const MAX_RECENT_SPAWNS: usize = 10;
+
+/// An object spawned on its own. Kept by name, its index changes when the file is reparsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecentSpawn {
+    pub file: FileId,
+    pub object: String,
+}
+
+/// The objects spawned last, newest first and each only once
+#[derive(Default)]
+pub struct RecentSpawns(Vec<RecentSpawn>);
+
+impl RecentSpawns {
+    /// Moves the object to the front, dropping the oldest one when the list is full
+    pub fn record(&mut self, file: FileId, object: String) {
+        let spawn = RecentSpawn { file, object };
+        self.0.retain(|recent| *recent != spawn);
+        self.0.insert(0, spawn);
+        self.0.truncate(MAX_RECENT_SPAWNS);
+    }
+
+    /// Adds an object from a saved session behind the ones already in the list
+    pub fn restore(&mut self, file: FileId, object: String) {
+        let spawn = RecentSpawn { file, object };
+        if self.0.len() < MAX_RECENT_SPAWNS && !self.0.contains(&spawn) {
+            self.0.push(spawn);
+        }
+    }
+
+    pub fn remove_file(&mut self, file: FileId) {
+        self.0.retain(|recent| recent.file != file);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RecentSpawn> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// A chip for each recent object, returns the (file, object) of the one clicked
+pub fn recent_spawns_ui(ui: &mut egui::Ui, app_state: &AppState) -> Option<(FileId, usize)> {
+    let mut clicked = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.weak(t!("recent_spawns.label"));
+        for recent in app_state.recent_spawns.iter() {
+            let Some(file) = app_state.file(recent.file) else {
+                continue;
+            };
+            let index = file
+                .meshes
+                .iter()
+                .position(|object| object.name == recent.object);
+            // Reparsed files can lose objects, and session files aren't parsed right away
+            let blocked = match index {
+                Some(_) => file.status.blocked(FileOp::Spawn),
+                None if file.parsed => Some(t!("recent_spawns.gone")),
+                None => Some(t!("recent_spawns.not_loaded")),
+            };
+            let selected =
+                index.is_some_and(|index| app_state.selected_object == Some((recent.file, index)));
+            let response = ui
+                .add_enabled(
+                    blocked.is_none(),
+                    egui::SelectableLabel::new(selected, recent.object.as_str()),
+                )
+                .on_hover_text(file.path.as_str())
+                .on_disabled_hover_text(blocked.unwrap_or_default());
+            if response.clicked() {
+                clicked = index.map(|index| (recent.file, index));
+            }
+        }
+    });
+    clicked
+}
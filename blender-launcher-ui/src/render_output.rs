@@ -0,0 +1,290 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, IoTaskPool, Task},
+};
+use bevy_egui::{egui, EguiContexts};
+use futures_lite::future;
+
+use crate::{
+    i18n::t,
+    render_queue::{JobStatus, RenderQueue},
+    settings::Settings,
+};
+
+/// Finished frames shown in the Renders section, older ones are dropped to free their textures
+const MAX_THUMBNAILS: usize = 12;
+/// Frames are scaled down to this on their longest side, big enough for the viewer
+const MAX_FRAME_SIZE: u32 = 1024;
+/// A frame that still doesn't decode after this many scans is skipped.
+/// Failures are usually Blender still writing the file.
+const MAX_DECODE_ATTEMPTS: u32 = 10;
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A finished frame, loaded as a texture
+struct RenderedFrame {
+    path: PathBuf,
+    texture: egui::TextureHandle,
+}
+
+/// Polls the folders renders write to and loads new frames as they appear.
+/// Polling rather than file system events, since those aren't reliable on network drives,
+/// which is where render output often goes.
+#[derive(Resource)]
+pub struct RenderOutputWatcher {
+    timer: Timer,
+    scan: Option<Task<Vec<(PathBuf, SystemTime)>>>,
+    /// When each file was last modified, as of the scan that picked it up
+    seen: HashMap<PathBuf, SystemTime>,
+    decodes: Vec<(PathBuf, Task<Result<egui::ColorImage, String>>)>,
+    attempts: HashMap<PathBuf, u32>,
+    /// Newest first
+    frames: VecDeque<RenderedFrame>,
+    /// The frame open in the viewer
+    viewing: Option<PathBuf>,
+}
+
+impl Default for RenderOutputWatcher {
+    fn default() -> Self {
+        Self {
+            timer: Timer::new(SCAN_INTERVAL, TimerMode::Repeating),
+            scan: None,
+            seen: HashMap::new(),
+            decodes: Vec::new(),
+            attempts: HashMap::new(),
+            frames: VecDeque::new(),
+            viewing: None,
+        }
+    }
+}
+
+pub struct RenderOutputPlugin;
+
+impl Plugin for RenderOutputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RenderOutputWatcher>()
+            .add_systems((scan_render_output, load_rendered_frames).chain())
+            .add_system(
+                frame_viewer_ui
+                    .run_if(|watcher: Res<RenderOutputWatcher>| watcher.viewing.is_some()),
+            );
+    }
+}
+
+/// The folders to watch: the global one from the settings, and those of started jobs
+fn watched_folders(queue: &RenderQueue, settings: &Settings) -> HashSet<PathBuf> {
+    queue
+        .jobs()
+        .iter()
+        .filter(|job| matches!(job.status, JobStatus::Running | JobStatus::Done))
+        .filter_map(|job| job.output_dir())
+        .chain(settings.render_output_dir.clone())
+        .collect()
+}
+
+fn is_frame(path: &Path) -> bool {
+    // EXR is left out, it'd need tone mapping to look like anything
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            ["png", "jpg", "jpeg"]
+                .iter()
+                .any(|frame| extension.eq_ignore_ascii_case(frame))
+        })
+}
+
+fn list_frames(folders: HashSet<PathBuf>) -> Vec<(PathBuf, SystemTime)> {
+    folders
+        .iter()
+        .filter_map(|folder| fs::read_dir(folder).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| is_frame(&entry.path()))
+        .filter_map(|entry| {
+            let modified = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()?;
+            Some((entry.path(), modified))
+        })
+        .collect()
+}
+
+fn decode_frame(path: &Path) -> Result<egui::ColorImage, String> {
+    let image = image::open(path)
+        .map_err(|error| error.to_string())?
+        .thumbnail(MAX_FRAME_SIZE, MAX_FRAME_SIZE)
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Ok(egui::ColorImage::from_rgba_unmultiplied(size, &image))
+}
+
+/// Lists the watched folders every so often, and starts decoding frames that are new or changed
+fn scan_render_output(
+    mut watcher: ResMut<RenderOutputWatcher>,
+    queue: Res<RenderQueue>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let watcher = &mut *watcher;
+    if let Some(scan) = watcher.scan.as_mut() {
+        let Some(mut found) = future::block_on(future::poll_once(scan)) else {
+            return;
+        };
+        watcher.scan = None;
+
+        // Only the newest frames would survive being shown anyway
+        found.retain(|(path, modified)| watcher.seen.get(path) != Some(modified));
+        found.sort_by(|(_, a), (_, b)| b.cmp(a));
+        found.truncate(MAX_THUMBNAILS);
+        for (path, modified) in found.into_iter().rev() {
+            if watcher
+                .decodes
+                .iter()
+                .any(|(decoding, _)| *decoding == path)
+            {
+                continue;
+            }
+            watcher.seen.insert(path.clone(), modified);
+            let task_path = path.clone();
+            let task = AsyncComputeTaskPool::get().spawn(async move { decode_frame(&task_path) });
+            watcher.decodes.push((path, task));
+        }
+        return;
+    }
+
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let folders = watched_folders(&queue, &settings);
+    if !folders.is_empty() {
+        watcher.scan = Some(IoTaskPool::get().spawn(async move { list_frames(folders) }));
+    }
+}
+
+/// Turns decoded frames into textures, putting back the ones that weren't fully written yet
+fn load_rendered_frames(mut contexts: EguiContexts, mut watcher: ResMut<RenderOutputWatcher>) {
+    let watcher = &mut *watcher;
+    let mut finished = Vec::new();
+    watcher.decodes.retain_mut(
+        |(path, task)| match future::block_on(future::poll_once(task)) {
+            Some(result) => {
+                finished.push((path.clone(), result));
+                false
+            }
+            None => true,
+        },
+    );
+
+    for (path, result) in finished {
+        match result {
+            Ok(image) => {
+                watcher.attempts.remove(&path);
+                let texture = contexts.ctx_mut().load_texture(
+                    path.display().to_string(),
+                    image,
+                    egui::TextureOptions::LINEAR,
+                );
+                // A re-rendered frame replaces the old one
+                watcher.frames.retain(|frame| frame.path != path);
+                watcher.frames.push_front(RenderedFrame { path, texture });
+                watcher.frames.truncate(MAX_THUMBNAILS);
+            }
+            Err(error) => {
+                let attempts = watcher.attempts.entry(path.clone()).or_default();
+                *attempts += 1;
+                if *attempts < MAX_DECODE_ATTEMPTS {
+                    // Forgetting it makes the next scan pick it up again
+                    watcher.seen.remove(&path);
+                } else {
+                    warn!(path = %path.display(), %error, "couldn't load rendered frame");
+                }
+            }
+        }
+    }
+}
+
+/// The latest finished frames, clicking one opens it in the viewer
+pub fn renders_ui(ui: &mut egui::Ui, watcher: &mut RenderOutputWatcher) {
+    ui.heading(t!("renders.heading"));
+    if watcher.frames.is_empty() {
+        ui.weak(t!("renders.empty"));
+        return;
+    }
+
+    let mut clicked = None;
+    ui.horizontal_wrapped(|ui| {
+        for frame in watcher.frames.iter() {
+            let size = fit(frame.texture.size_vec2(), egui::vec2(96.0, 96.0));
+            let file_name = frame
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if ui
+                .add(egui::ImageButton::new(frame.texture.id(), size))
+                .on_hover_text(file_name)
+                .clicked()
+            {
+                clicked = Some(frame.path.clone());
+            }
+        }
+    });
+    if clicked.is_some() {
+        watcher.viewing = clicked;
+    }
+}
+
+/// Scales `size` down to fit in `bounds`, keeping its aspect ratio
+fn fit(size: egui::Vec2, bounds: egui::Vec2) -> egui::Vec2 {
+    let scale = (bounds.x / size.x).min(bounds.y / size.y).min(1.0);
+    size * scale
+}
+
+/// Shows a frame over the whole window, until it's clicked or Escape is pressed
+fn frame_viewer_ui(mut contexts: EguiContexts, mut watcher: ResMut<RenderOutputWatcher>) {
+    let Some(frame) = watcher
+        .viewing
+        .as_ref()
+        .and_then(|viewing| watcher.frames.iter().find(|frame| frame.path == *viewing))
+    else {
+        // It was evicted by newer frames
+        watcher.viewing = None;
+        return;
+    };
+
+    let ctx = contexts.ctx_mut();
+    let mut close = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+    let screen_rect = ctx.screen_rect();
+    egui::Area::new("frame_viewer_backdrop")
+        .order(egui::Order::Middle)
+        .fixed_pos(screen_rect.min)
+        .show(ctx, |ui| {
+            close |= ui
+                .allocate_response(screen_rect.size(), egui::Sense::click())
+                .clicked();
+            ui.painter()
+                .rect_filled(screen_rect, 0.0, egui::Color32::from_black_alpha(192));
+        });
+
+    egui::Area::new("frame_viewer")
+        .order(egui::Order::Foreground)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            let size = fit(frame.texture.size_vec2(), screen_rect.size() * 0.9);
+            close |= ui
+                .add(egui::Image::new(frame.texture.id(), size).sense(egui::Sense::click()))
+                .on_hover_text(frame.path.display().to_string())
+                .clicked();
+        });
+
+    if close {
+        watcher.viewing = None;
+    }
+}
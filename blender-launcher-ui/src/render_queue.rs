@@ -0,0 +1,777 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    builds::BlenderInstalls,
+    commands::{Action, ActionEvent},
+    file_status::{FileOp, FileStatus},
+    i18n::t,
+    launch::effective_install,
+    render_output::{renders_ui, RenderOutputWatcher},
+    settings::{config_dir, Settings},
+    toasts::{Severity, Toasts},
+    AppState,
+};
+
+/// Lines of output kept per job, older ones are dropped
+const MAX_LOG_LINES: usize = 1000;
+/// What Blender prints once it's through with `-a`. A render we only know by its log
+/// finished if this is in there.
+const FINISHED_LINE: &str = "Blender quit";
+/// How often a job's log file is checked for new output
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => t!("render.status.queued"),
+            JobStatus::Running => t!("render.status.running"),
+            JobStatus::Done => t!("render.status.done"),
+            JobStatus::Failed => t!("render.status.failed"),
+            JobStatus::Cancelled => t!("render.status.cancelled"),
+        }
+    }
+}
+
+/// A background render of a frame range of one file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RenderJob {
+    pub id: u64,
+    pub file: String,
+    /// First and last frame, inclusive
+    pub frames: (i32, i32),
+    /// Blender's `-o` pattern, like `//render/frame_####`
+    pub output: String,
+    pub blender: PathBuf,
+    pub status: JobStatus,
+    /// Why the job failed, if it did
+    pub error: Option<String>,
+    /// How long the job ran, once it's finished
+    pub elapsed_secs: Option<u64>,
+    /// Blender's process while the job runs, so the next start can find renders the app
+    /// left running when it closed
+    #[serde(default)]
+    pub pid: Option<u32>,
+}
+
+impl RenderJob {
+    /// Records how the job ended, with the reason if it failed
+    fn finish(&mut self, outcome: Result<(), String>) {
+        self.pid = None;
+        match outcome {
+            Ok(()) => self.status = JobStatus::Done,
+            Err(error) => {
+                self.status = JobStatus::Failed;
+                self.error = Some(error);
+            }
+        }
+    }
+
+    /// The folder Blender writes this job's frames to. `//` is relative to the blend file.
+    pub fn output_dir(&self) -> Option<PathBuf> {
+        let output = match self.output.strip_prefix("//") {
+            Some(relative) => Path::new(&self.file).parent()?.join(relative),
+            None => PathBuf::from(&self.output),
+        };
+        // Without a trailing slash the last part is the file name pattern
+        if self.output.ends_with(['/', '\\']) {
+            Some(output)
+        } else {
+            output.parent().map(Path::to_path_buf)
+        }
+    }
+}
+
+/// The jobs, in the order they run. Saved to disk so a restart keeps them.
+#[derive(Default, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderQueue {
+    jobs: Vec<RenderJob>,
+    next_id: u64,
+}
+
+impl RenderQueue {
+    pub fn jobs(&self) -> &[RenderJob] {
+        &self.jobs
+    }
+
+    /// The first job Blender is working on
+    pub fn running(&self) -> Option<&RenderJob> {
+        self.jobs
+            .iter()
+            .find(|job| job.status == JobStatus::Running)
+    }
+
+    pub fn push(&mut self, file: String, frames: (i32, i32), output: String, blender: PathBuf) {
+        self.jobs.push(RenderJob {
+            id: self.next_id,
+            file,
+            frames,
+            output,
+            blender,
+            status: JobStatus::Queued,
+            error: None,
+            elapsed_secs: None,
+            pid: None,
+        });
+        self.next_id += 1;
+    }
+
+    fn job_mut(&mut self, id: u64) -> Option<&mut RenderJob> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    fn has_queued(&self) -> bool {
+        self.jobs.iter().any(|job| job.status == JobStatus::Queued)
+    }
+}
+
+/// How we know about a job's Blender
+enum JobProcess {
+    Child(Child),
+    /// Left running when the app last closed, all we have is its pid and log
+    Adopted(u32),
+}
+
+/// A job's Blender process
+struct RunningJob {
+    process: JobProcess,
+    started: Instant,
+    /// Cleared to stop following the job's log
+    following: Arc<AtomicBool>,
+}
+
+impl RunningJob {
+    /// How the job ended, `None` while it's still going
+    fn poll(&mut self, id: u64) -> Option<Result<(), String>> {
+        match &mut self.process {
+            JobProcess::Child(child) => match child.try_wait() {
+                Ok(None) => None,
+                Ok(Some(status)) if status.success() => Some(Ok(())),
+                Ok(Some(status)) => Some(Err(t!("render.exit_status", status = status))),
+                Err(error) => Some(Err(error.to_string())),
+            },
+            // Not our child, so there's no exit status to wait for
+            JobProcess::Adopted(pid) => (!process_alive(*pid)).then(|| outcome_from_log(id)),
+        }
+    }
+}
+
+impl Drop for RunningJob {
+    fn drop(&mut self) {
+        self.following.store(false, Ordering::Relaxed);
+    }
+}
+
+/// The processes behind running jobs, and every job's log.
+/// Kept apart from `RenderQueue` so new log lines don't count as a change to save.
+#[derive(Default, Resource)]
+pub struct RenderProcesses {
+    running: HashMap<u64, RunningJob>,
+    logs: HashMap<u64, Arc<Mutex<Vec<String>>>>,
+}
+
+impl RenderProcesses {
+    /// Picks up the renders the app left running when it last closed
+    fn adopt(queue: &RenderQueue) -> Self {
+        let mut processes = Self::default();
+        for job in queue.jobs.iter() {
+            let (JobStatus::Running, Some(pid)) = (job.status, job.pid) else {
+                continue;
+            };
+            info!(file = %job.file, pid, "picked up a render left running");
+            let log = Arc::new(Mutex::new(Vec::new()));
+            let following = follow_log(job.id, log.clone());
+            processes.logs.insert(job.id, log);
+            processes.running.insert(
+                job.id,
+                RunningJob {
+                    process: JobProcess::Adopted(pid),
+                    started: Instant::now(),
+                    following,
+                },
+            );
+        }
+        processes
+    }
+
+    pub fn is_busy(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// How many renders are running, counting ones picked up from the last run
+    pub fn running_count(&self) -> usize {
+        self.running.len()
+    }
+
+    fn elapsed(&self, id: u64) -> Option<Duration> {
+        self.running
+            .get(&id)
+            .map(|running| running.started.elapsed())
+    }
+
+    /// The frame a job's Blender last said it was rendering, from its `Fra:42 Mem:...` lines
+    pub fn current_frame(&self, id: u64) -> Option<i32> {
+        let log = self.logs.get(&id)?.lock().ok()?;
+        log.iter().rev().find_map(|line| {
+            line.strip_prefix("Fra:")?
+                .split_whitespace()
+                .next()?
+                .parse()
+                .ok()
+        })
+    }
+
+    fn log(&self, id: u64) -> Vec<String> {
+        self.logs
+            .get(&id)
+            .map(|log| log.lock().map(|log| log.clone()).unwrap_or_default())
+            .unwrap_or_default()
+    }
+
+    /// Whether a job is a render picked up from the last run rather than one we started
+    fn is_adopted(&self, id: u64) -> bool {
+        self.running.get(&id).map_or(false, |running| {
+            matches!(running.process, JobProcess::Adopted(_))
+        })
+    }
+
+    fn kill(&mut self, id: u64) {
+        if let Some(mut running) = self.running.remove(&id) {
+            match &mut running.process {
+                JobProcess::Child(child) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+                JobProcess::Adopted(pid) => terminate(*pid),
+            }
+        }
+    }
+
+    /// Stops every running render, e.g. when the app exits
+    pub fn kill_all(&mut self) {
+        let ids: Vec<u64> = self.running.keys().copied().collect();
+        for id in ids {
+            self.kill(id);
+        }
+    }
+
+    /// Leaves every running render to carry on without us, e.g. when the app exits.
+    /// Their jobs stay running in the saved queue, with the pid the next start looks for.
+    pub fn detach_all(&mut self) {
+        // Dropping a `Child` doesn't stop the process, and Blender writes to its log file
+        self.running.clear();
+    }
+}
+
+/// Whether a process is still there. Only unix can tell without being its parent, so
+/// elsewhere a render left running is taken as over and its log says how it went.
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // Signal 0 checks the process exists without sending anything
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    false
+}
+
+/// Asks a process that isn't our child to stop
+#[cfg(unix)]
+fn terminate(pid: u32) {
+    unsafe {
+        libc::kill(pid as libc::pid_t, libc::SIGTERM);
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate(_pid: u32) {}
+
+/// The add-a-job form in the render queue window
+#[derive(Resource)]
+struct RenderQueueWindow {
+    open: bool,
+    start: i32,
+    end: i32,
+    output: String,
+}
+
+impl Default for RenderQueueWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            start: 1,
+            end: 250,
+            output: "//render/frame_####".to_string(),
+        }
+    }
+}
+
+pub struct RenderQueuePlugin;
+
+impl Plugin for RenderQueuePlugin {
+    fn build(&self, app: &mut App) {
+        let queue = load_render_queue();
+        app.insert_resource(RenderProcesses::adopt(&queue))
+            .insert_resource(queue)
+            .init_resource::<RenderQueueWindow>()
+            .add_system(toggle_render_queue.run_if(on_event::<ActionEvent>()))
+            .add_system(run_render_jobs.run_if(
+                |queue: Res<RenderQueue>, processes: Res<RenderProcesses>| {
+                    queue.has_queued() || processes.is_busy()
+                },
+            ))
+            .add_system(save_render_queue.run_if(resource_changed::<RenderQueue>()))
+            .add_system(render_queue_ui.run_if(|window: Res<RenderQueueWindow>| window.open));
+    }
+}
+
+fn render_queue_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("render_queue.json"))
+}
+
+/// Where Blender writes a job's output. A file rather than a pipe so the render can outlive
+/// the app, and the next start can read how it went.
+fn render_log_path(id: u64) -> PathBuf {
+    config_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("render_logs")
+        .join(format!("{}.log", id))
+}
+
+fn create_render_log(id: u64) -> io::Result<fs::File> {
+    let path = render_log_path(id);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::File::create(path)
+}
+
+/// Whether a render we only know by its log got to the end
+fn outcome_from_log(id: u64) -> Result<(), String> {
+    let finished = fs::read(render_log_path(id)).map_or(false, |log| {
+        String::from_utf8_lossy(&log)
+            .lines()
+            .any(|line| line.trim() == FINISHED_LINE)
+    });
+    if finished {
+        Ok(())
+    } else {
+        Err(t!("render.interrupted").to_string())
+    }
+}
+
+/// Reads the saved queue. Jobs that were running when the app closed are picked up again
+/// if their Blender still is, the rest are settled from their logs. Ones that didn't make
+/// it to the end are marked as failed for the user to retry rather than started again.
+fn load_render_queue() -> RenderQueue {
+    let Some(contents) = render_queue_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return RenderQueue::default();
+    };
+
+    let mut queue: RenderQueue = serde_json::from_str(&contents).unwrap_or_else(|error| {
+        warn!(%error, "couldn't parse the render queue");
+        RenderQueue::default()
+    });
+    for job in queue.jobs.iter_mut() {
+        if job.status == JobStatus::Running && !job.pid.map_or(false, process_alive) {
+            job.finish(outcome_from_log(job.id));
+        }
+    }
+    queue
+}
+
+pub fn write_render_queue(queue: &RenderQueue) -> io::Result<()> {
+    let Some(path) = render_queue_path() else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(queue)?)
+}
+
+fn save_render_queue(queue: Res<RenderQueue>, mut toasts: ResMut<Toasts>) {
+    if let Err(error) = write_render_queue(&queue) {
+        error!(%error, "couldn't save the render queue");
+        toasts.push(Severity::Error, t!("render.save_failed", error = error));
+    }
+}
+
+fn toggle_render_queue(
+    mut action_events: EventReader<ActionEvent>,
+    mut window: ResMut<RenderQueueWindow>,
+) {
+    for ActionEvent(action) in action_events.iter() {
+        if *action == Action::ShowRenderQueue {
+            window.open = !window.open;
+        }
+    }
+}
+
+/// Follows a job's log file as Blender writes to it, stdout and stderr interleaved,
+/// until the returned flag is cleared
+fn follow_log(id: u64, log: Arc<Mutex<Vec<String>>>) -> Arc<AtomicBool> {
+    let following = Arc::new(AtomicBool::new(true));
+    let Ok(file) = fs::File::open(render_log_path(id)) else {
+        return following;
+    };
+    let keep_following = following.clone();
+    thread::spawn(move || {
+        let mut reader = BufReader::new(file);
+        let mut line = Vec::new();
+        loop {
+            // Read what's there once more after being told to stop, it's the end of the log
+            let more = keep_following.load(Ordering::Relaxed);
+            while matches!(reader.read_until(b'\n', &mut line), Ok(read) if read > 0) {
+                // The rest of a half written line comes on a later read
+                if line.last() != Some(&b'\n') {
+                    break;
+                }
+                let Ok(mut log) = log.lock() else {
+                    return;
+                };
+                if log.len() >= MAX_LOG_LINES {
+                    log.remove(0);
+                }
+                log.push(String::from_utf8_lossy(&line).trim_end().to_string());
+                line.clear();
+            }
+            if !more {
+                return;
+            }
+            thread::sleep(LOG_POLL_INTERVAL);
+        }
+    });
+    following
+}
+
+/// Starts queued jobs while there are free slots, and picks up the ones that finished
+/// Whether the file a job renders is open and busy with something else.
+/// The same blend can be in the list twice, so every copy of it counts.
+fn file_busy(app_state: &AppState, path: &str) -> bool {
+    app_state
+        .files
+        .iter()
+        .any(|file| file.path == path && file.status.is_busy())
+}
+
+/// Moves every open copy of the file a job renders along, jobs only know their file by path
+fn update_file_status(
+    app_state: &mut AppState,
+    path: &str,
+    update: impl Fn(FileStatus) -> FileStatus,
+) {
+    for file in app_state.files.iter_mut().filter(|file| file.path == path) {
+        file.status = update(file.status);
+    }
+}
+
+fn run_render_jobs(
+    mut queue: ResMut<RenderQueue>,
+    mut processes: ResMut<RenderProcesses>,
+    mut app_state: ResMut<AppState>,
+    settings: Res<Settings>,
+    mut toasts: ResMut<Toasts>,
+) {
+    let mut finished = Vec::new();
+    for (id, running) in processes.running.iter_mut() {
+        if let Some(outcome) = running.poll(*id) {
+            finished.push((*id, outcome, running.started.elapsed()));
+        }
+    }
+    for (id, outcome, elapsed) in finished {
+        let adopted = processes.is_adopted(id);
+        processes.running.remove(&id);
+        let Some(job) = queue.job_mut(id) else {
+            continue;
+        };
+        // Renders picked up from the last run never marked their file as rendering
+        if !adopted {
+            update_file_status(&mut app_state, &job.file, |status| status.finish(true));
+        }
+        job.elapsed_secs = Some(elapsed.as_secs());
+        if outcome.is_ok() {
+            toasts.push(Severity::Success, t!("render.done", file = job.file));
+        } else {
+            toasts.push(Severity::Error, t!("render.failed", file = job.file));
+        }
+        job.finish(outcome);
+    }
+
+    // Only touch the queue when a job actually starts, so it isn't saved every frame.
+    // Jobs for files that are busy, e.g. reloading, wait for them and let later jobs go first.
+    while processes.running.len() < settings.render_concurrency.max(1) {
+        let Some(index) = queue
+            .jobs
+            .iter()
+            .position(|job| job.status == JobStatus::Queued && !file_busy(&app_state, &job.file))
+        else {
+            break;
+        };
+        let job = &mut queue.jobs[index];
+
+        let (start, end) = job.frames;
+        let spawned = create_render_log(job.id).and_then(|log_file| {
+            let stderr = log_file.try_clone()?;
+            Command::new(&job.blender)
+                .arg("--background")
+                .arg(&job.file)
+                // The output and frame range have to come before `-a`, Blender reads them in order
+                .args(["-o", &job.output])
+                .args(["-s", &start.to_string(), "-e", &end.to_string(), "-a"])
+                .stdin(Stdio::null())
+                .stdout(log_file)
+                .stderr(stderr)
+                .spawn()
+        });
+
+        match spawned {
+            Ok(child) => {
+                info!(file = %job.file, start, end, "started render job");
+                let log = Arc::new(Mutex::new(Vec::new()));
+                let following = follow_log(job.id, log.clone());
+                processes.logs.insert(job.id, log);
+                job.pid = Some(child.id());
+                processes.running.insert(
+                    job.id,
+                    RunningJob {
+                        process: JobProcess::Child(child),
+                        started: Instant::now(),
+                        following,
+                    },
+                );
+                job.status = JobStatus::Running;
+                job.error = None;
+                job.elapsed_secs = None;
+                update_file_status(&mut app_state, &job.file, |status| {
+                    status.begin(FileOp::Render).unwrap_or(status)
+                });
+            }
+            Err(error) => {
+                warn!(file = %job.file, %error, "couldn't start render job");
+                job.status = JobStatus::Failed;
+                job.error = Some(error.to_string());
+            }
+        }
+    }
+}
+
+/// What the user asked for in the job list
+enum JobAction {
+    MoveUp(usize),
+    MoveDown(usize),
+    Cancel(u64),
+    Retry(u64),
+    Remove(u64),
+}
+
+fn render_queue_ui(
+    mut contexts: EguiContexts,
+    mut window: ResMut<RenderQueueWindow>,
+    mut queue: ResMut<RenderQueue>,
+    mut processes: ResMut<RenderProcesses>,
+    mut app_state: ResMut<AppState>,
+    installs: Res<BlenderInstalls>,
+    settings: Res<Settings>,
+    mut watcher: ResMut<RenderOutputWatcher>,
+) {
+    let mut open = true;
+    let mut job_action = None;
+    let mut add_job = None;
+    let window = &mut *window;
+
+    egui::Window::new(t!("render.heading"))
+        .id(egui::Id::new("render_queue"))
+        .open(&mut open)
+        .default_width(480.0)
+        .show(contexts.ctx_mut(), |ui| {
+            // New jobs render the selected file with the Blender it'd open in
+            match app_state.selected_file() {
+                Some(file) => {
+                    let install = effective_install(file, &installs, &settings);
+                    ui.horizontal(|ui| {
+                        ui.label(t!("render.frames"));
+                        ui.add(egui::DragValue::new(&mut window.start));
+                        ui.label("–");
+                        ui.add(egui::DragValue::new(&mut window.end));
+                        window.end = window.end.max(window.start);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(t!("render.output"));
+                        ui.text_edit_singleline(&mut window.output);
+                    });
+                    // One job per file at a time, and none while the file is busy
+                    let pending = queue.jobs().iter().any(|job| {
+                        job.file == file.path
+                            && matches!(job.status, JobStatus::Queued | JobStatus::Running)
+                    });
+                    let blocked = if pending {
+                        Some(t!("render.already_queued"))
+                    } else {
+                        file.status.blocked(FileOp::Render)
+                    };
+                    let label = t!("render.add", file = file.path);
+                    let response = ui.add_enabled(
+                        install.is_some() && blocked.is_none(),
+                        egui::Button::new(label),
+                    );
+                    let response = match (install, blocked) {
+                        (_, Some(reason)) => response.on_disabled_hover_text(reason),
+                        (Some(install), None) => response.on_hover_text(install.label()),
+                        (None, None) => response.on_disabled_hover_text(t!("launch.no_install")),
+                    };
+                    if response.clicked() {
+                        if let Some(install) = install {
+                            add_job = Some((file.path.clone(), install.executable.clone()));
+                        }
+                    }
+                }
+                None => {
+                    ui.weak(t!("render.select_file"));
+                }
+            }
+
+            ui.separator();
+            if queue.jobs().is_empty() {
+                ui.weak(t!("render.empty"));
+            }
+            egui::ScrollArea::vertical()
+                .max_height(400.0)
+                .show(ui, |ui| {
+                    let job_count = queue.jobs().len();
+                    for (index, job) in queue.jobs().iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(index > 0, egui::Button::new("⏶").small())
+                                .clicked()
+                            {
+                                job_action = Some(JobAction::MoveUp(index));
+                            }
+                            if ui
+                                .add_enabled(index + 1 < job_count, egui::Button::new("⏷").small())
+                                .clicked()
+                            {
+                                job_action = Some(JobAction::MoveDown(index));
+                            }
+
+                            ui.label(t!(
+                                "render.job",
+                                file = job.file,
+                                start = job.frames.0,
+                                end = job.frames.1
+                            ));
+                            let status = ui.strong(job.status.label());
+                            if let Some(error) = &job.error {
+                                status.on_hover_text(error.as_str());
+                            }
+
+                            let elapsed = processes
+                                .elapsed(job.id)
+                                .map(|elapsed| elapsed.as_secs())
+                                .or(job.elapsed_secs);
+                            if let Some(secs) = elapsed {
+                                ui.weak(format!("{}:{:02}", secs / 60, secs % 60));
+                            }
+
+                            match job.status {
+                                JobStatus::Queued | JobStatus::Running => {
+                                    if ui.small_button(t!("render.cancel")).clicked() {
+                                        job_action = Some(JobAction::Cancel(job.id));
+                                    }
+                                }
+                                JobStatus::Done | JobStatus::Failed | JobStatus::Cancelled => {
+                                    if ui.small_button(t!("render.retry")).clicked() {
+                                        job_action = Some(JobAction::Retry(job.id));
+                                    }
+                                    if ui.small_button(t!("render.remove")).clicked() {
+                                        job_action = Some(JobAction::Remove(job.id));
+                                    }
+                                }
+                            }
+                        });
+
+                        let log = processes.log(job.id);
+                        if !log.is_empty() {
+                            egui::CollapsingHeader::new(t!("render.log"))
+                                .id_source(("render_log", job.id))
+                                .show(ui, |ui| {
+                                    egui::ScrollArea::vertical()
+                                        .max_height(150.0)
+                                        .stick_to_bottom(true)
+                                        .show(ui, |ui| {
+                                            for line in log.iter() {
+                                                ui.monospace(line.as_str());
+                                            }
+                                        });
+                                });
+                        }
+                    }
+                });
+
+            ui.separator();
+            renders_ui(ui, &mut watcher);
+        });
+
+    if let Some((file, blender)) = add_job {
+        let output = window.output.clone();
+        queue.push(file, (window.start, window.end), output, blender);
+    }
+    match job_action {
+        Some(JobAction::MoveUp(index)) => queue.jobs.swap(index, index - 1),
+        Some(JobAction::MoveDown(index)) => queue.jobs.swap(index, index + 1),
+        Some(JobAction::Cancel(id)) => {
+            let adopted = processes.is_adopted(id);
+            processes.kill(id);
+            if let Some(job) = queue.job_mut(id) {
+                if job.status == JobStatus::Running && !adopted {
+                    update_file_status(&mut app_state, &job.file, FileStatus::cancel);
+                }
+                job.status = JobStatus::Cancelled;
+                job.pid = None;
+            }
+        }
+        Some(JobAction::Retry(id)) => {
+            processes.logs.remove(&id);
+            if let Some(job) = queue.job_mut(id) {
+                job.status = JobStatus::Queued;
+                job.error = None;
+                job.elapsed_secs = None;
+            }
+        }
+        Some(JobAction::Remove(id)) => {
+            processes.logs.remove(&id);
+            let _ = fs::remove_file(render_log_path(id));
+            queue.jobs.retain(|job| job.id != id);
+        }
+        None => {}
+    }
+    if !open {
+        window.open = false;
+    }
+}
@@ -0,0 +1,88 @@
+use std::{collections::HashSet, io, process::Command};
+
+use bevy::prelude::*;
+
+use crate::{
+    commands::Keybindings,
+    metadata_cache::{load_metadata_cache, MetadataCache},
+    session::load_session,
+    settings::{load_saved_settings, Settings},
+    AppState,
+};
+
+/// `--safe-mode` starts with default settings and an empty file list, and without the
+/// metadata cache, in case one of them is what crashes the app. None of them are written
+/// back while it's on, so the saved ones can still be repaired or loaded later.
+#[derive(Default, Resource)]
+pub struct SafeMode {
+    active: bool,
+    /// Asked to load the saved settings and session, done on the next update
+    leaving: bool,
+}
+
+impl SafeMode {
+    pub fn new(active: bool) -> Self {
+        Self {
+            active,
+            leaving: false,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn leave(&mut self) {
+        self.leaving = true;
+    }
+}
+
+/// Whether `--safe-mode` was passed on the command line
+pub fn cli_safe_mode() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--safe-mode")
+}
+
+/// Starts another copy of the launcher in safe mode, for the caller to exit after
+pub fn restart_in_safe_mode() -> io::Result<()> {
+    Command::new(std::env::current_exe()?)
+        .arg("--safe-mode")
+        .spawn()?;
+    Ok(())
+}
+
+pub struct SafeModePlugin;
+
+impl Plugin for SafeModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(leave_safe_mode.run_if(|safe_mode: Res<SafeMode>| safe_mode.leaving));
+    }
+}
+
+/// Loads the saved settings, metadata cache and session the way a normal start does.
+/// Files opened in safe mode stay in the list.
+fn leave_safe_mode(
+    mut safe_mode: ResMut<SafeMode>,
+    mut settings: ResMut<Settings>,
+    mut keybindings: ResMut<Keybindings>,
+    mut metadata_cache: ResMut<MetadataCache>,
+    mut app_state: ResMut<AppState>,
+) {
+    info!("leaving safe mode");
+    *safe_mode = SafeMode::new(false);
+    let (saved_settings, saved_keybindings) = load_saved_settings();
+    *settings = saved_settings;
+    *keybindings = saved_keybindings;
+    *metadata_cache = load_metadata_cache();
+
+    let opened: HashSet<String> = app_state
+        .files
+        .iter()
+        .map(|file| file.path.clone())
+        .collect();
+    let first_loaded = app_state.next_file_id;
+    load_session(&mut app_state, settings.workspace.as_deref());
+    // Nothing has started on the files just added, so the copies can simply be dropped
+    app_state
+        .files
+        .retain(|file| file.id.0 < first_loaded || !opened.contains(&file.path));
+}
@@ -0,0 +1,296 @@
+use std::collections::HashSet;
+
+use bevy_egui::egui;
+use blend::{Blend, Instance};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::t,
+    metadata::{id_name, list_iter},
+    File,
+};
+
+/// `LAYER_COLLECTION_EXCLUDE`, the checkbox next to a collection in the outliner
+const LAYER_COLLECTION_EXCLUDE: i16 = 1 << 4;
+/// Deepest collection nesting we follow, in case a broken file links collections in a loop
+const MAX_COLLECTION_DEPTH: usize = 64;
+/// Longest object, collection, or view layer list we follow
+const MAX_LIST_ITEMS: usize = 65536;
+
+/// A view layer and the objects it leaves out
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ViewLayerInfo {
+    pub name: String,
+    /// Objects of the scene that are only in collections excluded from this layer
+    pub excluded: Vec<String>,
+    /// What Blender considers the active object in this layer
+    pub active_object: Option<String>,
+}
+
+/// A scene (`SC`) block and its view layers
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneInfo {
+    pub name: String,
+    /// Every object in the scene's collections. `None` for files from before 2.80,
+    /// which have no collections, so every object counts as part of the scene.
+    pub objects: Option<Vec<String>>,
+    /// The scene's collection and everything nested in it, `None` where `objects` is
+    #[serde(default)]
+    pub collection: Option<CollectionInfo>,
+    pub view_layers: Vec<ViewLayerInfo>,
+}
+
+/// A collection with the objects directly in it and the collections nested in it,
+/// as Blender's outliner shows them
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CollectionInfo {
+    pub name: String,
+    pub objects: Vec<String>,
+    pub children: Vec<CollectionInfo>,
+}
+
+impl CollectionInfo {
+    /// The objects in this collection and every collection nested in it
+    pub fn all_objects<'a>(&'a self, names: &mut Vec<&'a str>) {
+        names.extend(self.objects.iter().map(String::as_str));
+        for child in self.children.iter() {
+            child.all_objects(names);
+        }
+    }
+
+    /// This collection or the first one nested in it with the given name
+    pub fn find(&self, name: &str) -> Option<&CollectionInfo> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(name))
+    }
+}
+
+/// Which scene and view layer "Spawn scene" follows for a file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SceneChoice {
+    pub scene: usize,
+    pub view_layer: usize,
+    /// Spawns objects in excluded collections anyway
+    pub include_excluded: bool,
+}
+
+/// Reads every scene with its objects and what each of its view layers excludes
+pub fn parse_scenes(blend: &Blend) -> Vec<SceneInfo> {
+    blend
+        .instances_with_code(*b"SC")
+        .map(|scene| {
+            let collection = scene
+                .is_valid("master_collection")
+                .then(|| collection_tree(&scene.get("master_collection"), 0));
+            let objects = collection.as_ref().map(|collection| {
+                let mut names = Vec::new();
+                collection.all_objects(&mut names);
+                names.sort_unstable();
+                names.dedup();
+                names.into_iter().map(str::to_string).collect::<Vec<_>>()
+            });
+
+            let view_layers = list_iter(&scene.get("view_layers"))
+                .take(MAX_LIST_ITEMS)
+                .map(|view_layer| {
+                    let mut included = HashSet::new();
+                    for layer_collection in
+                        list_iter(&view_layer.get("layer_collections")).take(MAX_LIST_ITEMS)
+                    {
+                        included_objects(&layer_collection, 0, &mut included);
+                    }
+                    // `basact` is the active `Base`, which points at the object
+                    let active_object = view_layer
+                        .is_valid("basact")
+                        .then(|| view_layer.get("basact"))
+                        .filter(|base| base.is_valid("object"))
+                        .map(|base| id_name(&base.get("object")));
+                    ViewLayerInfo {
+                        name: view_layer.get_string("name"),
+                        excluded: objects
+                            .iter()
+                            .flatten()
+                            .filter(|name| !included.contains(*name))
+                            .cloned()
+                            .collect(),
+                        active_object,
+                    }
+                })
+                .collect();
+
+            SceneInfo {
+                name: id_name(&scene),
+                objects,
+                collection,
+                view_layers,
+            }
+        })
+        .collect()
+}
+
+/// Index of the scene the file was saved with open, from its first window.
+/// Files saved without a window manager (e.g. from a script) give `None`.
+pub fn active_scene(blend: &Blend, scenes: &[SceneInfo]) -> Option<usize> {
+    let window_manager = blend.instances_with_code(*b"WM").next()?;
+    let window = list_iter(&window_manager.get("windows")).next()?;
+    if !window.is_valid("scene") {
+        return None;
+    }
+    let name = id_name(&window.get("scene"));
+    scenes.iter().position(|scene| scene.name == name)
+}
+
+/// A collection with what's in it, down to `MAX_COLLECTION_DEPTH`
+fn collection_tree(collection: &Instance, depth: usize) -> CollectionInfo {
+    let objects = list_iter(&collection.get("gobject"))
+        .take(MAX_LIST_ITEMS)
+        .filter(|item| item.is_valid("ob"))
+        .map(|item| id_name(&item.get("ob")))
+        .collect();
+    let children = if depth < MAX_COLLECTION_DEPTH {
+        list_iter(&collection.get("children"))
+            .take(MAX_LIST_ITEMS)
+            .filter(|child| child.is_valid("collection"))
+            .map(|child| collection_tree(&child.get("collection"), depth + 1))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    CollectionInfo {
+        name: id_name(collection),
+        objects,
+        children,
+    }
+}
+
+/// The objects a view layer's collection tree includes. Excluding a collection
+/// also excludes the collections nested in it.
+fn included_objects(layer_collection: &Instance, depth: usize, included: &mut HashSet<String>) {
+    if depth > MAX_COLLECTION_DEPTH
+        || layer_collection.get_i16("flag") & LAYER_COLLECTION_EXCLUDE != 0
+    {
+        return;
+    }
+    if layer_collection.is_valid("collection") {
+        let collection = layer_collection.get("collection");
+        for item in list_iter(&collection.get("gobject")).take(MAX_LIST_ITEMS) {
+            if item.is_valid("ob") {
+                included.insert(id_name(&item.get("ob")));
+            }
+        }
+    }
+    for child in list_iter(&layer_collection.get("layer_collections")).take(MAX_LIST_ITEMS) {
+        included_objects(&child, depth + 1, included);
+    }
+}
+
+impl File {
+    /// The view layer "Spawn scene" follows, `None` when the file has no scenes
+    pub fn chosen_view_layer(&self) -> Option<(&SceneInfo, Option<&ViewLayerInfo>)> {
+        let scene = self.scenes.get(self.scene_choice.scene)?;
+        Some((scene, scene.view_layers.get(self.scene_choice.view_layer)))
+    }
+
+    /// Index of the chosen view layer's active object
+    pub fn active_object(&self) -> Option<usize> {
+        let (_, view_layer) = self.chosen_view_layer()?;
+        let name = view_layer?.active_object.as_ref()?;
+        self.meshes.iter().position(|object| &object.name == name)
+    }
+
+    /// Objects "Spawn scene" leaves out: those in other scenes, and those
+    /// the chosen view layer excludes unless that's overridden
+    pub fn skipped_by_scene(&self) -> HashSet<&str> {
+        let Some((scene, view_layer)) = self.chosen_view_layer() else {
+            return HashSet::new();
+        };
+        let mut skipped: HashSet<&str> = HashSet::new();
+        if let Some(objects) = &scene.objects {
+            let in_scene: HashSet<&str> = objects.iter().map(String::as_str).collect();
+            skipped.extend(
+                self.meshes
+                    .iter()
+                    .map(|object| object.name.as_str())
+                    .filter(|name| !in_scene.contains(name)),
+            );
+        }
+        if let Some(view_layer) = view_layer {
+            if !self.scene_choice.include_excluded {
+                skipped.extend(view_layer.excluded.iter().map(String::as_str));
+            }
+        }
+        skipped
+    }
+
+    /// The chosen view layer's name, if it excludes the object
+    pub fn excluding_view_layer(&self, object: &str) -> Option<&str> {
+        let (_, view_layer) = self.chosen_view_layer()?;
+        let view_layer = view_layer?;
+        view_layer
+            .excluded
+            .iter()
+            .any(|name| name == object)
+            .then_some(view_layer.name.as_str())
+    }
+}
+
+/// Picks the scene and view layer "Spawn scene" follows for `file`
+pub fn scene_selector_ui(ui: &mut egui::Ui, file: &File, choice: &mut SceneChoice) {
+    if file.scenes.is_empty() {
+        return;
+    }
+    let file_id = file.id.0;
+
+    let scene_name = file
+        .scenes
+        .get(choice.scene)
+        .map_or("", |scene| scene.name.as_str());
+    egui::ComboBox::new(("scene", file_id), t!("scenes.scene"))
+        .selected_text(scene_name)
+        .show_ui(ui, |ui| {
+            for (index, scene) in file.scenes.iter().enumerate() {
+                let label = if file.active_scene == Some(index) {
+                    t!("scenes.active", scene = scene.name)
+                } else {
+                    scene.name.clone()
+                };
+                if ui
+                    .selectable_value(&mut choice.scene, index, label)
+                    .clicked()
+                {
+                    choice.view_layer = 0;
+                }
+            }
+        });
+
+    let Some(scene) = file.scenes.get(choice.scene) else {
+        return;
+    };
+    if scene.view_layers.is_empty() {
+        return;
+    }
+    let layer_name = scene
+        .view_layers
+        .get(choice.view_layer)
+        .map_or("", |view_layer| view_layer.name.as_str());
+    egui::ComboBox::new(("view_layer", file_id), t!("scenes.view_layer"))
+        .selected_text(layer_name)
+        .show_ui(ui, |ui| {
+            for (index, view_layer) in scene.view_layers.iter().enumerate() {
+                ui.selectable_value(&mut choice.view_layer, index, view_layer.name.as_str());
+            }
+        });
+
+    let excluded = scene
+        .view_layers
+        .get(choice.view_layer)
+        .map_or(0, |view_layer| view_layer.excluded.len());
+    if excluded > 0 {
+        ui.checkbox(
+            &mut choice.include_excluded,
+            t!("scenes.include_excluded", count = excluded),
+        );
+    }
+}
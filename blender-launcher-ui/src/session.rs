@@ -0,0 +1,251 @@
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crash::remember_session,
+    i18n::t,
+    safe_mode::SafeMode,
+    settings::{config_dir, CameraSettings, LightingSettings, Settings},
+    toasts::{Severity, Toasts},
+    AppState,
+};
+
+/// The file list as the user left it, so it comes back in the same order next launch.
+/// Each workspace has its own.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct SessionFile {
+    files: Vec<String>,
+    /// Blender executable each file is pinned to, keyed by the file's path
+    open_with: HashMap<String, PathBuf>,
+    /// Missing from sessions saved before there were workspaces
+    view: Option<WorkspaceView>,
+    /// The (file path, object name) of each recently spawned object, newest first
+    recent_spawns: Vec<(String, String)>,
+}
+
+/// The camera and preview options a workspace brings back when it's switched to
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceView {
+    camera: CameraSettings,
+    lighting: LightingSettings,
+    show_origins: bool,
+    show_empties: bool,
+    snap_to_ground: bool,
+    two_sided: bool,
+    highlight_backfaces: bool,
+    bloom: bool,
+    bloom_intensity: f32,
+}
+
+impl WorkspaceView {
+    fn from_settings(settings: &Settings) -> Self {
+        Self {
+            camera: settings.camera,
+            lighting: settings.lighting,
+            show_origins: settings.show_origins,
+            show_empties: settings.show_empties,
+            snap_to_ground: settings.snap_to_ground,
+            two_sided: settings.two_sided,
+            highlight_backfaces: settings.highlight_backfaces,
+            bloom: settings.bloom,
+            bloom_intensity: settings.bloom_intensity,
+        }
+    }
+
+    pub fn apply(&self, settings: &mut Settings) {
+        settings.camera = self.camera;
+        settings.lighting = self.lighting;
+        settings.show_origins = self.show_origins;
+        settings.show_empties = self.show_empties;
+        settings.snap_to_ground = self.snap_to_ground;
+        settings.two_sided = self.two_sided;
+        settings.highlight_backfaces = self.highlight_backfaces;
+        settings.bloom = self.bloom;
+        settings.bloom_intensity = self.bloom_intensity;
+    }
+}
+
+impl Default for WorkspaceView {
+    fn default() -> Self {
+        Self::from_settings(&Settings::default())
+    }
+}
+
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        // Safe mode starts with an empty list and leaves the saved one as it was
+        let normal_start = |safe_mode: Res<SafeMode>| !safe_mode.is_active();
+        app.add_startup_system(restore_session.run_if(normal_start))
+            .add_startup_system(open_cli_files.after(restore_session).run_if(normal_start))
+            .add_system(save_session.run_if(normal_start));
+    }
+}
+
+/// Where the named workspaces keep their sessions, one file per workspace
+pub fn workspaces_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("workspaces"))
+}
+
+/// The session file of a named workspace, or of the default one with `None`.
+/// The default workspace keeps the session file from before there were workspaces.
+pub fn session_path(workspace: Option<&str>) -> Option<PathBuf> {
+    match workspace {
+        Some(name) => workspaces_dir().map(|dir| dir.join(format!("{}.json", name))),
+        None => config_dir().map(|dir| dir.join("session.json")),
+    }
+}
+
+/// Adds the files from the last session back to the list.
+/// Metadata is still loaded lazily when a file is selected.
+pub fn restore_session(mut app_state: ResMut<AppState>, settings: Res<Settings>) {
+    load_session(&mut app_state, settings.workspace.as_deref());
+}
+
+/// Adds a workspace's files to the list, and returns the view it was saved with
+pub fn load_session(app_state: &mut AppState, workspace: Option<&str>) -> Option<WorkspaceView> {
+    let path = session_path(workspace)?;
+    let contents = fs::read_to_string(&path).ok()?;
+
+    let mut session: SessionFile = serde_json::from_str(&contents).unwrap_or_else(|error| {
+        warn!(path = %path.display(), %error, "couldn't parse session");
+        SessionFile::default()
+    });
+
+    for file_path in session.files {
+        // Files can be moved or deleted between sessions
+        if PathBuf::from(&file_path).exists() {
+            let blender_override = session.open_with.remove(&file_path);
+            let file_id = app_state.add_file(file_path);
+            if let Some(file) = app_state.file_mut(file_id) {
+                file.blender_override = blender_override;
+            }
+        }
+    }
+    for (file_path, object) in session.recent_spawns {
+        // Leaving safe mode keeps the copy that was already open, which comes first
+        let file_id = app_state
+            .files
+            .iter()
+            .find(|file| file.path == file_path)
+            .map(|file| file.id);
+        if let Some(file_id) = file_id {
+            app_state.recent_spawns.restore(file_id, object);
+        }
+    }
+    session.view
+}
+
+/// Files passed on the command line, e.g. by a file manager opening a `.blend` with us.
+/// Flags (and the value after `--log-level`) are skipped.
+fn cli_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--log-level" {
+            args.next();
+        } else if !arg.starts_with("--") {
+            files.push(PathBuf::from(arg));
+        }
+    }
+    files
+}
+
+/// Adds the command line files (unless they're already in the session) and selects the last one
+fn open_cli_files(mut app_state: ResMut<AppState>) {
+    let current_dir = std::env::current_dir().unwrap_or_default();
+
+    let mut opened = None;
+    for path in cli_files() {
+        // File managers pass absolute paths, a terminal might not
+        let path = current_dir.join(path);
+        if !path.is_file() {
+            warn!(path = %path.display(), "couldn't find file from the command line");
+            continue;
+        }
+
+        let path = path.display().to_string();
+        let existing = app_state.files.iter().find(|file| file.path == path);
+        opened = Some(match existing {
+            Some(file) => file.id,
+            None => app_state.add_file(path),
+        });
+    }
+
+    if opened.is_some() {
+        app_state.select_file(opened);
+    }
+}
+
+/// Writes the file list whenever files are added, removed, reordered, or pinned to a Blender,
+/// or an object is spawned, and the workspace's view when one of its options changes
+fn save_session(
+    app_state: Res<AppState>,
+    settings: Res<Settings>,
+    mut last_saved: Local<Option<(Option<String>, SessionFile)>>,
+    mut toasts: ResMut<Toasts>,
+) {
+    if !app_state.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    let saved = (
+        settings.workspace.clone(),
+        current_session(&app_state, &settings),
+    );
+    if last_saved.as_ref() == Some(&saved) {
+        return;
+    }
+
+    if let Err(error) = write_session(&app_state, &settings) {
+        error!(%error, "couldn't save session");
+        toasts.push(Severity::Error, t!("session.save_failed", error = error));
+    }
+
+    *last_saved = Some(saved);
+}
+
+fn current_session(app_state: &AppState, settings: &Settings) -> SessionFile {
+    SessionFile {
+        files: app_state
+            .files
+            .iter()
+            .map(|file| file.path.clone())
+            .collect(),
+        open_with: app_state
+            .files
+            .iter()
+            .filter_map(|file| Some((file.path.clone(), file.blender_override.clone()?)))
+            .collect(),
+        view: Some(WorkspaceView::from_settings(settings)),
+        recent_spawns: app_state
+            .recent_spawns
+            .iter()
+            .filter_map(|recent| {
+                let file = app_state.file(recent.file)?;
+                Some((file.path.clone(), recent.object.clone()))
+            })
+            .collect(),
+    }
+}
+
+/// Writes the current file list to the active workspace's session file
+pub fn write_session(app_state: &AppState, settings: &Settings) -> io::Result<()> {
+    let Some(path) = session_path(settings.workspace.as_deref()) else {
+        return Ok(());
+    };
+
+    let session = current_session(app_state, settings);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(&session)?;
+    // A crash writes it again, in case it happened halfway through this
+    remember_session(path.clone(), json.clone());
+    fs::write(&path, json)
+}
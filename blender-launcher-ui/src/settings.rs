@@ -0,0 +1,1071 @@
+use std::{collections::HashSet, fs, io, path::PathBuf};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    accessibility::{self, status_label, Motion, Status},
+    auto_preview::AutoPreview,
+    camera::OrbitMode,
+    commands::{keybindings_ui, Action, Binding, Keybindings},
+    confirm::ConfirmKind,
+    file_actions::{file_toolbar_settings_ui, FILE_ACTIONS},
+    i18n::{self, t, Language},
+    launch::OpenWithRule,
+    lighting::SHADOW_MAP_SIZES,
+    lod::{compile_lod_pattern, DEFAULT_LOD_PATTERN},
+    logging::LogLevel,
+    mesh_builder::MeshLoader,
+    object_table::{ObjectColumns, ObjectListMode},
+    safe_mode::SafeMode,
+    shading::PeekKey,
+    toasts::{Severity, Toasts},
+    tray::TRAY_SUPPORTED,
+};
+
+/// User configurable options for the launcher
+#[derive(Clone, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Objects with more ngons than this get a warning badge
+    pub ngon_warning_threshold: usize,
+    /// How far apart an object's scale axes can be before it's flagged as non-uniform
+    pub scale_tolerance: f32,
+    /// Images with a side larger than this show up in the texture audit
+    pub max_texture_size: u32,
+    /// Multiplier on top of the window's native scale factor
+    pub ui_scale: f32,
+    /// Overrides egui's default text sizes when set
+    pub base_font_size: Option<f32>,
+    /// Render the log console with a monospace font
+    pub log_console_monospace: bool,
+    pub panel_layout: PanelLayout,
+    pub theme: Theme,
+    pub motion: Motion,
+    /// Status indicators use blue and orange instead of green and red
+    pub color_blind_palette: bool,
+    /// Used for selections and links across the whole app
+    pub accent_color: [u8; 3],
+    /// Destructive actions the user asked us not to confirm again
+    pub skip_confirmations: HashSet<ConfirmKind>,
+    /// Shows how often the event driven systems ran
+    pub debug_overlay: bool,
+    /// How many blend files we parse at the same time
+    pub parse_concurrency: usize,
+    /// Reuses metadata of files that haven't changed since they were last parsed
+    pub metadata_cache: bool,
+    /// Lists objects and materials by name rather than in the order the file stores them,
+    /// which changes when a file is saved again
+    pub sort_by_name: bool,
+    /// How many queued renders run at the same time
+    pub render_concurrency: usize,
+    /// Stops running renders when the app closes, rather than leaving them to finish and
+    /// picking them up again on the next start
+    pub kill_renders_on_exit: bool,
+    /// Folder to preview finished frames from, on top of the queued jobs' output folders
+    pub render_output_dir: Option<PathBuf>,
+    /// Folder the game loads blends relative to. Copied asset paths are relative to it,
+    /// spawning still goes through the absolute path.
+    pub asset_root: Option<PathBuf>,
+    /// How many mesh loads "Spawn scene" starts per frame
+    pub spawn_batch_size: usize,
+    /// Where preview meshes come from. bevy_blender is what a game would see, the built-in
+    /// loader finds meshes whose name doesn't match their object.
+    pub mesh_loader: MeshLoader,
+    /// Used when `--log-level` isn't passed, only read at startup
+    pub log_level: LogLevel,
+    /// Watches loaded files and refreshes their previews when saved from Blender,
+    /// only read at startup
+    pub hot_reload: bool,
+    /// Draws the helpers over the previews: origin markers, the move gizmo and the
+    /// placement ghost. Turning it off hides them all at once, whatever their own settings.
+    pub show_overlays: bool,
+    /// Draws an axis cross at each previewed object's origin
+    pub show_origins: bool,
+    /// Draws empties as wire shapes, like Blender's viewport. They're spawned either way,
+    /// since objects parented to them need them for their place.
+    pub show_empties: bool,
+    /// Rests previews on the ground plane instead of where they were modeled
+    pub snap_to_ground: bool,
+    pub instance_grid: InstanceGrid,
+    pub contact_sheet: ContactSheetLayout,
+    /// Shows a simplified copy of meshes with more triangles than `decimation_threshold`
+    pub preview_decimation: bool,
+    pub decimation_threshold: usize,
+    /// Draws both sides of every face, so flipped normals don't make geometry disappear
+    pub two_sided: bool,
+    /// Draws faces pointing away from the camera in red
+    pub highlight_backfaces: bool,
+    /// Held to see the materials while a debug shading mode is on
+    pub peek_key: PeekKey,
+    pub bloom: bool,
+    pub bloom_intensity: f32,
+    /// Turns off shadows and anti-aliasing while frames take longer than `frame_guard_ms`
+    pub frame_guard: bool,
+    pub frame_guard_ms: f32,
+    pub camera: CameraSettings,
+    pub lighting: LightingSettings,
+    /// Closing the window hides it to the system tray, when the build supports a tray
+    pub minimize_to_tray: bool,
+    pub language: Language,
+    /// Shows translation keys instead of strings, for checking translations
+    pub show_translation_keys: bool,
+    /// Executable of the Blender to launch, the newest install when unset or missing
+    pub default_blender: Option<PathBuf>,
+    /// Lists daily builds next to the stable releases in the builds window
+    pub include_daily_builds: bool,
+    /// Picks the Blender for files that aren't pinned to one
+    pub open_with_rule: OpenWithRule,
+    pub object_list: ObjectListMode,
+    /// Summarizes an object when its row is hovered
+    pub object_tooltips: bool,
+    /// Spawns an object of each file selected, once its metadata is in
+    pub auto_preview: AutoPreview,
+    /// Which stats the object table shows, when it's used
+    pub object_columns: ObjectColumns,
+    /// Regex matching the names of LOD objects, with a `base` and a `level` group.
+    /// Objects with the same base are listed together.
+    pub lod_pattern: String,
+    /// Actions the toolbar of the hovered or selected file shows. The context menu has all.
+    pub file_toolbar: Vec<Action>,
+    /// The workspace whose files are in the list, the default one when unset
+    pub workspace: Option<String>,
+    /// Files added lately, newest first, for File ▸ Recent
+    pub recent_files: Vec<String>,
+}
+
+/// Projection of the preview camera
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    /// Vertical field of view
+    pub fov_degrees: f32,
+    pub near: f32,
+    pub far: f32,
+    /// Fits `near` and `far` around the previewed objects instead
+    pub auto_clip: bool,
+    pub orbit_mode: OrbitMode,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        // Bevy's default perspective projection
+        Self {
+            fov_degrees: 45.0,
+            near: 0.1,
+            far: 1000.0,
+            auto_clip: false,
+            orbit_mode: OrbitMode::default(),
+        }
+    }
+}
+
+/// The preview's light, placed around the camera target
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LightingSettings {
+    pub shadows: bool,
+    /// Size of each face of the shadow cube map, in pixels
+    pub shadow_map_size: usize,
+    /// From the camera target
+    pub distance: f32,
+    /// Around the vertical axis, 0° is towards +Z
+    pub azimuth_degrees: f32,
+    /// Above the ground plane
+    pub elevation_degrees: f32,
+    /// Moves the light back far enough to cover everything previewed,
+    /// so shadows of large scenes don't get cut off
+    pub auto_distance: bool,
+}
+
+impl Default for LightingSettings {
+    fn default() -> Self {
+        // Where the light used to be fixed, at (4, 8, 4)
+        Self {
+            shadows: true,
+            shadow_map_size: 1024,
+            distance: 9.8,
+            azimuth_degrees: 45.0,
+            elevation_degrees: 54.7,
+            auto_distance: true,
+        }
+    }
+}
+
+/// Layout for spawning one mesh many times on the XZ plane
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstanceGrid {
+    pub rows: u32,
+    pub columns: u32,
+    /// Distance between instances, in world units
+    pub spacing: f32,
+}
+
+impl Default for InstanceGrid {
+    fn default() -> Self {
+        Self {
+            rows: 3,
+            columns: 3,
+            spacing: 2.0,
+        }
+    }
+}
+
+/// Grid of object thumbnails in an exported contact sheet
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContactSheetLayout {
+    pub columns: usize,
+    /// Rows per image. Files with more objects than fit are split over numbered images.
+    pub rows: usize,
+    /// Width and height of a thumbnail in the sheet, in pixels
+    pub cell_size: u32,
+}
+
+impl Default for ContactSheetLayout {
+    fn default() -> Self {
+        Self {
+            columns: 8,
+            rows: 8,
+            cell_size: 128,
+        }
+    }
+}
+
+impl ContactSheetLayout {
+    pub fn per_page(&self) -> usize {
+        self.columns.max(1) * self.rows.max(1)
+    }
+}
+
+impl InstanceGrid {
+    /// Positions of every instance, centered on the origin
+    pub fn positions(&self) -> impl Iterator<Item = Vec3> + '_ {
+        let offset = |count: u32| (count.max(1) - 1) as f32 * 0.5;
+        (0..self.rows.max(1)).flat_map(move |row| {
+            (0..self.columns.max(1)).map(move |column| {
+                Vec3::new(
+                    (column as f32 - offset(self.columns)) * self.spacing,
+                    0.0,
+                    (row as f32 - offset(self.rows)) * self.spacing,
+                )
+            })
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    Dark,
+    Light,
+    /// Follows the OS preference where we can detect it, otherwise dark
+    System,
+}
+
+impl Theme {
+    fn visuals(&self) -> egui::Visuals {
+        let dark = match self {
+            Theme::Dark => true,
+            Theme::Light => false,
+            Theme::System => dark_light::detect() != dark_light::Mode::Light,
+        };
+
+        if dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        }
+    }
+}
+
+/// Sizes (in egui points) and collapsed state of the panels around the viewport
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelLayout {
+    pub left_width: f32,
+    pub right_width: f32,
+    pub top_height: f32,
+    pub bottom_height: f32,
+    pub top_collapsed: bool,
+    pub bottom_collapsed: bool,
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self {
+            left_width: 200.0,
+            right_width: 250.0,
+            top_height: 40.0,
+            bottom_height: 120.0,
+            top_collapsed: false,
+            bottom_collapsed: false,
+        }
+    }
+}
+
+impl PanelLayout {
+    /// Keeps a layout saved on a big monitor from swallowing a smaller window.
+    /// Each side panel gets at most a third of the window, top and bottom a quarter.
+    pub fn clamped(&self, window_width: f32, window_height: f32) -> Self {
+        Self {
+            left_width: self.left_width.min(window_width / 3.0),
+            right_width: self.right_width.min(window_width / 3.0),
+            top_height: self.top_height.min(window_height / 4.0),
+            bottom_height: self.bottom_height.min(window_height / 4.0),
+            ..self.clone()
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ngon_warning_threshold: 10,
+            scale_tolerance: 0.001,
+            max_texture_size: 2048,
+            ui_scale: 1.0,
+            base_font_size: None,
+            log_console_monospace: true,
+            panel_layout: PanelLayout::default(),
+            theme: Theme::Dark,
+            motion: Motion::default(),
+            color_blind_palette: false,
+            accent_color: [0, 92, 128],
+            skip_confirmations: HashSet::new(),
+            debug_overlay: false,
+            // Leave a core free so the UI stays responsive
+            parse_concurrency: num_cpus::get_physical().saturating_sub(1).max(1),
+            metadata_cache: true,
+            sort_by_name: true,
+            render_concurrency: 1,
+            kill_renders_on_exit: false,
+            render_output_dir: None,
+            asset_root: None,
+            spawn_batch_size: 8,
+            mesh_loader: MeshLoader::default(),
+            log_level: LogLevel::Info,
+            hot_reload: false,
+            show_overlays: true,
+            show_origins: false,
+            show_empties: true,
+            snap_to_ground: false,
+            instance_grid: InstanceGrid::default(),
+            contact_sheet: ContactSheetLayout::default(),
+            preview_decimation: false,
+            decimation_threshold: 1_000_000,
+            two_sided: false,
+            highlight_backfaces: false,
+            peek_key: PeekKey::default(),
+            bloom: false,
+            bloom_intensity: 0.3,
+            // 10 FPS, past the point of the app being pleasant to use
+            frame_guard: true,
+            frame_guard_ms: 100.0,
+            camera: CameraSettings::default(),
+            lighting: LightingSettings::default(),
+            minimize_to_tray: false,
+            language: Language::default(),
+            show_translation_keys: false,
+            default_blender: None,
+            include_daily_builds: false,
+            open_with_rule: OpenWithRule::default(),
+            object_list: ObjectListMode::default(),
+            object_tooltips: true,
+            auto_preview: AutoPreview::Off,
+            object_columns: ObjectColumns::default(),
+            lod_pattern: DEFAULT_LOD_PATTERN.to_string(),
+            file_toolbar: FILE_ACTIONS.iter().map(|(action, _)| *action).collect(),
+            workspace: None,
+            recent_files: Vec::new(),
+        }
+    }
+}
+
+/// Everything we write to the settings file
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SettingsFile {
+    settings: Settings,
+    keybindings: Vec<Binding>,
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let safe_mode = app
+            .world
+            .get_resource::<SafeMode>()
+            .is_some_and(SafeMode::is_active);
+        let (settings, keybindings) = if safe_mode {
+            let settings = Settings {
+                metadata_cache: false,
+                ..Default::default()
+            };
+            (settings, Keybindings::default())
+        } else {
+            load_saved_settings()
+        };
+
+        app.insert_resource(settings)
+            .insert_resource(keybindings)
+            .add_system(save_settings.run_if(|safe_mode: Res<SafeMode>| !safe_mode.is_active()))
+            .add_system(apply_ui_settings);
+    }
+}
+
+/// Where the launcher keeps its config, e.g. `~/.config/blender-launcher` on Linux
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("blender-launcher"))
+}
+
+/// The log level from the settings file, read before the rest of the app is set up
+pub fn saved_log_level() -> LogLevel {
+    load_settings_file().settings.log_level
+}
+
+/// Whether to watch for changes, which the asset server needs to know when it's created
+pub fn saved_hot_reload() -> bool {
+    load_settings_file().settings.hot_reload
+}
+
+fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("settings.json"))
+}
+
+/// The settings and keybindings from the settings file, or the defaults without one
+pub fn load_saved_settings() -> (Settings, Keybindings) {
+    let settings_file = load_settings_file();
+    let keybindings = Keybindings::from_bindings(&settings_file.keybindings);
+    (settings_file.settings, keybindings)
+}
+
+/// Reads the settings file, falling back to defaults if it's missing or broken
+fn load_settings_file() -> SettingsFile {
+    let Some(path) = settings_path() else {
+        return SettingsFile::default();
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|error| {
+            warn!(path = %path.display(), %error, "couldn't parse settings");
+            SettingsFile::default()
+        }),
+        Err(_) => SettingsFile::default(),
+    }
+}
+
+/// Writes the settings file whenever the settings or keybindings change
+fn save_settings(
+    settings: Res<Settings>,
+    keybindings: Res<Keybindings>,
+    mut toasts: ResMut<Toasts>,
+) {
+    if !settings.is_changed() && !keybindings.is_changed() {
+        return;
+    }
+
+    if let Err(error) = write_settings(&settings, &keybindings) {
+        error!(%error, "couldn't save settings");
+        toasts.push(Severity::Error, t!("settings.save_failed", error = error));
+    }
+}
+
+/// Writes the settings and keybindings to the settings file
+pub fn write_settings(settings: &Settings, keybindings: &Keybindings) -> io::Result<()> {
+    let Some(path) = settings_path() else {
+        return Ok(());
+    };
+
+    let settings_file = SettingsFile {
+        settings: settings.clone(),
+        keybindings: keybindings.bindings(),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(&settings_file)?;
+    fs::write(&path, json)
+}
+
+/// Applies the UI scale, font size, theme, and language whenever the settings change
+fn apply_ui_settings(
+    settings: Res<Settings>,
+    mut egui_settings: ResMut<EguiSettings>,
+    mut contexts: EguiContexts,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    i18n::set_language(settings.language, settings.show_translation_keys);
+    accessibility::set_color_blind_palette(settings.color_blind_palette);
+
+    // bevy_egui recomputes `pixels_per_point` every frame from the window's scale factor
+    // multiplied by this, so this is where the UI scale has to go (not `set_pixels_per_point`)
+    egui_settings.scale_factor = settings.ui_scale.clamp(0.75, 2.0) as f64;
+
+    let ctx = contexts.ctx_mut();
+    let mut style = (*ctx.style()).clone();
+
+    let [r, g, b] = settings.accent_color;
+    let accent = egui::Color32::from_rgb(r, g, b);
+    style.visuals = settings.theme.visuals();
+    style.visuals.selection.bg_fill = accent;
+    style.visuals.hyperlink_color = accent;
+    style.visuals.warn_fg_color = Status::Warning.color();
+    style.visuals.error_fg_color = Status::Error.color();
+
+    style.animation_time = if settings.motion.is_reduced() {
+        0.0
+    } else {
+        egui::Style::default().animation_time
+    };
+
+    style.text_styles = egui::Style::default().text_styles;
+    if let Some(base_size) = settings.base_font_size {
+        for (text_style, font_id) in style.text_styles.iter_mut() {
+            font_id.size = match text_style {
+                egui::TextStyle::Heading => base_size * 1.4,
+                egui::TextStyle::Small => base_size * 0.7,
+                _ => base_size,
+            };
+        }
+    }
+    ctx.set_style(style);
+}
+
+/// Renders the settings controls.
+/// Returns `true` if anything changed, so callers can mark the resources as changed
+/// only when needed (rather than every frame the panel is open).
+pub fn settings_ui(
+    ui: &mut egui::Ui,
+    settings: &mut Settings,
+    keybindings: &mut Keybindings,
+) -> bool {
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.language"));
+        egui::ComboBox::from_id_source("language")
+            .selected_text(settings.language.native_name())
+            .show_ui(ui, |ui| {
+                for language in Language::ALL {
+                    changed |= ui
+                        .selectable_value(&mut settings.language, language, language.native_name())
+                        .changed();
+                }
+            });
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.ngon_threshold"));
+        changed |= ui
+            .add(egui::DragValue::new(&mut settings.ngon_warning_threshold).clamp_range(0..=10000))
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.scale_tolerance"));
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut settings.scale_tolerance)
+                    .speed(0.0001)
+                    .clamp_range(0.0..=1.0),
+            )
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.max_texture_size"));
+        changed |= ui
+            .add(egui::DragValue::new(&mut settings.max_texture_size).clamp_range(1..=16384))
+            .changed();
+    });
+
+    changed |= ui
+        .add(egui::Slider::new(&mut settings.ui_scale, 0.75..=2.0).text(t!("settings.ui_scale")))
+        .changed();
+
+    ui.horizontal(|ui| {
+        let mut override_font_size = settings.base_font_size.is_some();
+        if ui
+            .checkbox(&mut override_font_size, t!("settings.base_font_size"))
+            .changed()
+        {
+            settings.base_font_size = override_font_size.then_some(14.0);
+            changed = true;
+        }
+        if let Some(base_font_size) = settings.base_font_size.as_mut() {
+            changed |= ui
+                .add(egui::DragValue::new(base_font_size).clamp_range(8.0..=32.0))
+                .changed();
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.parse_concurrency"));
+        changed |= ui
+            .add(egui::DragValue::new(&mut settings.parse_concurrency).clamp_range(1..=64))
+            .changed();
+    });
+    changed |= ui
+        .checkbox(&mut settings.sort_by_name, t!("settings.sort_by_name"))
+        .on_hover_text(t!("settings.sort_by_name.hint"))
+        .changed();
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.render_concurrency"));
+        changed |= ui
+            .add(egui::DragValue::new(&mut settings.render_concurrency).clamp_range(1..=16))
+            .changed();
+    });
+    changed |= ui
+        .checkbox(
+            &mut settings.kill_renders_on_exit,
+            t!("settings.kill_renders_on_exit"),
+        )
+        .on_hover_text(t!("settings.kill_renders_on_exit.hint"))
+        .changed();
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.render_output_dir"));
+        match &settings.render_output_dir {
+            Some(dir) => ui.monospace(dir.display().to_string()),
+            None => ui.weak(t!("settings.render_output_dir.none")),
+        };
+        if ui.button(t!("settings.choose_folder")).clicked() {
+            if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                settings.render_output_dir = Some(dir);
+                changed = true;
+            }
+        }
+        if settings.render_output_dir.is_some() && ui.button(t!("settings.clear")).clicked() {
+            settings.render_output_dir = None;
+            changed = true;
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.asset_root"));
+        match &settings.asset_root {
+            Some(dir) => ui.monospace(dir.display().to_string()),
+            None => ui.weak(t!("settings.asset_root.none")),
+        };
+        if ui.button(t!("settings.choose_folder")).clicked() {
+            if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                settings.asset_root = Some(dir);
+                changed = true;
+            }
+        }
+        if settings.asset_root.is_some() && ui.button(t!("settings.clear")).clicked() {
+            settings.asset_root = None;
+            changed = true;
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.spawn_batch_size"));
+        changed |= ui
+            .add(egui::DragValue::new(&mut settings.spawn_batch_size).clamp_range(1..=256))
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.contact_sheet"));
+        let layout = &mut settings.contact_sheet;
+        changed |= ui
+            .add(egui::DragValue::new(&mut layout.columns).clamp_range(1..=64))
+            .on_hover_text(t!("settings.contact_sheet.columns"))
+            .changed();
+        ui.label("×");
+        changed |= ui
+            .add(egui::DragValue::new(&mut layout.rows).clamp_range(1..=64))
+            .on_hover_text(t!("settings.contact_sheet.rows"))
+            .changed();
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut layout.cell_size)
+                    .clamp_range(16..=1024)
+                    .suffix(" px"),
+            )
+            .on_hover_text(t!("settings.contact_sheet.cell_size"))
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.lod_pattern"));
+        changed |= ui
+            .add(egui::TextEdit::singleline(&mut settings.lod_pattern).code_editor())
+            .on_hover_text(t!("settings.lod_pattern.hint"))
+            .changed();
+        if ui.button(t!("settings.reset")).clicked() {
+            settings.lod_pattern = DEFAULT_LOD_PATTERN.to_string();
+            changed = true;
+        }
+    });
+    if let Err(error) = compile_lod_pattern(&settings.lod_pattern) {
+        status_label(ui, Status::Error, error);
+    }
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.mesh_loader"));
+        for loader in MeshLoader::ALL {
+            changed |= ui
+                .selectable_value(&mut settings.mesh_loader, loader, loader.label())
+                .changed();
+        }
+    });
+
+    changed |= ui
+        .checkbox(
+            &mut settings.log_console_monospace,
+            t!("settings.monospace_console"),
+        )
+        .changed();
+    if TRAY_SUPPORTED {
+        changed |= ui
+            .checkbox(
+                &mut settings.minimize_to_tray,
+                t!("settings.minimize_to_tray"),
+            )
+            .changed();
+    }
+
+    changed |= file_toolbar_settings_ui(ui, keybindings, &mut settings.file_toolbar);
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.open_with"));
+        egui::ComboBox::from_id_source("open_with_rule")
+            .selected_text(settings.open_with_rule.label())
+            .show_ui(ui, |ui| {
+                for rule in OpenWithRule::ALL {
+                    changed |= ui
+                        .selectable_value(&mut settings.open_with_rule, rule, rule.label())
+                        .changed();
+                }
+            });
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.theme"));
+        for (theme, label) in [
+            (Theme::Dark, t!("theme.dark")),
+            (Theme::Light, t!("theme.light")),
+            (Theme::System, t!("theme.system")),
+        ] {
+            changed |= ui
+                .selectable_value(&mut settings.theme, theme, label)
+                .changed();
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.motion"));
+        for motion in Motion::ALL {
+            changed |= ui
+                .selectable_value(&mut settings.motion, motion, motion.label())
+                .changed();
+        }
+        if settings.motion == Motion::System {
+            ui.weak(if settings.motion.is_reduced() {
+                t!("settings.motion.detected_reduced")
+            } else {
+                t!("settings.motion.detected_full")
+            });
+        }
+    });
+    ui.horizontal(|ui| {
+        changed |= ui
+            .checkbox(
+                &mut settings.color_blind_palette,
+                t!("settings.color_blind_palette"),
+            )
+            .changed();
+        for status in [Status::Ok, Status::Warning, Status::Error] {
+            ui.colored_label(status.color(), status.icon());
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.accent_color"));
+        changed |= ui
+            .color_edit_button_srgb(&mut settings.accent_color)
+            .changed();
+    });
+
+    if !settings.skip_confirmations.is_empty() {
+        ui.horizontal(|ui| {
+            let mut skipped: Vec<&str> = settings
+                .skip_confirmations
+                .iter()
+                .map(|kind| kind.label())
+                .collect();
+            skipped.sort_unstable();
+            ui.label(t!("settings.not_confirming", actions = skipped.join(", ")));
+            if ui.small_button(t!("settings.ask_again")).clicked() {
+                settings.skip_confirmations.clear();
+                changed = true;
+            }
+        });
+    }
+
+    changed |= ui
+        .checkbox(
+            &mut settings.object_tooltips,
+            t!("settings.object_tooltips"),
+        )
+        .changed();
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.auto_preview"))
+            .on_hover_text(t!("settings.auto_preview.hint"));
+        egui::ComboBox::from_id_source("auto_preview")
+            .selected_text(settings.auto_preview.label())
+            .show_ui(ui, |ui| {
+                for mode in AutoPreview::ALL {
+                    changed |= ui
+                        .selectable_value(&mut settings.auto_preview, mode, mode.label())
+                        .changed();
+                }
+            });
+    });
+    changed |= ui
+        .checkbox(&mut settings.show_overlays, t!("settings.show_overlays"))
+        .on_hover_text(t!("settings.show_overlays.hint"))
+        .changed();
+    changed |= ui
+        .checkbox(&mut settings.show_origins, t!("settings.show_origins"))
+        .changed();
+    changed |= ui
+        .checkbox(&mut settings.show_empties, t!("settings.show_empties"))
+        .changed();
+    changed |= ui
+        .checkbox(&mut settings.snap_to_ground, t!("settings.snap_to_ground"))
+        .changed();
+    changed |= ui
+        .checkbox(&mut settings.two_sided, t!("settings.two_sided"))
+        .changed();
+    changed |= ui
+        .checkbox(
+            &mut settings.highlight_backfaces,
+            t!("settings.highlight_backfaces"),
+        )
+        .changed();
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.peek_key"))
+            .on_hover_text(t!("settings.peek_key.hint"));
+        egui::ComboBox::from_id_source("peek_key")
+            .selected_text(settings.peek_key.label())
+            .show_ui(ui, |ui| {
+                for key in PeekKey::ALL {
+                    changed |= ui
+                        .selectable_value(&mut settings.peek_key, key, key.label())
+                        .changed();
+                }
+            });
+    });
+    ui.horizontal(|ui| {
+        changed |= ui
+            .checkbox(
+                &mut settings.preview_decimation,
+                t!("settings.decimate_over"),
+            )
+            .changed();
+        changed |= ui
+            .add_enabled(
+                settings.preview_decimation,
+                egui::DragValue::new(&mut settings.decimation_threshold)
+                    .speed(1000.0)
+                    .clamp_range(1000..=100_000_000),
+            )
+            .changed();
+        ui.label(t!("settings.decimate_unit"));
+    });
+    ui.horizontal(|ui| {
+        changed |= ui
+            .checkbox(&mut settings.bloom, t!("settings.bloom"))
+            .changed();
+        changed |= ui
+            .add_enabled(
+                settings.bloom,
+                egui::Slider::new(&mut settings.bloom_intensity, 0.0..=1.0),
+            )
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        changed |= ui
+            .checkbox(&mut settings.frame_guard, t!("settings.frame_guard"))
+            .on_hover_text(t!("settings.frame_guard.hint"))
+            .changed();
+        changed |= ui
+            .add_enabled(
+                settings.frame_guard,
+                egui::DragValue::new(&mut settings.frame_guard_ms)
+                    .speed(1.0)
+                    .clamp_range(16.0..=1000.0)
+                    .suffix(" ms"),
+            )
+            .changed();
+    });
+    ui.collapsing(t!("settings.camera"), |ui| {
+        changed |= camera_settings_ui(ui, &mut settings.camera);
+    });
+    ui.collapsing(t!("settings.lighting"), |ui| {
+        changed |= lighting_settings_ui(ui, &mut settings.lighting);
+    });
+    changed |= ui
+        .checkbox(&mut settings.debug_overlay, t!("settings.debug_overlay"))
+        .changed();
+    changed |= ui
+        .checkbox(&mut settings.metadata_cache, t!("settings.metadata_cache"))
+        .on_hover_text(t!("settings.metadata_cache.hint"))
+        .changed();
+    changed |= ui
+        .checkbox(
+            &mut settings.show_translation_keys,
+            t!("settings.show_translation_keys"),
+        )
+        .changed();
+    ui.horizontal(|ui| {
+        ui.label(t!("settings.log_level"));
+        egui::ComboBox::from_id_source("log_level")
+            .selected_text(settings.log_level.label())
+            .show_ui(ui, |ui| {
+                for level in LogLevel::ALL {
+                    changed |= ui
+                        .selectable_value(&mut settings.log_level, level, level.label())
+                        .changed();
+                }
+            });
+        ui.weak(t!("settings.on_restart"));
+    });
+    ui.horizontal(|ui| {
+        changed |= ui
+            .checkbox(&mut settings.hot_reload, t!("settings.hot_reload"))
+            .on_hover_text(t!("settings.hot_reload.hint"))
+            .changed();
+        ui.weak(t!("settings.on_restart"));
+    });
+
+    ui.collapsing(t!("settings.shortcuts"), |ui| {
+        changed |= keybindings_ui(ui, keybindings);
+    });
+
+    changed
+}
+
+fn camera_settings_ui(ui: &mut egui::Ui, camera: &mut CameraSettings) -> bool {
+    let mut changed = false;
+
+    changed |= ui
+        .add(
+            egui::Slider::new(&mut camera.fov_degrees, 10.0..=120.0)
+                .text(t!("camera.fov"))
+                .suffix("°"),
+        )
+        .changed();
+    changed |= ui
+        .checkbox(&mut camera.auto_clip, t!("camera.auto_clip"))
+        .on_hover_text(t!("camera.auto_clip_hint"))
+        .changed();
+    ui.add_enabled_ui(!camera.auto_clip, |ui| {
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut camera.near, 0.0001..=10.0)
+                    .logarithmic(true)
+                    .text(t!("camera.near")),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut camera.far, 1.0..=100_000.0)
+                    .logarithmic(true)
+                    .text(t!("camera.far")),
+            )
+            .changed();
+    });
+    // The far plane has to stay in front of the near one
+    camera.far = camera.far.max(camera.near * 2.0);
+
+    // Resets the projection, the orbit mode is a preference rather than a view setting
+    let default = CameraSettings {
+        orbit_mode: camera.orbit_mode,
+        ..Default::default()
+    };
+    if ui
+        .add_enabled(*camera != default, egui::Button::new(t!("camera.reset")))
+        .clicked()
+    {
+        *camera = default;
+        changed = true;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label(t!("camera.orbit"));
+        for mode in OrbitMode::ALL {
+            changed |= ui
+                .selectable_value(&mut camera.orbit_mode, mode, mode.label())
+                .changed();
+        }
+    });
+
+    changed
+}
+
+fn lighting_settings_ui(ui: &mut egui::Ui, lighting: &mut LightingSettings) -> bool {
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        changed |= ui
+            .checkbox(&mut lighting.shadows, t!("lighting.shadows"))
+            .changed();
+        ui.add_enabled_ui(lighting.shadows, |ui| {
+            egui::ComboBox::from_id_source("shadow_map_size")
+                .selected_text(t!(
+                    "lighting.shadow_map_size",
+                    size = lighting.shadow_map_size
+                ))
+                .show_ui(ui, |ui| {
+                    for size in SHADOW_MAP_SIZES {
+                        changed |= ui
+                            .selectable_value(
+                                &mut lighting.shadow_map_size,
+                                size,
+                                t!("lighting.shadow_map_size", size = size),
+                            )
+                            .changed();
+                    }
+                });
+        });
+    });
+    changed |= ui
+        .add(
+            egui::Slider::new(&mut lighting.azimuth_degrees, 0.0..=360.0)
+                .text(t!("lighting.azimuth"))
+                .suffix("°"),
+        )
+        .changed();
+    changed |= ui
+        .add(
+            // Straight overhead the light has no way to tell which way is up
+            egui::Slider::new(&mut lighting.elevation_degrees, 0.0..=89.0)
+                .text(t!("lighting.elevation"))
+                .suffix("°"),
+        )
+        .changed();
+    changed |= ui
+        .add(
+            egui::Slider::new(&mut lighting.distance, 1.0..=1000.0)
+                .logarithmic(true)
+                .text(t!("lighting.distance")),
+        )
+        .changed();
+    changed |= ui
+        .checkbox(&mut lighting.auto_distance, t!("lighting.auto_distance"))
+        .on_hover_text(t!("lighting.auto_distance_hint"))
+        .changed();
+
+    if ui
+        .add_enabled(
+            *lighting != LightingSettings::default(),
+            egui::Button::new(t!("lighting.reset")),
+        )
+        .clicked()
+    {
+        *lighting = LightingSettings::default();
+        changed = true;
+    }
+
+    changed
+}
@@ -0,0 +1,329 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+use serde::{Deserialize, Serialize};
+
+use crate::{i18n::t, settings::Settings, AppState, BlenderPreviewObject, OccupiedScreenSpace};
+
+/// How previews are drawn, picked from the dropdown in the viewport corner
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource)]
+pub enum ViewportShading {
+    #[default]
+    Material,
+    /// The mesh's color attribute, unlit
+    VertexColors,
+}
+
+impl ViewportShading {
+    fn label(&self) -> &'static str {
+        match self {
+            ViewportShading::Material => t!("shading.material"),
+            ViewportShading::VertexColors => t!("shading.vertex_colors"),
+        }
+    }
+}
+
+/// The key held to see the materials for a moment while a debug shading mode is on
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PeekKey {
+    Off,
+    #[default]
+    Alt,
+    Ctrl,
+    Space,
+}
+
+impl PeekKey {
+    pub const ALL: [PeekKey; 4] = [PeekKey::Off, PeekKey::Alt, PeekKey::Ctrl, PeekKey::Space];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PeekKey::Off => t!("peek_key.off"),
+            PeekKey::Alt => "Alt",
+            PeekKey::Ctrl => "Ctrl",
+            PeekKey::Space => "Space",
+        }
+    }
+
+    fn keys(&self) -> &'static [KeyCode] {
+        match self {
+            PeekKey::Off => &[],
+            PeekKey::Alt => &[KeyCode::LAlt, KeyCode::RAlt],
+            PeekKey::Ctrl => &[KeyCode::LControl, KeyCode::RControl],
+            PeekKey::Space => &[KeyCode::Space],
+        }
+    }
+}
+
+/// Whether the peek key is held, which shows the materials whatever the shading mode
+#[derive(Default, Resource)]
+pub struct ShadingPeek(pub bool);
+
+/// The material a preview had before a shading mode replaced it, kept until the shading
+/// goes back to [`ViewportShading::Material`]. Anything changing a preview's material while
+/// it's there changes this one instead, see [`set_lit_material`].
+#[derive(Component)]
+pub struct OriginalMaterial(pub Handle<StandardMaterial>);
+
+/// The material a preview shows in material shading, whichever mode is on
+pub fn lit_material<'a>(
+    material: &'a Handle<StandardMaterial>,
+    original: Option<&'a OriginalMaterial>,
+) -> &'a Handle<StandardMaterial> {
+    original.map_or(material, |original| &original.0)
+}
+
+/// Gives a preview a new material without undoing the shading mode. It shows once the
+/// shading goes back to materials, or right away while peeking.
+pub fn set_lit_material(
+    material: &mut Handle<StandardMaterial>,
+    original: Option<&mut OriginalMaterial>,
+    handle: Handle<StandardMaterial>,
+) {
+    match original {
+        Some(original) => original.0 = handle,
+        None => *material = handle,
+    }
+}
+
+/// Unlit white, so the vertex colors it gets multiplied with show as they are
+#[derive(Resource)]
+struct VertexColorMaterial(Handle<StandardMaterial>);
+
+pub struct ShadingPlugin;
+
+impl Plugin for ShadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewportShading>()
+            .init_resource::<ShadingPeek>()
+            .add_startup_system(setup_vertex_color_material)
+            .add_system(update_peek)
+            // After the frame's spawns are flushed, so new previews never show a frame of the
+            // wrong material
+            .add_system(apply_shading.in_base_set(CoreSet::PostUpdate))
+            .add_system(shading_ui);
+    }
+}
+
+fn setup_vertex_color_material(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(VertexColorMaterial(materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        unlit: true,
+        ..Default::default()
+    })));
+}
+
+/// Follows the peek key, except while typing or while another window has focus. The release of
+/// a key held while switching away, like Alt+Tab, never arrives.
+fn update_peek(
+    mut contexts: EguiContexts,
+    settings: Res<Settings>,
+    keys: Res<Input<KeyCode>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut peek: ResMut<ShadingPeek>,
+) {
+    let focused = windows.get_single().map_or(false, |window| window.focused);
+    let held = focused
+        && !contexts.ctx_mut().wants_keyboard_input()
+        && keys.any_pressed(settings.peek_key.keys().iter().copied());
+    if peek.0 != held {
+        peek.0 = held;
+    }
+}
+
+/// Swaps preview materials to match the shading mode.
+/// Runs every frame since spawned previews get their material once loaded.
+/// The originals stay on the previews while peeking, so releasing the key only swaps handles.
+fn apply_shading(
+    mut commands: Commands,
+    shading: Res<ViewportShading>,
+    peek: Res<ShadingPeek>,
+    vertex_color_material: Res<VertexColorMaterial>,
+    mut previews: Query<
+        (
+            Entity,
+            &mut Handle<StandardMaterial>,
+            Option<&OriginalMaterial>,
+        ),
+        With<BlenderPreviewObject>,
+    >,
+) {
+    let debug_material = match *shading {
+        ViewportShading::Material => None,
+        ViewportShading::VertexColors => Some(&vertex_color_material.0),
+    };
+    for (entity, mut material, original) in previews.iter_mut() {
+        let Some(debug_material) = debug_material else {
+            if let Some(original) = original {
+                *material = original.0.clone();
+                commands.entity(entity).remove::<OriginalMaterial>();
+            }
+            continue;
+        };
+        if original.is_none() {
+            commands
+                .entity(entity)
+                .insert(OriginalMaterial(material.clone()));
+        }
+        let shown = match original {
+            Some(original) if peek.0 => &original.0,
+            // Still on its own material
+            None if peek.0 => continue,
+            _ => debug_material,
+        };
+        // Only touch the handle when it's different, the renderer picks up every change
+        if *material != *shown {
+            *material = shown.clone();
+        }
+    }
+}
+
+/// The shading dropdown in the top left corner of the viewport
+fn shading_ui(
+    mut contexts: EguiContexts,
+    mut shading: ResMut<ViewportShading>,
+    settings: Res<Settings>,
+    app_state: Res<AppState>,
+    occupied_screen_space: Res<OccupiedScreenSpace>,
+    egui_settings: Res<EguiSettings>,
+    meshes: Res<Assets<Mesh>>,
+    previews: Query<(&BlenderPreviewObject, Option<&Handle<Mesh>>)>,
+) {
+    let has_vertex_colors = previews.iter().any(|(_, handle)| {
+        handle
+            .and_then(|handle| meshes.get(handle))
+            .is_some_and(|mesh| mesh.attribute(Mesh::ATTRIBUTE_COLOR).is_some())
+    });
+
+    // Point out when the file has colors the importer didn't bring in
+    let mut file_color_attributes: Vec<&str> = previews
+        .iter()
+        .filter_map(|(preview, _)| {
+            let object = app_state.file(preview.file)?.meshes.get(preview.mesh)?;
+            object.stats.as_ref()
+        })
+        .flat_map(|stats| stats.color_attributes.iter().map(String::as_str))
+        .collect();
+    file_color_attributes.sort_unstable();
+    file_color_attributes.dedup();
+    let disabled_reason = if file_color_attributes.is_empty() {
+        t!("shading.no_color_attribute").to_string()
+    } else {
+        t!(
+            "shading.color_attributes_not_imported",
+            names = file_color_attributes.join(", ")
+        )
+    };
+
+    let ui_scale = egui_settings.scale_factor as f32;
+    let offset = egui::vec2(
+        occupied_screen_space.left / ui_scale + 8.0,
+        occupied_screen_space.top / ui_scale + 8.0,
+    );
+
+    let mut selected = *shading;
+    egui::Area::new("viewport_shading")
+        .anchor(egui::Align2::LEFT_TOP, offset)
+        .show(contexts.ctx_mut(), |ui| {
+            let combo = egui::ComboBox::from_id_source("viewport_shading")
+                .selected_text(selected.label())
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(
+                        &mut selected,
+                        ViewportShading::Material,
+                        ViewportShading::Material.label(),
+                    );
+                    let vertex_colors = ui
+                        .add_enabled(
+                            has_vertex_colors,
+                            egui::SelectableLabel::new(
+                                selected == ViewportShading::VertexColors,
+                                ViewportShading::VertexColors.label(),
+                            ),
+                        )
+                        .on_disabled_hover_text(disabled_reason);
+                    if vertex_colors.clicked() {
+                        selected = ViewportShading::VertexColors;
+                    }
+                });
+            if selected != ViewportShading::Material && settings.peek_key != PeekKey::Off {
+                combo
+                    .response
+                    .on_hover_text(t!("shading.peek_hint", key = settings.peek_key.label()));
+            }
+        });
+
+    shading.set_if_neq(selected);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::HandleId;
+
+    use super::*;
+    use crate::FileId;
+
+    fn material() -> Handle<StandardMaterial> {
+        Handle::weak(HandleId::random::<StandardMaterial>())
+    }
+
+    fn shown(app: &mut App, entity: Entity) -> Handle<StandardMaterial> {
+        app.world
+            .get::<Handle<StandardMaterial>>(entity)
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn peeking_keeps_the_original_materials() {
+        let vertex_colors = material();
+        let lit = material();
+        let mut app = App::new();
+        app.insert_resource(ViewportShading::VertexColors)
+            .insert_resource(VertexColorMaterial(vertex_colors.clone()))
+            .init_resource::<ShadingPeek>()
+            .add_system(apply_shading);
+        let entity = app
+            .world
+            .spawn((
+                BlenderPreviewObject {
+                    file: FileId(0),
+                    mesh: 0,
+                    original_transform: Transform::default(),
+                },
+                lit.clone(),
+            ))
+            .id();
+
+        app.update();
+        assert_eq!(shown(&mut app, entity), vertex_colors);
+
+        // Held and released a few times over
+        for _ in 0..3 {
+            app.world.resource_mut::<ShadingPeek>().0 = true;
+            app.update();
+            assert_eq!(shown(&mut app, entity), lit);
+            app.world.resource_mut::<ShadingPeek>().0 = false;
+            app.update();
+            assert_eq!(shown(&mut app, entity), vertex_colors);
+        }
+
+        // A material given while shaded is the one that comes back
+        let replacement = material();
+        let mut previews = app
+            .world
+            .query::<(&mut Handle<StandardMaterial>, Option<&mut OriginalMaterial>)>();
+        let (mut current, mut original) = previews.get_mut(&mut app.world, entity).unwrap();
+        assert_eq!(original.as_deref().map(|original| &original.0), Some(&lit));
+        set_lit_material(&mut current, original.as_deref_mut(), replacement.clone());
+        app.update();
+        assert_eq!(shown(&mut app, entity), vertex_colors);
+
+        *app.world.resource_mut::<ViewportShading>() = ViewportShading::Material;
+        app.update();
+        assert_eq!(shown(&mut app, entity), replacement);
+        assert!(app.world.get::<OriginalMaterial>(entity).is_none());
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{asset_paths::game_path, AppState};
+
+/// Bumped when a field changes meaning. Fields a version doesn't know are ignored,
+/// so files from newer versions still open with what this one understands.
+const FORMAT_VERSION: u32 = 1;
+
+/// A workspace's file list in a file that can be handed to someone else
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SharedWorkspace {
+    pub version: u32,
+    pub files: Vec<SharedFile>,
+}
+
+impl Default for SharedWorkspace {
+    fn default() -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            files: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SharedFile {
+    /// Relative to the root picked on export when the file is under it, with forward slashes
+    pub path: String,
+    pub relative: bool,
+    /// The Blender the file is pinned to. Only kept on import when it exists there too.
+    #[serde(default)]
+    pub open_with: Option<PathBuf>,
+}
+
+impl SharedWorkspace {
+    pub fn from_files(app_state: &AppState, root: &Path) -> Self {
+        let files = app_state
+            .files
+            .iter()
+            .map(|file| {
+                let relative = game_path(&file.path, Some(root));
+                SharedFile {
+                    relative: relative.is_some(),
+                    path: relative.unwrap_or_else(|| file.path.clone()),
+                    open_with: file.blender_override.clone(),
+                }
+            })
+            .collect();
+        Self {
+            version: FORMAT_VERSION,
+            files,
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Written by a newer version, so some of what's in it may be left out
+    pub fn is_newer(&self) -> bool {
+        self.version > FORMAT_VERSION
+    }
+
+    pub fn has_relative_paths(&self) -> bool {
+        self.files.iter().any(|file| file.relative)
+    }
+}
+
+impl SharedFile {
+    /// Where the file is on this machine, with relative paths resolved against `root`
+    pub fn resolve(&self, root: Option<&Path>) -> PathBuf {
+        match root {
+            Some(root) if self.relative => self
+                .path
+                .split('/')
+                .fold(root.to_path_buf(), |path, part| path.join(part)),
+            _ => PathBuf::from(&self.path),
+        }
+    }
+}
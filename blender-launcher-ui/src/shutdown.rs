@@ -0,0 +1,90 @@
+use bevy::{app::AppExit, prelude::*, window::WindowCloseRequested};
+
+use crate::{
+    commands::Keybindings,
+    confirm::confirms_quit,
+    detach::DetachedPreview,
+    metadata_cache::{write_metadata_cache, MetadataCache},
+    parse_queue::ParseQueue,
+    render_queue::{write_render_queue, RenderProcesses, RenderQueue},
+    safe_mode::SafeMode,
+    session::write_session,
+    settings::{write_settings, Settings},
+    tray::closes_to_tray,
+    AppState,
+};
+
+/// Flushes everything we persist when the app is closing
+pub struct ShutdownPlugin;
+
+impl Plugin for ShutdownPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            flush_on_exit
+                .in_base_set(CoreSet::Last)
+                .run_if(on_event::<AppExit>().or_else(on_event::<WindowCloseRequested>())),
+        );
+    }
+}
+
+/// Writes the session, settings and render queue files and stops background parses and renders.
+/// The UI is going away, so failures are only logged (to stderr) rather than toasted.
+fn flush_on_exit(
+    app_state: Res<AppState>,
+    settings: Res<Settings>,
+    keybindings: Res<Keybindings>,
+    mut parse_queue: ResMut<ParseQueue>,
+    metadata_cache: Res<MetadataCache>,
+    render_queue: Res<RenderQueue>,
+    mut render_processes: ResMut<RenderProcesses>,
+    detached: Res<DetachedPreview>,
+    safe_mode: Res<SafeMode>,
+    mut exits: EventReader<AppExit>,
+    mut close_requests: EventReader<WindowCloseRequested>,
+    mut flushed: Local<bool>,
+) {
+    // Closing the detached preview just reattaches it, and the main window might only
+    // be hidden to the tray or wait on a confirmation. Either way the app keeps running.
+    let closing_app = close_requests.iter().any(|request| {
+        Some(request.window) != detached.window()
+            && !closes_to_tray(&settings)
+            && !confirms_quit(&settings, &render_processes)
+    });
+    let exiting = exits.iter().next().is_some();
+    if !exiting && !closing_app {
+        return;
+    }
+
+    // Closing the window and the exit that follows can land on different frames
+    if *flushed {
+        return;
+    }
+    *flushed = true;
+
+    parse_queue.cancel_all();
+    // The queue is saved with the renders still running either way. If they're left to
+    // finish the next start picks them up, killed ones it reads as interrupted.
+    if settings.kill_renders_on_exit {
+        render_processes.kill_all();
+    } else {
+        render_processes.detach_all();
+    }
+
+    // Safe mode leaves the saved session, settings and cache as they were
+    if !safe_mode.is_active() {
+        if let Err(error) = write_session(&app_state, &settings) {
+            error!(%error, "couldn't save session on exit");
+        }
+        if let Err(error) = write_settings(&settings, &keybindings) {
+            error!(%error, "couldn't save settings on exit");
+        }
+        if metadata_cache.is_dirty() {
+            if let Err(error) = write_metadata_cache(&metadata_cache) {
+                error!(%error, "couldn't save the metadata cache on exit");
+            }
+        }
+    }
+    if let Err(error) = write_render_queue(&render_queue) {
+        error!(%error, "couldn't save the render queue on exit");
+    }
+}
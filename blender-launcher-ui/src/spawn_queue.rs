@@ -0,0 +1,249 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_cache::AssetCache, debug::SystemRuns, file_status::FileOp, metadata::ObjectKind,
+    preview_info::PreviewObjectInfo, settings::Settings, AppState, BlenderPreviewObject, FileId,
+    CAMERA_TARGET,
+};
+
+/// Spawns every mesh, curve, and empty object in a file with its transform and parent
+pub struct SpawnSceneEvent(pub FileId);
+
+struct QueuedLoad {
+    entity: Entity,
+    path: String,
+    mesh_name: String,
+    mesh_datablock: String,
+    material: Handle<StandardMaterial>,
+}
+
+/// Preview entities still waiting on their mesh, closest to the camera target first
+#[derive(Default, Resource)]
+pub struct SpawnQueue {
+    queued: VecDeque<QueuedLoad>,
+    total: usize,
+}
+
+impl SpawnQueue {
+    pub fn is_busy(&self) -> bool {
+        !self.queued.is_empty()
+    }
+
+    /// (loaded, total) for the scene being spawned
+    pub fn progress(&self) -> (usize, usize) {
+        (self.total - self.queued.len(), self.total)
+    }
+}
+
+/// Drops the objects that haven't been given a mesh yet
+pub struct CancelSpawnQueue;
+
+pub struct SpawnQueuePlugin;
+
+impl Plugin for SpawnQueuePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnQueue>()
+            .add_event::<SpawnSceneEvent>()
+            .add_event::<CancelSpawnQueue>()
+            .add_system(cancel_spawn_queue.run_if(on_event::<CancelSpawnQueue>()))
+            .add_system(
+                drain_spawn_queue
+                    .after(cancel_spawn_queue)
+                    .run_if(|queue: Res<SpawnQueue>| queue.is_busy()),
+            )
+            // Newly spawned entities only show up in queries next frame,
+            // so the queue starts draining then
+            .add_system(
+                spawn_scene
+                    .after(drain_spawn_queue)
+                    .run_if(on_event::<SpawnSceneEvent>()),
+            );
+    }
+}
+
+/// Replaces the preview with every object in the file we can show.
+/// The entities are created straight away, their meshes are loaded a batch at a time.
+/// Objects keep their parents, so anything parented to an empty moves with it.
+fn spawn_scene(
+    mut commands: Commands,
+    mut spawn_scene_events: EventReader<SpawnSceneEvent>,
+    mut queue: ResMut<SpawnQueue>,
+    asset_server: Res<AssetServer>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    app_state: Res<AppState>,
+    blender_objects: Query<Entity, With<BlenderPreviewObject>>,
+    mut system_runs: ResMut<SystemRuns>,
+) {
+    system_runs.record("spawn_scene");
+
+    let Some(SpawnSceneEvent(file_id)) = spawn_scene_events.iter().last() else {
+        return;
+    };
+    let Some(file) = app_state
+        .file(*file_id)
+        .filter(|file| file.status.blocked(FileOp::Spawn).is_none())
+    else {
+        return;
+    };
+
+    for blender_entity in blender_objects.iter() {
+        commands.entity(blender_entity).despawn_recursive();
+    }
+
+    // Meshes and outlines have something to load, empties get a marker.
+    // The chosen scene and view layer decide which of them are part of the scene.
+    let skipped = file.skipped_by_scene();
+    let mut spawned: Vec<bool> = file
+        .meshes
+        .iter()
+        .map(|object| {
+            (object.stats.is_some() || object.outline.is_some() || object.kind == ObjectKind::Empty)
+                && !skipped.contains(object.name.as_str())
+        })
+        .collect();
+    let indices: HashMap<&str, usize> = file
+        .meshes
+        .iter()
+        .enumerate()
+        .map(|(index, object)| (object.name.as_str(), index))
+        .collect();
+    let parents: Vec<Option<usize>> = file
+        .meshes
+        .iter()
+        .map(|object| {
+            object
+                .parent
+                .as_deref()
+                .and_then(|parent| indices.get(parent).copied())
+        })
+        .collect();
+    // Parents we'd otherwise skip (armatures, lights) become plain nodes, so their
+    // children still land where they should. The walk is capped in case a broken file loops.
+    let shown: Vec<usize> = (0..spawned.len()).filter(|index| spawned[*index]).collect();
+    for index in shown {
+        let mut parent = parents[index];
+        for _ in 0..parents.len() {
+            let Some(ancestor) = parent else {
+                break;
+            };
+            spawned[ancestor] = true;
+            parent = parents[ancestor];
+        }
+    }
+
+    let mut entities: Vec<Option<Entity>> = vec![None; file.meshes.len()];
+    let mut loads = Vec::new();
+    for (mesh_index, object) in file.meshes.iter().enumerate() {
+        if !spawned[mesh_index] {
+            continue;
+        }
+
+        let transform = object.transform;
+        let entity = commands
+            .spawn((
+                BlenderPreviewObject {
+                    file: *file_id,
+                    mesh: mesh_index,
+                    original_transform: transform,
+                },
+                PreviewObjectInfo::new(*file_id, &object.name, object.mesh_datablock(), transform),
+                SpatialBundle::from_transform(transform),
+            ))
+            .id();
+        entities[mesh_index] = Some(entity);
+        if object.stats.is_none() && object.outline.is_none() {
+            continue;
+        }
+        let distance = object.location.distance_squared(CAMERA_TARGET);
+        loads.push((
+            distance,
+            QueuedLoad {
+                entity,
+                path: file.path.clone(),
+                mesh_name: object.name.clone(),
+                mesh_datablock: object.mesh_datablock().to_string(),
+                // Materials are few and shared, so they're picked up front
+                material: asset_cache.object_material(&asset_server, &mut materials, file, object),
+            },
+        ));
+    }
+
+    for (child, parent) in parents.iter().enumerate() {
+        if let (Some(child), Some(Some(parent))) =
+            (entities[child], parent.map(|parent| entities[parent]))
+        {
+            if child != parent {
+                commands.entity(parent).add_child(child);
+            }
+        }
+    }
+
+    loads.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    queue.queued = loads.into_iter().map(|(_, load)| load).collect();
+    queue.total = queue.queued.len();
+}
+
+fn cancel_spawn_queue(
+    mut commands: Commands,
+    mut cancel_events: EventReader<CancelSpawnQueue>,
+    mut queue: ResMut<SpawnQueue>,
+    blender_objects: Query<(), With<BlenderPreviewObject>>,
+) {
+    cancel_events.clear();
+
+    for load in queue.queued.drain(..) {
+        if blender_objects.contains(load.entity) {
+            commands.entity(load.entity).despawn_recursive();
+        }
+    }
+    queue.total = 0;
+}
+
+/// Gives the next batch of queued entities their mesh and material
+fn drain_spawn_queue(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut queue: ResMut<SpawnQueue>,
+    settings: Res<Settings>,
+    blender_objects: Query<(), With<BlenderPreviewObject>>,
+    mut system_runs: ResMut<SystemRuns>,
+) {
+    system_runs.record("drain_spawn_queue");
+
+    let mut started = 0;
+    while started < settings.spawn_batch_size.max(1) {
+        let Some(load) = queue.queued.pop_front() else {
+            break;
+        };
+        // The preview might have been cleared or replaced since
+        if !blender_objects.contains(load.entity) {
+            continue;
+        }
+
+        let handles = (
+            asset_cache.mesh_named(
+                &asset_server,
+                &load.path,
+                &load.mesh_name,
+                &load.mesh_datablock,
+            ),
+            load.material,
+        );
+        // Something else might despawn the preview this frame before our insert is applied
+        let entity = load.entity;
+        commands.add(move |world: &mut World| {
+            if let Some(mut entity) = world.get_entity_mut(entity) {
+                entity.insert(handles);
+            }
+        });
+        started += 1;
+    }
+
+    if queue.queued.is_empty() {
+        queue.total = 0;
+    }
+}
@@ -0,0 +1,18 @@
+//! What the launcher binary sets up around [`crate::BlenderLauncherPlugin`]: the command line,
+//! crash reports, and logging into the console panel. Host apps have their own, so the
+//! plugin leaves these out.
+//!
+//! [`LoggingPlugin`] replaces Bevy's `LogPlugin`, which has to be disabled for it.
+//! [`parse_blend_file`] and [`load_object_mesh`] are what `--bench` and the criterion
+//! benchmarks time, [`open_blend`] and [`peak_memory`] what the memory benchmark measures.
+
+pub use crate::{
+    bench::{cli_bench, peak_memory, run_bench, BenchArgs},
+    crash::{install_panic_hook, reports_dir},
+    logging::{cli_log_level, LogLevel, LoggingPlugin},
+    mesh_builder::load_object_mesh,
+    metadata::open_blend,
+    parse_queue::parse_blend_file,
+    safe_mode::cli_safe_mode,
+    settings::{saved_hot_reload, saved_log_level},
+};
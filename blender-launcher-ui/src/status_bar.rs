@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use bevy_egui::egui;
+
+use crate::{
+    contact_sheet::ContactSheetExports,
+    i18n::t,
+    parse_queue::ParseQueue,
+    render_queue::{RenderProcesses, RenderQueue},
+    spawn_queue::SpawnQueue,
+    AppState,
+};
+
+/// What the status bar reports on besides the files and the preview
+#[derive(SystemParam)]
+pub struct StatusSources<'w> {
+    render_queue: Res<'w, RenderQueue>,
+    render_processes: Res<'w, RenderProcesses>,
+    contact_sheets: Res<'w, ContactSheetExports>,
+    diagnostics: Res<'w, Diagnostics>,
+}
+
+/// What's on screen, for the status bar and the inspector
+#[derive(Clone, Copy, Default)]
+pub struct PreviewTotals {
+    pub objects: usize,
+    pub triangles: usize,
+}
+
+/// The selected file on the left, whatever is running in the background in the middle and
+/// the preview's cost on the right. Returns true when the running operations were clicked,
+/// which asks for the log console.
+pub fn status_bar_ui(
+    ui: &mut egui::Ui,
+    app_state: &AppState,
+    parse_queue: &ParseQueue,
+    spawn_queue: &SpawnQueue,
+    sources: &StatusSources,
+    totals: PreviewTotals,
+) -> bool {
+    match app_state.selected_file() {
+        Some(file) => {
+            let name = Path::new(&file.path)
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .unwrap_or_else(|| file.path.as_str().into());
+            ui.label(t!("status.file", name = name, objects = file.meshes.len()))
+                .on_hover_text(&file.path);
+        }
+        None => {
+            ui.weak(t!("status.no_file"));
+        }
+    }
+    ui.separator();
+
+    let operations = operations(app_state, parse_queue, spawn_queue, sources);
+    let clicked = if operations.is_empty() {
+        ui.weak(t!("status.idle"));
+        false
+    } else {
+        ui.link(operations.join(" · "))
+            .on_hover_text(t!("status.show_log"))
+            .clicked()
+    };
+
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+        if let Some(fps) = sources
+            .diagnostics
+            .get(FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed())
+        {
+            ui.monospace(t!("status.fps", fps = format!("{fps:.0}")));
+            ui.separator();
+        }
+        ui.label(t!(
+            "status.preview",
+            objects = totals.objects,
+            triangles = totals.triangles
+        ));
+    });
+    clicked
+}
+
+/// A short line for each thing running in the background, e.g. "Parsing 3/7"
+fn operations(
+    app_state: &AppState,
+    parse_queue: &ParseQueue,
+    spawn_queue: &SpawnQueue,
+    sources: &StatusSources,
+) -> Vec<String> {
+    let mut operations = Vec::new();
+    let (parsed, total) = parse_queue.progress();
+    if total > 0 {
+        operations.push(t!("status.parsing", done = parsed, total = total));
+    }
+    let (loaded, total) = spawn_queue.progress();
+    if total > 0 {
+        operations.push(t!("status.loading", done = loaded, total = total));
+    }
+    let (done, total) = app_state
+        .files
+        .iter()
+        .filter_map(|file| file.thumbnail_progress)
+        .fold((0, 0), |(done, total), (file_done, file_total)| {
+            (done + file_done, total + file_total)
+        });
+    if total > 0 {
+        operations.push(t!("status.thumbnails", done = done, total = total));
+    }
+    if let Some(job) = sources.render_queue.running() {
+        operations.push(match sources.render_processes.current_frame(job.id) {
+            Some(frame) => t!("status.rendering_frame", frame = frame),
+            None => t!("status.rendering").to_string(),
+        });
+    }
+    if sources.contact_sheets.is_exporting() {
+        operations.push(t!("status.exporting").to_string());
+    }
+    operations
+}
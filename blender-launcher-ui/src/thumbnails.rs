@@ -0,0 +1,518 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::LoadState,
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+};
+use bevy_egui::{egui, EguiContexts};
+use futures_lite::future;
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::{
+    asset_cache::AssetCache,
+    file_status::FileOp,
+    i18n::t,
+    metadata::ObjectKind,
+    metadata_cache::Fingerprint,
+    offscreen::{mesh_sphere, render_target, ImageReadback, Studio, StudioRig},
+    settings::config_dir,
+    toasts::{Severity, Toasts},
+    AppState, File, FileId,
+};
+
+/// Width and height of a thumbnail, in pixels
+pub const THUMBNAIL_SIZE: u32 = 128;
+/// Render layer of the thumbnail studio, next to the quick look's
+const LAYER: u8 = 2;
+/// Where the studio is set up, away from the preview and the quick look
+const ORIGIN: Vec3 = Vec3::new(10_000.0, -10_000.0, 0.0);
+/// Frames a framed studio renders before it's read back, so the moved camera
+/// has made it through the pipelined renderer
+const SETTLE_FRAMES: u32 = 3;
+/// How many cached thumbnails a job picks up per frame before rendering the next one
+const CACHED_PER_FRAME: usize = 2;
+/// Radius of the sphere materials are rendered on
+const BALL_RADIUS: f32 = 1.0;
+
+/// What a thumbnail shows: an object as it previews, or a material on a sphere
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Subject {
+    Object,
+    Material,
+}
+
+pub enum ThumbnailAction {
+    Generate(FileId),
+    Cancel(FileId),
+}
+
+/// One object or material being rendered
+struct Capture {
+    subject: Subject,
+    name: String,
+    studio: Studio,
+    image: Handle<Image>,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    /// Frames rendered since the camera was fitted, `None` until the mesh has loaded
+    settled: Option<u32>,
+    requested: bool,
+}
+
+/// Renders the thumbnails of one file's objects and materials, one at a time
+struct Job {
+    file: FileId,
+    dir: PathBuf,
+    queued: VecDeque<(Subject, String)>,
+    total: usize,
+    capture: Option<Capture>,
+}
+
+#[derive(Default, Resource)]
+struct Thumbnails {
+    jobs: Vec<Job>,
+    /// Cached thumbnails being read for a file
+    loads: Vec<(FileId, Task<Vec<((Subject, String), egui::ColorImage)>>)>,
+}
+
+/// The sphere every material thumbnail is rendered on
+#[derive(Resource)]
+struct MaterialBall(Handle<Mesh>);
+
+pub struct ThumbnailsPlugin;
+
+impl Plugin for ThumbnailsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ThumbnailAction>()
+            .init_resource::<Thumbnails>()
+            .add_startup_system(setup_material_ball)
+            .add_system(start_thumbnail_jobs.run_if(on_event::<ThumbnailAction>()))
+            .add_system(
+                advance_thumbnail_jobs
+                    .run_if(|thumbnails: Res<Thumbnails>| !thumbnails.jobs.is_empty()),
+            )
+            .add_system(load_cached_thumbnails.run_if(resource_changed::<AppState>()))
+            .add_system(
+                finish_cached_loads
+                    .run_if(|thumbnails: Res<Thumbnails>| !thumbnails.loads.is_empty()),
+            );
+    }
+}
+
+fn setup_material_ball(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(MaterialBall(meshes.add(Mesh::from(shape::UVSphere {
+        radius: BALL_RADIUS,
+        sectors: 48,
+        stacks: 24,
+    }))));
+}
+
+/// Thumbnails of a file's objects are kept in a folder named after its contents,
+/// so an edited file doesn't show stale ones
+pub fn cache_dir(path: &str) -> Option<PathBuf> {
+    let fingerprint = Fingerprint::of(Path::new(path)).ok()?;
+    Some(config_dir()?.join("thumbnails").join(fingerprint.key()))
+}
+
+/// Names can have any character, so the file is named after their hash.
+/// Materials go in a folder of their own, since an object and a material can share a name.
+pub fn thumbnail_path(dir: &Path, subject: Subject, name: &str) -> PathBuf {
+    let file_name = format!("{:016x}.png", xxh3_64(name.as_bytes()));
+    match subject {
+        Subject::Object => dir.join(file_name),
+        Subject::Material => dir.join("materials").join(file_name),
+    }
+}
+
+fn decode_thumbnail(path: &Path) -> Option<egui::ColorImage> {
+    let image = image::open(path).ok()?.to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Some(egui::ColorImage::from_rgba_unmultiplied(size, &image))
+}
+
+pub fn thumbnail_objects(file: &File) -> impl Iterator<Item = &str> {
+    file.meshes
+        .iter()
+        .filter(|object| object.kind == ObjectKind::Mesh)
+        .map(|object| object.name.as_str())
+}
+
+/// Everything in a file that gets a thumbnail, objects first
+fn thumbnail_subjects(file: &File) -> impl Iterator<Item = (Subject, String)> + '_ {
+    thumbnail_objects(file)
+        .map(|name| (Subject::Object, name.to_string()))
+        .chain(
+            file.materials
+                .iter()
+                .map(|name| (Subject::Material, name.clone())),
+        )
+}
+
+fn start_thumbnail_jobs(
+    mut commands: Commands,
+    mut actions: EventReader<ThumbnailAction>,
+    mut app_state: ResMut<AppState>,
+    mut thumbnails: ResMut<Thumbnails>,
+    mut images: ResMut<Assets<Image>>,
+    mut toasts: ResMut<Toasts>,
+) {
+    for action in actions.iter() {
+        match action {
+            ThumbnailAction::Generate(file_id) => {
+                let Some(file) = app_state.file_mut(*file_id) else {
+                    continue;
+                };
+                if thumbnails.jobs.iter().any(|job| job.file == *file_id) {
+                    continue;
+                }
+                let Some(dir) = cache_dir(&file.path) else {
+                    toasts.push(Severity::Error, t!("thumbnails.no_cache_dir"));
+                    continue;
+                };
+                // The button is disabled then, but the event could have been sent before
+                let Ok(status) = file.status.begin(FileOp::Render) else {
+                    continue;
+                };
+                file.status = status;
+                let queued: VecDeque<(Subject, String)> = thumbnail_subjects(file).collect();
+                file.thumbnail_progress = Some((0, queued.len()));
+                thumbnails.jobs.push(Job {
+                    file: *file_id,
+                    dir,
+                    total: queued.len(),
+                    queued,
+                    capture: None,
+                });
+            }
+            ThumbnailAction::Cancel(file_id) => {
+                thumbnails.jobs.retain_mut(|job| {
+                    if job.file != *file_id {
+                        return true;
+                    }
+                    if let Some(capture) = job.capture.take() {
+                        capture.studio.despawn(&mut commands);
+                        images.remove(&capture.image);
+                    }
+                    false
+                });
+                if let Some(file) = app_state.file_mut(*file_id) {
+                    file.thumbnail_progress = None;
+                    file.status = file.status.cancel();
+                }
+            }
+        }
+    }
+}
+
+/// Moves the first job along by one step: picking up cached thumbnails, or rendering,
+/// fitting the camera, reading back and saving the next object or material
+#[allow(clippy::too_many_arguments)]
+fn advance_thumbnail_jobs(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    asset_server: Res<AssetServer>,
+    mut asset_cache: ResMut<AssetCache>,
+    mut app_state: ResMut<AppState>,
+    mut thumbnails: ResMut<Thumbnails>,
+    mut readback: ResMut<ImageReadback>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    meshes: Res<Assets<Mesh>>,
+    ball: Res<MaterialBall>,
+    mut rig: StudioRig,
+) {
+    let Some(job) = thumbnails.jobs.first_mut() else {
+        return;
+    };
+    // The file was removed, or switched out with its workspace
+    let Some(file) = app_state.file_mut(job.file) else {
+        if let Some(capture) = job.capture.take() {
+            capture.studio.despawn(&mut commands);
+            images.remove(&capture.image);
+        }
+        thumbnails.jobs.remove(0);
+        return;
+    };
+    match job.capture.as_mut() {
+        None => {
+            let mut picked_up = 0;
+            while let Some((subject, name)) = job.queued.pop_front() {
+                let path = thumbnail_path(&job.dir, subject, &name);
+                if let Some(image) = path.is_file().then(|| decode_thumbnail(&path)).flatten() {
+                    let texture =
+                        contexts
+                            .ctx_mut()
+                            .load_texture(&name, image, egui::TextureOptions::LINEAR);
+                    file.thumbnails
+                        .get_or_insert_with(HashMap::new)
+                        .insert((subject, name), texture);
+                    picked_up += 1;
+                    if picked_up == CACHED_PER_FRAME {
+                        break;
+                    }
+                    continue;
+                }
+
+                let image = images.add(render_target(THUMBNAIL_SIZE));
+                let (mesh, material) = match subject {
+                    Subject::Object => {
+                        match file.meshes.iter().find(|object| object.name == name) {
+                            Some(object) => (
+                                asset_cache.mesh(&asset_server, &file.path, object),
+                                asset_cache.object_material(
+                                    &asset_server,
+                                    &mut materials,
+                                    file,
+                                    object,
+                                ),
+                            ),
+                            None => (Handle::default(), Handle::default()),
+                        }
+                    }
+                    Subject::Material => (
+                        ball.0.clone(),
+                        asset_cache.material(&asset_server, &file.path, &name),
+                    ),
+                };
+                let studio = Studio::spawn(
+                    &mut commands,
+                    ORIGIN,
+                    LAYER,
+                    image.clone(),
+                    mesh.clone(),
+                    material.clone(),
+                );
+                job.capture = Some(Capture {
+                    subject,
+                    name,
+                    studio,
+                    image,
+                    mesh,
+                    material,
+                    settled: None,
+                    requested: false,
+                });
+                break;
+            }
+        }
+        Some(capture) => match capture.settled {
+            None => {
+                let failed = |state: LoadState| state == LoadState::Failed;
+                if failed(asset_server.get_load_state(&capture.mesh))
+                    || failed(asset_server.get_load_state(&capture.material))
+                {
+                    warn!(
+                        subject = ?capture.subject,
+                        name = %capture.name,
+                        "couldn't load thumbnail subject"
+                    );
+                    let capture = job.capture.take().unwrap();
+                    capture.studio.despawn(&mut commands);
+                    images.remove(&capture.image);
+                } else if let (Some(bounds), true) = (
+                    meshes
+                        .get(&capture.mesh)
+                        .and_then(|mesh| match capture.subject {
+                            // A little margin, the sphere's bounding box would leave a lot more
+                            Subject::Material => Some((Vec3::ZERO, BALL_RADIUS * 1.1)),
+                            Subject::Object => mesh_sphere(mesh),
+                        }),
+                    material_ready(&materials, &images, &capture.material),
+                ) {
+                    rig.frame(&capture.studio, ORIGIN, bounds, (0.4, 0.3));
+                    capture.settled = Some(0);
+                }
+            }
+            Some(frames) if frames < SETTLE_FRAMES => capture.settled = Some(frames + 1),
+            Some(_) if !capture.requested => {
+                readback.request(&capture.image);
+                capture.requested = true;
+            }
+            Some(_) => {
+                if let Some(pixels) = readback.take(&capture.image) {
+                    let capture = job.capture.take().unwrap();
+                    let path = thumbnail_path(&job.dir, capture.subject, &capture.name);
+                    save_thumbnail(&path, &pixels);
+                    let image = egui::ColorImage::from_rgba_unmultiplied(
+                        [THUMBNAIL_SIZE as usize; 2],
+                        &pixels,
+                    );
+                    let texture = contexts.ctx_mut().load_texture(
+                        &capture.name,
+                        image,
+                        egui::TextureOptions::LINEAR,
+                    );
+                    file.thumbnails
+                        .get_or_insert_with(HashMap::new)
+                        .insert((capture.subject, capture.name), texture);
+                    capture.studio.despawn(&mut commands);
+                    images.remove(&capture.image);
+                }
+            }
+        },
+    }
+
+    let remaining = job.queued.len() + usize::from(job.capture.is_some());
+    if remaining == 0 {
+        file.thumbnail_progress = None;
+        file.status = file.status.finish(true);
+        thumbnails.jobs.remove(0);
+    } else {
+        file.thumbnail_progress = Some((job.total - remaining, job.total));
+    }
+}
+
+/// Whether the material and its base color texture have loaded, so the thumbnail
+/// doesn't catch the object before its textures show up
+fn material_ready(
+    materials: &Assets<StandardMaterial>,
+    images: &Assets<Image>,
+    material: &Handle<StandardMaterial>,
+) -> bool {
+    let Some(material) = materials.get(material) else {
+        return false;
+    };
+    material
+        .base_color_texture
+        .as_ref()
+        .map_or(true, |texture| images.contains(texture))
+}
+
+fn save_thumbnail(path: &Path, pixels: &[u8]) {
+    let dir = path.parent().unwrap_or(path);
+    let saved = fs::create_dir_all(dir)
+        .map_err(image::ImageError::IoError)
+        .and_then(|()| {
+            image::save_buffer(
+                path,
+                pixels,
+                THUMBNAIL_SIZE,
+                THUMBNAIL_SIZE,
+                image::ColorType::Rgba8,
+            )
+        });
+    if let Err(error) = saved {
+        // The thumbnail still shows for this session
+        warn!(path = %path.display(), %error, "couldn't cache thumbnail");
+    }
+}
+
+/// Reads the cached thumbnails of the selected file, the first time it's selected
+/// after being parsed
+fn load_cached_thumbnails(app_state: Res<AppState>, mut thumbnails: ResMut<Thumbnails>) {
+    let Some(file) = app_state.selected_file() else {
+        return;
+    };
+    if !file.parsed
+        || file.thumbnails.is_some()
+        || thumbnails
+            .loads
+            .iter()
+            .any(|(loading, _)| *loading == file.id)
+    {
+        return;
+    }
+
+    let path = file.path.clone();
+    let subjects: HashSet<(Subject, String)> = thumbnail_subjects(file).collect();
+    let task = IoTaskPool::get().spawn(async move {
+        let Some(dir) = cache_dir(&path) else {
+            return Vec::new();
+        };
+        subjects
+            .into_iter()
+            .filter_map(|(subject, name)| {
+                let image = decode_thumbnail(&thumbnail_path(&dir, subject, &name))?;
+                Some(((subject, name), image))
+            })
+            .collect()
+    });
+    thumbnails.loads.push((file.id, task));
+}
+
+fn finish_cached_loads(
+    mut contexts: EguiContexts,
+    mut app_state: ResMut<AppState>,
+    mut thumbnails: ResMut<Thumbnails>,
+) {
+    let mut loaded = Vec::new();
+    thumbnails.loads.retain_mut(
+        |(file, task)| match future::block_on(future::poll_once(task)) {
+            Some(images) => {
+                loaded.push((*file, images));
+                false
+            }
+            None => true,
+        },
+    );
+
+    for (file_id, images) in loaded {
+        let Some(file) = app_state.file_mut(file_id) else {
+            continue;
+        };
+        let textures = file.thumbnails.get_or_insert_with(HashMap::new);
+        for (key, image) in images {
+            let texture =
+                contexts
+                    .ctx_mut()
+                    .load_texture(&key.1, image, egui::TextureOptions::LINEAR);
+            textures.insert(key, texture);
+        }
+    }
+}
+
+/// The generate button for a file, or the progress of the job with a cancel button
+pub fn thumbnails_ui(ui: &mut egui::Ui, file: &File, events: &mut EventWriter<ThumbnailAction>) {
+    if thumbnail_subjects(file).next().is_none() {
+        return;
+    }
+    ui.horizontal(|ui| match file.thumbnail_progress {
+        Some((done, total)) => {
+            ui.add(
+                egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                    .desired_width(150.0)
+                    .text(t!("thumbnails.progress", done = done, total = total)),
+            );
+            if ui.button(t!("thumbnails.cancel")).clicked() {
+                events.send(ThumbnailAction::Cancel(file.id));
+            }
+        }
+        None => {
+            let blocked = file.status.blocked(FileOp::Render);
+            if ui
+                .add_enabled(
+                    blocked.is_none(),
+                    egui::Button::new(t!("thumbnails.generate")),
+                )
+                .on_disabled_hover_text(blocked.unwrap_or_default())
+                .clicked()
+            {
+                events.send(ThumbnailAction::Generate(file.id));
+            }
+        }
+    });
+}
+
+pub fn thumbnail<'a>(
+    file: &'a File,
+    subject: Subject,
+    name: &str,
+) -> Option<&'a egui::TextureHandle> {
+    file.thumbnails.as_ref()?.get(&(subject, name.to_string()))
+}
+
+/// A thumbnail in front of an object's row, or nothing if it has none
+pub fn row_thumbnail(ui: &mut egui::Ui, file: &File, name: &str) {
+    let Some(texture) = thumbnail(file, Subject::Object, name) else {
+        return;
+    };
+    let size = ui.spacing().interact_size.y;
+    ui.image(texture, egui::vec2(size, size)).on_hover_ui(|ui| {
+        ui.image(texture, egui::Vec2::splat(THUMBNAIL_SIZE as f32));
+    });
+}
@@ -0,0 +1,206 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiSettings};
+
+use crate::{
+    accessibility::Status,
+    commands::{Action, ActionEvent},
+    i18n::t,
+    settings::Settings,
+    OccupiedScreenSpace,
+};
+
+/// How long a toast stays up, in seconds
+const TOAST_DURATION: f32 = 4.0;
+/// How long the fade out at the end of a toast's life takes, in seconds
+const TOAST_FADE: f32 = 0.75;
+/// We only stack this many toasts, older ones are dropped
+const MAX_TOASTS: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn icon(&self) -> &'static str {
+        match self {
+            Severity::Info => "ℹ",
+            Severity::Success => "✔",
+            Severity::Warning => "⚠",
+            Severity::Error => "✖",
+        }
+    }
+
+    fn color(&self) -> egui::Color32 {
+        match self {
+            Severity::Info => egui::Color32::LIGHT_BLUE,
+            Severity::Success => Status::Ok.color(),
+            Severity::Warning => Status::Warning.color(),
+            Severity::Error => Status::Error.color(),
+        }
+    }
+}
+
+/// An optional button on a toast
+#[derive(Clone, Debug)]
+pub enum ToastAction {
+    /// Expands the log console
+    ShowLog,
+    /// Opens the file manager with the file selected
+    RevealFile(PathBuf),
+    /// Despawns everything in the preview
+    ClearPreview,
+}
+
+impl ToastAction {
+    fn label(&self) -> &'static str {
+        match self {
+            ToastAction::ShowLog => t!("toasts.show_in_log"),
+            ToastAction::RevealFile(_) => t!("toasts.reveal_file"),
+            ToastAction::ClearPreview => t!("actions.clear_preview"),
+        }
+    }
+}
+
+struct Toast {
+    severity: Severity,
+    message: String,
+    action: Option<ToastAction>,
+    /// When the toast was shown, `None` until the first frame it's rendered
+    shown_at: Option<f32>,
+}
+
+/// The one place background work reports back to the user
+#[derive(Default, Resource)]
+pub struct Toasts {
+    queue: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn push(&mut self, severity: Severity, message: impl Into<String>) {
+        self.push_toast(severity, message.into(), None);
+    }
+
+    pub fn push_with_action(
+        &mut self,
+        severity: Severity,
+        message: impl Into<String>,
+        action: ToastAction,
+    ) {
+        self.push_toast(severity, message.into(), Some(action));
+    }
+
+    fn push_toast(&mut self, severity: Severity, message: String, action: Option<ToastAction>) {
+        self.queue.push(Toast {
+            severity,
+            message,
+            action,
+            shown_at: None,
+        });
+        if self.queue.len() > MAX_TOASTS {
+            self.queue.remove(0);
+        }
+    }
+}
+
+pub struct ToastsPlugin;
+
+impl Plugin for ToastsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Toasts>().add_system(toasts_ui);
+    }
+}
+
+/// Renders the toasts stacked in the bottom right corner of the viewport
+fn toasts_ui(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    mut toasts: ResMut<Toasts>,
+    mut settings: ResMut<Settings>,
+    occupied_screen_space: Res<OccupiedScreenSpace>,
+    egui_settings: Res<EguiSettings>,
+    mut actions: EventWriter<ActionEvent>,
+) {
+    if toasts.queue.is_empty() {
+        return;
+    }
+
+    let now = time.elapsed_seconds();
+    let reduced_motion = settings.motion.is_reduced();
+    toasts.queue.retain(|toast| {
+        toast
+            .shown_at
+            .is_none_or(|shown_at| now - shown_at < TOAST_DURATION)
+    });
+
+    // Keep the toasts inside the viewport rather than on top of the panels
+    let ui_scale = egui_settings.scale_factor as f32;
+    let offset = egui::vec2(
+        -(occupied_screen_space.right / ui_scale + 12.0),
+        -(occupied_screen_space.bottom / ui_scale + 12.0),
+    );
+
+    let mut clicked_action = None;
+    egui::Area::new("toasts")
+        .anchor(egui::Align2::RIGHT_BOTTOM, offset)
+        .interactable(true)
+        .show(contexts.ctx_mut(), |ui| {
+            for toast in toasts.queue.iter_mut() {
+                let shown_at = *toast.shown_at.get_or_insert(now);
+                let remaining = TOAST_DURATION - (now - shown_at);
+                // With reduced motion toasts disappear at the end instead of fading
+                let alpha = if reduced_motion {
+                    1.0
+                } else {
+                    (remaining / TOAST_FADE).clamp(0.0, 1.0)
+                };
+
+                let fill = ui.visuals().window_fill.linear_multiply(alpha);
+                egui::Frame::popup(ui.style())
+                    .fill(fill)
+                    .stroke(egui::Stroke::new(
+                        1.0,
+                        toast.severity.color().linear_multiply(alpha),
+                    ))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(
+                                toast.severity.color().linear_multiply(alpha),
+                                toast.severity.icon(),
+                            );
+                            let text_color = ui.visuals().text_color().linear_multiply(alpha);
+                            ui.colored_label(text_color, toast.message.as_str());
+
+                            if let Some(action) = &toast.action {
+                                if ui.small_button(action.label()).clicked() {
+                                    clicked_action = Some(action.clone());
+                                }
+                            }
+                        });
+                    });
+            }
+        });
+
+    match clicked_action {
+        Some(ToastAction::ShowLog) => {
+            settings.panel_layout.bottom_collapsed = false;
+        }
+        Some(ToastAction::RevealFile(path)) => {
+            if let Err(error) = opener::reveal(&path) {
+                toasts.push(
+                    Severity::Error,
+                    t!("toasts.reveal_failed", path = path.display(), error = error),
+                );
+            }
+        }
+        Some(ToastAction::ClearPreview) => {
+            actions.send(ActionEvent(Action::ClearPreview));
+        }
+        None => {}
+    }
+}
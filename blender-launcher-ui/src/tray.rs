@@ -0,0 +1,180 @@
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowCloseRequested},
+};
+
+use crate::{
+    confirm::{confirms_quit, Confirmation, DestructiveAction},
+    render_queue::RenderProcesses,
+    settings::Settings,
+};
+
+/// Whether this build can put an icon in the system tray.
+/// Linux trays need a GTK main loop of their own, so for now it's Windows and macOS only.
+pub const TRAY_SUPPORTED: bool = cfg!(all(
+    feature = "tray",
+    any(target_os = "windows", target_os = "macos")
+));
+
+/// Does closing the main window hide it to the tray instead of quitting?
+pub fn closes_to_tray(settings: &Settings) -> bool {
+    TRAY_SUPPORTED && settings.minimize_to_tray
+}
+
+pub struct TrayPlugin;
+
+impl Plugin for TrayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(handle_close_requests.run_if(on_event::<WindowCloseRequested>()));
+
+        #[cfg(all(feature = "tray", any(target_os = "windows", target_os = "macos")))]
+        app.add_startup_system(platform::setup_tray)
+            .add_system(platform::update_tray_menu.run_if(resource_changed::<crate::AppState>()))
+            .add_system(platform::handle_tray_menu);
+    }
+}
+
+/// Closing a window despawns it (and the app exits once the primary one is gone),
+/// unless the main window should be hidden to the tray or closing it needs confirming
+fn handle_close_requests(
+    mut commands: Commands,
+    mut close_requests: EventReader<WindowCloseRequested>,
+    settings: Res<Settings>,
+    render_processes: Res<RenderProcesses>,
+    mut confirmation: ResMut<Confirmation>,
+    mut primary_windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for request in close_requests.iter() {
+        match primary_windows.get_mut(request.window) {
+            Ok(mut window) if closes_to_tray(&settings) => window.visible = false,
+            Ok(_) if confirms_quit(&settings, &render_processes) => {
+                confirmation.request(DestructiveAction::Quit)
+            }
+            _ => commands.entity(request.window).despawn(),
+        }
+    }
+}
+
+#[cfg(all(feature = "tray", any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use bevy::{app::AppExit, prelude::*, window::PrimaryWindow};
+    use tray_icon::{
+        icon::Icon,
+        menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+        TrayIcon, TrayIconBuilder,
+    };
+
+    use crate::{i18n::t, settings::Settings, AppState, FileId};
+
+    /// How many files the tray menu lists
+    const RECENT_FILE_COUNT: usize = 5;
+    const ICON_SIZE: u32 = 32;
+
+    /// The tray icon and what each of its menu entries does.
+    /// Tray handles aren't `Send`, so this lives on the main thread.
+    pub struct Tray {
+        icon: TrayIcon,
+        entries: Vec<(MenuItem, TrayEntry)>,
+    }
+
+    #[derive(Clone, Copy)]
+    enum TrayEntry {
+        OpenLauncher,
+        OpenFile(FileId),
+        Quit,
+    }
+
+    pub fn setup_tray(world: &mut World) {
+        let accent = world.resource::<Settings>().accent_color;
+        // A plain square in the accent color until we ship a proper icon
+        let rgba = [accent[0], accent[1], accent[2], 255].repeat((ICON_SIZE * ICON_SIZE) as usize);
+        let icon = match Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE) {
+            Ok(icon) => icon,
+            Err(error) => {
+                warn!(%error, "couldn't create the tray icon");
+                return;
+            }
+        };
+
+        let (menu, entries) = build_menu(world.resource::<AppState>());
+        let result = TrayIconBuilder::new()
+            .with_tooltip(t!("app.name"))
+            .with_icon(icon)
+            .with_menu(Box::new(menu))
+            .build();
+        match result {
+            Ok(icon) => world.insert_non_send_resource(Tray { icon, entries }),
+            Err(error) => warn!(%error, "couldn't add the tray icon"),
+        }
+    }
+
+    /// Recent files first, newest at the top, then the launcher entries
+    fn build_menu(app_state: &AppState) -> (Menu, Vec<(MenuItem, TrayEntry)>) {
+        let menu = Menu::new();
+        let mut entries = Vec::new();
+
+        for file in app_state.files.iter().rev().take(RECENT_FILE_COUNT) {
+            entries.push((
+                MenuItem::new(&file.path, true, None),
+                TrayEntry::OpenFile(file.id),
+            ));
+        }
+        let recent_count = entries.len();
+        entries.push((
+            MenuItem::new(t!("tray.open_launcher"), true, None),
+            TrayEntry::OpenLauncher,
+        ));
+        entries.push((MenuItem::new(t!("tray.quit"), true, None), TrayEntry::Quit));
+
+        for (index, (item, _)) in entries.iter().enumerate() {
+            if index == recent_count && recent_count > 0 {
+                let _ = menu.append(&PredefinedMenuItem::separator());
+            }
+            let _ = menu.append(item);
+        }
+
+        (menu, entries)
+    }
+
+    /// Keeps the recent files in the menu up to date
+    pub fn update_tray_menu(tray: Option<NonSendMut<Tray>>, app_state: Res<AppState>) {
+        let Some(mut tray) = tray else {
+            return;
+        };
+
+        let (menu, entries) = build_menu(&app_state);
+        tray.icon.set_menu(Some(Box::new(menu)));
+        tray.entries = entries;
+    }
+
+    pub fn handle_tray_menu(
+        tray: Option<NonSend<Tray>>,
+        mut app_state: ResMut<AppState>,
+        mut primary_windows: Query<&mut Window, With<PrimaryWindow>>,
+        mut exit_events: EventWriter<AppExit>,
+    ) {
+        let Some(tray) = tray else {
+            return;
+        };
+
+        while let Ok(event) = MenuEvent::receiver().try_recv() {
+            let Some((_, entry)) = tray.entries.iter().find(|(item, _)| item.id() == event.id)
+            else {
+                continue;
+            };
+
+            match *entry {
+                TrayEntry::Quit => exit_events.send(AppExit),
+                TrayEntry::OpenLauncher | TrayEntry::OpenFile(_) => {
+                    if let TrayEntry::OpenFile(file_id) = *entry {
+                        app_state.select_file(Some(file_id));
+                    }
+                    for mut window in primary_windows.iter_mut() {
+                        window.visible = true;
+                        window.focused = true;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use blend::Blend;
+use serde::{Deserialize, Serialize};
+
+use crate::{i18n::t, BlendObject};
+
+const METERS_PER_INCH: f32 = 0.0254;
+
+/// The scene's unit system, `USER_UNIT_*` in Blender
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSystem {
+    /// Plain Blender units
+    None,
+    #[default]
+    Metric,
+    Imperial,
+}
+
+/// The scene's unit settings, which say what one Blender unit is
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneUnits {
+    pub system: UnitSystem,
+    /// Meters per Blender unit
+    pub scale_length: f32,
+}
+
+impl Default for SceneUnits {
+    fn default() -> Self {
+        Self {
+            system: UnitSystem::Metric,
+            scale_length: 1.0,
+        }
+    }
+}
+
+impl SceneUnits {
+    /// Reads the unit settings of the file's first scene
+    pub fn from_blend(blend: &Blend) -> Self {
+        let Some(scene) = blend.instances_with_code(*b"SC").next() else {
+            return Self::default();
+        };
+        let unit = scene.get("unit");
+        let system = match unit.get_u8("system") {
+            0 => UnitSystem::None,
+            2 => UnitSystem::Imperial,
+            _ => UnitSystem::Metric,
+        };
+        let scale_length = unit.get_f32("scale_length");
+        Self {
+            system,
+            // Files from before unit settings existed store 0
+            scale_length: if scale_length > 0.0 {
+                scale_length
+            } else {
+                1.0
+            },
+        }
+    }
+
+    /// Converts Blender units to the scene's, meters unless the unit system is `None`
+    pub fn to_scene(&self, blender_units: f32) -> f32 {
+        match self.system {
+            UnitSystem::None => blender_units,
+            UnitSystem::Metric | UnitSystem::Imperial => blender_units * self.scale_length,
+        }
+    }
+
+    /// Formats a length in Blender units the way Blender would show it
+    pub fn format_length(&self, blender_units: f32) -> String {
+        let length = self.to_scene(blender_units);
+        match self.system {
+            UnitSystem::None => trim(length, ""),
+            UnitSystem::Metric => {
+                let meters = length.abs();
+                if meters >= 1000.0 {
+                    trim(length / 1000.0, " km")
+                } else if meters >= 1.0 {
+                    trim(length, " m")
+                } else if meters >= 0.01 {
+                    trim(length * 100.0, " cm")
+                } else {
+                    trim(length * 1000.0, " mm")
+                }
+            }
+            UnitSystem::Imperial => {
+                let inches = length / METERS_PER_INCH;
+                let feet = (inches.abs() / 12.0).floor();
+                if feet < 1.0 {
+                    trim(inches, "\"")
+                } else {
+                    let rest = inches.abs() - feet * 12.0;
+                    let sign = if inches < 0.0 { "-" } else { "" };
+                    format!("{}{}' {}", sign, feet, trim(rest, "\""))
+                }
+            }
+        }
+    }
+}
+
+/// Up to three decimals, without trailing zeros
+fn trim(value: f32, suffix: &str) -> String {
+    let formatted = format!("{:.3}", value);
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    format!("{}{}", formatted, suffix)
+}
+
+/// Size of the object's mesh bounds times its scale, in Blender units on Blender's axes.
+/// The same numbers as the Dimensions in Blender's N panel.
+pub fn dimensions(object: &BlendObject) -> Option<[f32; 3]> {
+    let size = object.bounds?.size();
+    Some([0, 1, 2].map(|axis| size[axis] * object.scale[axis].abs()))
+}
+
+/// Scale that causes trouble on import: negative flips normals, non-uniform breaks colliders
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScaleIssues {
+    pub negative: bool,
+    pub non_uniform: bool,
+}
+
+impl ScaleIssues {
+    /// Axes count as uniform while they're less than `tolerance` apart,
+    /// relative to the largest axis once that's over 1
+    pub fn of(scale: [f32; 3], tolerance: f32) -> Self {
+        let magnitudes = scale.map(f32::abs);
+        let largest = magnitudes.iter().copied().fold(0.0, f32::max);
+        let smallest = magnitudes.iter().copied().fold(f32::INFINITY, f32::min);
+        Self {
+            negative: scale.iter().any(|axis| *axis < 0.0),
+            non_uniform: largest - smallest > tolerance * largest.max(1.0),
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        self.negative || self.non_uniform
+    }
+
+    /// What's wrong, for a warning next to the object
+    pub fn describe(&self, scale: [f32; 3]) -> String {
+        let mut problems = Vec::new();
+        if self.negative {
+            problems.push(t!("scale.negative"));
+        }
+        if self.non_uniform {
+            problems.push(t!("scale.non_uniform"));
+        }
+        t!(
+            "scale.warning",
+            problems = problems.join(", "),
+            x = scale[0],
+            y = scale[1],
+            z = scale[2]
+        )
+    }
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// How far the cursor may move between pressing and releasing for it to count as a click,
+/// in pixels. Anything further is an orbit.
+const CLICK_SLOP: f32 = 4.0;
+
+/// Tells clicks in the viewport from orbits, which start with the same left button press.
+/// Each tool that picks things with a click keeps one.
+#[derive(Default)]
+pub struct ViewportClick {
+    /// Where the left button went down over the viewport
+    pressed_at: Option<Vec2>,
+}
+
+impl ViewportClick {
+    /// Remembers where the button went down, or forgets it when that was over a panel
+    pub fn press(&mut self, cursor: Option<Vec2>, over_panels: bool) {
+        self.pressed_at = cursor.filter(|_| !over_panels);
+    }
+
+    /// Whether letting go of the button at `cursor` ends a click, which uses up the press
+    pub fn release(&mut self, cursor: Option<Vec2>) -> bool {
+        self.pressed_at
+            .take()
+            .zip(cursor)
+            .is_some_and(|(pressed_at, cursor)| pressed_at.distance(cursor) <= CLICK_SLOP)
+    }
+
+    /// [`Self::press`] and [`Self::release`] for this frame's presses of the left button.
+    /// True on the frame a click ends.
+    pub fn clicked(
+        &mut self,
+        mouse_buttons: &Input<MouseButton>,
+        cursor: Option<Vec2>,
+        over_panels: bool,
+    ) -> bool {
+        if mouse_buttons.just_pressed(MouseButton::Left) {
+            self.press(cursor, over_panels);
+        }
+        mouse_buttons.just_released(MouseButton::Left) && self.release(cursor)
+    }
+}
+
+/// Where a point in the window's viewport is in egui's coordinates, for drawing over it.
+/// Viewport coordinates start at the bottom left, egui's at the top left.
+pub fn viewport_to_egui(position: Vec2, window: &Window, ui_scale: f32) -> egui::Pos2 {
+    egui::pos2(
+        position.x / ui_scale,
+        (window.height() - position.y) / ui_scale,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_are_told_from_drags() {
+        let mut click = ViewportClick::default();
+        click.press(Some(Vec2::new(10.0, 10.0)), false);
+        assert!(click.release(Some(Vec2::new(12.0, 13.0))));
+        // The press is used up
+        assert!(!click.release(Some(Vec2::new(12.0, 13.0))));
+
+        click.press(Some(Vec2::new(10.0, 10.0)), false);
+        assert!(!click.release(Some(Vec2::new(30.0, 10.0))));
+
+        // Presses on panels, and releases outside the window, aren't clicks
+        click.press(Some(Vec2::new(10.0, 10.0)), true);
+        assert!(!click.release(Some(Vec2::new(10.0, 10.0))));
+        click.press(Some(Vec2::new(10.0, 10.0)), false);
+        assert!(!click.release(None));
+    }
+}
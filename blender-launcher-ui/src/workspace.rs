@@ -0,0 +1,389 @@
+use std::{fs, io, path::Path};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_egui::egui;
+use rfd::FileDialog;
+
+use crate::{
+    confirm::{Confirmation, DestructiveAction},
+    i18n::t,
+    session::{load_session, restore_session, session_path, workspaces_dir, write_session},
+    settings::Settings,
+    shared_workspace::SharedWorkspace,
+    toasts::{Severity, ToastAction, Toasts},
+    AppState, BlenderPreviewObject,
+};
+
+/// Characters a workspace name can't have, since it's also the name of its session file
+const RESERVED_CHARACTERS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+pub enum WorkspaceAction {
+    /// Swaps the file list for a workspace's, the default one with `None`
+    Switch(Option<String>),
+    /// Starts an empty workspace and switches to it
+    Create(String),
+    Rename {
+        from: String,
+        to: String,
+    },
+    Delete(String),
+    /// Writes the file list to a file to share, relative to a root the user picks
+    Export,
+    /// Reads a shared file list, adding to the current one or replacing it
+    Import {
+        replace: bool,
+    },
+}
+
+/// The named workspaces. The default one always exists, so it isn't listed.
+#[derive(Default, Resource)]
+pub struct Workspaces {
+    names: Vec<String>,
+}
+
+impl Workspaces {
+    /// Lists the workspaces that have a session file
+    fn load() -> Self {
+        let mut names: Vec<String> = workspaces_dir()
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                Some(path.file_stem()?.to_string_lossy().into_owned())
+            })
+            .collect();
+        names.sort();
+        Self { names }
+    }
+
+    fn add(&mut self, name: String) {
+        self.names.push(name);
+        self.names.sort();
+    }
+
+    /// Why `name` can't be used for a new workspace, if it can't
+    fn check_name(&self, name: &str) -> Result<(), &'static str> {
+        if name.is_empty() {
+            Err(t!("workspace.name_empty"))
+        } else if name.starts_with('.') || name.contains(RESERVED_CHARACTERS) {
+            Err(t!("workspace.name_invalid"))
+        } else if self.names.iter().any(|existing| existing == name) {
+            Err(t!("workspace.name_taken"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub struct WorkspacePlugin;
+
+impl Plugin for WorkspacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<WorkspaceAction>()
+            .insert_resource(Workspaces::load())
+            .add_startup_system(forget_missing_workspace.before(restore_session))
+            .add_system(apply_workspace_actions.run_if(on_event::<WorkspaceAction>()));
+    }
+}
+
+/// Falls back to the default workspace when the saved one was deleted outside the app
+fn forget_missing_workspace(mut settings: ResMut<Settings>, workspaces: Res<Workspaces>) {
+    let Some(name) = &settings.workspace else {
+        return;
+    };
+    if !workspaces.names.contains(name) {
+        warn!(
+            workspace = name,
+            "couldn't find the last workspace, using the default one"
+        );
+        settings.workspace = None;
+    }
+}
+
+/// Everything that goes along with the files in the list, and is swapped out with them
+#[derive(SystemParam)]
+struct FileList<'w, 's> {
+    commands: Commands<'w, 's>,
+    app_state: ResMut<'w, AppState>,
+    settings: ResMut<'w, Settings>,
+    previews: Query<'w, 's, Entity, With<BlenderPreviewObject>>,
+}
+
+impl FileList<'_, '_> {
+    /// Saves the current workspace, then replaces the file list and view with another's
+    fn switch_to(&mut self, workspace: Option<String>) -> io::Result<()> {
+        if self.settings.workspace == workspace {
+            return Ok(());
+        }
+        // The regular save only ever writes the active workspace, this is the last chance
+        write_session(&self.app_state, &self.settings)?;
+        self.clear();
+
+        if let Some(view) = load_session(&mut self.app_state, workspace.as_deref()) {
+            view.apply(&mut self.settings);
+        }
+        self.settings.workspace = workspace;
+        Ok(())
+    }
+
+    /// Empties the list and the preview. The history of the files goes with the
+    /// `FileRemoved` events for them.
+    fn clear(&mut self) {
+        for entity in self.previews.iter() {
+            self.commands.entity(entity).despawn_recursive();
+        }
+        self.app_state.files.clear();
+        self.app_state.select_file(None);
+        self.app_state.scroll_to_object = None;
+    }
+}
+
+fn apply_workspace_actions(
+    mut actions: EventReader<WorkspaceAction>,
+    mut workspaces: ResMut<Workspaces>,
+    mut file_list: FileList,
+    mut toasts: ResMut<Toasts>,
+) {
+    for action in actions.iter() {
+        let result = match action {
+            WorkspaceAction::Switch(workspace) => file_list.switch_to(workspace.clone()),
+            WorkspaceAction::Create(name) => {
+                let name = name.trim();
+                if let Err(problem) = workspaces.check_name(name) {
+                    toasts.push(Severity::Warning, problem);
+                    continue;
+                }
+                file_list.switch_to(Some(name.to_string())).map(|()| {
+                    workspaces.add(name.to_string());
+                })
+            }
+            WorkspaceAction::Rename { from, to } => {
+                let to = to.trim();
+                if let Err(problem) = workspaces.check_name(to) {
+                    toasts.push(Severity::Warning, problem);
+                    continue;
+                }
+                rename_session(from, to).map(|()| {
+                    workspaces.names.retain(|name| name != from);
+                    workspaces.add(to.to_string());
+                    if file_list.settings.workspace.as_ref() == Some(from) {
+                        file_list.settings.workspace = Some(to.to_string());
+                    }
+                })
+            }
+            WorkspaceAction::Delete(name) => {
+                let switched = if file_list.settings.workspace.as_ref() == Some(name) {
+                    file_list.switch_to(None)
+                } else {
+                    Ok(())
+                };
+                switched.and_then(|()| delete_session(name)).map(|()| {
+                    workspaces.names.retain(|existing| existing != name);
+                })
+            }
+            WorkspaceAction::Export => export_dialog(&file_list, &mut toasts),
+            WorkspaceAction::Import { replace } => {
+                import_dialog(&mut file_list, *replace, &mut toasts)
+            }
+        };
+        if let Err(error) = result {
+            error!(%error, "couldn't update workspaces");
+            toasts.push(Severity::Error, t!("workspace.failed", error = error));
+        }
+    }
+}
+
+fn export_dialog(file_list: &FileList, toasts: &mut Toasts) -> io::Result<()> {
+    let mut root_dialog = FileDialog::new().set_title(t!("workspace.export_root"));
+    if let Some(asset_root) = &file_list.settings.asset_root {
+        root_dialog = root_dialog.set_directory(asset_root);
+    }
+    let Some(root) = root_dialog.pick_folder() else {
+        return Ok(());
+    };
+    let file_name = format!(
+        "{}.json",
+        file_list
+            .settings
+            .workspace
+            .as_deref()
+            .unwrap_or("workspace")
+    );
+    let Some(path) = FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .set_directory(&root)
+        .set_file_name(&file_name)
+        .save_file()
+    else {
+        return Ok(());
+    };
+
+    SharedWorkspace::from_files(&file_list.app_state, &root).write(&path)?;
+    toasts.push_with_action(
+        Severity::Success,
+        t!("workspace.exported"),
+        ToastAction::RevealFile(path),
+    );
+    Ok(())
+}
+
+/// Adds the files of a shared workspace that exist here, and reports the ones that don't
+fn import_dialog(file_list: &mut FileList, replace: bool, toasts: &mut Toasts) -> io::Result<()> {
+    let Some(path) = FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+        return Ok(());
+    };
+    let shared = SharedWorkspace::read(&path)?;
+    if shared.is_newer() {
+        toasts.push(Severity::Warning, t!("workspace.import_newer"));
+    }
+    let root = if shared.has_relative_paths() {
+        // Shared workspaces usually sit in the folder they're relative to
+        let Some(root) = FileDialog::new()
+            .set_title(t!("workspace.import_root"))
+            .set_directory(path.parent().unwrap_or(Path::new("/")))
+            .pick_folder()
+        else {
+            return Ok(());
+        };
+        Some(root)
+    } else {
+        None
+    };
+
+    if replace {
+        file_list.clear();
+    }
+    let mut added = 0;
+    let mut missing = Vec::new();
+    for entry in shared.files {
+        let resolved = entry.resolve(root.as_deref());
+        if !resolved.is_file() {
+            warn!(path = %resolved.display(), "couldn't find file from imported workspace");
+            missing.push(entry.path);
+            continue;
+        }
+        let resolved = resolved.display().to_string();
+        if file_list
+            .app_state
+            .files
+            .iter()
+            .any(|file| file.path == resolved)
+        {
+            continue;
+        }
+        let file_id = file_list.app_state.add_file(resolved);
+        if let Some(file) = file_list.app_state.file_mut(file_id) {
+            file.blender_override = entry.open_with.filter(|blender| blender.is_file());
+        }
+        added += 1;
+    }
+
+    toasts.push(Severity::Success, t!("workspace.imported", count = added));
+    if !missing.is_empty() {
+        toasts.push(
+            Severity::Warning,
+            t!(
+                "workspace.import_missing",
+                count = missing.len(),
+                files = missing.join(", ")
+            ),
+        );
+    }
+    Ok(())
+}
+
+fn rename_session(from: &str, to: &str) -> io::Result<()> {
+    let (Some(from), Some(to)) = (session_path(Some(from)), session_path(Some(to))) else {
+        return Ok(());
+    };
+    // A workspace created this frame might not have been saved yet
+    if !from.exists() {
+        return Ok(());
+    }
+    fs::rename(from, to)
+}
+
+fn delete_session(name: &str) -> io::Result<()> {
+    let Some(path) = session_path(Some(name)) else {
+        return Ok(());
+    };
+    match fs::remove_file(path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+/// The workspace dropdown for the top panel, with a menu to create, rename and delete them
+pub fn workspace_ui(
+    ui: &mut egui::Ui,
+    workspaces: &Workspaces,
+    active: Option<&str>,
+    actions: &mut EventWriter<WorkspaceAction>,
+    confirmation: &mut Confirmation,
+) {
+    ui.label(t!("workspace.label"));
+    egui::ComboBox::from_id_source("workspace")
+        .selected_text(active.unwrap_or(t!("workspace.default")))
+        .show_ui(ui, |ui| {
+            if ui
+                .selectable_label(active.is_none(), t!("workspace.default"))
+                .clicked()
+            {
+                actions.send(WorkspaceAction::Switch(None));
+            }
+            for name in workspaces.names.iter() {
+                if ui
+                    .selectable_label(active == Some(name.as_str()), name)
+                    .clicked()
+                {
+                    actions.send(WorkspaceAction::Switch(Some(name.clone())));
+                }
+            }
+        });
+
+    ui.menu_button("⚙", |ui| {
+        let name_id = ui.make_persistent_id("workspace_name");
+        let mut name: String = ui.data(|data| data.get_temp(name_id)).unwrap_or_default();
+        ui.text_edit_singleline(&mut name)
+            .on_hover_text(t!("workspace.name_hint"));
+
+        if ui.button(t!("workspace.create")).clicked() {
+            actions.send(WorkspaceAction::Create(name.clone()));
+            name.clear();
+            ui.close_menu();
+        }
+        if let Some(active) = active {
+            if ui.button(t!("workspace.rename")).clicked() {
+                actions.send(WorkspaceAction::Rename {
+                    from: active.to_string(),
+                    to: name.clone(),
+                });
+                name.clear();
+                ui.close_menu();
+            }
+            if ui.button(t!("workspace.delete")).clicked() {
+                confirmation.request(DestructiveAction::DeleteWorkspace(active.to_string()));
+                ui.close_menu();
+            }
+        }
+
+        ui.separator();
+        if ui.button(t!("workspace.export")).clicked() {
+            actions.send(WorkspaceAction::Export);
+            ui.close_menu();
+        }
+        if ui.button(t!("workspace.import_merge")).clicked() {
+            actions.send(WorkspaceAction::Import { replace: false });
+            ui.close_menu();
+        }
+        if ui.button(t!("workspace.import_replace")).clicked() {
+            actions.send(WorkspaceAction::Import { replace: true });
+            ui.close_menu();
+        }
+        ui.data_mut(|data| data.insert_temp(name_id, name));
+    });
+}